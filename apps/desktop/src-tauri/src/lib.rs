@@ -2,7 +2,9 @@ use aes_gcm::aead::{Aead, KeyInit};
 use base64::Engine;
 use rand_core::RngCore;
 use sandpaper_core::blocks::BlockType;
-use sandpaper_core::db::{BlockPageRecord, BlockSearchResult, BlockSnapshot, Database};
+use sandpaper_core::db::{BlockSearchResult, BlockSnapshot, Database, PageRecord};
+use sandpaper_core::ops::{diff_ops, summarize_ops, BlockChange};
+use sandpaper_core::plugin_events::{block_events, PluginEvent};
 use sandpaper_core::plugins;
 use sandpaper_core::plugins::{
     check_manifest_compatibility, discover_plugins, install_plugin, list_plugins, remove_plugin,
@@ -10,6 +12,8 @@ use sandpaper_core::plugins::{
     PluginRegistry, PluginRenderer, PluginRuntime, PluginRuntimeError, PluginRuntimeLoadResult,
     PluginSettingsSchema, PluginToolbarAction,
 };
+use sandpaper_core::review::{ReviewGrade, DAY_MS};
+use sandpaper_core::templates::{expand_template, template_pages, TemplateVars};
 use sandpaper_core::vaults::{VaultConfig, VaultRecord, VaultStore};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -17,7 +21,7 @@ use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{mpsc, Arc, Mutex};
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Manager};
 #[cfg(target_os = "macos")]
 use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
 #[cfg(target_os = "windows")]
@@ -150,9 +154,26 @@ struct ReviewQueueItemResponse {
     template: Option<String>,
     status: String,
     last_reviewed_at: Option<i64>,
+    ease: f64,
+    interval_days: i64,
+    repetitions: i64,
+    lapses: i64,
     text: String,
 }
 
+#[derive(Debug, Serialize)]
+struct ReviewStatsResponse {
+    due: i64,
+    pending: i64,
+    done: i64,
+    reviewed: i64,
+    failed: i64,
+    retention: Option<f64>,
+    average_ease: Option<f64>,
+    /// Items due on each of the next days, starting today.
+    forecast: Vec<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ReviewActionPayload {
@@ -260,6 +281,9 @@ enum PluginRuntimeRequest {
         payload: Value,
         reply: mpsc::Sender<Result<Value, plugins::PluginError>>,
     },
+    DispatchEvents {
+        events: Vec<PluginEvent>,
+    },
     Shutdown,
 }
 
@@ -278,10 +302,15 @@ fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
     }
 }
 
+/// Tells the frontend which pages plugin event handlers rewrote, so it reloads them before
+/// its next save writes the old blocks back.
+const PLUGIN_PAGES_CHANGED_EVENT: &str = "plugin-pages-changed";
+
 struct RuntimeState {
     sender: mpsc::Sender<PluginRuntimeRequest>,
     thread: Mutex<Option<std::thread::JoinHandle<()>>>,
     last_error: Arc<Mutex<Option<plugins::PluginRuntimeError>>>,
+    app: Arc<Mutex<Option<AppHandle>>>,
 }
 
 impl RuntimeState {
@@ -290,6 +319,8 @@ impl RuntimeState {
         let last_error: Arc<Mutex<Option<plugins::PluginRuntimeError>>> =
             Arc::new(Mutex::new(None));
         let thread_errors = Arc::clone(&last_error);
+        let app: Arc<Mutex<Option<AppHandle>>> = Arc::new(Mutex::new(None));
+        let thread_app = Arc::clone(&app);
         let handle = std::thread::spawn(move || {
             enum ThreadControl {
                 Continue,
@@ -308,9 +339,11 @@ impl RuntimeState {
                             settings,
                             reply,
                         } => {
-                            let result = Self::with_runtime(&mut runtime, |runtime| {
-                                runtime.load_plugins(&plugins, settings)
-                            });
+                            let result =
+                                Self::with_runtime(&mut runtime, &thread_errors, |runtime| {
+                                    runtime.set_database(open_active_database().ok());
+                                    runtime.load_plugins(&plugins, settings)
+                                });
                             let _ = reply.send(result);
                             ThreadControl::Continue
                         }
@@ -321,9 +354,15 @@ impl RuntimeState {
                             text,
                             reply,
                         } => {
-                            let result = Self::with_runtime(&mut runtime, |runtime| {
-                                runtime.render_block(&plugin_id, &renderer_id, &block_uid, &text)
-                            });
+                            let result =
+                                Self::with_runtime(&mut runtime, &thread_errors, |runtime| {
+                                    runtime.render_block(
+                                        &plugin_id,
+                                        &renderer_id,
+                                        &block_uid,
+                                        &text,
+                                    )
+                                });
                             let _ = reply.send(result);
                             ThreadControl::Continue
                         }
@@ -336,16 +375,17 @@ impl RuntimeState {
                             value,
                             reply,
                         } => {
-                            let result = Self::with_runtime(&mut runtime, |runtime| {
-                                runtime.handle_block_action(
-                                    &plugin_id,
-                                    &renderer_id,
-                                    &block_uid,
-                                    &text,
-                                    &action_id,
-                                    value,
-                                )
-                            });
+                            let result =
+                                Self::with_runtime(&mut runtime, &thread_errors, |runtime| {
+                                    runtime.handle_block_action(
+                                        &plugin_id,
+                                        &renderer_id,
+                                        &block_uid,
+                                        &text,
+                                        &action_id,
+                                        value,
+                                    )
+                                });
                             let _ = reply.send(result);
                             ThreadControl::Continue
                         }
@@ -355,12 +395,22 @@ impl RuntimeState {
                             payload,
                             reply,
                         } => {
-                            let result = Self::with_runtime(&mut runtime, |runtime| {
-                                runtime.emit_event(&plugin_id, &event, payload)
-                            });
+                            let result =
+                                Self::with_runtime(&mut runtime, &thread_errors, |runtime| {
+                                    runtime.emit_event(&plugin_id, &event, payload)
+                                });
                             let _ = reply.send(result);
                             ThreadControl::Continue
                         }
+                        PluginRuntimeRequest::DispatchEvents { events } => {
+                            if let Some(runtime) = runtime.as_mut() {
+                                let changed =
+                                    Self::dispatch_events_on(runtime, events, &thread_errors);
+                                Self::persist_disabled_plugins(runtime, &thread_errors);
+                                Self::notify_pages_changed(&thread_app, changed);
+                            }
+                            ThreadControl::Continue
+                        }
                         PluginRuntimeRequest::Shutdown => ThreadControl::Shutdown,
                     }));
 
@@ -381,6 +431,22 @@ impl RuntimeState {
             sender,
             thread: Mutex::new(Some(handle)),
             last_error,
+            app,
+        }
+    }
+
+    fn attach_app(&self, handle: AppHandle) {
+        let mut guard = self.app.lock().unwrap_or_else(|err| err.into_inner());
+        *guard = Some(handle);
+    }
+
+    fn notify_pages_changed(app: &Arc<Mutex<Option<AppHandle>>>, changed: Vec<String>) {
+        if changed.is_empty() {
+            return;
+        }
+        let guard = app.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(app) = guard.as_ref() {
+            let _ = app.emit(PLUGIN_PAGES_CHANGED_EVENT, changed);
         }
     }
 
@@ -492,8 +558,11 @@ impl RuntimeState {
         }
     }
 
+    /// Runs `f` on the runtime, starting it if needed. Plugins `f` pushed over their limits
+    /// are turned off before the caller replies, so the next plugin listing already has them.
     fn with_runtime<F, R>(
         runtime: &mut Option<PluginRuntime>,
+        errors: &Arc<Mutex<Option<plugins::PluginRuntimeError>>>,
         f: F,
     ) -> Result<R, plugins::PluginError>
     where
@@ -503,27 +572,99 @@ impl RuntimeState {
             *runtime = Some(PluginRuntime::new()?);
         }
         match runtime.as_mut() {
-            Some(runtime) => f(runtime),
+            Some(runtime) => {
+                let result = f(runtime);
+                Self::persist_disabled_plugins(runtime, errors);
+                result
+            }
             None => Err(plugins::PluginError::Runtime(Box::new(
                 plugins::PluginRuntimeError::new("runtime-unavailable"),
             ))),
         }
     }
 
+    /// Turns off, in the vault's registry, plugins the runtime disabled for hitting their
+    /// limits too often. The frontend sees them disabled the next time it lists plugins.
+    fn persist_disabled_plugins(
+        runtime: &mut PluginRuntime,
+        errors: &Arc<Mutex<Option<plugins::PluginRuntimeError>>>,
+    ) {
+        let disabled = runtime.take_disabled_plugins();
+        if disabled.is_empty() {
+            return;
+        }
+        let Ok(vault_root) = resolve_active_vault_path() else {
+            return;
+        };
+        let registry = plugin_registry_for_vault(&vault_root);
+        for plugin_id in disabled {
+            if let Err(err) = registry.set_enabled(&plugin_id, false) {
+                set_shared_error(
+                    errors,
+                    format!("plugin-disable-failed: {plugin_id}: {err:?}"),
+                );
+            }
+        }
+    }
+
+    /// Runs event handlers and writes the edits they return, returning the uids of the pages
+    /// they changed. Nobody waits on events, so failures land in `last_error`.
+    fn dispatch_events_on(
+        runtime: &mut PluginRuntime,
+        events: Vec<PluginEvent>,
+        errors: &Arc<Mutex<Option<plugins::PluginRuntimeError>>>,
+    ) -> Vec<String> {
+        for event in events {
+            if !runtime.has_event_handlers(event.name) {
+                continue;
+            }
+            let outcome = runtime.dispatch_event(&event);
+            let mut failures = outcome.errors;
+            for (plugin_id, result) in outcome.results {
+                if result.edits.is_empty() {
+                    continue;
+                }
+                if let Err(err) = runtime.apply_event_edits(&plugin_id, &event, result.edits) {
+                    failures.push(err);
+                }
+            }
+            for err in failures {
+                let message = Self::format_runtime_error(&Self::describe_plugin_error(&err));
+                set_shared_error(errors, message);
+            }
+        }
+        runtime.take_data_changes()
+    }
+
+    fn dispatch_events(&self, events: Vec<PluginEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let _ = self
+            .sender
+            .send(PluginRuntimeRequest::DispatchEvents { events });
+    }
+
     fn load_plugins(
         &self,
         plugins: Vec<PluginDescriptor>,
         settings: HashMap<String, Value>,
     ) -> Result<PluginRuntimeLoadResult, String> {
         let (reply, recv) = mpsc::channel();
-        self.request(
+        let result = self.request(
             PluginRuntimeRequest::LoadPlugins {
                 plugins,
                 settings,
                 reply,
             },
             recv,
-        )
+        )?;
+        // Plugins that failed to load are left out of `loaded`; the first failure is kept
+        // for the frontend to show.
+        if let Some(error) = result.errors.first() {
+            self.record_error(error.clone());
+        }
+        Ok(result)
     }
 
     fn render_block(
@@ -949,42 +1090,6 @@ fn ensure_file_asset_from_bytes(
     })
 }
 
-fn normalize_wiki_target(raw: &str) -> Option<String> {
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return None;
-    }
-    let before_alias = trimmed.split('|').next().unwrap_or(trimmed);
-    let before_heading = before_alias.split('#').next().unwrap_or(before_alias);
-    let title = before_heading.trim();
-    if title.is_empty() {
-        None
-    } else {
-        Some(title.to_string())
-    }
-}
-
-fn extract_wikilinks(text: &str) -> Vec<String> {
-    let mut links = Vec::new();
-    let mut index = 0;
-    while let Some(open) = text[index..].find("[[") {
-        let start = index + open + 2;
-        let remainder = &text[start..];
-        let close = match remainder.find("]]") {
-            Some(value) => value,
-            None => break,
-        };
-        let raw = &remainder[..close];
-        if let Some(target) = normalize_wiki_target(raw) {
-            if !links.contains(&target) {
-                links.push(target);
-            }
-        }
-        index = start + close + 2;
-    }
-    links
-}
-
 fn fallback_page_title(page_uid: &str) -> &str {
     if page_uid == "inbox" {
         "Inbox"
@@ -1087,6 +1192,8 @@ fn store_device_clock(db: &Database, clock: i64) -> Result<(), String> {
         .map_err(|err| format!("{:?}", err))
 }
 
+/// The webview saves whole block lists and keeps no op log, so sync ops come from
+/// diffing the snapshot it replaced.
 fn build_sync_ops(
     page_uid: &str,
     device_id: &str,
@@ -1095,104 +1202,55 @@ fn build_sync_ops(
     mut clock: i64,
 ) -> Result<(Vec<PendingSyncOp>, i64), String> {
     let mut ops = Vec::new();
-    let mut previous_by_id = std::collections::HashMap::new();
-    for (index, block) in previous.iter().enumerate() {
-        previous_by_id.insert(block.uid.clone(), (block, index));
-    }
-    let mut next_ids = std::collections::HashSet::new();
-
-    for (index, block) in next.iter().enumerate() {
-        next_ids.insert(block.uid.as_str());
-        let sort_key = format!("{:06}", index);
-        let timestamp = chrono::Utc::now().timestamp_millis();
-
-        if let Some((prev, prev_index)) = previous_by_id.get(&block.uid) {
-            if block.text != prev.text || block.block_type != prev.block_type {
-                clock += 1;
-                let op_id = uuid::Uuid::new_v4().to_string();
-                let payload = serde_json::json!({
-                    "opId": op_id,
-                    "pageId": page_uid,
-                    "blockId": block.uid,
-                    "deviceId": device_id,
-                    "clock": clock,
-                    "timestamp": timestamp,
-                    "kind": "edit",
-                    "text": block.text,
-                    "blockType": block.block_type
-                });
-                ops.push(PendingSyncOp {
-                    op_id,
-                    op_type: "edit".to_string(),
-                    payload: serde_json::to_vec(&payload).map_err(|err| format!("{:?}", err))?,
-                });
-            }
-
-            if block.indent != prev.indent || *prev_index != index {
-                clock += 1;
-                let op_id = uuid::Uuid::new_v4().to_string();
-                let payload = serde_json::json!({
-                    "opId": op_id,
-                    "pageId": page_uid,
-                    "blockId": block.uid,
-                    "deviceId": device_id,
-                    "clock": clock,
-                    "timestamp": timestamp,
-                    "kind": "move",
-                    "parentId": serde_json::Value::Null,
-                    "sortKey": sort_key,
-                    "indent": block.indent,
-                    "blockType": block.block_type
-                });
-                ops.push(PendingSyncOp {
-                    op_id,
-                    op_type: "move".to_string(),
-                    payload: serde_json::to_vec(&payload).map_err(|err| format!("{:?}", err))?,
-                });
-            }
-        } else {
-            clock += 1;
-            let op_id = uuid::Uuid::new_v4().to_string();
-            let payload = serde_json::json!({
-                "opId": op_id,
-                "pageId": page_uid,
-                "blockId": block.uid,
-                "deviceId": device_id,
-                "clock": clock,
-                "timestamp": timestamp,
-                "kind": "add",
-                "parentId": serde_json::Value::Null,
-                "sortKey": sort_key,
-                "indent": block.indent,
-                "text": block.text,
-                "blockType": block.block_type
-            });
-            ops.push(PendingSyncOp {
-                op_id,
-                op_type: "add".to_string(),
-                payload: serde_json::to_vec(&payload).map_err(|err| format!("{:?}", err))?,
-            });
-        }
-    }
-
-    for (uid, _) in previous_by_id.iter() {
-        if next_ids.contains(uid.as_str()) {
-            continue;
-        }
+    for change in summarize_ops(previous, &diff_ops(previous, next)) {
         clock += 1;
         let op_id = uuid::Uuid::new_v4().to_string();
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "opId": op_id,
             "pageId": page_uid,
-            "blockId": uid,
             "deviceId": device_id,
             "clock": clock,
             "timestamp": chrono::Utc::now().timestamp_millis(),
-            "kind": "delete"
         });
+        let (op_type, fields) = match change {
+            BlockChange::Added { index, block } => (
+                "add",
+                serde_json::json!({
+                    "blockId": block.uid,
+                    "parentId": serde_json::Value::Null,
+                    "sortKey": format!("{:06}", index),
+                    "indent": block.indent,
+                    "text": block.text,
+                    "blockType": block.block_type
+                }),
+            ),
+            BlockChange::Edited { block } => (
+                "edit",
+                serde_json::json!({
+                    "blockId": block.uid,
+                    "text": block.text,
+                    "blockType": block.block_type
+                }),
+            ),
+            BlockChange::Moved { index, block } => (
+                "move",
+                serde_json::json!({
+                    "blockId": block.uid,
+                    "parentId": serde_json::Value::Null,
+                    "sortKey": format!("{:06}", index),
+                    "indent": block.indent,
+                    "blockType": block.block_type
+                }),
+            ),
+            BlockChange::Deleted { uid } => ("delete", serde_json::json!({ "blockId": uid })),
+        };
+        if let (Some(payload), Some(fields)) = (payload.as_object_mut(), fields.as_object()) {
+            payload.extend(fields.clone());
+            payload.insert("kind".to_string(), Value::from(op_type));
+        }
         ops.push(PendingSyncOp {
             op_id,
-            op_type: "delete".to_string(),
+            op_type: op_type.to_string(),
             payload: serde_json::to_vec(&payload).map_err(|err| format!("{:?}", err))?,
         });
     }
@@ -1472,17 +1530,25 @@ fn detect_sync_conflicts(blocks: &[BlockSnapshot], ops: &[SyncOpPayload]) -> Vec
     conflicts
 }
 
+/// Answers counted towards the retention shown in review stats.
+const REVIEW_STATS_WINDOW_DAYS: i64 = 30;
+const REVIEW_FORECAST_DAYS: usize = 14;
+
 fn next_review_due(interval_days: i64) -> i64 {
     let millis = interval_days * 24 * 60 * 60 * 1000;
     chrono::Utc::now().timestamp_millis() + millis
 }
 
-fn resolve_review_interval(action: &str, last_interval: i64) -> i64 {
+/// Maps a review pane action to a grade. `None` retires the item. The old snooze and schedule
+/// buttons map to hard and good so older frontends keep working.
+fn resolve_review_action(action: &str) -> Result<Option<ReviewGrade>, String> {
     match action {
-        "snooze" => 1,
-        "later" => std::cmp::min(14, std::cmp::max(1, last_interval * 2)),
-        "done" => 30,
-        _ => std::cmp::min(7, std::cmp::max(1, last_interval)),
+        "done" => Ok(None),
+        "snooze" => Ok(Some(ReviewGrade::Hard)),
+        "later" => Ok(Some(ReviewGrade::Good)),
+        other => ReviewGrade::parse(other)
+            .map(Some)
+            .ok_or_else(|| format!("Unknown review action: {other}")),
     }
 }
 
@@ -1756,6 +1822,10 @@ fn list_review_queue_due(limit: i64) -> Result<Vec<ReviewQueueItemResponse>, Str
             template: item.template,
             status: item.status,
             last_reviewed_at: item.last_reviewed_at,
+            ease: item.card.ease,
+            interval_days: item.card.interval_days,
+            repetitions: item.card.repetitions,
+            lapses: item.card.lapses,
             text,
         });
     }
@@ -1763,31 +1833,84 @@ fn list_review_queue_due(limit: i64) -> Result<Vec<ReviewQueueItemResponse>, Str
 }
 
 #[tauri::command]
-fn update_review_queue_item(payload: ReviewActionPayload) -> Result<(), String> {
+fn update_review_queue_item(
+    payload: ReviewActionPayload,
+    state: tauri::State<'_, Arc<RuntimeState>>,
+) -> Result<(), String> {
     let db = open_active_database()?;
     let now = chrono::Utc::now().timestamp_millis();
-    let interval = resolve_review_interval(&payload.action, 3);
-    let next_due = if payload.action == "done" {
-        None
-    } else {
-        Some(next_review_due(interval))
-    };
-    let status = if payload.action == "done" {
-        "done"
-    } else {
-        "pending"
+    let grade = resolve_review_action(&payload.action)?;
+    let item = match grade {
+        Some(grade) => db
+            .grade_review_queue_item(payload.id, grade, now)
+            .map_err(|err| format!("{:?}", err))?,
+        None => {
+            db.mark_review_queue_item(payload.id, "done", now, None)
+                .map_err(|err| format!("{:?}", err))?;
+            db.get_review_queue_item(payload.id)
+                .map_err(|err| format!("{:?}", err))?
+        }
     };
-    db.mark_review_queue_item(payload.id, status, now, next_due)
-        .map_err(|err| format!("{:?}", err))?;
+    if let Some(item) = item {
+        state.dispatch_events(vec![PluginEvent::review_completed(&item, grade)]);
+    }
     Ok(())
 }
 
 #[tauri::command]
-fn create_review_template(payload: ReviewTemplatePayload) -> Result<(), String> {
-    let mut db = open_active_database()?;
-    let page_uid = sanitize_kebab(&payload.page_uid);
-    let page_id = ensure_page(&db, &page_uid, &payload.title)?;
-    let blocks = vec![
+fn review_stats() -> Result<ReviewStatsResponse, String> {
+    let db = open_active_database()?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let stats = db
+        .review_stats(now, now - REVIEW_STATS_WINDOW_DAYS * DAY_MS)
+        .map_err(|err| format!("{:?}", err))?;
+    let day_start = chrono::Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+        .map(|midnight| midnight.timestamp_millis())
+        .unwrap_or(now);
+    let forecast = db
+        .review_forecast(day_start, REVIEW_FORECAST_DAYS)
+        .map_err(|err| format!("{:?}", err))?;
+    Ok(ReviewStatsResponse {
+        due: stats.due,
+        pending: stats.pending,
+        done: stats.done,
+        reviewed: stats.reviewed,
+        failed: stats.failed,
+        retention: stats.retention,
+        average_ease: stats.average_ease,
+        forecast,
+    })
+}
+
+/// Blocks of the `#template` page named `name`, expanded for a page titled `title`.
+fn user_template_blocks(
+    db: &Database,
+    name: &str,
+    title: &str,
+) -> Result<Option<Vec<BlockSnapshot>>, String> {
+    let pages = template_pages(db).map_err(|err| format!("{:?}", err))?;
+    let Some(page) = pages.into_iter().find(|page| {
+        page.uid == sanitize_kebab(name) || page.title.eq_ignore_ascii_case(name.trim())
+    }) else {
+        return Ok(None);
+    };
+    let blocks = db
+        .load_blocks_for_page(page.id)
+        .map_err(|err| format!("{:?}", err))?;
+    let vars = TemplateVars {
+        now: chrono::Local::now().naive_local(),
+        title: title.to_string(),
+        fields: HashMap::new(),
+    };
+    let expanded = expand_template(&blocks, &vars);
+    Ok((!expanded.blocks.is_empty()).then_some(expanded.blocks))
+}
+
+fn default_review_template_blocks() -> Vec<BlockSnapshot> {
+    vec![
         BlockSnapshot {
             uid: uuid::Uuid::new_v4().to_string(),
             text: "Summary".to_string(),
@@ -1812,7 +1935,18 @@ fn create_review_template(payload: ReviewTemplatePayload) -> Result<(), String>
             indent: 1,
             block_type: BlockType::Text,
         },
-    ];
+    ]
+}
+
+#[tauri::command]
+fn create_review_template(payload: ReviewTemplatePayload) -> Result<(), String> {
+    let mut db = open_active_database()?;
+    let page_uid = sanitize_kebab(&payload.page_uid);
+    let page_id = ensure_page(&db, &page_uid, &payload.title)?;
+    let blocks = match user_template_blocks(&db, &payload.template, &payload.title)? {
+        Some(blocks) => blocks,
+        None => default_review_template_blocks(),
+    };
     db.replace_blocks_for_page(page_id, &blocks)
         .map_err(|err| format!("{:?}", err))?;
     let due_at = next_review_due(1);
@@ -1865,7 +1999,7 @@ fn rename_page(payload: RenamePagePayload) -> Result<PageSummary, String> {
         .get_page_by_uid(&page_uid)
         .map_err(|err| format!("{:?}", err))?
         .ok_or_else(|| "Page not found".to_string())?;
-    db.update_page_title(page.id, title)
+    db.rename_page(page.id, title)
         .map_err(|err| format!("{:?}", err))?;
     Ok(PageSummary {
         uid: page_uid,
@@ -1917,33 +2051,25 @@ fn list_page_wikilink_backlinks(page_uid: String) -> Result<Vec<PageBacklinkEntr
     let db = open_active_database()?;
     let target_uid = sanitize_kebab(&page_uid);
     let records = db
-        .list_blocks_with_wikilinks()
+        .list_backlinks_with_own_page(&target_uid)
         .map_err(|err| format!("{:?}", err))?;
-    let mut results = Vec::new();
-    for record in records {
-        if link_matches_target(&record, &target_uid) {
-            results.push(PageBacklinkEntry {
-                block_uid: record.block_uid,
-                text: record.text,
-                page_uid: record.page_uid,
-                page_title: record.page_title,
-            });
-        }
-    }
-    Ok(results)
-}
-
-fn link_matches_target(record: &BlockPageRecord, target_uid: &str) -> bool {
-    for link in extract_wikilinks(&record.text) {
-        if sanitize_kebab(&link) == target_uid {
-            return true;
-        }
-    }
-    false
+    Ok(records
+        .into_iter()
+        .map(|record| PageBacklinkEntry {
+            block_uid: record.block_uid,
+            text: record.text,
+            page_uid: record.page_uid,
+            page_title: record.page_title,
+        })
+        .collect())
 }
 
 #[tauri::command]
-fn save_page_blocks(page_uid: String, blocks: Vec<BlockSnapshot>) -> Result<(), String> {
+fn save_page_blocks(
+    page_uid: String,
+    blocks: Vec<BlockSnapshot>,
+    state: tauri::State<'_, Arc<RuntimeState>>,
+) -> Result<(), String> {
     let mut db = open_active_database()?;
     let title = fallback_page_title(&page_uid);
     let page_id = ensure_page(&db, &page_uid, title)?;
@@ -1972,6 +2098,20 @@ fn save_page_blocks(page_uid: String, blocks: Vec<BlockSnapshot>) -> Result<(),
         store_device_clock(&db, next_clock)?;
     }
 
+    let page = PageRecord {
+        id: page_id,
+        title: db
+            .get_page_by_id(page_id)
+            .ok()
+            .flatten()
+            .map(|page| page.title)
+            .unwrap_or_else(|| title.to_string()),
+        uid: page_uid,
+    };
+    let mut events = block_events(&page, &previous, &blocks);
+    events.push(PluginEvent::page_saved(&page, &blocks));
+    state.dispatch_events(events);
+
     Ok(())
 }
 
@@ -2576,6 +2716,7 @@ async fn load_plugins_command(
                 panels: Vec::new(),
                 toolbar_actions: Vec::new(),
                 renderers: Vec::new(),
+                errors: Vec::new(),
             }
         } else {
             state.load_plugins(allowed, settings_by_plugin)?
@@ -2712,6 +2853,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
+            app.state::<Arc<RuntimeState>>().attach_app(app.handle().clone());
             let window = app.get_webview_window("main").unwrap();
 
             #[cfg(target_os = "macos")]
@@ -2761,6 +2903,7 @@ pub fn run() {
             add_review_queue_item,
             list_review_queue_due,
             update_review_queue_item,
+            review_stats,
             create_review_template,
             write_shadow_markdown,
             export_markdown,
@@ -2807,10 +2950,10 @@ mod tests {
         compute_missing_permissions, detect_sync_conflicts, encrypt_sync_payload,
         ensure_plugin_permission, get_plugin_settings, list_permissions_for_plugins,
         ensure_file_asset_from_bytes, ensure_image_asset_from_bytes, load_sync_config,
-        next_review_due, resolve_review_interval,
+        next_review_due, resolve_review_action,
         run_blocking, sanitize_asset_stem, sanitize_kebab, set_plugin_settings,
         shadow_markdown_path, write_shadow_markdown_to_vault, BlockSnapshot, BlockType, Database,
-        PageBlocksResponse, PluginInfo, RuntimeState, SyncOpPayload,
+        PageBlocksResponse, PluginInfo, ReviewGrade, RuntimeState, SyncOpPayload,
     };
     use aes_gcm::aead::Aead;
     use aes_gcm::aead::KeyInit;
@@ -3050,10 +3193,12 @@ mod tests {
         let plugin = build_missing_entry_plugin(dir.path());
         let state = RuntimeState::new();
 
-        let error = state
+        let result = state
             .load_plugins(vec![plugin], HashMap::new())
-            .expect_err("load plugins");
-        assert!(error.contains("plugin-error: io"), "error was {error}");
+            .expect("load plugins");
+        assert!(result.loaded.is_empty());
+        let error = state.last_error().expect("last error");
+        assert!(error.message.starts_with("io:"), "{error:?}");
 
         state.shutdown();
 
@@ -3416,11 +3561,13 @@ mod tests {
     }
 
     #[test]
-    fn resolve_review_interval_behaves_for_actions() {
-        assert_eq!(resolve_review_interval("snooze", 3), 1);
-        assert_eq!(resolve_review_interval("later", 2), 4);
-        assert_eq!(resolve_review_interval("later", 10), 14);
-        assert_eq!(resolve_review_interval("done", 3), 30);
+    fn resolve_review_action_maps_buttons_to_grades() {
+        assert_eq!(resolve_review_action("again"), Ok(Some(ReviewGrade::Again)));
+        assert_eq!(resolve_review_action("easy"), Ok(Some(ReviewGrade::Easy)));
+        assert_eq!(resolve_review_action("snooze"), Ok(Some(ReviewGrade::Hard)));
+        assert_eq!(resolve_review_action("later"), Ok(Some(ReviewGrade::Good)));
+        assert_eq!(resolve_review_action("done"), Ok(None));
+        assert!(resolve_review_action("skip").is_err());
     }
 
     #[test]
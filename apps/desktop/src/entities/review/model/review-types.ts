@@ -14,9 +14,17 @@ export type ReviewQueueItem = {
   template?: string | null;
   status: string;
   last_reviewed_at: Timestamp | null;
+  ease: number;
+  interval_days: number;
+  repetitions: number;
+  lapses: number;
   text: string;
 };
 
+export type ReviewGrade = "again" | "hard" | "good" | "easy";
+
+export type ReviewAction = ReviewGrade | "done";
+
 export type ReviewTemplate = {
   id: string;
   title: string;
@@ -47,6 +47,7 @@ describe("createVaultLoaders", () => {
       setActiveId: vi.fn(),
       setFocusedId: vi.fn(),
       markSaved: vi.fn(),
+      cancelPendingSave: vi.fn(),
       toPayload: (block: Block) => ({
         uid: block.id,
         text: block.text,
@@ -108,6 +109,7 @@ describe("createVaultLoaders", () => {
       setActiveId,
       setFocusedId,
       markSaved,
+      cancelPendingSave: vi.fn(),
       toPayload: (block: Block) => ({
         uid: block.id,
         text: block.text,
@@ -136,4 +138,72 @@ describe("createVaultLoaders", () => {
     expect(setFocusedId).toHaveBeenCalledWith(null);
     expect(markSaved).toHaveBeenCalled();
   });
+
+  it("reloads the active page when plugins changed it", async () => {
+    const [, setPages] = createSignal<PageSummary[]>([]);
+    const [activePageUid, setActivePageUid] = createSignal("page-1");
+    const [activeVault] = createSignal<VaultRecord | null>(null);
+    const invoke = vi.fn(async (command: string) => {
+      if (command === "list_pages") return [{ uid: "page-1", title: "One" }];
+      if (command === "load_page_blocks") {
+        return {
+          page_uid: "page-1",
+          title: "One",
+          blocks: [{ uid: "b1", text: "edited by plugin", indent: 0 }]
+        };
+      }
+      return null;
+    });
+    const setBlocks = vi.fn();
+    const setActiveId = vi.fn();
+    const setFocusedId = vi.fn();
+    const cancelPendingSave = vi.fn();
+
+    const api = createVaultLoaders({
+      isTauri: () => true,
+      invoke,
+      localPages: {},
+      setPages,
+      activePageUid,
+      setActivePageUid,
+      activeVault,
+      resolvePageUid: (value) => value,
+      snapshotBlocks: (items) => items,
+      saveLocalPageSnapshot: vi.fn(),
+      buildLocalDefaults: () => [],
+      buildEmptyBlocks: () => [],
+      buildDefaultBlocks: () => [],
+      makeLocalId: () => "local",
+      makeRandomId: () => "rand",
+      setBlocks,
+      setPageTitle: vi.fn(),
+      setRenameTitle: vi.fn(),
+      setActiveId,
+      setFocusedId,
+      markSaved: vi.fn(),
+      cancelPendingSave,
+      toPayload: (block: Block) => ({
+        uid: block.id,
+        text: block.text,
+        indent: block.indent
+      }),
+      serializePageToMarkdown: () => "",
+      shadowWriter: { scheduleWrite: vi.fn() },
+      setReviewSummary: vi.fn(),
+      setReviewItems: vi.fn(),
+      setReviewBusy: vi.fn(),
+      defaultPageUid: "inbox"
+    });
+
+    await api.reloadChangedPages(["page-2"]);
+    expect(setBlocks).not.toHaveBeenCalled();
+
+    await api.reloadChangedPages(["page-1"]);
+    expect(cancelPendingSave).toHaveBeenCalledWith("page-1");
+    expect(setBlocks).toHaveBeenCalledWith([
+      { id: "b1", text: "edited by plugin", indent: 0, block_type: "text" }
+    ]);
+    expect(setActiveId).not.toHaveBeenCalled();
+    expect(setFocusedId).not.toHaveBeenCalled();
+  });
 });
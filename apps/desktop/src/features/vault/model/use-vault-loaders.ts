@@ -37,6 +37,7 @@ export type VaultLoaderDependencies = {
   setActiveId: Setter<string | null>;
   setFocusedId: Setter<string | null>;
   markSaved: () => void;
+  cancelPendingSave: (pageUid: PageId) => void;
   toPayload: (block: Block) => BlockPayload;
   serializePageToMarkdown: (page: {
     id: string;
@@ -236,11 +237,40 @@ export const createVaultLoaders = (deps: VaultLoaderDependencies) => {
     }
   };
 
+  // Plugin event handlers can rewrite a page after it was saved. Swap in the stored
+  // blocks without moving focus so the next save does not write the old ones back.
+  const reloadChangedPages = async (pageUids: PageId[]) => {
+    if (!deps.isTauri() || pageUids.length === 0) return;
+    await loadPages();
+    const resolvedUid = deps.resolvePageUid(deps.activePageUid());
+    if (!pageUids.includes(resolvedUid)) return;
+    try {
+      const response = (await deps.invoke("load_page_blocks", {
+        pageUid: resolvedUid,
+        page_uid: resolvedUid
+      })) as PageBlocksResponse;
+      if (deps.resolvePageUid(deps.activePageUid()) !== resolvedUid) return;
+      deps.cancelPendingSave(resolvedUid);
+      deps.setBlocks(
+        response.blocks.map((block) => ({
+          id: block.uid,
+          text: block.text,
+          indent: block.indent,
+          block_type: resolveBlockType({ text: block.text, block_type: block.block_type })
+        }))
+      );
+      deps.markSaved();
+    } catch (error) {
+      console.error("Failed to reload changed page", error);
+    }
+  };
+
   return {
     loadPages,
     loadActivePage,
     loadReviewSummary,
     loadReviewQueue,
-    loadBlocks
+    loadBlocks,
+    reloadChangedPages
   };
 };
@@ -7,6 +7,7 @@ import {
 } from "solid-js";
 import { createStore, produce } from "solid-js/store";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import {
   createShadowWriter,
   serializePageToMarkdown
@@ -373,6 +374,7 @@ export const createMainPageState = () => {
     setActiveId,
     setFocusedId,
     markSaved,
+    cancelPendingSave,
     toPayload,
     serializePageToMarkdown,
     shadowWriter,
@@ -386,7 +388,8 @@ export const createMainPageState = () => {
     loadActivePage,
     loadReviewSummary,
     loadReviewQueue,
-    loadBlocks
+    loadBlocks,
+    reloadChangedPages
   } = vaultLoaders;
 
   const pageOps = createPageOps({
@@ -780,7 +783,21 @@ export const createMainPageState = () => {
     setShowStatusSurfaces(readStoredToggle(STATUS_SURFACES_KEY, true));
     void loadVaults();
 
+    let unlistenPluginPages: (() => void) | undefined;
+    if (isTauri()) {
+      void listen<string[]>("plugin-pages-changed", (event) => {
+        void reloadChangedPages(event.payload);
+      })
+        .then((unlisten) => {
+          unlistenPluginPages = unlisten;
+        })
+        .catch((error) => {
+          console.error("Failed to listen for plugin page changes", error);
+        });
+    }
+
     onCleanup(() => {
+      unlistenPluginPages?.();
       scrollMeter.dispose();
       cancelPendingSave(resolvePageUid(activePageUid()));
       if (highlightTimeout) {
@@ -1,5 +1,10 @@
 import { createSignal, type Accessor } from "solid-js";
-import type { ReviewQueueItem, ReviewQueueSummary, ReviewTemplate } from "../../../entities/review/model/review-types";
+import type {
+  ReviewAction,
+  ReviewQueueItem,
+  ReviewQueueSummary,
+  ReviewTemplate
+} from "../../../entities/review/model/review-types";
 import { formatReviewDate } from "./review-utils";
 
 type InvokeFn = typeof import("@tauri-apps/api/core").invoke;
@@ -99,7 +104,7 @@ export const createReviewState = (deps: ReviewDeps) => {
     }
   };
 
-  const handleReviewAction = async (item: ReviewQueueItem, action: string) => {
+  const handleReviewAction = async (item: ReviewQueueItem, action: ReviewAction) => {
     if (!deps.isTauri()) return;
     setReviewBusy(true);
     try {
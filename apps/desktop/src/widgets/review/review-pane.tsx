@@ -1,11 +1,20 @@
 import { For, Show, type Accessor, type Setter } from "solid-js";
 import type {
+  ReviewAction,
+  ReviewGrade,
   ReviewQueueItem,
   ReviewQueueSummary,
   ReviewTemplate
 } from "../../entities/review/model/review-types";
 import { EmptyState } from "../../shared/ui/empty-state";
 
+const REVIEW_GRADES: { grade: ReviewGrade; label: string }[] = [
+  { grade: "again", label: "Again" },
+  { grade: "hard", label: "Hard" },
+  { grade: "good", label: "Good" },
+  { grade: "easy", label: "Easy" }
+];
+
 type ReviewPaneProps = {
   summary: Accessor<ReviewQueueSummary>;
   items: Accessor<ReviewQueueItem[]>;
@@ -15,7 +24,7 @@ type ReviewPaneProps = {
   selectedTemplate: Accessor<string>;
   setSelectedTemplate: Setter<string>;
   formatReviewDate: (value: number | null) => string;
-  onAction: (item: ReviewQueueItem, action: "snooze" | "later" | "done") => void;
+  onAction: (item: ReviewQueueItem, action: ReviewAction) => void;
   onCreateTemplate: () => void;
   isTauri: () => boolean;
   activeId: Accessor<string | null>;
@@ -57,26 +66,31 @@ export const ReviewPane = (props: ReviewPaneProps) => (
               <div class="review-card__meta">
                 <span>{item.page_uid}</span>
                 <span>Due {props.formatReviewDate(item.due_at)}</span>
+                <Show when={item.repetitions > 0}>
+                  <span>
+                    {item.repetitions} in a row · every {item.interval_days}d
+                  </span>
+                </Show>
               </div>
               <div class="review-card__text">{item.text || "Untitled"}</div>
               <div class="review-card__actions">
+                <For each={REVIEW_GRADES}>
+                  {(entry) => (
+                    <button
+                      class={`review-card__button ${
+                        entry.grade === "good" ? "is-primary" : ""
+                      }`}
+                      disabled={props.busy()}
+                      onClick={() => props.onAction(item, entry.grade)}
+                    >
+                      {entry.label}
+                    </button>
+                  )}
+                </For>
                 <button
                   class="review-card__button"
                   disabled={props.busy()}
-                  onClick={() => props.onAction(item, "snooze")}
-                >
-                  Snooze
-                </button>
-                <button
-                  class="review-card__button"
-                  disabled={props.busy()}
-                  onClick={() => props.onAction(item, "later")}
-                >
-                  Schedule
-                </button>
-                <button
-                  class="review-card__button is-primary"
-                  disabled={props.busy()}
+                  title="Remove from the review queue"
                   onClick={() => props.onAction(item, "done")}
                 >
                   Done
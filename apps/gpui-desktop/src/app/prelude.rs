@@ -19,18 +19,31 @@ pub(crate) use sandpaper_core::{
     app::{self, AppError},
     blocks::BlockType,
     db::{
-        BlockPageRecord, BlockSnapshot, Database, PagePropertyRecord, PageRecord,
-        PropertyDefinition,
+        BlockEmbed, BlockPageRecord, BlockSnapshot, Database, PagePropertyRecord, PageRecord,
+        PropertyDefinition, TagSummary,
     },
     editor::EditorModel,
-    links::{extract_block_refs, extract_wikilinks, replace_wikilinks_in_text, strip_wikilinks},
+    flashcards::{flashcard_at, Flashcard},
+    fuzzy::{fuzzy_score, TitleIndex},
+    links::{format_tag, strip_wikilinks},
+    ops::{invert_ops, EditorOp},
     plugins::{
         check_manifest_compatibility, discover_plugins, list_plugins, PluginBlockView,
-        PluginCommand, PluginDescriptor, PluginInfo, PluginPanel, PluginRegistry, PluginRenderer,
-        PluginRuntime, PluginRuntimeError, PluginRuntimeLoadResult, PluginSettingSchema,
-        PluginSettingsSchema, PluginToolbarAction,
+        PluginCommand, PluginDescriptor, PluginInfo, PluginPanel, PluginPanelNode, PluginPanelView,
+        PluginRegistry, PluginRenderer, PluginRuntime, PluginRuntimeError, PluginRuntimeLoadResult,
+        PluginSettingSchema, PluginSettingsSchema, PluginToolbarAction,
+    },
+    properties::{parse_block_properties, strip_block_properties, ALIAS_KEY},
+    review::{format_review_interval, ReviewCard, ReviewGrade, ReviewStats, DAY_MS},
+    search::{snippet_highlights, SearchHit, SearchQuery},
+    tasks::{
+        is_task_property, parse_task, set_task_state, strip_task_properties, toggle_task, Agenda,
+        Task, TaskFilter, TaskRecord, TaskState,
     },
     vaults::{VaultRecord, VaultStore},
+    views::{
+        group_rows, parse_select_options, DatabaseViewConfig, PageQueryRow, ViewLayout, TITLE_KEY,
+    },
 };
 pub(crate) use serde_json::Value;
 pub(crate) use std::collections::{HashMap, HashSet};
@@ -1,12 +1,16 @@
 use crate::app::prelude::*;
 
+pub(crate) mod agenda;
 mod agent_debug;
+pub(crate) mod block_refs;
 pub(crate) mod connections;
 pub(crate) mod constants;
 mod data;
 mod diagram;
 mod editor;
 pub(crate) mod helpers;
+pub(crate) mod history;
+pub(crate) mod journals;
 mod lifecycle;
 pub(crate) mod markdown;
 mod notifications;
@@ -15,8 +19,10 @@ pub(crate) mod outline;
 mod palette;
 mod plugin_blocks;
 pub(crate) mod plugins;
+pub(crate) mod query_blocks;
 mod shadow_writer;
 mod state;
+pub(crate) mod templates;
 
 pub(crate) use notifications::{NotificationItem, NotificationKind};
 pub(crate) use state::{
@@ -28,6 +34,7 @@ pub(crate) enum Mode {
     Capture,
     Editor,
     Review,
+    Journals,
 }
 
 impl Mode {
@@ -36,6 +43,7 @@ impl Mode {
             Mode::Capture => "capture",
             Mode::Editor => "editor",
             Mode::Review => "review",
+            Mode::Journals => "journals",
         }
     }
 
@@ -44,6 +52,7 @@ impl Mode {
             "capture" => Some(Mode::Capture),
             "editor" => Some(Mode::Editor),
             "review" => Some(Mode::Review),
+            "journals" => Some(Mode::Journals),
             _ => None,
         }
     }
@@ -55,6 +64,8 @@ pub(crate) enum WorkspacePanel {
     Backlinks,
     Plugins,
     Connections,
+    History,
+    Agenda,
 }
 
 impl WorkspacePanel {
@@ -64,6 +75,8 @@ impl WorkspacePanel {
             WorkspacePanel::Backlinks => "backlinks",
             WorkspacePanel::Plugins => "plugins",
             WorkspacePanel::Connections => "connections",
+            WorkspacePanel::History => "history",
+            WorkspacePanel::Agenda => "agenda",
         }
     }
 
@@ -73,6 +86,8 @@ impl WorkspacePanel {
             "backlinks" => Some(WorkspacePanel::Backlinks),
             "plugins" => Some(WorkspacePanel::Plugins),
             "connections" => Some(WorkspacePanel::Connections),
+            "history" => Some(WorkspacePanel::History),
+            "agenda" => Some(WorkspacePanel::Agenda),
             _ => None,
         }
     }
@@ -218,6 +233,7 @@ pub(crate) enum SlashAction {
     TextTransform,
     SetBlockType(BlockType),
     InsertImage,
+    InsertTemplate,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -293,6 +309,11 @@ pub(crate) const SLASH_COMMANDS: &[SlashCommandDef] = &[
         label: "Image",
         action: SlashAction::InsertImage,
     },
+    SlashCommandDef {
+        id: "template",
+        label: "Insert template",
+        action: SlashAction::InsertTemplate,
+    },
     SlashCommandDef {
         id: "bold",
         label: "Bold",
@@ -313,6 +334,13 @@ pub(crate) struct BacklinkEntry {
     pub(crate) text: String,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct DatabaseCellEdit {
+    pub(crate) page_id: i64,
+    pub(crate) key: String,
+    pub(crate) value_type: String,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct UnlinkedReference {
     pub(crate) block_uid: String,
@@ -329,6 +357,8 @@ pub(crate) struct ReviewDisplayItem {
     pub(crate) page_title: String,
     pub(crate) text: String,
     pub(crate) due_at: i64,
+    pub(crate) card: ReviewCard,
+    pub(crate) flashcard: Option<Flashcard>,
 }
 
 #[derive(Clone, Debug)]
@@ -393,10 +423,13 @@ impl PaneSelection {
     }
 }
 
+/// Pane state around a structural edit. Block content is not copied; the edit's ops carry it.
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct PaneHistorySnapshot {
     pub(crate) page: PageRecord,
-    pub(crate) editor: EditorModel,
+    pub(crate) active_ix: usize,
+    /// Length of the editor's pending op log when the snapshot was taken.
+    pub(crate) op_mark: usize,
     pub(crate) selection: PaneSelection,
     pub(crate) dirty: bool,
     pub(crate) cursor: usize,
@@ -407,6 +440,7 @@ pub(crate) struct StructuralHistoryEntry {
     pub(crate) pane: EditorPane,
     pub(crate) before: PaneHistorySnapshot,
     pub(crate) after: PaneHistorySnapshot,
+    pub(crate) ops: Vec<EditorOp>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -480,6 +514,11 @@ pub(crate) enum PaletteAction {
     OpenPluginPanel(PluginPanel),
     ClosePluginPanel,
     OpenPage(String),
+    OpenBlock {
+        page_uid: String,
+        block_uid: String,
+    },
+    InsertTemplate(String),
     ToggleFocusMode,
     OpenQuickCapture,
     SwitchMode(Mode),
@@ -583,6 +622,10 @@ pub(crate) struct WikilinkMenuState {
     pub(crate) range_end: Option<usize>,
     pub(crate) has_closing: bool,
     pub(crate) query: String,
+    pub(crate) tag: bool,
+    pub(crate) tag_matches: Vec<TagSummary>,
+    pub(crate) block_ref: bool,
+    pub(crate) block_matches: Vec<BlockPageRecord>,
     pub(crate) selected_index: usize,
     pub(crate) layer_priority: usize,
 }
@@ -598,6 +641,10 @@ impl WikilinkMenuState {
             range_end: None,
             has_closing: false,
             query: String::new(),
+            tag: false,
+            tag_matches: Vec::new(),
+            block_ref: false,
+            block_matches: Vec::new(),
             selected_index: 0,
             layer_priority: 0,
         }
@@ -670,7 +717,10 @@ pub(crate) struct PluginBlockPreviewState {
 #[derive(Clone, Debug)]
 pub(crate) enum WikilinkMenuItem {
     Page(PageRecord),
+    Alias { page: PageRecord, alias: String },
     Create { label: String, query: String },
+    Tag(TagSummary),
+    Block(BlockPageRecord),
 }
 
 #[allow(dead_code)]
@@ -753,6 +803,7 @@ actions!(
         SwitchToCapture,
         SwitchToEdit,
         SwitchToReview,
+        SwitchToJournals,
         UndoEdit,
         RedoEdit,
         OpenKeyboardShortcuts,
@@ -899,6 +950,7 @@ pub fn bind_keys(cx: &mut App) {
         KeyBinding::new("cmd-1", SwitchToCapture, None),
         KeyBinding::new("cmd-2", SwitchToEdit, None),
         KeyBinding::new("cmd-3", SwitchToReview, None),
+        KeyBinding::new("cmd-4", SwitchToJournals, None),
         KeyBinding::new("cmd-/", OpenKeyboardShortcuts, None),
     ]);
 
@@ -925,6 +977,7 @@ pub fn bind_keys(cx: &mut App) {
         KeyBinding::new("ctrl-1", SwitchToCapture, None),
         KeyBinding::new("ctrl-2", SwitchToEdit, None),
         KeyBinding::new("ctrl-3", SwitchToReview, None),
+        KeyBinding::new("ctrl-4", SwitchToJournals, None),
         KeyBinding::new("ctrl-/", OpenKeyboardShortcuts, None),
     ]);
 }
@@ -1004,7 +1057,7 @@ mod tests {
     use super::helpers::{
         apply_slash_command_text, count_case_insensitive_occurrences,
         count_case_insensitive_occurrences_outside_wikilinks, cycle_index, filter_slash_commands,
-        find_slash_query, find_wikilink_query, fuzzy_score, link_first_unlinked_reference,
+        find_slash_query, find_wikilink_query, link_first_unlinked_reference,
         parse_wikilink_tokens, resolve_cursor_for_blocks, score_palette_page, PageCursor,
         WikilinkToken,
     };
@@ -1013,6 +1066,7 @@ mod tests {
     use super::{SlashAction, SlashCommandDef, SLASH_COMMANDS};
     use sandpaper_core::blocks::BlockType;
     use sandpaper_core::db::BlockSnapshot;
+    use sandpaper_core::fuzzy::fuzzy_score;
 
     #[test]
     fn selection_set_range_normalizes() {
@@ -1135,14 +1189,14 @@ mod tests {
 
     #[test]
     fn score_palette_page_prioritizes_recency_when_query_empty() {
-        let recent = score_palette_page("", "Title", "", Some(0)).unwrap();
-        let older = score_palette_page("", "Title", "", Some(4)).unwrap();
+        let recent = score_palette_page("", Some(0), "", Some(0)).unwrap();
+        let older = score_palette_page("", Some(0), "", Some(4)).unwrap();
         assert!(recent > older);
     }
 
     #[test]
     fn score_palette_page_filters_non_matches() {
-        assert!(score_palette_page("foo", "Bar", "", Some(0)).is_none());
+        assert!(score_palette_page("foo", fuzzy_score("foo", "Bar"), "", Some(0)).is_none());
     }
 
     #[test]
@@ -1248,6 +1302,15 @@ mod tests {
         assert_eq!(image.action, SlashAction::InsertImage);
     }
 
+    #[test]
+    fn slash_commands_include_template_picker() {
+        let template = SLASH_COMMANDS
+            .iter()
+            .find(|command| command.id == "template")
+            .expect("template slash command");
+        assert_eq!(template.action, SlashAction::InsertTemplate);
+    }
+
     #[test]
     fn apply_slash_command_heading_sets_prefix() {
         let (next, cursor) = apply_slash_command_text("h2", "Note ", "", "2024-01-01");
@@ -0,0 +1,68 @@
+use super::*;
+
+/// Days after today that the agenda lists as upcoming.
+const AGENDA_UPCOMING_DAYS: i64 = 14;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AgendaState {
+    pub(crate) agenda: Agenda,
+    /// Open tasks across all pages, for the count in the panel header.
+    pub(crate) open_count: usize,
+}
+
+impl AppStore {
+    fn agenda_panel_visible(&self) -> bool {
+        self.settings.context_panel_open
+            && self.settings.context_panel_tab == WorkspacePanel::Agenda
+    }
+
+    /// Rebuilds the agenda from every open task while the agenda panel is showing.
+    pub(crate) fn refresh_agenda(&mut self) {
+        if !self.agenda_panel_visible() {
+            return;
+        }
+        let tasks = self
+            .app
+            .db
+            .as_ref()
+            .and_then(|db| db.query_tasks(&TaskFilter::open()).ok())
+            .unwrap_or_default();
+        let today = chrono::Local::now().date_naive();
+        self.editor.agenda = AgendaState {
+            open_count: tasks.len(),
+            agenda: Agenda::build(tasks, today, AGENDA_UPCOMING_DAYS),
+        };
+    }
+
+    /// Applies a state change made from the agenda, whether or not the task's page is open.
+    pub(crate) fn set_agenda_task_state(
+        &mut self,
+        record: &TaskRecord,
+        state: TaskState,
+        cx: &mut Context<Self>,
+    ) {
+        let now = chrono::Local::now().naive_local();
+        let text = [EditorPane::Primary, EditorPane::Secondary]
+            .into_iter()
+            .filter_map(|pane| self.editor_for_pane(pane))
+            .flat_map(|editor| editor.blocks().iter())
+            .find(|block| block.uid == record.block_uid)
+            .map(|block| block.text.clone())
+            .or_else(|| {
+                self.app
+                    .db
+                    .as_ref()
+                    .and_then(|db| db.lookup_blocks(&[record.block_uid.clone()]).ok())
+                    .and_then(|mut found| found.remove(&record.block_uid))
+                    .map(|found| found.text)
+            });
+        let Some(text) = text else {
+            return;
+        };
+        if !self.write_block_text(&record.block_uid, &set_task_state(&text, state, now), cx) {
+            return;
+        }
+        self.refresh_agenda();
+        cx.notify();
+    }
+}
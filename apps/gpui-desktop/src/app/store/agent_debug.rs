@@ -112,11 +112,11 @@ impl DebugSnapshotProvider for AppStore {
         let (block_count, active_block_index, active_block_text, active_block_type) =
             match self.editor_for_pane(pane) {
                 Some(editor) => {
-                    let count = editor.blocks.len();
+                    let count = editor.blocks().len();
                     let idx = editor.active_ix;
-                    let text = editor.blocks.get(idx).map(|b| b.text.clone());
+                    let text = editor.blocks().get(idx).map(|b| b.text.clone());
                     let btype = editor
-                        .blocks
+                        .blocks()
                         .get(idx)
                         .map(|b| format!("{:?}", b.block_type));
                     (count, Some(idx), text, btype)
@@ -381,9 +381,7 @@ impl ActionExecutor for AppStore {
                 let pane = self.editor.active_pane;
                 if let Some(editor) = self.editor_for_pane_mut(pane) {
                     let idx = editor.active_ix;
-                    if idx < editor.blocks.len() {
-                        editor.blocks[idx].text = text.clone();
-                    }
+                    editor.set_text(idx, &text);
                 }
                 let input = self.editor.block_input.clone();
                 let text_for_input = text.clone();
@@ -505,13 +503,13 @@ impl ActionExecutor for AppStore {
                     SlashAction::SetBlockType(block_type) => {
                         if let Some(editor) = self.editor_for_pane_mut(pane) {
                             let idx = editor.active_ix;
-                            if idx < editor.blocks.len() {
+                            if idx < editor.blocks().len() {
                                 let cleaned = helpers::clean_text_for_block_type(
-                                    &editor.blocks[idx].text,
+                                    &editor.blocks()[idx].text,
                                     block_type,
                                 );
-                                editor.blocks[idx].text = cleaned.clone();
-                                editor.blocks[idx].block_type = block_type;
+                                editor.set_text(idx, &cleaned);
+                                editor.set_block_type(idx, block_type);
                             }
                         }
                         self.sync_block_input_from_active_for_pane(pane, None, cx);
@@ -522,13 +520,13 @@ impl ActionExecutor for AppStore {
                     SlashAction::InsertImage => {
                         if let Some(editor) = self.editor_for_pane_mut(pane) {
                             let idx = editor.active_ix;
-                            if idx < editor.blocks.len() {
+                            if idx < editor.blocks().len() {
                                 let cleaned = helpers::clean_text_for_block_type(
-                                    &editor.blocks[idx].text,
+                                    &editor.blocks()[idx].text,
                                     BlockType::Image,
                                 );
-                                editor.blocks[idx].text = cleaned;
-                                editor.blocks[idx].block_type = BlockType::Image;
+                                editor.set_text(idx, &cleaned);
+                                editor.set_block_type(idx, BlockType::Image);
                             }
                         }
                         self.sync_block_input_from_active_for_pane(pane, None, cx);
@@ -543,6 +541,13 @@ impl ActionExecutor for AppStore {
                             "text transform commands are not supported via debug API",
                         ));
                     }
+                    SlashAction::InsertTemplate => {
+                        return Err(DebugResponse::error(
+                            422,
+                            "unsupported_command",
+                            "template commands open a dialog and are not supported via debug API",
+                        ));
+                    }
                 }
             }
             ("page-dialog", "click") | ("new-page-action", "click") => {
@@ -910,10 +915,7 @@ mod tests {
                     uid: "inbox".to_string(),
                     title: "Inbox".to_string(),
                 });
-                app.editor.editor = Some(EditorModel {
-                    blocks: Vec::new(),
-                    active_ix: 0,
-                });
+                app.editor.editor = Some(EditorModel::default());
                 app.update_block_list_for_pane(EditorPane::Primary);
 
                 let request = DebugActRequest {
@@ -924,7 +926,7 @@ mod tests {
                 let result = app.execute_debug_action(&request, cx);
                 assert!(result.is_ok());
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert!(!editor.blocks.is_empty());
+                assert!(!editor.blocks().is_empty());
             });
         })
         .expect("window update");
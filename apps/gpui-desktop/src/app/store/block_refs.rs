@@ -0,0 +1,274 @@
+use super::*;
+use sandpaper_core::links::{extract_block_refs, extract_embeds, strip_block_refs, EmbedTarget};
+
+const EMBED_HEADER_HEIGHT: f32 = 26.0;
+const EMBED_PADDING_HEIGHT: f32 = 16.0;
+/// Rows shown for one embed; longer pages end in a "more" line.
+pub(crate) const EMBED_MAX_ROWS: usize = 50;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct EmbedEdit {
+    pub(crate) host_uid: String,
+    pub(crate) block_uid: String,
+}
+
+/// Referenced blocks resolved for the open panes, refreshed alongside backlinks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockRefState {
+    pub(crate) targets: HashMap<String, BlockPageRecord>,
+    pub(crate) embeds: HashMap<EmbedTarget, BlockEmbed>,
+    pub(crate) counts: HashMap<String, i64>,
+    pub(crate) edit: Option<EmbedEdit>,
+}
+
+/// Returns what the block embeds when its whole text is `{{embed ((uid))}}` or
+/// `{{embed [[page]]}}`.
+pub(crate) fn block_embed_target(text: &str) -> Option<EmbedTarget> {
+    let trimmed = text.trim();
+    if !trimmed.starts_with("{{") || !trimmed.ends_with("}}") {
+        return None;
+    }
+    match extract_embeds(trimmed).as_slice() {
+        [target] => Some(target.clone()),
+        _ => None,
+    }
+}
+
+impl AppStore {
+    fn open_pane_blocks(&self) -> impl Iterator<Item = &BlockSnapshot> {
+        let primary = self
+            .editor
+            .editor
+            .iter()
+            .flat_map(|editor| editor.blocks().iter());
+        let secondary = self
+            .editor
+            .secondary_pane
+            .iter()
+            .flat_map(|pane| pane.editor.blocks().iter());
+        primary.chain(secondary)
+    }
+
+    /// Resolves every `((uid))`, block embed and page embed on the open pages and counts how
+    /// often their own blocks are referenced elsewhere.
+    pub(crate) fn refresh_block_refs(&mut self) {
+        let mut referenced = Vec::new();
+        let mut embedded = Vec::new();
+        let mut own = Vec::new();
+        for block in self.open_pane_blocks() {
+            own.push(block.uid.clone());
+            referenced.extend(extract_block_refs(&block.text));
+            if let Some(target) = block_embed_target(&block.text) {
+                if !embedded.contains(&target) {
+                    embedded.push(target);
+                }
+            }
+        }
+        referenced.sort();
+        referenced.dedup();
+
+        let Some(db) = self.app.db.as_ref() else {
+            return;
+        };
+        let targets = db.lookup_blocks(&referenced).unwrap_or_default();
+        let counts = db.count_block_references(&own).unwrap_or_default();
+        let embeds = embedded
+            .into_iter()
+            .filter_map(|target| {
+                let embed = match &target {
+                    EmbedTarget::Block(uid) => db.load_block_embed(uid),
+                    EmbedTarget::Page(name) => db.load_page_embed(name),
+                };
+                Some((target, embed.ok().flatten()?))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let embeds_changed = embeds != self.editor.block_refs.embeds;
+        let refs = &mut self.editor.block_refs;
+        refs.targets = targets;
+        refs.counts = counts;
+        refs.embeds = embeds;
+        if embeds_changed {
+            self.update_block_list_for_pane(EditorPane::Primary);
+            self.update_block_list_for_pane(EditorPane::Secondary);
+        }
+    }
+
+    /// Text of `uid` in an open pane, which may be ahead of what is saved.
+    pub(crate) fn live_block_text(&self, uid: &str) -> Option<String> {
+        self.open_pane_blocks()
+            .find(|block| block.uid == uid)
+            .map(|block| block.text.clone())
+    }
+
+    /// Current text of a referenced block, preferring unsaved edits in an open pane.
+    pub(crate) fn block_ref_text(&self, uid: &str) -> Option<String> {
+        let text = self.live_block_text(uid).or_else(|| {
+            self.editor
+                .block_refs
+                .targets
+                .get(uid)
+                .map(|record| record.text.clone())
+        })?;
+        Some(strip_block_refs(&strip_block_properties(&text)))
+    }
+
+    pub(crate) fn block_ref_count(&self, uid: &str) -> i64 {
+        self.editor.block_refs.counts.get(uid).copied().unwrap_or(0)
+    }
+
+    pub(crate) fn embed_for_text(&self, text: &str) -> Option<(EmbedTarget, &BlockEmbed)> {
+        let target = block_embed_target(text)?;
+        let embed = self.editor.block_refs.embeds.get(&target)?;
+        Some((target, embed))
+    }
+
+    fn embedded_block(&self, uid: &str) -> Option<&BlockSnapshot> {
+        self.editor
+            .block_refs
+            .embeds
+            .values()
+            .flat_map(|embed| embed.blocks.iter())
+            .find(|block| block.uid == uid)
+    }
+
+    pub(crate) fn embed_extra_height(&self, text: &str) -> f32 {
+        let Some((_, embed)) = self.embed_for_text(text) else {
+            return 0.0;
+        };
+        let rows = embed.blocks.len().min(EMBED_MAX_ROWS + 1);
+        EMBED_HEADER_HEIGHT + EMBED_PADDING_HEIGHT + rows as f32 * BLOCK_ROW_HEIGHT
+    }
+
+    pub(crate) fn open_block_ref(
+        &mut self,
+        uid: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.focus_block_by_uid(uid, Some(&mut *window), cx) {
+            return;
+        }
+        let page_uid = self
+            .editor
+            .block_refs
+            .targets
+            .get(uid)
+            .map(|record| record.page_uid.clone())
+            .or_else(|| {
+                self.editor
+                    .block_refs
+                    .embeds
+                    .values()
+                    .find(|embed| embed.blocks.iter().any(|block| block.uid == uid))
+                    .map(|embed| embed.page_uid.clone())
+            });
+        if let Some(page_uid) = page_uid {
+            self.open_page_and_focus_block(&page_uid, uid, window, cx);
+        }
+    }
+
+    pub(crate) fn begin_embed_edit(
+        &mut self,
+        edit: EmbedEdit,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(text) = self.live_block_text(&edit.block_uid).or_else(|| {
+            self.embedded_block(&edit.block_uid)
+                .map(|block| block.text.clone())
+        }) else {
+            return;
+        };
+        self.editor.block_refs.edit = Some(edit);
+        self.editor.embed_input.update(cx, |input, cx| {
+            input.set_value(text, window, cx);
+        });
+        window.focus(&self.editor.embed_input.focus_handle(cx), cx);
+        cx.notify();
+    }
+
+    pub(crate) fn commit_embed_edit(&mut self, cx: &mut Context<Self>) {
+        let Some(edit) = self.editor.block_refs.edit.take() else {
+            return;
+        };
+        let value = self.editor.embed_input.read(cx).value().to_string();
+        if !self.write_block_text(&edit.block_uid, &value, cx) {
+            self.editor.block_refs.edit = Some(edit);
+            cx.notify();
+            return;
+        }
+        self.refresh_block_refs();
+        self.schedule_references_refresh(cx);
+        cx.notify();
+    }
+
+    /// Replaces a block's text wherever it lives: through the pane editor when its page is open,
+    /// otherwise straight to the database. Returns false if the database write failed.
+    pub(crate) fn write_block_text(
+        &mut self,
+        block_uid: &str,
+        value: &str,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let open_in = [EditorPane::Primary, EditorPane::Secondary]
+            .into_iter()
+            .find_map(|pane| {
+                let editor = self.editor_for_pane(pane)?;
+                let ix = editor
+                    .blocks()
+                    .iter()
+                    .position(|block| block.uid == block_uid)?;
+                Some((pane, ix))
+            });
+        if let Some((pane, ix)) = open_in {
+            let history_before = self.pane_snapshot(pane, cx);
+            let is_active = {
+                let Some(editor) = self.editor_for_pane_mut(pane) else {
+                    return true;
+                };
+                if !editor.set_text(ix, value) {
+                    return true;
+                }
+                editor.active_ix == ix
+            };
+            self.update_block_list_for_pane(pane);
+            if is_active {
+                self.sync_block_input_from_active_for_pane(pane, None, cx);
+            }
+            self.mark_dirty_for_pane(pane, cx);
+            self.record_structural_history_if_changed(pane, history_before, cx);
+            true
+        } else if let Some(db) = self.app.db.as_ref() {
+            db.update_block_text_by_uid(block_uid, value).is_ok()
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn cancel_embed_edit(&mut self, cx: &mut Context<Self>) {
+        if self.editor.block_refs.edit.take().is_some() {
+            cx.notify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::block_embed_target;
+    use sandpaper_core::links::EmbedTarget;
+
+    #[test]
+    fn block_embed_target_requires_a_whole_block_embed() {
+        assert_eq!(
+            block_embed_target("  {{embed ((b-1))}} "),
+            Some(EmbedTarget::Block("b-1".to_string()))
+        );
+        assert_eq!(
+            block_embed_target("{{embed [[Weekly Plan]]}}"),
+            Some(EmbedTarget::Page("Weekly Plan".to_string()))
+        );
+        assert_eq!(block_embed_target("see {{embed ((b-1))}}"), None);
+        assert_eq!(block_embed_target("((b-1))"), None);
+    }
+}
@@ -1,5 +1,10 @@
 use super::helpers::{format_snippet, now_millis};
 use super::*;
+use sandpaper_core::plugin_events::PluginEvent;
+
+/// Answers counted towards the retention shown in the review panel.
+const REVIEW_STATS_WINDOW_DAYS: i64 = 30;
+const REVIEW_FORECAST_DAYS: usize = 7;
 
 impl AppStore {
     pub(crate) fn schedule_highlight_clear(&mut self, cx: &mut Context<Self>) {
@@ -54,6 +59,15 @@ impl AppStore {
             self.editor.search_blocks.clear();
             return;
         };
+        let parsed = db
+            .parse_search_query(query)
+            .unwrap_or_else(|_| SearchQuery::parse(query));
+        self.editor.search_blocks = db.search(&parsed, 20).unwrap_or_default();
+        // Scoped queries (page:, tag:, type:, ...) only make sense for blocks.
+        if parsed.has_filters() || !parsed.excluded.is_empty() {
+            self.editor.search_pages.clear();
+            return;
+        }
         let page_ids = db.search_pages(query).unwrap_or_default();
         let lookup: HashMap<i64, PageRecord> = self
             .editor
@@ -66,9 +80,6 @@ impl AppStore {
             .iter()
             .filter_map(|id| lookup.get(id).cloned())
             .collect();
-        self.editor.search_blocks = db
-            .search_block_page_summaries(query, 20)
-            .unwrap_or_default();
     }
 
     pub(crate) fn schedule_references_refresh(&mut self, cx: &mut Context<Self>) {
@@ -95,6 +106,7 @@ impl AppStore {
         self.editor.backlinks.clear();
         self.editor.unlinked_references.clear();
         self.editor.block_backlinks.clear();
+        self.editor.tagged_blocks.clear();
 
         let Some(active_page) = self.editor.active_page.as_ref() else {
             return;
@@ -104,30 +116,36 @@ impl AppStore {
         };
 
         let active_uid = active_page.uid.clone();
-        let active_title = active_page.title.clone();
 
-        if let Ok(records) = db.list_blocks_with_wikilinks() {
-            for record in records {
-                if record.page_uid == active_uid {
-                    continue;
-                }
-                let mut is_match = false;
-                for link in extract_wikilinks(&record.text) {
-                    let target_uid = app::sanitize_kebab(&link);
-                    if target_uid == active_uid || link.eq_ignore_ascii_case(&active_title) {
-                        is_match = true;
-                        break;
-                    }
-                }
-                if is_match {
-                    self.editor.backlinks.push(BacklinkEntry {
-                        block_uid: record.block_uid,
-                        page_uid: record.page_uid,
-                        page_title: record.page_title,
-                        text: record.text,
-                    });
-                }
-            }
+        if let Ok(records) = db.list_tagged_blocks(&active_uid) {
+            self.editor.tagged_blocks = records
+                .into_iter()
+                .map(|record| BacklinkEntry {
+                    block_uid: record.block_uid,
+                    page_uid: record.page_uid,
+                    page_title: record.page_title,
+                    text: record.text,
+                })
+                .collect();
+        }
+        let tagged_uids: HashSet<&str> = self
+            .editor
+            .tagged_blocks
+            .iter()
+            .map(|entry| entry.block_uid.as_str())
+            .collect();
+
+        if let Ok(records) = db.list_backlinks(&active_uid) {
+            self.editor.backlinks = records
+                .into_iter()
+                .filter(|record| !tagged_uids.contains(record.block_uid.as_str()))
+                .map(|record| BacklinkEntry {
+                    block_uid: record.block_uid,
+                    page_uid: record.page_uid,
+                    page_title: record.page_title,
+                    text: record.text,
+                })
+                .collect();
         }
         if self.editor.backlinks.len() > 20 {
             self.editor.backlinks.truncate(20);
@@ -146,7 +164,7 @@ impl AppStore {
             .cloned()
             .collect::<Vec<_>>();
 
-        for block in editor.blocks.iter() {
+        for block in editor.blocks().iter() {
             let stripped = strip_wikilinks(&block.text);
             if stripped.trim().is_empty() {
                 continue;
@@ -180,6 +198,8 @@ impl AppStore {
             self.editor.unlinked_references.truncate(12);
         }
 
+        self.refresh_block_refs();
+        self.refresh_query_blocks();
         self.refresh_block_backlinks();
     }
 
@@ -189,7 +209,7 @@ impl AppStore {
         let Some(editor) = self.editor.editor.as_ref() else {
             return;
         };
-        if editor.blocks.is_empty() {
+        if editor.blocks().is_empty() {
             return;
         }
         let Some(db) = self.app.db.as_ref() else {
@@ -198,27 +218,16 @@ impl AppStore {
 
         let active_block_uid = editor.active().uid.clone();
 
-        if let Ok(records) = db.list_blocks_with_block_refs() {
-            for record in records {
-                if record.block_uid == active_block_uid {
-                    continue;
-                }
-                let mut is_match = false;
-                for link in extract_block_refs(&record.text) {
-                    if link == active_block_uid {
-                        is_match = true;
-                        break;
-                    }
-                }
-                if is_match {
-                    self.editor.block_backlinks.push(BacklinkEntry {
-                        block_uid: record.block_uid,
-                        page_uid: record.page_uid,
-                        page_title: record.page_title,
-                        text: record.text,
-                    });
-                }
-            }
+        if let Ok(records) = db.list_block_backlinks(&active_block_uid) {
+            self.editor.block_backlinks = records
+                .into_iter()
+                .map(|record| BacklinkEntry {
+                    block_uid: record.block_uid,
+                    page_uid: record.page_uid,
+                    page_title: record.page_title,
+                    text: record.text,
+                })
+                .collect();
         }
 
         if self.editor.block_backlinks.len() > 20 {
@@ -259,17 +268,16 @@ impl AppStore {
                 .map(|page| page.title.clone())
                 .unwrap_or_else(|| page_uid.clone());
 
-            let text = if let Some(page) = &page {
+            let (text, flashcard) = if let Some(page) = &page {
                 let blocks = blocks_cache
                     .entry(page.uid.clone())
                     .or_insert_with(|| db.load_blocks_for_page(page.id).unwrap_or_default());
-                blocks
-                    .iter()
-                    .find(|block| block.uid == block_uid)
-                    .map(|block| block.text.clone())
-                    .unwrap_or_else(|| "Block not found.".to_string())
+                match blocks.iter().position(|block| block.uid == block_uid) {
+                    Some(ix) => (blocks[ix].text.clone(), flashcard_at(blocks, ix)),
+                    None => ("Block not found.".to_string(), None),
+                }
             } else {
-                "Block not found.".to_string()
+                ("Block not found.".to_string(), None)
             };
 
             display_items.push(ReviewDisplayItem {
@@ -279,10 +287,32 @@ impl AppStore {
                 page_title,
                 text,
                 due_at: item.due_at,
+                card: item.card,
+                flashcard,
             });
         }
 
+        let day_start = Local::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .map(|midnight| midnight.timestamp_millis())
+            .unwrap_or(now);
+        self.editor.review_stats = db
+            .review_stats(now, now - REVIEW_STATS_WINDOW_DAYS * DAY_MS)
+            .unwrap_or_default();
+        self.editor.review_forecast = db
+            .review_forecast(day_start, REVIEW_FORECAST_DAYS)
+            .unwrap_or_default();
+
         self.editor.review_items = display_items;
+        let ids = self
+            .editor
+            .review_items
+            .iter()
+            .map(|item| item.id)
+            .collect::<HashSet<_>>();
+        self.editor.review_revealed.retain(|id| ids.contains(id));
         if self.editor.review_items.is_empty() {
             self.editor.review_selected_index = 0;
         } else {
@@ -299,28 +329,44 @@ impl AppStore {
             return;
         };
         let now = now_millis();
-        let _ = db.mark_review_queue_item(item_id, "done", now, None);
+        let done = db
+            .mark_review_queue_item(item_id, "done", now, None)
+            .ok()
+            .and_then(|_| db.get_review_queue_item(item_id).ok().flatten());
         self.load_review_items(cx);
+        if let Some(item) = done {
+            self.dispatch_plugin_events(vec![PluginEvent::review_completed(&item, None)], cx);
+        }
     }
 
-    pub(crate) fn review_snooze_day(&mut self, item_id: i64, cx: &mut Context<Self>) {
+    pub(crate) fn review_grade(
+        &mut self,
+        item_id: i64,
+        grade: ReviewGrade,
+        cx: &mut Context<Self>,
+    ) {
         let Some(db) = self.app.db.as_ref() else {
             return;
         };
-        let now = now_millis();
-        let next = now + 86_400_000;
-        let _ = db.mark_review_queue_item(item_id, "pending", now, Some(next));
+        let graded = db
+            .grade_review_queue_item(item_id, grade, now_millis())
+            .ok()
+            .flatten();
+        self.editor.review_revealed.remove(&item_id);
         self.load_review_items(cx);
+        if let Some(item) = graded {
+            self.dispatch_plugin_events(
+                vec![PluginEvent::review_completed(&item, Some(grade))],
+                cx,
+            );
+        }
     }
 
-    pub(crate) fn review_snooze_week(&mut self, item_id: i64, cx: &mut Context<Self>) {
-        let Some(db) = self.app.db.as_ref() else {
-            return;
-        };
-        let now = now_millis();
-        let next = now + 604_800_000;
-        let _ = db.mark_review_queue_item(item_id, "pending", now, Some(next));
-        self.load_review_items(cx);
+    /// Shows the back of a flashcard, or the answers of a cloze card.
+    pub(crate) fn reveal_review_answer(&mut self, item_id: i64, cx: &mut Context<Self>) {
+        if self.editor.review_revealed.insert(item_id) {
+            cx.notify();
+        }
     }
 }
 
@@ -370,7 +416,8 @@ mod tests {
                 let db = Database::new_in_memory().expect("db init");
                 setup_db_with_pages(&db);
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 // Seed some fake results first
                 app.editor.search_pages.push(PageRecord {
@@ -397,7 +444,8 @@ mod tests {
                 let db = Database::new_in_memory().expect("db init");
                 setup_db_with_pages(&db);
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 app.editor.sidebar_search_query = "   ".to_string();
                 app.refresh_search_results();
@@ -431,7 +479,8 @@ mod tests {
                 let db = Database::new_in_memory().expect("db init");
                 setup_db_with_pages(&db);
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 app.editor.sidebar_search_query = "Alpha".to_string();
                 app.refresh_search_results();
@@ -454,7 +503,8 @@ mod tests {
                     .expect("insert block");
 
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 app.editor.sidebar_search_query = "Hello".to_string();
                 app.refresh_search_results();
@@ -465,6 +515,39 @@ mod tests {
         .unwrap();
     }
 
+    #[gpui::test]
+    fn refresh_search_applies_structured_filters(cx: &mut TestAppContext) {
+        let (app, window) = setup_app(cx);
+        cx.update_window(*window, |_root, _window, cx| {
+            app.update(cx, |app, _cx| {
+                let db = Database::new_in_memory().expect("db init");
+                setup_db_with_pages(&db);
+                let alpha = db.get_page_by_uid("alpha").unwrap().expect("alpha exists");
+                let beta = db.get_page_by_uid("beta").unwrap().expect("beta exists");
+                db.insert_block(alpha.id, "b1", None, "a", "Alpha plan #urgent", "{}")
+                    .expect("insert block");
+                db.insert_block(beta.id, "b2", None, "a", "Beta plan (draft)", "{}")
+                    .expect("insert block");
+
+                app.app.db = Some(db);
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
+
+                app.editor.sidebar_search_query = "plan tag:urgent".to_string();
+                app.refresh_search_results();
+                assert!(app.editor.search_pages.is_empty());
+                assert_eq!(app.editor.search_blocks.len(), 1);
+                assert_eq!(app.editor.search_blocks[0].block_uid, "b1");
+
+                app.editor.sidebar_search_query = "(draft".to_string();
+                app.refresh_search_results();
+                assert_eq!(app.editor.search_blocks.len(), 1);
+                assert_eq!(app.editor.search_blocks[0].block_uid, "b2");
+            });
+        })
+        .unwrap();
+    }
+
     #[gpui::test]
     fn refresh_references_finds_backlinks(cx: &mut TestAppContext) {
         let (app, window) = setup_app(cx);
@@ -486,7 +569,8 @@ mod tests {
                 .expect("insert link block");
 
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 // Open alpha page so references point to it
                 app.open_page("alpha", cx);
@@ -524,7 +608,8 @@ mod tests {
                 .expect("insert self link block");
 
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 app.open_page("alpha", cx);
 
@@ -556,7 +641,8 @@ mod tests {
                     .expect("upsert review");
 
                 app.app.db = Some(db);
-                app.editor.pages = app.app.db.as_ref().unwrap().list_pages().unwrap();
+                app.editor
+                    .set_pages(app.app.db.as_ref().unwrap().list_pages().unwrap());
 
                 app.load_review_items(cx);
                 assert_eq!(app.editor.review_items.len(), 1);
@@ -5,41 +5,7 @@ use std::path::Path;
 
 const HISTORY_MAX_ENTRIES: usize = 200;
 const TEXT_HISTORY_COALESCE_WINDOW_MS: i64 = 750;
-
-pub(crate) fn update_wikilinks_in_db(
-    db: &Database,
-    from_title: &str,
-    to_title: &str,
-) -> HashMap<String, String> {
-    let from = from_title.trim();
-    let to = to_title.trim();
-    if from.is_empty() || to.is_empty() {
-        return HashMap::new();
-    }
-    if app::sanitize_kebab(from) == app::sanitize_kebab(to) {
-        return HashMap::new();
-    }
-
-    let Ok(records) = db.list_blocks_with_wikilinks() else {
-        return HashMap::new();
-    };
-
-    let mut updated_blocks = HashMap::new();
-    for record in records {
-        let next = replace_wikilinks_in_text(&record.text, from, to);
-        if next == record.text {
-            continue;
-        }
-        if db
-            .update_block_text_by_uid(&record.block_uid, &next)
-            .is_ok()
-        {
-            updated_blocks.insert(record.block_uid, next);
-        }
-    }
-
-    updated_blocks
-}
+const WIKILINK_MENU_LIMIT: usize = 50;
 
 impl AppStore {
     fn next_popup_layer_priority(&mut self) -> usize {
@@ -70,12 +36,12 @@ impl AppStore {
         }
     }
 
-    fn pane_cursor_for_snapshot(&self, pane: EditorPane, cx: &App) -> Option<usize> {
+    pub(crate) fn pane_cursor_for_snapshot(&self, pane: EditorPane, cx: &App) -> Option<usize> {
         let editor = self.editor_for_pane(pane)?;
-        if editor.active_ix >= editor.blocks.len() {
+        if editor.active_ix >= editor.blocks().len() {
             return Some(0);
         }
-        let block = &editor.blocks[editor.active_ix];
+        let block = &editor.blocks()[editor.active_ix];
         if pane == self.editor.active_pane {
             let input = self.editor.block_input.read(cx);
             return Some(input.cursor().min(input.text().len()));
@@ -89,22 +55,23 @@ impl AppStore {
         Some(cursor.min(block.text.len()))
     }
 
-    fn pane_snapshot(&self, pane: EditorPane, cx: &App) -> Option<PaneHistorySnapshot> {
+    pub(crate) fn pane_snapshot(&self, pane: EditorPane, cx: &App) -> Option<PaneHistorySnapshot> {
         let page = self.page_for_pane(pane)?.clone();
-        let editor = self.editor_for_pane(pane)?.clone();
+        let editor = self.editor_for_pane(pane)?;
         let selection = self.selection_for_pane(pane)?.clone();
         let dirty = self.pane_dirty(pane)?;
         let cursor = self.pane_cursor_for_snapshot(pane, cx)?;
         Some(PaneHistorySnapshot {
             page,
-            editor,
+            active_ix: editor.active_ix,
+            op_mark: editor.pending_ops().len(),
             selection,
             dirty,
             cursor,
         })
     }
 
-    fn record_structural_history_if_changed(
+    pub(crate) fn record_structural_history_if_changed(
         &mut self,
         pane: EditorPane,
         before: Option<PaneHistorySnapshot>,
@@ -119,13 +86,19 @@ impl AppStore {
         let Some(after) = self.pane_snapshot(pane, cx) else {
             return;
         };
-        if before == after {
+        // Ops logged before `before` are plain typing, which text history already covers.
+        let Some(mut ops) = self.editor_for_pane_mut(pane).map(EditorModel::take_ops) else {
+            return;
+        };
+        let ops = ops.split_off(before.op_mark.min(ops.len()));
+        if ops.is_empty() || before.page.uid != after.page.uid {
             return;
         }
         self.push_history_entry(HistoryEntry::Structural(StructuralHistoryEntry {
             pane,
             before,
             after,
+            ops,
         }));
     }
 
@@ -175,43 +148,25 @@ impl AppStore {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if !self
-            .editor
-            .pages
-            .iter()
-            .any(|page| page.uid == snapshot.page.uid)
-        {
-            self.editor.pages.push(snapshot.page.clone());
-        }
-
+        let block_count = self
+            .editor_for_pane(pane)
+            .map(|editor| editor.blocks().len())
+            .unwrap_or(0);
         match pane {
             EditorPane::Primary => {
-                self.editor.active_page = Some(snapshot.page.clone());
-                self.editor.editor = Some(snapshot.editor.clone());
-                self.editor.primary_selection = snapshot.selection.clone();
                 self.app.primary_dirty = snapshot.dirty;
                 self.editor
                     .blocks_list_state
-                    .reset(snapshot.editor.blocks.len(), px(BLOCK_ROW_HEIGHT));
-                self.update_block_list_for_pane(EditorPane::Primary);
-                if let Some(db) = self.app.db.as_mut() {
-                    let _ = db.set_kv("active.page", &snapshot.page.uid);
-                }
-                self.load_page_properties();
+                    .reset(block_count, px(BLOCK_ROW_HEIGHT));
             }
             EditorPane::Secondary => {
-                let list_state =
-                    PaneListState::new(snapshot.editor.blocks.len(), px(BLOCK_ROW_HEIGHT));
-                self.editor.secondary_pane = Some(SecondaryPane {
-                    page: snapshot.page.clone(),
-                    editor: snapshot.editor.clone(),
-                    list_state,
-                    selection: snapshot.selection.clone(),
-                    dirty: snapshot.dirty,
-                });
-                self.update_block_list_for_pane(EditorPane::Secondary);
+                if let Some(secondary) = self.editor.secondary_pane.as_mut() {
+                    secondary.dirty = snapshot.dirty;
+                    secondary.list_state = PaneListState::new(block_count, px(BLOCK_ROW_HEIGHT));
+                }
             }
         }
+        self.update_block_list_for_pane(pane);
 
         self.editor.active_pane = pane;
         self.update_save_state_from_dirty();
@@ -261,7 +216,7 @@ impl AppStore {
                         self.app.primary_dirty = secondary.dirty;
                         self.editor
                             .blocks_list_state
-                            .reset(secondary.editor.blocks.len(), px(BLOCK_ROW_HEIGHT));
+                            .reset(secondary.editor.blocks().len(), px(BLOCK_ROW_HEIGHT));
                         self.update_block_list_for_pane(EditorPane::Primary);
                         self.editor.active_pane = EditorPane::Primary;
                         self.update_save_state_from_dirty();
@@ -316,16 +271,14 @@ impl AppStore {
                 return false;
             };
             let Some(ix) = editor
-                .blocks
+                .blocks()
                 .iter()
                 .position(|block| block.uid == entry.block_uid)
             else {
                 return false;
             };
             editor.active_ix = ix;
-            let changed = editor.blocks[ix].text != *next_text;
-            editor.blocks[ix].text = next_text.clone();
-            changed
+            editor.set_text(ix, next_text)
         };
 
         self.editor.active_pane = entry.pane;
@@ -349,6 +302,42 @@ impl AppStore {
         true
     }
 
+    fn apply_structural_history_entry(
+        &mut self,
+        entry: &StructuralHistoryEntry,
+        undo: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let (target, ops) = if undo {
+            (&entry.before, invert_ops(&entry.ops))
+        } else {
+            (&entry.after, entry.ops.clone())
+        };
+        if !self.ensure_page_loaded_for_history(entry.pane, &target.page.uid, cx) {
+            return false;
+        }
+
+        {
+            let Some(editor) = self.editor_for_pane_mut(entry.pane) else {
+                return false;
+            };
+            // Replay on a copy so an entry that no longer fits leaves the pane untouched.
+            let mut replay = editor.clone();
+            if !replay.apply_ops(&ops) {
+                return false;
+            }
+            replay.take_ops();
+            replay.set_active_ix(target.active_ix);
+            *editor = replay;
+        }
+
+        self.apply_pane_snapshot(entry.pane, target, window, cx);
+        self.mark_dirty_for_pane(entry.pane, cx);
+        self.schedule_references_refresh(cx);
+        true
+    }
+
     fn apply_history_entry(
         &mut self,
         entry: &HistoryEntry,
@@ -358,15 +347,7 @@ impl AppStore {
     ) -> bool {
         match entry {
             HistoryEntry::Structural(structural) => {
-                let target = if undo {
-                    &structural.before
-                } else {
-                    &structural.after
-                };
-                self.apply_pane_snapshot(structural.pane, target, window, cx);
-                self.mark_dirty_for_pane(structural.pane, cx);
-                self.schedule_references_refresh(cx);
-                true
+                self.apply_structural_history_entry(structural, undo, window, cx)
             }
             HistoryEntry::Text(text) => self.apply_text_history_entry(text, undo, window, cx),
         }
@@ -478,7 +459,13 @@ impl AppStore {
         block_type: BlockType,
         text: &str,
     ) -> gpui::Pixels {
-        let mut height = f32::from(Self::row_height_for_block_text(text));
+        // Task properties render as chips on the todo row rather than as property lines.
+        let row_text: std::borrow::Cow<str> = if matches!(block_type, BlockType::Todo) {
+            strip_task_properties(text).into()
+        } else {
+            text.into()
+        };
+        let mut height = f32::from(Self::row_height_for_block_text(&row_text));
 
         // Virtual-list rows are height-clipped by content masks. Keep row sizing in sync with
         // renderer-specific vertical padding/margins so blocks do not crop on the bottom edge.
@@ -589,6 +576,13 @@ impl AppStore {
         text: &str,
         cx: &mut Context<Self>,
     ) {
+        let query_extra = self
+            .editor_for_pane(pane)
+            .and_then(|editor| editor.blocks().get(actual_ix))
+            .map(|block| self.query_block_extra_height(&block.uid, text))
+            .unwrap_or(0.0);
+        let desired = Self::row_height_for_block_type_and_text(block_type, text)
+            + px(self.embed_extra_height(text) + query_extra);
         let Some(list_state) = self.list_state_for_pane_mut(pane) else {
             return;
         };
@@ -603,7 +597,6 @@ impl AppStore {
         if visible_ix >= list_state.item_sizes.len() {
             return;
         }
-        let desired = Self::row_height_for_block_type_and_text(block_type, text);
         if list_state.item_sizes[visible_ix].height != desired {
             let sizes = Rc::make_mut(&mut list_state.item_sizes);
             sizes[visible_ix] = size(px(0.), desired);
@@ -632,16 +625,16 @@ impl AppStore {
                 return;
             };
             let Some(ix) = editor
-                .blocks
+                .blocks()
                 .iter()
                 .position(|block| block.uid == block_uid)
             else {
                 return;
             };
-            let block = &editor.blocks[ix];
+            let block = &editor.blocks()[ix];
             let is_active = self.editor.active_pane == pane
-                && editor.active_ix < editor.blocks.len()
-                && editor.blocks[editor.active_ix].uid == block_uid;
+                && editor.active_ix < editor.blocks().len()
+                && editor.blocks()[editor.active_ix].uid == block_uid;
             (ix, block.block_type, block.text.clone(), is_active)
         };
 
@@ -657,10 +650,10 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if block_ix >= editor.blocks.len() {
+            if block_ix >= editor.blocks().len() {
                 return;
             }
-            editor.blocks[block_ix].text = text.clone();
+            editor.set_text(block_ix, &text);
         }
 
         self.record_page_cursor_for_pane(pane, &block_uid, cursor);
@@ -881,7 +874,7 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane(pane) else {
                 return false;
             };
-            let Some(block) = editor.blocks.get(actual_ix) else {
+            let Some(block) = editor.blocks().get(actual_ix) else {
                 return false;
             };
             block.text.len()
@@ -997,7 +990,7 @@ impl AppStore {
                 return false;
             };
             editor
-                .blocks
+                .blocks()
                 .get(next_actual_ix)
                 .map(|block| if forward { 0 } else { block.text.len() })
                 .unwrap_or(0)
@@ -1048,7 +1041,7 @@ impl AppStore {
                 return false;
             };
             let next_cursor = editor
-                .blocks
+                .blocks()
                 .get(target_actual_ix)
                 .map(|block| if to_bottom { block.text.len() } else { 0 })
                 .unwrap_or(0);
@@ -1108,7 +1101,7 @@ impl AppStore {
                 .and_then(|selection| selection.anchor)
                 .unwrap_or(active_visible_ix);
             let next_cursor = editor
-                .blocks
+                .blocks()
                 .get(target_actual_ix)
                 .map(|block| if to_bottom { 0 } else { block.text.len() })
                 .unwrap_or(0);
@@ -1167,7 +1160,7 @@ impl AppStore {
                 return false;
             };
             let next_cursor = editor
-                .blocks
+                .blocks()
                 .get(target_actual_ix)
                 .map(|block| if to_bottom { block.text.len() } else { 0 })
                 .unwrap_or(0);
@@ -1219,7 +1212,7 @@ impl AppStore {
             };
             selected_actual
                 .iter()
-                .filter_map(|ix| editor.blocks.get(*ix))
+                .filter_map(|ix| editor.blocks().get(*ix))
                 .map(|block| BlockClipboardItem {
                     text: block.text.clone(),
                     indent: block.indent,
@@ -1289,7 +1282,7 @@ impl AppStore {
                 return false;
             };
             let active_uid = editor
-                .blocks
+                .blocks()
                 .get(editor.active_ix)
                 .map(|block| block.uid.clone());
             let clones: Vec<BlockSnapshot> = clipboard
@@ -1321,11 +1314,10 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return false;
             };
-            let insert_at = insert_at.min(editor.blocks.len());
-            editor.blocks.splice(insert_at..insert_at, clones);
+            editor.insert_blocks(insert_at, clones);
             if let Some(active_uid) = active_uid.as_ref() {
                 if let Some(ix) = editor
-                    .blocks
+                    .blocks()
                     .iter()
                     .position(|block| &block.uid == active_uid)
                 {
@@ -1339,7 +1331,7 @@ impl AppStore {
             (self.editor_for_pane(pane), self.list_state_for_pane(pane))
         {
             if let Some((start, end)) = crate::app::store::outline::restore_visible_range_by_uids(
-                &editor.blocks,
+                editor.blocks(),
                 &list_state.actual_to_visible,
                 &inserted_uids,
             ) {
@@ -1469,7 +1461,7 @@ impl AppStore {
                 .and_then(|page| self.editor.collapsed_by_page_uid.get(&page.uid))
                 .cloned()
                 .unwrap_or_default();
-            let outline = crate::app::store::outline::build_outline(&editor.blocks, &collapsed);
+            let outline = crate::app::store::outline::build_outline(editor.blocks(), &collapsed);
 
             let mut block_renderers_by_lang: HashMap<String, &PluginRenderer> = HashMap::new();
             if let Some(status) = self.plugins.plugin_status.as_ref() {
@@ -1489,7 +1481,7 @@ impl AppStore {
 
             let mut sizes = Vec::with_capacity(outline.visible_to_actual.len());
             for actual_ix in outline.visible_to_actual.iter().copied() {
-                let Some(block) = editor.blocks.get(actual_ix) else {
+                let Some(block) = editor.blocks().get(actual_ix) else {
                     continue;
                 };
 
@@ -1497,7 +1489,7 @@ impl AppStore {
                     Self::row_height_for_block_type_and_text(block.block_type, &block.text);
                 if matches!(block.block_type, BlockType::ColumnLayout) {
                     height = height.max(Self::row_height_for_column_layout_block(
-                        &editor.blocks,
+                        editor.blocks(),
                         actual_ix,
                     ));
                 }
@@ -1538,6 +1530,8 @@ impl AppStore {
                         }
                     }
                 }
+                height += px(self.embed_extra_height(&block.text)
+                    + self.query_block_extra_height(&block.uid, &block.text));
 
                 sizes.push(size(px(0.), height));
             }
@@ -1622,7 +1616,7 @@ impl AppStore {
         let Some(editor) = self.editor_for_pane(pane) else {
             return;
         };
-        let Some(block) = editor.blocks.get(actual_ix) else {
+        let Some(block) = editor.blocks().get(actual_ix) else {
             return;
         };
 
@@ -1632,7 +1626,7 @@ impl AppStore {
             .copied()
             .unwrap_or_else(|| {
                 editor
-                    .blocks
+                    .blocks()
                     .get(actual_ix + 1)
                     .is_some_and(|next| next.indent > block.indent)
             });
@@ -1663,7 +1657,7 @@ impl AppStore {
             if let Some(editor) = self.editor_for_pane_mut(pane) {
                 let active_ix = editor.active_ix;
                 if active_ix != actual_ix {
-                    let end = crate::app::store::outline::subtree_end(&editor.blocks, actual_ix);
+                    let end = crate::app::store::outline::subtree_end(editor.blocks(), actual_ix);
                     if active_ix > actual_ix && active_ix <= end {
                         editor.active_ix = actual_ix;
                         active_changed_in_pane = true;
@@ -1691,7 +1685,7 @@ impl AppStore {
         if (active_changed_in_pane || active_changed) && pane == self.editor.active_pane {
             let cursor = self
                 .editor_for_pane(pane)
-                .and_then(|editor| editor.blocks.get(editor.active_ix))
+                .and_then(|editor| editor.blocks().get(editor.active_ix))
                 .map(|block| block.text.len())
                 .unwrap_or(0);
             self.sync_block_input_from_active_with_cursor_for_pane(pane, cursor, window, cx);
@@ -1713,7 +1707,7 @@ impl AppStore {
         let Some(editor) = self.editor_for_pane(pane) else {
             return;
         };
-        let collapsed = crate::app::store::outline::fold_to_level(&editor.blocks, level);
+        let collapsed = crate::app::store::outline::fold_to_level(editor.blocks(), level);
         self.editor
             .collapsed_by_page_uid
             .insert(page_uid.clone(), collapsed);
@@ -1836,7 +1830,7 @@ impl AppStore {
     ) {
         let cursor = self
             .editor_for_pane(pane)
-            .and_then(|editor| editor.blocks.get(editor.active_ix))
+            .and_then(|editor| editor.blocks().get(editor.active_ix))
             .map(|block| block.text.len())
             .unwrap_or(0);
         self.sync_block_input_from_active_with_cursor_for_pane(pane, cursor, window, cx);
@@ -1859,11 +1853,11 @@ impl AppStore {
             self.editor.block_input_binding = None;
             return;
         };
-        if editor.active_ix >= editor.blocks.len() {
+        if editor.active_ix >= editor.blocks().len() {
             self.editor.block_input_binding = None;
             return;
         }
-        let block = &editor.blocks[editor.active_ix];
+        let block = &editor.blocks()[editor.active_ix];
         let text = block.text.clone();
         let cursor = cursor.min(text.len());
         let block_uid = block.uid.clone();
@@ -1946,7 +1940,7 @@ impl AppStore {
             let Some(db) = self.app.db.as_ref() else {
                 return;
             };
-            let Some(page) = db.get_page_by_uid(&normalized).ok().flatten() else {
+            let Some(page) = db.resolve_page(&normalized).ok().flatten() else {
                 return;
             };
             let blocks = db.load_blocks_for_page(page.id).unwrap_or_default();
@@ -1954,7 +1948,7 @@ impl AppStore {
         };
         self.load_collapsed_state_for_page(&page.uid);
         let editor = EditorModel::new(blocks);
-        let list_state = PaneListState::new(editor.blocks.len(), px(BLOCK_ROW_HEIGHT));
+        let list_state = PaneListState::new(editor.blocks().len(), px(BLOCK_ROW_HEIGHT));
         self.editor.secondary_pane = Some(SecondaryPane {
             page,
             editor,
@@ -1986,7 +1980,7 @@ impl AppStore {
             return;
         };
         let editor = editor.clone();
-        let list_state = PaneListState::new(editor.blocks.len(), px(BLOCK_ROW_HEIGHT));
+        let list_state = PaneListState::new(editor.blocks().len(), px(BLOCK_ROW_HEIGHT));
         let selection = PaneSelection::new();
         let dirty = self.app.primary_dirty;
 
@@ -2047,7 +2041,7 @@ impl AppStore {
             self.editor
                 .editor
                 .as_ref()
-                .map(|e| e.blocks.len())
+                .map(|e| e.blocks().len())
                 .unwrap_or(0),
             px(BLOCK_ROW_HEIGHT),
         );
@@ -2065,7 +2059,7 @@ impl AppStore {
         if let Some(editor) = self.editor.editor.as_mut() {
             let cursor = if let Some(page_cursor) = self.editor.page_cursors.get(&page.uid) {
                 let (active_ix, offset) =
-                    helpers::resolve_cursor_for_blocks(&editor.blocks, Some(page_cursor));
+                    helpers::resolve_cursor_for_blocks(editor.blocks(), Some(page_cursor));
                 editor.active_ix = active_ix;
                 offset
             } else {
@@ -2173,9 +2167,18 @@ impl AppStore {
                         };
                         self.apply_wikilink_suggestion(&label, false, window, cx);
                     }
+                    WikilinkMenuItem::Alias { alias, .. } => {
+                        self.apply_wikilink_suggestion(&alias, false, window, cx);
+                    }
                     WikilinkMenuItem::Create { query, .. } => {
                         self.apply_wikilink_suggestion(&query, true, window, cx);
                     }
+                    WikilinkMenuItem::Tag(tag) => {
+                        self.apply_tag_suggestion(&tag.name, window, cx);
+                    }
+                    WikilinkMenuItem::Block(record) => {
+                        self.apply_block_ref_suggestion(&record.block_uid, window, cx);
+                    }
                 }
             }
             return;
@@ -2195,13 +2198,11 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if editor.active_ix >= editor.blocks.len() {
+            if editor.active_ix >= editor.blocks().len() {
                 return;
             }
 
-            if editor.blocks[editor.active_ix].text != text {
-                editor.blocks[editor.active_ix].text = text;
-            }
+            editor.set_text(editor.active_ix, &text);
 
             editor.split_active_and_insert_after(cursor_offset)
         };
@@ -2339,7 +2340,7 @@ impl AppStore {
         let Some(editor) = self.editor_for_pane_mut(pane) else {
             return;
         };
-        if editor.active_ix >= editor.blocks.len() {
+        if editor.active_ix >= editor.blocks().len() {
             return;
         }
         let cursor = editor.duplicate_active();
@@ -2515,9 +2516,18 @@ impl AppStore {
                             };
                             self.apply_wikilink_suggestion(&label, false, window, cx);
                         }
+                        WikilinkMenuItem::Alias { alias, .. } => {
+                            self.apply_wikilink_suggestion(&alias, false, window, cx);
+                        }
                         WikilinkMenuItem::Create { query, .. } => {
                             self.apply_wikilink_suggestion(&query, true, window, cx);
                         }
+                        WikilinkMenuItem::Tag(tag) => {
+                            self.apply_tag_suggestion(&tag.name, window, cx);
+                        }
+                        WikilinkMenuItem::Block(record) => {
+                            self.apply_block_ref_suggestion(&record.block_uid, window, cx);
+                        }
                     }
                 }
                 return true;
@@ -2611,14 +2621,12 @@ impl AppStore {
                     let Some(editor) = self.editor_for_pane_mut(pane) else {
                         return false;
                     };
-                    if editor.active_ix >= editor.blocks.len() {
+                    if editor.active_ix >= editor.blocks().len() {
                         return false;
                     }
 
                     let old_ix = editor.active_ix;
-                    if editor.blocks[old_ix].text != current_text {
-                        editor.blocks[old_ix].text = current_text.clone();
-                    }
+                    editor.set_text(old_ix, &current_text);
 
                     let cursor = if current_text.is_empty() {
                         editor.delete_active_if_empty()
@@ -2655,19 +2663,17 @@ impl AppStore {
                     let Some(editor) = self.editor_for_pane_mut(pane) else {
                         return false;
                     };
-                    if editor.active_ix >= editor.blocks.len() {
+                    if editor.active_ix >= editor.blocks().len() {
                         return false;
                     }
 
                     let old_ix = editor.active_ix;
                     let next_ix = old_ix + 1;
-                    if next_ix >= editor.blocks.len() {
+                    if next_ix >= editor.blocks().len() {
                         return false;
                     }
 
-                    if editor.blocks[old_ix].text != current_text {
-                        editor.blocks[old_ix].text = current_text;
-                    }
+                    editor.set_text(old_ix, &current_text);
 
                     let Some(cursor) = editor.merge_next_into_active(cursor_offset) else {
                         return false;
@@ -2731,7 +2737,7 @@ impl AppStore {
                         return false;
                     };
                     let cursor = editor
-                        .blocks
+                        .blocks()
                         .get(next_ix)
                         .map(|block| block.text.len())
                         .unwrap_or(0);
@@ -2798,7 +2804,7 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if actual_ix >= editor.blocks.len() {
+            if actual_ix >= editor.blocks().len() {
                 return;
             }
             editor.active_ix = actual_ix;
@@ -2875,7 +2881,7 @@ impl AppStore {
                 return false;
             };
             let Some(ix) = editor
-                .blocks
+                .blocks()
                 .iter()
                 .position(|block| block.uid == block_uid)
             else {
@@ -2894,7 +2900,7 @@ impl AppStore {
             let mut uids = Vec::new();
             let mut current = list_state.parent_by_actual.get(ix).copied().flatten();
             while let Some(parent_ix) = current {
-                if let Some(block) = editor.blocks.get(parent_ix) {
+                if let Some(block) = editor.blocks().get(parent_ix) {
                     uids.push(block.uid.clone());
                 }
                 current = list_state
@@ -2968,7 +2974,7 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if ix >= editor.blocks.len() {
+            if ix >= editor.blocks().len() {
                 return;
             }
             editor.active_ix = ix;
@@ -2999,10 +3005,10 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if layout_ix >= editor.blocks.len() {
+            if layout_ix >= editor.blocks().len() {
                 return;
             }
-            let layout_block = &editor.blocks[layout_ix];
+            let layout_block = &editor.blocks()[layout_ix];
             if !matches!(layout_block.block_type, BlockType::ColumnLayout) {
                 return;
             }
@@ -3010,8 +3016,8 @@ impl AppStore {
             let layout_indent = layout_block.indent;
             let mut insert_ix = layout_ix + 1;
             let mut column_count = 0usize;
-            while insert_ix < editor.blocks.len() {
-                let block = &editor.blocks[insert_ix];
+            while insert_ix < editor.blocks().len() {
+                let block = &editor.blocks()[insert_ix];
                 if block.indent <= layout_indent {
                     break;
                 }
@@ -3026,7 +3032,7 @@ impl AppStore {
             let column_label = format!("Column {}", column_count + 1);
             let column_indent = layout_indent + 1;
             let child_indent = column_indent + 1;
-            editor.blocks.insert(
+            editor.insert_block(
                 insert_ix,
                 BlockSnapshot {
                     uid: Uuid::new_v4().to_string(),
@@ -3035,7 +3041,7 @@ impl AppStore {
                     block_type: BlockType::Column,
                 },
             );
-            editor.blocks.insert(
+            editor.insert_block(
                 insert_ix + 1,
                 BlockSnapshot {
                     uid: Uuid::new_v4().to_string(),
@@ -3074,10 +3080,10 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if column_ix >= editor.blocks.len() {
+            if column_ix >= editor.blocks().len() {
                 return;
             }
-            let column_block = &editor.blocks[column_ix];
+            let column_block = &editor.blocks()[column_ix];
             if !matches!(column_block.block_type, BlockType::Column) {
                 return;
             }
@@ -3085,12 +3091,13 @@ impl AppStore {
             let column_indent = column_block.indent;
             let child_indent = column_indent + 1;
             let mut insert_ix = column_ix + 1;
-            while insert_ix < editor.blocks.len() && editor.blocks[insert_ix].indent > column_indent
+            while insert_ix < editor.blocks().len()
+                && editor.blocks()[insert_ix].indent > column_indent
             {
                 insert_ix += 1;
             }
 
-            editor.blocks.insert(
+            editor.insert_block(
                 insert_ix,
                 BlockSnapshot {
                     uid: Uuid::new_v4().to_string(),
@@ -3129,7 +3136,7 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if ix >= editor.blocks.len() {
+            if ix >= editor.blocks().len() {
                 return;
             }
             editor.active_ix = ix;
@@ -3163,10 +3170,10 @@ impl AppStore {
         let Some(db) = self.app.db.as_ref() else {
             return;
         };
-        if ix >= editor.blocks.len() {
+        if ix >= editor.blocks().len() {
             return;
         }
-        let block_uid = editor.blocks[ix].uid.clone();
+        let block_uid = editor.blocks()[ix].uid.clone();
         let now = now_millis();
         let _ = db.upsert_review_queue_item(&active_page.uid, &block_uid, now, None);
         if self.app.mode == Mode::Review {
@@ -3185,11 +3192,11 @@ impl AppStore {
         let Some(editor) = self.editor_for_pane_mut(pane) else {
             return;
         };
-        if ix >= editor.blocks.len() {
+        if ix >= editor.blocks().len() {
             return;
         }
         editor.active_ix = ix;
-        let current = editor.blocks[ix].text.clone();
+        let current = editor.blocks()[ix].text.clone();
         let separator = if current.ends_with(' ') || current.is_empty() {
             ""
         } else {
@@ -3197,7 +3204,7 @@ impl AppStore {
         };
         let next_text = format!("{current}{separator}[[Page]]");
         let next_cursor = next_text.len();
-        editor.blocks[ix].text = next_text;
+        editor.set_text(ix, &next_text);
         self.sync_block_input_from_active_with_cursor_for_pane(pane, next_cursor, Some(window), cx);
         window.focus(&self.editor.block_input.focus_handle(cx), cx);
         self.mark_dirty_for_pane(pane, cx);
@@ -3215,13 +3222,13 @@ impl AppStore {
             return;
         };
         let Some(ix) = editor
-            .blocks
+            .blocks()
             .iter()
             .position(|block| block.uid == reference.block_uid)
         else {
             return;
         };
-        let text = editor.blocks[ix].text.clone();
+        let text = editor.blocks()[ix].text.clone();
         let title = reference.page_title.trim();
         if title.is_empty() {
             return;
@@ -3236,7 +3243,7 @@ impl AppStore {
         else {
             return;
         };
-        editor.blocks[ix].text = next_text.clone();
+        editor.set_text(ix, &next_text);
         if editor.active_ix == ix {
             self.sync_block_input_from_active_with_cursor_for_pane(
                 EditorPane::Primary,
@@ -3280,12 +3287,12 @@ impl AppStore {
                 return;
             };
             let active_uid = editor
-                .blocks
+                .blocks()
                 .get(editor.active_ix)
                 .map(|block| block.uid.clone());
             let clones: Vec<BlockSnapshot> = selected_actual
                 .iter()
-                .filter_map(|ix| editor.blocks.get(*ix))
+                .filter_map(|ix| editor.blocks().get(*ix))
                 .map(|block| BlockSnapshot {
                     uid: Uuid::new_v4().to_string(),
                     text: block.text.clone(),
@@ -3309,12 +3316,11 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            let insert_at = insert_at.min(editor.blocks.len());
-            editor.blocks.splice(insert_at..insert_at, clones);
+            editor.insert_blocks(insert_at, clones);
 
             if let Some(active_uid) = active_uid.as_ref() {
                 if let Some(ix) = editor
-                    .blocks
+                    .blocks()
                     .iter()
                     .position(|block| &block.uid == active_uid)
                 {
@@ -3362,7 +3368,7 @@ impl AppStore {
             };
             let removed_uids: HashSet<String> = selected_actual
                 .iter()
-                .filter_map(|ix| editor.blocks.get(*ix).map(|block| block.uid.clone()))
+                .filter_map(|ix| editor.blocks().get(*ix).map(|block| block.uid.clone()))
                 .collect();
             if removed_uids.is_empty() {
                 return;
@@ -3372,9 +3378,9 @@ impl AppStore {
             let last_ix = *selected_actual.last().unwrap_or(&0);
 
             let mut next_ix = last_ix.saturating_add(1);
-            while next_ix < editor.blocks.len()
+            while next_ix < editor.blocks().len()
                 && editor
-                    .blocks
+                    .blocks()
                     .get(next_ix)
                     .is_some_and(|block| removed_uids.contains(&block.uid))
             {
@@ -3384,7 +3390,7 @@ impl AppStore {
             let mut prev_ix = first_ix as isize - 1;
             while prev_ix >= 0
                 && editor
-                    .blocks
+                    .blocks()
                     .get(prev_ix as usize)
                     .is_some_and(|block| removed_uids.contains(&block.uid))
             {
@@ -3392,11 +3398,11 @@ impl AppStore {
             }
 
             let target_uid = editor
-                .blocks
+                .blocks()
                 .get(next_ix)
                 .or_else(|| {
                     if prev_ix >= 0 {
-                        editor.blocks.get(prev_ix as usize)
+                        editor.blocks().get(prev_ix as usize)
                     } else {
                         None
                     }
@@ -3410,7 +3416,7 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane_mut(pane) else {
                 return;
             };
-            if removed_uids.len() >= editor.blocks.len() {
+            if removed_uids.len() >= editor.blocks().len() {
                 let replacement = BlockSnapshot {
                     uid: Uuid::new_v4().to_string(),
                     text: String::new(),
@@ -3418,13 +3424,17 @@ impl AppStore {
                     block_type: BlockType::Text,
                 };
                 let uid = replacement.uid.clone();
-                editor.blocks = vec![replacement];
+                editor.replace_blocks(vec![replacement]);
                 editor.active_ix = 0;
                 Some(uid)
             } else {
-                editor
-                    .blocks
-                    .retain(|block| !removed_uids.contains(&block.uid));
+                let remaining = editor
+                    .blocks()
+                    .iter()
+                    .filter(|block| !removed_uids.contains(&block.uid))
+                    .cloned()
+                    .collect();
+                editor.replace_blocks(remaining);
                 editor.active_ix = 0;
                 target_uid
             }
@@ -3468,14 +3478,11 @@ impl AppStore {
         let mut changed = false;
         if let Some(editor) = self.editor_for_pane_mut(pane) {
             for ix in selected_actual {
-                let Some(block) = editor.blocks.get_mut(ix) else {
+                let Some(block) = editor.blocks().get(ix) else {
                     continue;
                 };
                 let next = (block.indent + 1).max(0);
-                if next != block.indent {
-                    block.indent = next;
-                    changed = true;
-                }
+                changed |= editor.set_indent(ix, next);
             }
         }
 
@@ -3511,14 +3518,11 @@ impl AppStore {
         let mut changed = false;
         if let Some(editor) = self.editor_for_pane_mut(pane) {
             for ix in selected_actual {
-                let Some(block) = editor.blocks.get_mut(ix) else {
+                let Some(block) = editor.blocks().get(ix) else {
                     continue;
                 };
                 let next = (block.indent - 1).max(0);
-                if next != block.indent {
-                    block.indent = next;
-                    changed = true;
-                }
+                changed |= editor.set_indent(ix, next);
             }
         }
 
@@ -3532,38 +3536,38 @@ impl AppStore {
     }
 
     fn move_block_range(
-        blocks: &mut Vec<BlockSnapshot>,
+        editor: &mut EditorModel,
         start: usize,
         end: usize,
         insert_at: usize,
     ) -> bool {
-        if start >= blocks.len() || end >= blocks.len() || end < start {
+        let len = editor.blocks().len();
+        if start >= len || end >= len || end < start {
             return false;
         }
-        let segment: Vec<_> = blocks.drain(start..=end).collect();
-        let target = insert_at.min(blocks.len());
-        blocks.splice(target..target, segment);
-        true
+        let target = insert_at.min(len - (end - start + 1));
+        editor.move_span(start..end + 1, target)
     }
 
     fn move_block_range_before_index(
-        blocks: &mut Vec<BlockSnapshot>,
+        editor: &mut EditorModel,
         start: usize,
         end: usize,
         insert_before_ix: usize,
     ) -> bool {
-        if start >= blocks.len() || end >= blocks.len() || end < start {
+        let len = editor.blocks().len();
+        if start >= len || end >= len || end < start {
             return false;
         }
         if insert_before_ix >= start && insert_before_ix <= end + 1 {
             return false;
         }
         let length = end - start + 1;
-        let mut insert_at = insert_before_ix.min(blocks.len());
+        let mut insert_at = insert_before_ix.min(len);
         if insert_at > end {
             insert_at = insert_at.saturating_sub(length);
         }
-        Self::move_block_range(blocks, start, end, insert_at)
+        Self::move_block_range(editor, start, end, insert_at)
     }
 
     pub(crate) fn set_hovered_block_uid(
@@ -3593,7 +3597,7 @@ impl AppStore {
         };
         let Some(block_uid) = self
             .editor_for_pane(pane)
-            .and_then(|editor| editor.blocks.get(actual_ix))
+            .and_then(|editor| editor.blocks().get(actual_ix))
             .map(|block| block.uid.clone())
         else {
             return;
@@ -3648,7 +3652,10 @@ impl AppStore {
                 .visible_to_actual
                 .get(visible_ix.saturating_add(1))
                 .copied()
-                .or_else(|| self.editor_for_pane(pane).map(|editor| editor.blocks.len()))
+                .or_else(|| {
+                    self.editor_for_pane(pane)
+                        .map(|editor| editor.blocks().len())
+                })
                 .unwrap_or(actual_ix)
         } else {
             actual_ix
@@ -3676,7 +3683,7 @@ impl AppStore {
 
         if let Some(block_uid) = self
             .editor_for_pane(pane)
-            .and_then(|editor| editor.blocks.get(actual_ix))
+            .and_then(|editor| editor.blocks().get(actual_ix))
             .map(|block| block.uid.clone())
         {
             self.set_hovered_block_uid(Some(block_uid), cx);
@@ -3711,7 +3718,7 @@ impl AppStore {
             self.editor.drag_target = None;
             return false;
         };
-        if source.block_ix >= editor.blocks.len() {
+        if source.block_ix >= editor.blocks().len() {
             self.editor.drag_source = None;
             self.editor.drag_target = None;
             return false;
@@ -3756,10 +3763,10 @@ impl AppStore {
             };
             let selected_uids: Vec<String> = selected_actual
                 .iter()
-                .filter_map(|ix| editor.blocks.get(*ix).map(|block| block.uid.clone()))
+                .filter_map(|ix| editor.blocks().get(*ix).map(|block| block.uid.clone()))
                 .collect();
             let active_uid = editor
-                .blocks
+                .blocks()
                 .get(editor.active_ix)
                 .map(|block| block.uid.clone());
             (selected_uids, active_uid)
@@ -3774,11 +3781,11 @@ impl AppStore {
             let start_actual = *selected_actual.first().unwrap_or(&0);
             let mut end_actual = *selected_actual.last().unwrap_or(&start_actual);
             for ix in &selected_actual {
-                let subtree_end = crate::app::store::outline::subtree_end(&editor.blocks, *ix);
+                let subtree_end = crate::app::store::outline::subtree_end(editor.blocks(), *ix);
                 end_actual = end_actual.max(subtree_end);
             }
             let moved = Self::move_block_range_before_index(
-                &mut editor.blocks,
+                editor,
                 start_actual,
                 end_actual,
                 target.insert_before_ix,
@@ -3786,7 +3793,7 @@ impl AppStore {
             if moved {
                 if let Some(active_uid) = active_uid.as_ref() {
                     if let Some(ix) = editor
-                        .blocks
+                        .blocks()
                         .iter()
                         .position(|block| &block.uid == active_uid)
                     {
@@ -3815,7 +3822,7 @@ impl AppStore {
             (self.editor_for_pane(pane), self.list_state_for_pane(pane))
         {
             if let Some((start, end)) = crate::app::store::outline::restore_visible_range_by_uids(
-                &editor.blocks,
+                editor.blocks(),
                 &list_state.actual_to_visible,
                 &selected_uids,
             ) {
@@ -3868,10 +3875,10 @@ impl AppStore {
             };
             let selected_uids: Vec<String> = selected_actual
                 .iter()
-                .filter_map(|ix| editor.blocks.get(*ix).map(|block| block.uid.clone()))
+                .filter_map(|ix| editor.blocks().get(*ix).map(|block| block.uid.clone()))
                 .collect();
             let active_uid = editor
-                .blocks
+                .blocks()
                 .get(editor.active_ix)
                 .map(|block| block.uid.clone());
             (selected_uids, active_uid)
@@ -3885,7 +3892,7 @@ impl AppStore {
             let start_actual = *selected_actual.first().unwrap_or(&0);
             let mut end_actual = *selected_actual.last().unwrap_or(&start_actual);
             for ix in &selected_actual {
-                let subtree_end = crate::app::store::outline::subtree_end(&editor.blocks, *ix);
+                let subtree_end = crate::app::store::outline::subtree_end(editor.blocks(), *ix);
                 end_actual = end_actual.max(subtree_end);
             }
 
@@ -3899,7 +3906,7 @@ impl AppStore {
                 if prev_actual >= start_actual && prev_actual <= end_actual {
                     return;
                 }
-                Self::move_block_range(&mut editor.blocks, start_actual, end_actual, prev_actual);
+                Self::move_block_range(editor, start_actual, end_actual, prev_actual);
             } else if direction > 0 {
                 let next_visible = end_visible + 1;
                 if next_visible >= visible_to_actual.len() {
@@ -3908,17 +3915,18 @@ impl AppStore {
                 let Some(next_actual) = visible_to_actual.get(next_visible).copied() else {
                     return;
                 };
-                let next_end = crate::app::store::outline::subtree_end(&editor.blocks, next_actual);
+                let next_end =
+                    crate::app::store::outline::subtree_end(editor.blocks(), next_actual);
                 let length = end_actual - start_actual + 1;
                 let insert_at = next_end.saturating_sub(length).saturating_add(1);
-                Self::move_block_range(&mut editor.blocks, start_actual, end_actual, insert_at);
+                Self::move_block_range(editor, start_actual, end_actual, insert_at);
             } else {
                 return;
             }
 
             if let Some(active_uid) = active_uid.as_ref() {
                 if let Some(ix) = editor
-                    .blocks
+                    .blocks()
                     .iter()
                     .position(|block| &block.uid == active_uid)
                 {
@@ -3932,7 +3940,7 @@ impl AppStore {
             (self.editor_for_pane(pane), self.list_state_for_pane(pane))
         {
             if let Some((start, end)) = crate::app::store::outline::restore_visible_range_by_uids(
-                &editor.blocks,
+                editor.blocks(),
                 &list_state.actual_to_visible,
                 &selected_uids,
             ) {
@@ -3967,7 +3975,7 @@ impl AppStore {
             .insert_page("inbox", "Inbox")
             .map_err(|err| format!("create inbox page: {err}"))?;
         if let Ok(pages) = db.list_pages() {
-            self.editor.pages = pages;
+            self.editor.set_pages(pages);
         }
 
         Ok(PageRecord {
@@ -3985,13 +3993,13 @@ impl AppStore {
             .is_some_and(|active| active.uid == page.uid)
         {
             if let Some(editor) = self.editor.editor.as_ref() {
-                return editor.blocks.clone();
+                return editor.blocks().to_vec();
             }
         }
 
         if let Some(secondary) = self.editor.secondary_pane.as_ref() {
             if secondary.page.uid == page.uid {
-                return secondary.editor.blocks.clone();
+                return secondary.editor.blocks().to_vec();
             }
         }
 
@@ -4027,8 +4035,10 @@ impl AppStore {
             .is_some_and(|active| active.uid == page_uid)
         {
             if let Some(editor) = self.editor.editor.as_mut() {
-                editor.blocks = blocks_for_editor.clone();
-                editor.active_ix = editor.active_ix.min(editor.blocks.len().saturating_sub(1));
+                editor.replace_blocks(blocks_for_editor.clone());
+                editor.active_ix = editor
+                    .active_ix
+                    .min(editor.blocks().len().saturating_sub(1));
                 update_primary = true;
             }
         }
@@ -4042,11 +4052,11 @@ impl AppStore {
         let mut update_secondary = false;
         if let Some(secondary) = self.editor.secondary_pane.as_mut() {
             if secondary.page.uid == page_uid {
-                secondary.editor.blocks = blocks_for_editor;
+                secondary.editor.replace_blocks(blocks_for_editor);
                 secondary.editor.active_ix = secondary
                     .editor
                     .active_ix
-                    .min(secondary.editor.blocks.len().saturating_sub(1));
+                    .min(secondary.editor.blocks().len().saturating_sub(1));
                 update_secondary = true;
             }
         }
@@ -4294,7 +4304,39 @@ impl AppStore {
             self.close_wikilink_menu();
             return;
         }
-        let Some(query) = helpers::find_wikilink_query(text, cursor) else {
+        let wikilink_query = helpers::find_wikilink_query(text, cursor);
+        let mut block_matches = None;
+        let (query, tag_matches) = if let Some(query) = wikilink_query {
+            (query, None)
+        } else if let Some(query) = helpers::find_block_ref_query(text, cursor) {
+            let matches: Vec<BlockPageRecord> = self
+                .app
+                .db
+                .as_ref()
+                .and_then(|db| db.search_block_page_summaries(&query.query, 12).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|record| record.block_uid != block_uid)
+                .collect();
+            if matches.is_empty() {
+                self.close_wikilink_menu();
+                return;
+            }
+            block_matches = Some(matches);
+            (query, None)
+        } else if let Some(query) = helpers::find_tag_query(text, cursor) {
+            let matches = self
+                .app
+                .db
+                .as_ref()
+                .and_then(|db| db.search_tags(&query.query, 12).ok())
+                .unwrap_or_default();
+            if matches.is_empty() {
+                self.close_wikilink_menu();
+                return;
+            }
+            (query, Some(matches))
+        } else {
             self.close_wikilink_menu();
             return;
         };
@@ -4329,6 +4371,10 @@ impl AppStore {
             range_end: Some(query.range_end),
             has_closing: query.has_closing,
             query: query.query,
+            tag: tag_matches.is_some(),
+            tag_matches: tag_matches.unwrap_or_default(),
+            block_ref: block_matches.is_some(),
+            block_matches: block_matches.unwrap_or_default(),
             selected_index,
             layer_priority,
         };
@@ -4423,33 +4469,60 @@ impl AppStore {
         line.x_for_index(cursor)
     }
 
-    fn filtered_wikilink_pages(&self) -> Vec<PageRecord> {
-        let query = self.editor.wikilink_menu.query.trim().to_lowercase();
+    fn filtered_wikilink_pages(&self) -> Vec<WikilinkMenuItem> {
+        let query = self.editor.wikilink_menu.query.trim();
         if query.is_empty() {
-            return self.editor.pages.clone();
+            return self
+                .editor
+                .pages
+                .iter()
+                .cloned()
+                .map(WikilinkMenuItem::Page)
+                .collect();
         }
-        self.editor
+        let pages_by_uid: HashMap<&str, &PageRecord> = self
+            .editor
             .pages
             .iter()
-            .filter(|page| {
-                let title = page.title.to_lowercase();
-                let uid = page.uid.to_lowercase();
-                title.contains(&query) || uid.contains(&query)
+            .map(|page| (page.uid.as_str(), page))
+            .collect();
+        self.editor
+            .title_index
+            .search(query, &self.editor.recent_pages, WIKILINK_MENU_LIMIT)
+            .into_iter()
+            .filter_map(|found| {
+                let page = (*pages_by_uid.get(found.uid.as_str())?).clone();
+                Some(match found.alias {
+                    Some(alias) => WikilinkMenuItem::Alias { page, alias },
+                    None => WikilinkMenuItem::Page(page),
+                })
             })
-            .cloned()
             .collect()
     }
 
+    /// True when `normalized` (a kebab-cased name) is a page's uid, title or alias.
+    fn page_matches_name(&self, page: &PageRecord, normalized: &str) -> bool {
+        app::sanitize_kebab(&page.uid) == normalized
+            || app::sanitize_kebab(&page.title) == normalized
+            || self
+                .editor
+                .title_index
+                .aliases(&page.uid)
+                .iter()
+                .any(|alias| app::sanitize_kebab(alias) == normalized)
+    }
+
     fn wikilink_create_label(&self) -> Option<String> {
         let query = self.editor.wikilink_menu.query.trim();
         if query.is_empty() {
             return None;
         }
         let normalized = app::sanitize_kebab(query);
-        let exists = self.editor.pages.iter().any(|page| {
-            app::sanitize_kebab(&page.uid) == normalized
-                || app::sanitize_kebab(&page.title) == normalized
-        });
+        let exists = self
+            .editor
+            .pages
+            .iter()
+            .any(|page| self.page_matches_name(page, &normalized));
         if exists {
             None
         } else {
@@ -4458,11 +4531,30 @@ impl AppStore {
     }
 
     pub(crate) fn wikilink_menu_items(&self) -> Vec<WikilinkMenuItem> {
-        let mut items = self
-            .filtered_wikilink_pages()
-            .into_iter()
-            .map(WikilinkMenuItem::Page)
-            .collect::<Vec<_>>();
+        if self.editor.wikilink_menu.block_ref {
+            return self
+                .editor
+                .wikilink_menu
+                .block_matches
+                .iter()
+                .cloned()
+                .map(WikilinkMenuItem::Block)
+                .collect();
+        }
+        if self.editor.wikilink_menu.tag {
+            return self
+                .editor
+                .wikilink_menu
+                .tag_matches
+                .iter()
+                .cloned()
+                .map(WikilinkMenuItem::Tag)
+                .collect();
+        }
+        let mut items = self.filtered_wikilink_pages();
+        if let Some(item) = self.journal_link_suggestion() {
+            items.insert(0, item);
+        }
         if let Some(label) = self.wikilink_create_label() {
             items.push(WikilinkMenuItem::Create {
                 label,
@@ -4495,16 +4587,16 @@ impl AppStore {
         let Some(editor) = self.editor_for_pane_mut(pane) else {
             return;
         };
-        if block_ix >= editor.blocks.len() {
+        if block_ix >= editor.blocks().len() {
             return;
         }
         if expected_uid
             .as_ref()
-            .is_some_and(|uid| &editor.blocks[block_ix].uid != uid)
+            .is_some_and(|uid| &editor.blocks()[block_ix].uid != uid)
         {
             return;
         }
-        let text = editor.blocks[block_ix].text.clone();
+        let text = editor.blocks()[block_ix].text.clone();
         if range_start >= text.len() || range_end > text.len() || range_start >= range_end {
             return;
         }
@@ -4545,7 +4637,7 @@ impl AppStore {
         let next_text = format!("{before}[[{next_inner}]]{after}");
         let next_cursor = before.len() + 2 + next_inner.len() + 2;
 
-        editor.blocks[block_ix].text = next_text.clone();
+        editor.set_text(block_ix, &next_text);
         editor.active_ix = block_ix;
         self.set_active_pane(pane, cx);
         self.sync_block_input_from_active_with_cursor_for_pane(pane, next_cursor, Some(window), cx);
@@ -4560,30 +4652,243 @@ impl AppStore {
         }
     }
 
-    fn create_page_from_link(&mut self, title: &str) {
-        let Some(db) = self.app.db.as_mut() else {
+    pub(crate) fn apply_tag_suggestion(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let pane = self.editor.wikilink_menu.pane;
+        let history_before = self.pane_snapshot(pane, cx);
+        let expected_uid = self.editor.wikilink_menu.block_uid.clone();
+        let Some(block_ix) = self.editor.wikilink_menu.block_ix else {
             return;
         };
+        let Some(range_start) = self.editor.wikilink_menu.range_start else {
+            return;
+        };
+        let Some(range_end) = self.editor.wikilink_menu.range_end else {
+            return;
+        };
+        let Some(editor) = self.editor_for_pane_mut(pane) else {
+            return;
+        };
+        if block_ix >= editor.blocks().len() {
+            return;
+        }
+        if expected_uid
+            .as_ref()
+            .is_some_and(|uid| &editor.blocks()[block_ix].uid != uid)
+        {
+            return;
+        }
+        let text = editor.blocks()[block_ix].text.clone();
+        if range_start >= range_end || range_end > text.len() {
+            return;
+        }
+        if !text.is_char_boundary(range_start) || !text.is_char_boundary(range_end) {
+            return;
+        }
+
+        let before = &text[..range_start];
+        let after = &text[range_end..];
+        let separator = if after.starts_with(char::is_whitespace) {
+            ""
+        } else {
+            " "
+        };
+        let tag = format_tag(name);
+        let next_text = format!("{before}{tag}{separator}{after}");
+        let next_cursor = before.len() + tag.len() + separator.len();
+
+        editor.set_text(block_ix, &next_text);
+        editor.active_ix = block_ix;
+        self.set_active_pane(pane, cx);
+        self.sync_block_input_from_active_with_cursor_for_pane(pane, next_cursor, Some(window), cx);
+        window.focus(&self.editor.block_input.focus_handle(cx), cx);
+        self.close_wikilink_menu();
+        self.mark_dirty_for_pane(pane, cx);
+        self.schedule_references_refresh(cx);
+        self.record_structural_history_if_changed(pane, history_before, cx);
+    }
+
+    pub(crate) fn apply_block_ref_suggestion(
+        &mut self,
+        uid: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let pane = self.editor.wikilink_menu.pane;
+        let history_before = self.pane_snapshot(pane, cx);
+        let expected_uid = self.editor.wikilink_menu.block_uid.clone();
+        let Some(block_ix) = self.editor.wikilink_menu.block_ix else {
+            return;
+        };
+        let Some(range_start) = self.editor.wikilink_menu.range_start else {
+            return;
+        };
+        let Some(range_end) = self.editor.wikilink_menu.range_end else {
+            return;
+        };
+        let Some(editor) = self.editor_for_pane_mut(pane) else {
+            return;
+        };
+        if block_ix >= editor.blocks().len() {
+            return;
+        }
+        if expected_uid
+            .as_ref()
+            .is_some_and(|uid| &editor.blocks()[block_ix].uid != uid)
+        {
+            return;
+        }
+        let text = editor.blocks()[block_ix].text.clone();
+        if range_start >= range_end || range_end > text.len() {
+            return;
+        }
+        if !text.is_char_boundary(range_start) || !text.is_char_boundary(range_end) {
+            return;
+        }
+
+        let before = &text[..range_start];
+        let after = &text[range_end..];
+        let reference = format!("(({uid}))");
+        let next_text = format!("{before}{reference}{after}");
+        let next_cursor = before.len() + reference.len();
+
+        editor.set_text(block_ix, &next_text);
+        editor.active_ix = block_ix;
+        self.set_active_pane(pane, cx);
+        self.sync_block_input_from_active_with_cursor_for_pane(pane, next_cursor, Some(window), cx);
+        window.focus(&self.editor.block_input.focus_handle(cx), cx);
+        self.close_wikilink_menu();
+        self.mark_dirty_for_pane(pane, cx);
+        self.refresh_block_refs();
+        self.schedule_references_refresh(cx);
+        self.record_structural_history_if_changed(pane, history_before, cx);
+    }
+
+    pub(crate) fn update_database_view_config(
+        &mut self,
+        pane: EditorPane,
+        block_uid: &str,
+        update: impl FnOnce(&mut DatabaseViewConfig),
+        cx: &mut Context<Self>,
+    ) {
+        let history_before = self.pane_snapshot(pane, cx);
+        let Some(editor) = self.editor_for_pane_mut(pane) else {
+            return;
+        };
+        let Some(ix) = editor
+            .blocks()
+            .iter()
+            .position(|block| block.uid == block_uid)
+        else {
+            return;
+        };
+        let mut config = DatabaseViewConfig::from_block_text(&editor.blocks()[ix].text);
+        update(&mut config);
+        if !editor.set_text(ix, &config.to_block_text()) {
+            return;
+        }
+        self.mark_dirty_for_pane(pane, cx);
+        self.record_structural_history_if_changed(pane, history_before, cx);
+        cx.notify();
+    }
+
+    pub(crate) fn begin_database_cell_edit(
+        &mut self,
+        edit: DatabaseCellEdit,
+        value: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.database_cell_edit = Some(edit);
+        self.editor.database_cell_input.update(cx, |input, cx| {
+            input.set_value(value, window, cx);
+        });
+        window.focus(&self.editor.database_cell_input.focus_handle(cx), cx);
+        cx.notify();
+    }
+
+    pub(crate) fn commit_database_cell_edit(&mut self, cx: &mut Context<Self>) {
+        let Some(edit) = self.editor.database_cell_edit.take() else {
+            return;
+        };
+        let value = self
+            .editor
+            .database_cell_input
+            .read(cx)
+            .value()
+            .trim()
+            .to_string();
+        if let Some(db) = self.app.db.as_ref() {
+            // Keep the cell open when the value does not fit the property type.
+            if db
+                .set_page_property(edit.page_id, &edit.key, &value, &edit.value_type)
+                .is_err()
+            {
+                self.editor.database_cell_edit = Some(edit);
+                cx.notify();
+                return;
+            }
+        }
+        if self
+            .editor
+            .active_page
+            .as_ref()
+            .is_some_and(|page| page.id == edit.page_id)
+        {
+            self.load_page_properties();
+        }
+        if edit.key == ALIAS_KEY {
+            self.refresh_page_aliases();
+        }
+        cx.notify();
+    }
+
+    pub(crate) fn cancel_database_cell_edit(&mut self, cx: &mut Context<Self>) {
+        if self.editor.database_cell_edit.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn create_page_from_link(&mut self, title: &str) {
         let trimmed = title.trim();
         if trimmed.is_empty() {
             return;
         }
         let normalized = app::sanitize_kebab(trimmed);
-        let exists = self.editor.pages.iter().any(|page| {
-            app::sanitize_kebab(&page.uid) == normalized
-                || app::sanitize_kebab(&page.title) == normalized
-        });
+        let exists = self
+            .editor
+            .pages
+            .iter()
+            .any(|page| self.page_matches_name(page, &normalized));
         if exists {
             return;
         }
-        let Ok(uid) = app::resolve_unique_page_uid(db, trimmed) else {
+        let settings = &self.editor.journals.settings;
+        let journal_date = settings
+            .parse_title(trimmed)
+            .filter(|date| settings.title(*date) == trimmed);
+        let Some(db) = self.app.db.as_mut() else {
             return;
         };
-        if db.insert_page(&uid, trimmed).is_err() {
-            return;
+        if let Some(date) = journal_date {
+            // Daily pages start from the journal template.
+            if sandpaper_core::journals::ensure_journal_page(db, date, settings).is_err() {
+                return;
+            }
+        } else {
+            let Ok(uid) = app::resolve_unique_page_uid(db, trimmed) else {
+                return;
+            };
+            if db.insert_page(&uid, trimmed).is_err() {
+                return;
+            }
         }
         if let Ok(pages) = db.list_pages() {
-            self.editor.pages = pages;
+            self.editor.set_pages(pages);
             self.refresh_search_results();
         }
     }
@@ -4598,24 +4903,18 @@ impl AppStore {
         }
         let mut primary_changed = false;
         if let Some(editor) = self.editor.editor.as_mut() {
-            for block in editor.blocks.iter_mut() {
-                if let Some(next) = updated_blocks.get(&block.uid) {
-                    if block.text != *next {
-                        block.text = next.clone();
-                        primary_changed = true;
-                    }
+            for ix in 0..editor.blocks().len() {
+                if let Some(next) = updated_blocks.get(&editor.blocks()[ix].uid) {
+                    primary_changed |= editor.set_text(ix, next);
                 }
             }
         }
 
         let mut secondary_changed = false;
         if let Some(pane) = self.editor.secondary_pane.as_mut() {
-            for block in pane.editor.blocks.iter_mut() {
-                if let Some(next) = updated_blocks.get(&block.uid) {
-                    if block.text != *next {
-                        block.text = next.clone();
-                        secondary_changed = true;
-                    }
+            for ix in 0..pane.editor.blocks().len() {
+                if let Some(next) = updated_blocks.get(&pane.editor.blocks()[ix].uid) {
+                    secondary_changed |= pane.editor.set_text(ix, next);
                 }
             }
         }
@@ -4896,6 +5195,13 @@ impl AppStore {
                     .find(|page| page.title.eq_ignore_ascii_case(title))
                     .cloned()
             })
+            .or_else(|| {
+                self.editor
+                    .pages
+                    .iter()
+                    .find(|page| self.page_matches_name(page, &normalized))
+                    .cloned()
+            })
     }
 
     pub(crate) fn image_mime_type_for_path(path: &Path) -> Option<&'static str> {
@@ -4990,11 +5296,16 @@ impl AppStore {
                 return 0;
             };
 
-            let (insert_ix, indent) = if editor.blocks.is_empty() {
+            let (insert_ix, indent) = if editor.blocks().is_empty() {
                 (0usize, 0i64)
             } else {
-                let active_ix = editor.active_ix.min(editor.blocks.len().saturating_sub(1));
-                (active_ix.saturating_add(1), editor.blocks[active_ix].indent)
+                let active_ix = editor
+                    .active_ix
+                    .min(editor.blocks().len().saturating_sub(1));
+                (
+                    active_ix.saturating_add(1),
+                    editor.blocks()[active_ix].indent,
+                )
             };
 
             let mut next_ix = insert_ix;
@@ -5005,7 +5316,7 @@ impl AppStore {
                     indent,
                     block_type: BlockType::Image,
                 };
-                editor.blocks.insert(next_ix, block);
+                editor.insert_block(next_ix, block);
                 next_ix = next_ix.saturating_add(1);
             }
 
@@ -5054,16 +5365,16 @@ impl AppStore {
             let Some(editor) = self.editor_for_pane(pane) else {
                 return;
             };
-            if block_ix >= editor.blocks.len() {
+            if block_ix >= editor.blocks().len() {
                 return;
             }
             if expected_uid
                 .as_ref()
-                .is_some_and(|uid| &editor.blocks[block_ix].uid != uid)
+                .is_some_and(|uid| &editor.blocks()[block_ix].uid != uid)
             {
                 return;
             }
-            editor.blocks[block_ix].text.clone()
+            editor.blocks()[block_ix].text.clone()
         };
         let command_end = slash_index + 1 + query.len();
         if slash_index >= text.len() || command_end > text.len() {
@@ -5107,24 +5418,25 @@ impl AppStore {
                 let cursor = source.len();
                 (Some(BlockType::Image), source, cursor)
             }
+            SlashAction::InsertTemplate => (None, format!("{before}{after}"), before.len()),
         };
 
         let Some(editor) = self.editor_for_pane_mut(pane) else {
             return;
         };
-        if block_ix >= editor.blocks.len() {
+        if block_ix >= editor.blocks().len() {
             return;
         }
         if expected_uid
             .as_ref()
-            .is_some_and(|uid| &editor.blocks[block_ix].uid != uid)
+            .is_some_and(|uid| &editor.blocks()[block_ix].uid != uid)
         {
             return;
         }
         if let Some(block_type) = next_block_type {
-            editor.blocks[block_ix].block_type = block_type;
+            editor.set_block_type(block_ix, block_type);
         }
-        editor.blocks[block_ix].text = next_text.clone();
+        editor.set_text(block_ix, &next_text);
         editor.active_ix = block_ix;
         self.set_active_pane(pane, cx);
         self.sync_block_input_from_active_with_cursor_for_pane(pane, next_cursor, Some(window), cx);
@@ -5133,6 +5445,9 @@ impl AppStore {
         self.mark_dirty_for_pane(pane, cx);
         self.schedule_references_refresh(cx);
         self.record_structural_history_if_changed(pane, history_before, cx);
+        if action == SlashAction::InsertTemplate {
+            self.open_template_dialog(pane, window, cx);
+        }
     }
 }
 
@@ -5318,6 +5633,50 @@ mod tests {
         .unwrap();
     }
 
+    #[gpui::test]
+    fn wikilink_menu_offers_page_aliases(cx: &mut TestAppContext) {
+        cx.skip_drawing();
+        let app_handle: Rc<RefCell<Option<Entity<AppStore>>>> = Rc::new(RefCell::new(None));
+
+        {
+            let mut app = cx.app.borrow_mut();
+            gpui_component::init(&mut app);
+        }
+
+        let app_handle_for_window = app_handle.clone();
+        let window = cx.add_window(|window, cx| {
+            let app = cx.new(|cx| AppStore::new(window, cx));
+            *app_handle_for_window.borrow_mut() = Some(app.clone());
+            Root::new(app, window, cx)
+        });
+
+        let app = app_handle.borrow().clone().expect("app");
+        cx.update_window(*window, |_root, _window, cx| {
+            app.update(cx, |app, cx| {
+                app.app.mode = Mode::Editor;
+                app.editor.set_pages(vec![PageRecord {
+                    id: 1,
+                    uid: "knowledge-management".to_string(),
+                    title: "Knowledge Management".to_string(),
+                }]);
+                app.editor
+                    .title_index
+                    .set_aliases("knowledge-management", &["KM".to_string()]);
+
+                app.update_wikilink_menu(EditorPane::Primary, "block-1", 0, 4, "[[km", cx);
+
+                let items = app.wikilink_menu_items();
+                assert_eq!(items.len(), 1);
+                assert!(matches!(
+                    &items[0],
+                    WikilinkMenuItem::Alias { page, alias }
+                        if page.uid == "knowledge-management" && alias == "KM"
+                ));
+            });
+        })
+        .unwrap();
+    }
+
     #[gpui::test]
     fn opening_link_preview_closes_outline_overlay(cx: &mut TestAppContext) {
         cx.skip_drawing();
@@ -5518,6 +5877,13 @@ mod tests {
                 app.duplicate_block(&DuplicateBlock, window, cx);
                 assert_eq!(app.editor.editor.as_ref().expect("editor").blocks.len(), 2);
                 assert_eq!(app.editor.undo_stack.len(), 1);
+                let Some(HistoryEntry::Structural(entry)) = app.editor.undo_stack.last() else {
+                    panic!("expected structural history entry");
+                };
+                assert!(matches!(
+                    entry.ops.as_slice(),
+                    [EditorOp::InsertBlock { index: 1, .. }]
+                ));
 
                 app.undo_edit_action(&UndoEdit, window, cx);
                 assert_eq!(app.editor.editor.as_ref().expect("editor").blocks.len(), 1);
@@ -5573,7 +5939,7 @@ mod tests {
                 );
 
                 if let Some(editor) = app.editor.editor.as_mut() {
-                    editor.blocks[0].text = "h".to_string();
+                    editor.set_text(0, "h");
                 }
                 app.record_text_history_change(
                     EditorPane::Primary,
@@ -5587,7 +5953,7 @@ mod tests {
                     },
                 );
                 if let Some(editor) = app.editor.editor.as_mut() {
-                    editor.blocks[0].text = "he".to_string();
+                    editor.set_text(0, "he");
                 }
                 app.record_text_history_change(
                     EditorPane::Primary,
@@ -5606,7 +5972,7 @@ mod tests {
                         .editor
                         .as_ref()
                         .expect("editor")
-                        .blocks
+                        .blocks()
                         .first()
                         .expect("block")
                         .text,
@@ -5624,7 +5990,7 @@ mod tests {
                         .editor
                         .as_ref()
                         .expect("editor")
-                        .blocks
+                        .blocks()
                         .first()
                         .expect("block")
                         .text,
@@ -5637,7 +6003,7 @@ mod tests {
                         .editor
                         .as_ref()
                         .expect("editor")
-                        .blocks
+                        .blocks()
                         .first()
                         .expect("block")
                         .text,
@@ -5703,13 +6069,14 @@ mod tests {
                 .expect("seed page b");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
 
                 app.open_page("page-a", cx);
                 app.duplicate_block(&DuplicateBlock, window, cx);
@@ -6771,10 +7138,10 @@ mod tests {
                 assert!(app.editor.drag_target.is_none());
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].uid, "a1");
-                assert_eq!(editor.blocks[1].uid, "a3");
-                assert_eq!(editor.blocks[2].uid, "a2");
-                assert_eq!(editor.blocks[3].uid, "a4");
+                assert_eq!(editor.blocks()[0].uid, "a1");
+                assert_eq!(editor.blocks()[1].uid, "a3");
+                assert_eq!(editor.blocks()[2].uid, "a2");
+                assert_eq!(editor.blocks()[3].uid, "a4");
             });
         })
         .unwrap();
@@ -6854,10 +7221,10 @@ mod tests {
                 assert!(app.commit_block_drag_for_pane(EditorPane::Primary, cx));
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].uid, "a2");
-                assert_eq!(editor.blocks[1].uid, "a3");
-                assert_eq!(editor.blocks[2].uid, "a1");
-                assert_eq!(editor.blocks[3].uid, "a4");
+                assert_eq!(editor.blocks()[0].uid, "a2");
+                assert_eq!(editor.blocks()[1].uid, "a3");
+                assert_eq!(editor.blocks()[2].uid, "a1");
+                assert_eq!(editor.blocks()[3].uid, "a4");
                 assert_eq!(app.editor.primary_selection.range, Some((0, 1)));
                 assert_eq!(app.editor.primary_selection.anchor, Some(0));
             });
@@ -6933,10 +7300,10 @@ mod tests {
                 assert!(app.commit_block_drag_for_pane(EditorPane::Primary, cx));
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].uid, "a1");
-                assert_eq!(editor.blocks[1].uid, "a3");
-                assert_eq!(editor.blocks[2].uid, "a4");
-                assert_eq!(editor.blocks[3].uid, "a2");
+                assert_eq!(editor.blocks()[0].uid, "a1");
+                assert_eq!(editor.blocks()[1].uid, "a3");
+                assert_eq!(editor.blocks()[2].uid, "a4");
+                assert_eq!(editor.blocks()[3].uid, "a2");
             });
         })
         .unwrap();
@@ -6994,12 +7361,12 @@ mod tests {
                 assert_eq!(inserted, 1);
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks.len(), 2);
+                assert_eq!(editor.blocks().len(), 2);
                 assert_eq!(editor.active_ix, 1);
-                assert_eq!(editor.blocks[1].block_type, BlockType::Image);
-                assert!(editor.blocks[1].text.starts_with("![cat.png](/assets/"));
+                assert_eq!(editor.blocks()[1].block_type, BlockType::Image);
+                assert!(editor.blocks()[1].text.starts_with("![cat.png](/assets/"));
                 let source =
-                    helpers::extract_image_source(&editor.blocks[1].text).expect("image source");
+                    helpers::extract_image_source(&editor.blocks()[1].text).expect("image source");
                 let stored = vault_root.join(source.trim_start_matches('/'));
                 assert!(stored.exists());
                 assert!(app.app.primary_dirty);
@@ -7060,8 +7427,8 @@ mod tests {
                 assert_eq!(inserted, 0);
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks.len(), 1);
-                assert_eq!(editor.blocks[0].block_type, BlockType::Text);
+                assert_eq!(editor.blocks().len(), 1);
+                assert_eq!(editor.blocks()[0].block_type, BlockType::Text);
                 assert!(!app.app.primary_dirty);
             });
         })
@@ -7138,18 +7505,18 @@ mod tests {
                 assert!(app.paste_selection_blocks_in_pane(EditorPane::Primary, window, cx));
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks.len(), 5);
-                assert_eq!(editor.blocks[0].text, "A");
-                assert_eq!(editor.blocks[1].text, "B");
-                assert_eq!(editor.blocks[2].text, "A");
-                assert_eq!(editor.blocks[2].indent, 0);
-                assert_eq!(editor.blocks[2].block_type, BlockType::Text);
-                assert_eq!(editor.blocks[3].text, "B");
-                assert_eq!(editor.blocks[3].indent, 2);
-                assert_eq!(editor.blocks[3].block_type, BlockType::Todo);
-                assert_eq!(editor.blocks[4].text, "C");
-                assert_ne!(editor.blocks[0].uid, editor.blocks[2].uid);
-                assert_ne!(editor.blocks[1].uid, editor.blocks[3].uid);
+                assert_eq!(editor.blocks().len(), 5);
+                assert_eq!(editor.blocks()[0].text, "A");
+                assert_eq!(editor.blocks()[1].text, "B");
+                assert_eq!(editor.blocks()[2].text, "A");
+                assert_eq!(editor.blocks()[2].indent, 0);
+                assert_eq!(editor.blocks()[2].block_type, BlockType::Text);
+                assert_eq!(editor.blocks()[3].text, "B");
+                assert_eq!(editor.blocks()[3].indent, 2);
+                assert_eq!(editor.blocks()[3].block_type, BlockType::Todo);
+                assert_eq!(editor.blocks()[4].text, "C");
+                assert_ne!(editor.blocks()[0].uid, editor.blocks()[2].uid);
+                assert_ne!(editor.blocks()[1].uid, editor.blocks()[3].uid);
                 assert_eq!(app.editor.primary_selection.range, Some((2, 3)));
             });
         })
@@ -7223,8 +7590,8 @@ mod tests {
                 assert_eq!(copied.items[1].text, "B");
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks.len(), 1);
-                assert_eq!(editor.blocks[0].text, "C");
+                assert_eq!(editor.blocks().len(), 1);
+                assert_eq!(editor.blocks()[0].text, "C");
             });
         })
         .unwrap();
@@ -7279,9 +7646,9 @@ mod tests {
 
                 assert!(!app.paste_selection_blocks_in_pane(EditorPane::Primary, window, cx));
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks.len(), 2);
-                assert_eq!(editor.blocks[0].text, "A");
-                assert_eq!(editor.blocks[1].text, "B");
+                assert_eq!(editor.blocks().len(), 2);
+                assert_eq!(editor.blocks()[0].text, "A");
+                assert_eq!(editor.blocks()[1].text, "B");
             });
         })
         .unwrap();
@@ -7366,14 +7733,14 @@ mod tests {
             app.update(cx, |app, cx| {
                 assert_eq!(app.editor.undo_stack.len(), 1);
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].text, "Alpha changed");
-                assert_eq!(editor.blocks[1].text, "Beta");
+                assert_eq!(editor.blocks()[0].text, "Alpha changed");
+                assert_eq!(editor.blocks()[1].text, "Beta");
 
                 app.undo_edit_action(&UndoEdit, window, cx);
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].text, "Alpha");
-                assert_eq!(editor.blocks[1].text, "Beta");
+                assert_eq!(editor.blocks()[0].text, "Alpha");
+                assert_eq!(editor.blocks()[1].text, "Beta");
                 assert_eq!(editor.active_ix, 0);
             });
         })
@@ -7430,7 +7797,7 @@ mod tests {
                 );
 
                 if let Some(editor) = app.editor.editor.as_mut() {
-                    editor.blocks[0].text = "Alpha changed".to_string();
+                    editor.set_text(0, "Alpha changed");
                     editor.active_ix = 1;
                 }
                 app.record_text_history_change(
@@ -7459,8 +7826,8 @@ mod tests {
                 assert!(app.handle_block_input_key_down(EditorPane::Primary, &undo, window, cx));
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].text, "Alpha");
-                assert_eq!(editor.blocks[1].text, "Beta");
+                assert_eq!(editor.blocks()[0].text, "Alpha");
+                assert_eq!(editor.blocks()[1].text, "Beta");
             });
         })
         .unwrap();
@@ -7666,13 +8033,14 @@ mod tests {
                 .expect("seed project");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
                 app.editor.active_page = None;
                 app.editor.editor = None;
                 app.editor.secondary_pane = None;
@@ -7746,13 +8114,14 @@ mod tests {
                 .expect("seed inbox");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
                 app.editor.active_page = None;
                 app.editor.editor = None;
                 app.editor.secondary_pane = None;
@@ -7800,13 +8169,14 @@ mod tests {
                 db.insert_page("inbox", "Inbox").expect("insert inbox");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
                 app.editor.active_page = None;
                 app.editor.editor = None;
                 app.editor.secondary_pane = None;
@@ -7879,13 +8249,14 @@ mod tests {
                     .expect("seed review");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
                 app.editor.active_page = None;
                 app.editor.editor = None;
                 app.editor.secondary_pane = None;
@@ -7955,13 +8326,14 @@ mod tests {
                 .expect("seed inbox");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
                 app.open_page("inbox", cx);
                 assert_eq!(app.capture_queue_items().len(), 1);
 
@@ -7972,8 +8344,8 @@ mod tests {
 
                 assert!(app.capture_queue_items().is_empty());
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks.len(), 1);
-                assert!(editor.blocks[0].text.is_empty());
+                assert_eq!(editor.blocks().len(), 1);
+                assert!(editor.blocks()[0].text.is_empty());
             });
         })
         .unwrap();
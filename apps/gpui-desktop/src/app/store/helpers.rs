@@ -156,6 +156,76 @@ pub(crate) fn find_wikilink_query(text: &str, cursor: usize) -> Option<WikilinkQ
     })
 }
 
+fn is_tag_query_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '-' | '_' | '/')
+}
+
+/// Finds a `#tag` being typed at `cursor`. The range covers the whole tag token so accepting a
+/// suggestion replaces any characters already typed after the cursor.
+pub(crate) fn find_tag_query(text: &str, cursor: usize) -> Option<WikilinkQuery> {
+    let mut cursor = cursor.min(text.len());
+    while cursor > 0 && !text.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let before = &text[..cursor];
+    let token_start = before
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| !is_tag_query_char(*ch))
+        .map(|(ix, ch)| ix + ch.len_utf8())
+        .unwrap_or(0);
+    let hash_ix = token_start.checked_sub(1)?;
+    if !before[hash_ix..].starts_with('#') {
+        return None;
+    }
+    let at_boundary = before[..hash_ix]
+        .chars()
+        .next_back()
+        .is_none_or(|ch| ch.is_whitespace() || matches!(ch, '(' | '[' | ',' | ';'));
+    if !at_boundary {
+        return None;
+    }
+    let range_end = text[cursor..]
+        .char_indices()
+        .find(|(_, ch)| !is_tag_query_char(*ch))
+        .map(|(ix, _)| cursor + ix)
+        .unwrap_or(text.len());
+    let query = text[token_start..range_end].to_string();
+    if query.is_empty() {
+        return None;
+    }
+    Some(WikilinkQuery {
+        range_start: hash_ix,
+        range_end,
+        has_closing: false,
+        query,
+    })
+}
+
+/// Finds a `((block ref` being typed at `cursor`; the query is the free text searched for.
+pub(crate) fn find_block_ref_query(text: &str, cursor: usize) -> Option<WikilinkQuery> {
+    let mut cursor = cursor.min(text.len());
+    while cursor > 0 && !text.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let before = &text[..cursor];
+    let start = before.rfind("((")?;
+    let inner_start = start + 2;
+    if before[inner_start..].contains("))") {
+        return None;
+    }
+    let close_ix = text[inner_start..].find("))").map(|rel| inner_start + rel);
+    let inner_end = close_ix.unwrap_or(text.len());
+    let inner = &text[inner_start..inner_end];
+    let query = inner.split('|').next().unwrap_or(inner).trim().to_string();
+    Some(WikilinkQuery {
+        range_start: start,
+        range_end: close_ix.map(|ix| ix + 2).unwrap_or(text.len()),
+        has_closing: close_ix.is_some(),
+        query,
+    })
+}
+
 #[allow(dead_code)] // Used in tests via store.rs
 pub(crate) fn parse_wikilink_tokens(text: &str) -> Vec<WikilinkToken> {
     let mut tokens = Vec::new();
@@ -236,55 +306,6 @@ pub(crate) fn resolve_cursor_for_blocks(
     (0, blocks[0].text.len())
 }
 
-pub(crate) fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
-    let query = query.trim().to_lowercase();
-    if query.is_empty() {
-        return Some(0);
-    }
-
-    let text_lower = text.to_lowercase();
-    let query_chars: Vec<char> = query.chars().collect();
-    let text_chars: Vec<char> = text_lower.chars().collect();
-
-    if query_chars.is_empty() || text_chars.is_empty() {
-        return None;
-    }
-
-    let mut score: i64 = 0;
-    let mut search_idx = 0usize;
-    let mut last_match: Option<usize> = None;
-
-    for q in query_chars {
-        let mut found = None;
-        for (ix, ch) in text_chars.iter().enumerate().skip(search_idx) {
-            if *ch == q {
-                found = Some(ix);
-                break;
-            }
-        }
-        let ix = found?;
-
-        score += 10;
-        if let Some(prev) = last_match {
-            if ix == prev + 1 {
-                score += 6;
-            } else {
-                let gap = (ix - prev - 1) as i64;
-                score -= gap.min(3);
-            }
-        }
-        if ix == 0 || !text_chars[ix - 1].is_alphanumeric() {
-            score += 3;
-        }
-
-        last_match = Some(ix);
-        search_idx = ix + 1;
-    }
-
-    let length_penalty = (text_chars.len().saturating_sub(query.len())) as i64 / 4;
-    Some(score - length_penalty)
-}
-
 pub(crate) fn count_case_insensitive_occurrences(text: &str, needle: &str) -> usize {
     let needle = needle.trim();
     if needle.is_empty() {
@@ -648,9 +669,10 @@ pub(crate) fn link_first_unlinked_reference(
     Some((next_text, next_cursor))
 }
 
+/// Combines a page's title score (from the title index) with a match in its block snippet.
 pub(crate) fn score_palette_page(
     query: &str,
-    title: &str,
+    title_score: Option<i64>,
     snippet: &str,
     recent_rank: Option<usize>,
 ) -> Option<i64> {
@@ -663,7 +685,6 @@ pub(crate) fn score_palette_page(
         return Some(recency_boost);
     }
 
-    let title_score = fuzzy_score(query, title);
     let snippet_score = fuzzy_score(query, snippet).map(|score| score.saturating_sub(3));
     let best = match (title_score, snippet_score) {
         (Some(title), Some(snippet)) => title.max(snippet),
@@ -811,6 +832,42 @@ mod tests {
         assert!(q.has_closing);
     }
 
+    #[test]
+    fn find_tag_query_reads_tag_at_cursor() {
+        let q = find_tag_query("plan #proj work", 10).unwrap();
+        assert_eq!(q.range_start, 5);
+        assert_eq!(q.range_end, 10);
+        assert_eq!(q.query, "proj");
+
+        let q = find_tag_query("#area/ho", 8).unwrap();
+        assert_eq!(q.range_start, 0);
+        assert_eq!(q.query, "area/ho");
+    }
+
+    #[test]
+    fn find_tag_query_rejects_headings_and_mid_word() {
+        assert!(find_tag_query("# ", 2).is_none());
+        assert!(find_tag_query("#", 1).is_none());
+        assert!(find_tag_query("issue#12", 8).is_none());
+        assert!(find_tag_query("plain text", 5).is_none());
+    }
+
+    #[test]
+    fn find_block_ref_query_reads_text_inside_parens() {
+        let q = find_block_ref_query("see ((weekly plan", 17).unwrap();
+        assert_eq!(q.range_start, 4);
+        assert_eq!(q.range_end, 17);
+        assert_eq!(q.query, "weekly plan");
+        assert!(!q.has_closing);
+
+        let q = find_block_ref_query("see ((plan)) now", 8).unwrap();
+        assert_eq!(q.range_end, 12);
+        assert!(q.has_closing);
+
+        assert!(find_block_ref_query("see ((plan)) now", 14).is_none());
+        assert!(find_block_ref_query("plain (text)", 8).is_none());
+    }
+
     #[test]
     fn cycle_index_forward_wraps() {
         assert_eq!(cycle_index(2, 3, true), 0);
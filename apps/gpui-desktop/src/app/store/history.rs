@@ -0,0 +1,178 @@
+use super::*;
+use sandpaper_core::revisions::{diff_blocks, BlockDiff, PageRevision, PageRevisionRecord};
+
+#[derive(Clone, Debug)]
+pub(crate) struct HistoryPreview {
+    pub(crate) revision: PageRevision,
+    /// Changes from the revision to the page as it is now.
+    pub(crate) diff: Vec<BlockDiff>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PageHistoryState {
+    pub(crate) revisions: Vec<PageRevisionRecord>,
+    pub(crate) preview: Option<HistoryPreview>,
+}
+
+impl AppStore {
+    fn history_panel_visible(&self) -> bool {
+        self.settings.context_panel_open
+            && self.settings.context_panel_tab == WorkspacePanel::History
+    }
+
+    /// Reloads the active page's revision list while the history panel is showing.
+    pub(crate) fn refresh_page_history(&mut self) {
+        if !self.history_panel_visible() {
+            return;
+        }
+        let page_id = self.editor.active_page.as_ref().map(|page| page.id);
+        let revisions = match (self.app.db.as_ref(), page_id) {
+            (Some(db), Some(page_id)) => db.list_page_revisions(page_id).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let history = &mut self.editor.page_history;
+        history.revisions = revisions;
+        if history.preview.as_ref().is_some_and(|preview| {
+            Some(preview.revision.page_id) != page_id
+                || !history
+                    .revisions
+                    .iter()
+                    .any(|revision| revision.id == preview.revision.id)
+        }) {
+            history.preview = None;
+        }
+        self.refresh_history_diff();
+    }
+
+    fn refresh_history_diff(&mut self) {
+        let current = self
+            .editor
+            .editor
+            .as_ref()
+            .map(|editor| editor.blocks().to_vec())
+            .unwrap_or_default();
+        if let Some(preview) = self.editor.page_history.preview.as_mut() {
+            preview.diff = diff_blocks(&preview.revision.blocks, &current);
+        }
+    }
+
+    pub(crate) fn preview_page_revision(&mut self, revision_id: i64, cx: &mut Context<Self>) {
+        if self
+            .editor
+            .page_history
+            .preview
+            .as_ref()
+            .is_some_and(|preview| preview.revision.id == revision_id)
+        {
+            self.editor.page_history.preview = None;
+            cx.notify();
+            return;
+        }
+        let Some(revision) = self
+            .app
+            .db
+            .as_ref()
+            .and_then(|db| db.load_page_revision(revision_id).ok().flatten())
+        else {
+            return;
+        };
+        self.editor.page_history.preview = Some(HistoryPreview {
+            revision,
+            diff: Vec::new(),
+        });
+        self.refresh_history_diff();
+        cx.notify();
+    }
+
+    /// Replaces the active page with the previewed revision.
+    pub(crate) fn restore_previewed_revision(&mut self, cx: &mut Context<Self>) {
+        let Some(preview) = self.editor.page_history.preview.take() else {
+            return;
+        };
+        let Some(page) = self.editor.active_page.clone() else {
+            return;
+        };
+        if preview.revision.page_id != page.id {
+            return;
+        }
+        if self.app.primary_dirty {
+            self.save(cx);
+        }
+        let restored = self.app.db.as_mut().is_some_and(|db| {
+            db.restore_page_revision(preview.revision.id)
+                .unwrap_or(false)
+        });
+        if !restored {
+            self.editor.page_history.preview = Some(preview);
+            return;
+        }
+        self.open_page(&page.uid, cx);
+        self.refresh_page_history();
+        cx.notify();
+    }
+
+    /// Copies one block from the previewed revision into the active page: an existing block with
+    /// the same uid gets the old content back, a deleted one is reinserted after the block that
+    /// preceded it in the revision.
+    pub(crate) fn restore_revision_block(&mut self, block_uid: &str, cx: &mut Context<Self>) {
+        let pane = EditorPane::Primary;
+        let Some(preview) = self.editor.page_history.preview.as_ref() else {
+            return;
+        };
+        let Some(revision_ix) = preview
+            .revision
+            .blocks
+            .iter()
+            .position(|block| block.uid == block_uid)
+        else {
+            return;
+        };
+        let block = preview.revision.blocks[revision_ix].clone();
+        let preceding: Vec<String> = preview.revision.blocks[..revision_ix]
+            .iter()
+            .rev()
+            .map(|block| block.uid.clone())
+            .collect();
+
+        let history_before = self.pane_snapshot(pane, cx);
+        let active_changed = {
+            let Some(editor) = self.editor.editor.as_mut() else {
+                return;
+            };
+            if let Some(ix) = editor
+                .blocks()
+                .iter()
+                .position(|existing| existing.uid == block.uid)
+            {
+                editor.update_block(ix, &block);
+                ix == editor.active_ix
+            } else {
+                let insert_at = preceding
+                    .iter()
+                    .find_map(|uid| {
+                        editor
+                            .blocks()
+                            .iter()
+                            .position(|existing| &existing.uid == uid)
+                    })
+                    .map(|ix| ix + 1)
+                    .unwrap_or(0);
+                editor.insert_block(insert_at, block);
+                if insert_at <= editor.active_ix && editor.blocks().len() > 1 {
+                    editor.active_ix += 1;
+                }
+                false
+            }
+        };
+
+        self.update_block_list_for_pane(pane);
+        if active_changed {
+            self.sync_block_input_from_active_for_pane(pane, None, cx);
+        }
+        self.mark_dirty_for_pane(pane, cx);
+        self.schedule_references_refresh(cx);
+        self.record_structural_history_if_changed(pane, history_before, cx);
+        self.refresh_history_diff();
+        cx.notify();
+    }
+}
@@ -0,0 +1,206 @@
+use super::*;
+use chrono::{Datelike, Months, NaiveDate};
+use sandpaper_core::journals::{ensure_journal_page, journal_pages, JournalSettings};
+
+/// Daily pages added to the journal feed each time it loads further back.
+const JOURNAL_BATCH_DAYS: usize = 7;
+/// Title formats offered in settings.
+pub(crate) const JOURNAL_TITLE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%b %-d, %Y", "%A, %-d %B %Y"];
+
+#[derive(Clone, Debug)]
+pub(crate) struct JournalDay {
+    pub(crate) date: NaiveDate,
+    pub(crate) page: PageRecord,
+    pub(crate) blocks: Vec<BlockSnapshot>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct JournalState {
+    pub(crate) settings: JournalSettings,
+    /// Daily pages shown in the feed, newest first.
+    pub(crate) days: Vec<JournalDay>,
+    /// Whether older daily pages exist past the last one shown.
+    pub(crate) has_more: bool,
+    /// Every date with a daily page, for the calendar.
+    pub(crate) dates: HashSet<NaiveDate>,
+    /// The newest day in the feed; `None` follows today.
+    pub(crate) anchor: Option<NaiveDate>,
+    pub(crate) limit: usize,
+    /// First day of the month shown in the calendar; `None` follows today.
+    pub(crate) calendar_month: Option<NaiveDate>,
+}
+
+impl Default for JournalState {
+    fn default() -> Self {
+        Self {
+            settings: JournalSettings::default(),
+            days: Vec::new(),
+            has_more: false,
+            dates: HashSet::new(),
+            anchor: None,
+            limit: JOURNAL_BATCH_DAYS,
+            calendar_month: None,
+        }
+    }
+}
+
+impl JournalState {
+    pub(crate) fn calendar_month(&self, today: NaiveDate) -> NaiveDate {
+        self.calendar_month
+            .or(self.anchor)
+            .unwrap_or(today)
+            .with_day(1)
+            .unwrap_or(today)
+    }
+}
+
+impl AppStore {
+    /// Loads the vault's journal settings and starts the feed back at today.
+    pub(crate) fn reset_journals(&mut self) {
+        let settings = self
+            .app
+            .db
+            .as_ref()
+            .and_then(|db| JournalSettings::load(db).ok())
+            .unwrap_or_default();
+        self.editor.journals = JournalState {
+            settings,
+            ..JournalState::default()
+        };
+    }
+
+    /// Reloads the daily pages in the journal feed while journals mode is showing.
+    pub(crate) fn refresh_journals(&mut self) {
+        if self.app.mode != Mode::Journals {
+            return;
+        }
+        let Some(db) = self.app.db.as_ref() else {
+            return;
+        };
+        let journals = &mut self.editor.journals;
+        let pages = journal_pages(db, &journals.settings).unwrap_or_default();
+        let newest = journals.anchor.unwrap_or_else(|| Local::now().date_naive());
+        let mut shown = pages.iter().filter(|(date, _)| *date <= newest);
+        journals.days = shown
+            .by_ref()
+            .take(journals.limit)
+            .map(|(date, page)| JournalDay {
+                date: *date,
+                page: page.clone(),
+                blocks: db.load_blocks_for_page(page.id).unwrap_or_default(),
+            })
+            .collect();
+        journals.has_more = shown.next().is_some();
+        journals.dates = pages.iter().map(|(date, _)| *date).collect();
+    }
+
+    pub(crate) fn load_older_journals(&mut self, cx: &mut Context<Self>) {
+        self.editor.journals.limit += JOURNAL_BATCH_DAYS;
+        self.refresh_journals();
+        cx.notify();
+    }
+
+    /// Starts the journal feed at `date`, or back at today for `None`.
+    pub(crate) fn jump_to_journal_date(&mut self, date: Option<NaiveDate>, cx: &mut Context<Self>) {
+        let journals = &mut self.editor.journals;
+        journals.anchor = date;
+        journals.limit = JOURNAL_BATCH_DAYS;
+        journals.calendar_month = None;
+        self.refresh_journals();
+        cx.notify();
+    }
+
+    pub(crate) fn shift_journal_calendar(&mut self, months: i32, cx: &mut Context<Self>) {
+        let today = Local::now().date_naive();
+        let current = self.editor.journals.calendar_month(today);
+        let step = Months::new(months.unsigned_abs());
+        let next = if months < 0 {
+            current.checked_sub_months(step)
+        } else {
+            current.checked_add_months(step)
+        };
+        if let Some(next) = next {
+            self.editor.journals.calendar_month = Some(next);
+            cx.notify();
+        }
+    }
+
+    /// Opens the daily page for `date` in the editor, creating it from the journal template.
+    pub(crate) fn open_journal_day(&mut self, date: NaiveDate, cx: &mut Context<Self>) {
+        let settings = self.editor.journals.settings.clone();
+        let Some(db) = self.app.db.as_mut() else {
+            return;
+        };
+        let Ok((page, created)) = ensure_journal_page(db, date, &settings) else {
+            return;
+        };
+        if created {
+            self.editor.set_pages(db.list_pages().unwrap_or_default());
+            self.refresh_search_results();
+        }
+        self.set_mode(Mode::Editor, cx);
+        self.open_page(&page.uid, cx);
+    }
+
+    /// Follows a wikilink clicked in block `block_uid`. A date link like `[[next friday]]`
+    /// opens that day's daily page, counted from the page the link is on; other links open
+    /// their page.
+    pub(crate) fn open_link(&mut self, target: &str, block_uid: &str, cx: &mut Context<Self>) {
+        let date = self
+            .app
+            .db
+            .as_ref()
+            .and_then(|db| db.resolve_date_link(target, Some(block_uid)).ok())
+            .flatten();
+        match date {
+            Some(date) => self.open_journal_day(date, cx),
+            None => self.open_page(target, cx),
+        }
+    }
+
+    /// Offers the dated daily page for a wikilink query like `tomorrow`, counted from the page
+    /// being edited.
+    pub(crate) fn journal_link_suggestion(&self) -> Option<WikilinkMenuItem> {
+        let query = self.editor.wikilink_menu.query.trim();
+        let block_uid = self.editor.wikilink_menu.block_uid.as_deref();
+        let date = self
+            .app
+            .db
+            .as_ref()?
+            .resolve_date_link(query, block_uid)
+            .ok()??;
+        let title = self.editor.journals.settings.title(date);
+        if title.eq_ignore_ascii_case(query) {
+            return None;
+        }
+        Some(WikilinkMenuItem::Create {
+            label: format!("{query} → {title}"),
+            query: title,
+        })
+    }
+
+    pub(crate) fn set_journal_title_format(&mut self, format: &str, cx: &mut Context<Self>) {
+        self.editor.journals.settings.title_format = format.to_string();
+        self.save_journal_settings(cx);
+    }
+
+    /// Uses the active page as the template for new daily pages, or clears it for `None`.
+    pub(crate) fn set_journal_template(
+        &mut self,
+        page: Option<PageRecord>,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.journals.settings.template = page.map(|page| page.uid);
+        self.save_journal_settings(cx);
+    }
+
+    fn save_journal_settings(&mut self, cx: &mut Context<Self>) {
+        if let Some(db) = self.app.db.as_ref() {
+            if let Err(err) = self.editor.journals.settings.save(db) {
+                tracing::warn!(error = ?err, "failed to save journal settings");
+            }
+        }
+        self.refresh_journals();
+        cx.notify();
+    }
+}
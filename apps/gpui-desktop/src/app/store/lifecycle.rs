@@ -1,27 +1,18 @@
-use super::editor::update_wikilinks_in_db;
 use super::helpers::{default_vault_path, expand_tilde};
 use super::*;
 use gpui_component::{Theme, ThemeMode};
-
-fn daily_note_title(date: chrono::NaiveDate) -> String {
-    date.format("%Y-%m-%d").to_string()
-}
-
-fn ensure_daily_note_in_db(db: &Database, date: chrono::NaiveDate) -> Result<bool, String> {
-    let title = daily_note_title(date);
-    let daily_uid = app::sanitize_kebab(&title);
-
-    let pages = db.list_pages().map_err(|err| format!("{err:?}"))?;
-    let exists = pages.iter().any(|page| {
-        app::sanitize_kebab(&page.uid) == daily_uid || app::sanitize_kebab(&page.title) == daily_uid
-    });
-    if exists {
-        return Ok(false);
-    }
-
-    db.insert_page(&daily_uid, &title)
-        .map_err(|err| format!("{err:?}"))?;
-    Ok(true)
+use sandpaper_core::journals::{ensure_journal_page, JournalSettings};
+use sandpaper_core::plugin_events::{
+    block_events, PluginEvent, BLOCK_CHANGED, BLOCK_CREATED, BLOCK_DELETED, PAGE_SAVED,
+};
+
+fn ensure_daily_note_in_db(
+    db: &mut Database,
+    date: chrono::NaiveDate,
+    settings: &JournalSettings,
+) -> Result<bool, String> {
+    let (_, created) = ensure_journal_page(db, date, settings).map_err(|err| format!("{err:?}"))?;
+    Ok(created)
 }
 
 fn should_focus_mode_input(
@@ -121,6 +112,7 @@ impl AppStore {
     pub(crate) fn boot(&mut self, cx: &mut Context<Self>) {
         self.editor.backlinks.clear();
         self.editor.block_backlinks.clear();
+        self.editor.tagged_blocks.clear();
         self.editor.unlinked_references.clear();
         self.editor.secondary_pane = None;
         self.editor.primary_selection.clear();
@@ -156,9 +148,10 @@ impl AppStore {
                 let pages = db.list_pages().unwrap_or_default();
                 let active_uid = db.get_kv("active.page").ok().flatten();
 
-                self.editor.pages = pages;
+                self.editor.set_pages(pages);
                 self.editor.pages_loading = false;
                 self.app.db = Some(db);
+                self.refresh_page_aliases();
                 self.app.active_vault_root = Some(vault.root.clone());
                 self.on_vault_changed(cx);
 
@@ -172,10 +165,14 @@ impl AppStore {
                     self.open_page("Inbox", cx);
                 }
 
-                if let Some(db) = self.app.db.as_ref() {
-                    if ensure_daily_note_in_db(db, Local::now().date_naive()).unwrap_or(false) {
-                        self.editor.pages = db.list_pages().unwrap_or_default();
+                let settings = self.editor.journals.settings.clone();
+                if let Some(db) = self.app.db.as_mut() {
+                    if ensure_daily_note_in_db(db, Local::now().date_naive(), &settings)
+                        .unwrap_or(false)
+                    {
+                        self.editor.set_pages(db.list_pages().unwrap_or_default());
                         self.refresh_search_results();
+                        self.refresh_journals();
                     }
                 }
             }
@@ -299,6 +296,7 @@ impl AppStore {
                 });
             }
         }
+        self.reset_journals();
         // Restore persisted mode
         let restored_mode = self.settings.last_mode;
         if restored_mode != self.app.mode {
@@ -308,6 +306,10 @@ impl AppStore {
         self.refresh_search_results();
         self.load_review_items(cx);
         self.load_plugins(None, cx);
+        if let Some(root) = self.app.active_vault_root.clone() {
+            let event = PluginEvent::vault_changed(self.app.active_vault_id.as_deref(), &root);
+            self.dispatch_plugin_events(vec![event], cx);
+        }
     }
 
     pub(crate) fn apply_theme_preference(&mut self, cx: &mut Context<Self>) {
@@ -343,6 +345,8 @@ impl AppStore {
         if tab == WorkspacePanel::Review {
             self.load_review_items(cx);
         }
+        self.refresh_page_history();
+        self.refresh_agenda();
         self.persist_settings();
         cx.notify();
     }
@@ -351,7 +355,9 @@ impl AppStore {
         let next = match self.settings.context_panel_tab {
             WorkspacePanel::Review => WorkspacePanel::Backlinks,
             WorkspacePanel::Backlinks => WorkspacePanel::Connections,
-            WorkspacePanel::Connections => WorkspacePanel::Plugins,
+            WorkspacePanel::Connections => WorkspacePanel::History,
+            WorkspacePanel::History => WorkspacePanel::Agenda,
+            WorkspacePanel::Agenda => WorkspacePanel::Plugins,
             WorkspacePanel::Plugins => WorkspacePanel::Review,
         };
         self.set_context_panel_tab(next, cx);
@@ -539,12 +545,31 @@ impl AppStore {
     }
 
     pub(crate) fn save(&mut self, cx: &mut Context<Self>) {
+        // The panel's context holds the saved blocks, so render it again for them.
+        if self.save_dirty_panes(cx)
+            && self.plugins.plugin_active_panel.is_some()
+            && self.settings.context_panel_open
+        {
+            self.refresh_plugin_panel(None, cx);
+        }
+    }
+
+    /// Writes the dirty panes' blocks and returns whether any were saved.
+    pub(crate) fn save_dirty_panes(&mut self, cx: &mut Context<Self>) -> bool {
         self.flush_bound_block_input(cx);
+        // Previous blocks are only loaded when someone listens for what changed.
+        let watch_saves = self.has_plugin_event_handlers(&[
+            PAGE_SAVED,
+            BLOCK_CREATED,
+            BLOCK_CHANGED,
+            BLOCK_DELETED,
+        ]);
+        let mut events = Vec::new();
 
         let Some(db) = self.app.db.as_mut() else {
             self.app.save_state = SaveState::Error("Database not available.".into());
             cx.notify();
-            return;
+            return false;
         };
 
         self.app.save_state = SaveState::Saving;
@@ -554,10 +579,22 @@ impl AppStore {
         let mut error: Option<String> = None;
 
         if self.app.primary_dirty {
-            match (self.editor.active_page.clone(), self.editor.editor.as_ref()) {
+            match (self.editor.active_page.clone(), self.editor.editor.as_mut()) {
                 (Some(active_page), Some(editor)) => {
-                    match db.replace_blocks_for_page(active_page.id, &editor.blocks) {
+                    let previous = watch_saves
+                        .then(|| db.load_blocks_for_page(active_page.id).unwrap_or_default());
+                    match db.replace_blocks_for_page(active_page.id, editor.blocks()) {
                         Ok(_) => {
+                            if let Some(previous) = previous {
+                                events.extend(block_events(
+                                    &active_page,
+                                    &previous,
+                                    editor.blocks(),
+                                ));
+                                events.push(PluginEvent::page_saved(&active_page, editor.blocks()));
+                            }
+                            // Saved ops are spent; undo keeps its own copies.
+                            editor.take_ops();
                             self.app.primary_dirty = false;
                             saved_any = true;
                         }
@@ -574,8 +611,19 @@ impl AppStore {
 
         if let Some(pane) = self.editor.secondary_pane.as_mut() {
             if pane.dirty {
-                match db.replace_blocks_for_page(pane.page.id, &pane.editor.blocks) {
+                let previous =
+                    watch_saves.then(|| db.load_blocks_for_page(pane.page.id).unwrap_or_default());
+                match db.replace_blocks_for_page(pane.page.id, pane.editor.blocks()) {
                     Ok(_) => {
+                        if let Some(previous) = previous {
+                            events.extend(block_events(
+                                &pane.page,
+                                &previous,
+                                pane.editor.blocks(),
+                            ));
+                            events.push(PluginEvent::page_saved(&pane.page, pane.editor.blocks()));
+                        }
+                        pane.editor.take_ops();
                         pane.dirty = false;
                         saved_any = true;
                     }
@@ -593,6 +641,8 @@ impl AppStore {
         } else if saved_any {
             self.app.save_state = SaveState::Saved;
             self.refresh_references();
+            self.refresh_page_history();
+            self.refresh_agenda();
         } else {
             self.app.save_state = SaveState::Saved;
         }
@@ -600,8 +650,12 @@ impl AppStore {
         if saved_any {
             self.schedule_shadow_write_flush(cx);
         }
+        if !events.is_empty() {
+            self.dispatch_plugin_events(events, cx);
+        }
 
         cx.notify();
+        saved_any
     }
 
     pub(crate) fn open_page(&mut self, uid: &str, cx: &mut Context<Self>) {
@@ -610,7 +664,7 @@ impl AppStore {
         };
 
         let normalized = app::sanitize_kebab(uid);
-        let page = match db.get_page_by_uid(&normalized) {
+        let page = match db.resolve_page(&normalized) {
             Ok(Some(page)) => page,
             Ok(None) => {
                 let title = uid.trim();
@@ -641,11 +695,11 @@ impl AppStore {
         self.update_save_state_from_dirty();
         self.editor
             .blocks_list_state
-            .reset(editor.blocks.len(), px(BLOCK_ROW_HEIGHT));
+            .reset(editor.blocks().len(), px(BLOCK_ROW_HEIGHT));
         self.editor.active_pane = EditorPane::Primary;
 
         let page_cursor = self.editor.page_cursors.get(&page.uid);
-        let (active_ix, cursor) = helpers::resolve_cursor_for_blocks(&editor.blocks, page_cursor);
+        let (active_ix, cursor) = helpers::resolve_cursor_for_blocks(editor.blocks(), page_cursor);
         editor.active_ix = active_ix;
         self.record_recent_page(&page.uid);
 
@@ -657,8 +711,13 @@ impl AppStore {
         self.close_wikilink_menu();
         self.close_outline_menu();
         self.refresh_references();
+        self.refresh_page_history();
         self.load_page_properties();
         self.schedule_connections_refresh(cx);
+        self.dispatch_plugin_events(vec![PluginEvent::page_opened(&page)], cx);
+        if self.plugins.plugin_active_panel.is_some() && self.settings.context_panel_open {
+            self.refresh_plugin_panel(None, cx);
+        }
         cx.notify();
     }
 
@@ -668,6 +727,13 @@ impl AppStore {
         self.ui.page_dialog_open = true;
         self.ui.page_dialog_mode = mode;
         self.ui.page_dialog_error = None;
+        if !already_open {
+            self.ui.page_dialog_template = None;
+            self.ui.page_dialog_templates = match mode {
+                PageDialogMode::Create => self.template_pages(),
+                PageDialogMode::Rename => Vec::new(),
+            };
+        }
 
         let initial: String = match mode {
             PageDialogMode::Create => "".to_string(),
@@ -744,6 +810,16 @@ impl AppStore {
         cx.notify();
     }
 
+    /// Picks the template a page created from the page dialog starts with.
+    pub(crate) fn set_page_dialog_template(
+        &mut self,
+        page_uid: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        self.ui.page_dialog_template = page_uid;
+        cx.notify();
+    }
+
     pub(crate) fn close_page_dialog(&mut self, cx: &mut Context<Self>) {
         self.ui.page_dialog_open = false;
         self.ui.page_dialog_error = None;
@@ -794,9 +870,16 @@ impl AppStore {
                     let pages = db.list_pages().unwrap_or_default();
                     (uid, pages)
                 };
-                self.editor.pages = pages;
+                self.editor.set_pages(pages);
                 self.refresh_search_results();
                 self.open_page(&uid, cx);
+                if let Some(template_uid) = self.ui.page_dialog_template.take() {
+                    // Runs after the page dialog closes, since the template may open its own.
+                    cx.defer_in(window, move |this, window, cx| {
+                        this.set_active_pane(EditorPane::Primary, cx);
+                        this.insert_template(&template_uid, window, cx);
+                    });
+                }
                 self.close_page_dialog(cx);
                 window.push_notification(
                     (
@@ -813,26 +896,25 @@ impl AppStore {
                     cx.notify();
                     return false;
                 };
-                let old_title = active.title.clone();
                 let (updated_blocks, pages, active_page) = {
                     let Some(db) = self.app.db.as_ref() else {
                         self.ui.page_dialog_error = Some("Database not available.".into());
                         cx.notify();
                         return false;
                     };
-                    if db.update_page_title(active.id, &title).is_err() {
+                    let Ok(updated_blocks) = db.rename_page_and_links(active.id, &title) else {
                         self.ui.page_dialog_error = Some("Failed to rename page.".into());
                         cx.notify();
                         return false;
-                    }
-                    let updated_blocks = update_wikilinks_in_db(db, &old_title, &title);
+                    };
                     let pages = db.list_pages().unwrap_or_default();
                     let active_page = db.get_page_by_uid(&active.uid).ok().flatten();
                     (updated_blocks, pages, active_page)
                 };
 
                 self.apply_wikilink_updates(&updated_blocks, cx);
-                self.editor.pages = pages;
+                self.editor.set_pages(pages);
+                self.refresh_page_aliases();
                 self.editor.active_page = active_page;
                 if let Some(pane) = self.editor.secondary_pane.as_mut() {
                     if pane.page.id == active.id {
@@ -954,7 +1036,7 @@ impl AppStore {
             (uid, pages)
         };
 
-        self.editor.pages = pages;
+        self.editor.set_pages(pages);
         self.refresh_search_results();
         self.set_mode(Mode::Editor, cx);
         self.open_page(&uid, cx);
@@ -995,6 +1077,9 @@ impl AppStore {
             Mode::Review => {
                 self.refresh_feed(cx);
             }
+            Mode::Journals => {
+                self.refresh_journals();
+            }
         }
 
         // Persist mode across sessions
@@ -1022,7 +1107,7 @@ impl AppStore {
                         window.focus(&self.editor.block_input.focus_handle(cx), cx);
                     });
                 }
-                Mode::Review => {}
+                Mode::Review | Mode::Journals => {}
             }
         }
 
@@ -1056,6 +1141,15 @@ impl AppStore {
         self.set_mode(Mode::Review, cx);
     }
 
+    pub(crate) fn switch_to_journals_action(
+        &mut self,
+        _: &SwitchToJournals,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_mode(Mode::Journals, cx);
+    }
+
     pub(crate) fn new_page(&mut self, _: &NewPage, _window: &mut Window, cx: &mut Context<Self>) {
         self.open_page_dialog(PageDialogMode::Create, cx);
     }
@@ -1167,6 +1261,16 @@ impl AppStore {
         self.editor.page_properties = db.get_page_properties(page.id).unwrap_or_default();
     }
 
+    pub(crate) fn refresh_page_aliases(&mut self) {
+        let aliases = self
+            .app
+            .db
+            .as_ref()
+            .and_then(|db| db.list_all_page_aliases().ok())
+            .unwrap_or_default();
+        self.editor.title_index.sync_aliases(&aliases);
+    }
+
     pub(crate) fn set_page_property(
         &mut self,
         key: &str,
@@ -1182,6 +1286,9 @@ impl AppStore {
         };
         let _ = db.set_page_property(page.id, key, value, value_type);
         self.load_page_properties();
+        if key == ALIAS_KEY {
+            self.refresh_page_aliases();
+        }
         cx.notify();
     }
 
@@ -1194,6 +1301,9 @@ impl AppStore {
         };
         let _ = db.delete_page_property(page.id, key);
         self.load_page_properties();
+        if key == ALIAS_KEY {
+            self.refresh_page_aliases();
+        }
         cx.notify();
     }
 
@@ -1360,13 +1470,14 @@ mod tests {
 
     #[test]
     fn ensure_daily_note_creates_page_without_changing_active() {
-        let db = Database::new_in_memory().expect("db init");
+        let mut db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
         db.insert_page("inbox", "Inbox").expect("insert inbox");
         db.set_kv("active.page", "inbox").expect("set active");
 
         let date = chrono::NaiveDate::from_ymd_opt(2026, 1, 31).expect("date");
-        let created = ensure_daily_note_in_db(&db, date).expect("ensure");
+        let created =
+            ensure_daily_note_in_db(&mut db, date, &JournalSettings::default()).expect("ensure");
         assert!(created);
 
         let active = db.get_kv("active.page").expect("get kv");
@@ -1381,7 +1492,7 @@ mod tests {
 
     #[test]
     fn ensure_daily_note_is_noop_when_title_matches() {
-        let db = Database::new_in_memory().expect("db init");
+        let mut db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
         db.insert_page("inbox", "Inbox").expect("insert inbox");
         db.insert_page("custom", "2026-01-31")
@@ -1389,7 +1500,8 @@ mod tests {
 
         let before = db.list_pages().expect("list pages").len();
         let date = chrono::NaiveDate::from_ymd_opt(2026, 1, 31).expect("date");
-        let created = ensure_daily_note_in_db(&db, date).expect("ensure");
+        let created =
+            ensure_daily_note_in_db(&mut db, date, &JournalSettings::default()).expect("ensure");
         assert!(!created);
 
         let after = db.list_pages().expect("list pages").len();
@@ -1423,13 +1535,14 @@ mod tests {
                     .expect("insert project");
 
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
                 app.open_page("project", cx);
 
                 app.ui.capture_overlay_target = QuickAddTarget::DailyNote;
@@ -1496,13 +1609,14 @@ mod tests {
                 db.run_migrations().expect("migrations");
                 db.insert_page("inbox", "Inbox").expect("insert inbox");
                 app.app.db = Some(db);
-                app.editor.pages = app
-                    .app
-                    .db
-                    .as_ref()
-                    .expect("db")
-                    .list_pages()
-                    .expect("list pages");
+                app.editor.set_pages(
+                    app.app
+                        .db
+                        .as_ref()
+                        .expect("db")
+                        .list_pages()
+                        .expect("list pages"),
+                );
 
                 app.ui.capture_overlay_target = QuickAddTarget::TaskInbox;
                 app.editor.capture_input.update(cx, |input, cx| {
@@ -1557,7 +1671,7 @@ mod tests {
                 assert!(active_page.title.starts_with("Test Page"));
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                let blocks = &editor.blocks;
+                let blocks = editor.blocks();
 
                 let expected = [
                     BlockType::Text,
@@ -1659,8 +1773,8 @@ mod tests {
                 app.editor.text_history_suppression_depth = 0;
 
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].text, "Alpha");
-                assert_eq!(editor.blocks[1].text, "Beta");
+                assert_eq!(editor.blocks()[0].text, "Alpha");
+                assert_eq!(editor.blocks()[1].text, "Beta");
             });
         })
         .unwrap();
@@ -1670,8 +1784,8 @@ mod tests {
         cx.update_window(*window, |_root, _window, cx| {
             app.update(cx, |app, _cx| {
                 let editor = app.editor.editor.as_ref().expect("editor");
-                assert_eq!(editor.blocks[0].text, "Alpha");
-                assert_eq!(editor.blocks[1].text, "Beta");
+                assert_eq!(editor.blocks()[0].text, "Alpha");
+                assert_eq!(editor.blocks()[1].text, "Beta");
                 assert!(app.editor.undo_stack.is_empty());
             });
         })
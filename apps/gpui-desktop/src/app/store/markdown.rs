@@ -20,6 +20,7 @@ pub(crate) struct MarkdownList {
 pub(crate) enum InlineMarkdownToken {
     Text(String),
     Wikilink { target: String, label: String },
+    BlockRef { uid: String, label: Option<String> },
     Link { href: String, label: String },
     Code(String),
     Bold(String),
@@ -169,6 +170,7 @@ pub(crate) fn parse_inline_markdown_tokens(text: &str) -> Vec<InlineMarkdownToke
             ("**", 3),
             ("~~", 4),
             ("*", 5),
+            ("((", 6),
         ];
 
         let mut next: Option<(usize, &str, usize)> = None;
@@ -216,6 +218,31 @@ pub(crate) fn parse_inline_markdown_tokens(text: &str) -> Vec<InlineMarkdownToke
                 }
                 cursor = token_end;
             }
+            "((" => {
+                let inner_start = cursor + 2;
+                let Some(rel_end) = text[inner_start..].find("))") else {
+                    tokens.push(InlineMarkdownToken::Text(text[cursor..].to_string()));
+                    break;
+                };
+                let inner_end = inner_start + rel_end;
+                let token_end = inner_end + 2;
+                let mut parts = text[inner_start..inner_end].splitn(2, '|');
+                let uid = parts.next().unwrap_or("").trim();
+                let label = parts
+                    .next()
+                    .map(|label| label.trim())
+                    .filter(|label| !label.is_empty());
+                if uid.is_empty() || uid.contains(char::is_whitespace) {
+                    tokens.push(InlineMarkdownToken::Text("((".to_string()));
+                    cursor = inner_start;
+                    continue;
+                }
+                tokens.push(InlineMarkdownToken::BlockRef {
+                    uid: uid.to_string(),
+                    label: label.map(str::to_string),
+                });
+                cursor = token_end;
+            }
             "[" => {
                 if remaining.starts_with("[[") {
                     tokens.push(InlineMarkdownToken::Text("[".to_string()));
@@ -465,6 +492,25 @@ mod tests {
             ]
         );
 
+        assert_eq!(
+            parse_inline_markdown_tokens("See ((b-1)) and ((b-2|that)) (( not a ref))"),
+            vec![
+                InlineMarkdownToken::Text("See ".to_string()),
+                InlineMarkdownToken::BlockRef {
+                    uid: "b-1".to_string(),
+                    label: None
+                },
+                InlineMarkdownToken::Text(" and ".to_string()),
+                InlineMarkdownToken::BlockRef {
+                    uid: "b-2".to_string(),
+                    label: Some("that".to_string())
+                },
+                InlineMarkdownToken::Text(" ".to_string()),
+                InlineMarkdownToken::Text("((".to_string()),
+                InlineMarkdownToken::Text(" not a ref))".to_string()),
+            ]
+        );
+
         assert_eq!(
             parse_inline_markdown_tokens("Ignore [X](javascript:alert(1))"),
             vec![InlineMarkdownToken::Text(
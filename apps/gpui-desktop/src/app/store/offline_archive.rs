@@ -424,7 +424,7 @@ impl AppStore {
 
                 match import_offline_archive_zip(db, &bytes) {
                     Ok(imported) => {
-                        this.editor.pages = db.list_pages().unwrap_or_default();
+                        this.editor.set_pages(db.list_pages().unwrap_or_default());
                         this.refresh_search_results();
 
                         if let Some(first) = imported.first().cloned() {
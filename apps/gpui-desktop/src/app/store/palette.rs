@@ -4,6 +4,7 @@ use super::*;
 enum PaletteSection {
     Suggested,
     Pages,
+    Blocks,
     Commands,
     Plugins,
     Settings,
@@ -14,6 +15,7 @@ impl PaletteSection {
         match self {
             PaletteSection::Suggested => "suggested",
             PaletteSection::Pages => "pages",
+            PaletteSection::Blocks => "blocks",
             PaletteSection::Commands => "commands",
             PaletteSection::Plugins => "plugins",
             PaletteSection::Settings => "settings",
@@ -24,6 +26,7 @@ impl PaletteSection {
         match self {
             PaletteSection::Suggested => "Suggested",
             PaletteSection::Pages => "Pages",
+            PaletteSection::Blocks => "Blocks",
             PaletteSection::Commands => "Commands",
             PaletteSection::Plugins => "Plugins",
             PaletteSection::Settings => "Settings",
@@ -43,6 +46,7 @@ fn section_for_action(action: &PaletteAction) -> PaletteSection {
         | PaletteAction::OpenPluginPanel(_)
         | PaletteAction::ClosePluginPanel => PaletteSection::Plugins,
         PaletteAction::OpenPage(_) => PaletteSection::Pages,
+        PaletteAction::OpenBlock { .. } => PaletteSection::Blocks,
         _ => PaletteSection::Commands,
     }
 }
@@ -195,6 +199,18 @@ impl AppStore {
             hint: Some(panel_hint.clone()),
             action: PaletteAction::OpenContextPanel(WorkspacePanel::Plugins),
         });
+        items.push(PaletteItem {
+            id: "open-history-panel".to_string(),
+            label: "Open page history".to_string(),
+            hint: Some(panel_hint.clone()),
+            action: PaletteAction::OpenContextPanel(WorkspacePanel::History),
+        });
+        items.push(PaletteItem {
+            id: "open-agenda-panel".to_string(),
+            label: "Open agenda".to_string(),
+            hint: Some(panel_hint.clone()),
+            action: PaletteAction::OpenContextPanel(WorkspacePanel::Agenda),
+        });
         items.push(PaletteItem {
             id: "cycle-context-panel".to_string(),
             label: "Cycle context panel".to_string(),
@@ -318,6 +334,7 @@ impl AppStore {
         let capture_hint = shortcut_hint(ShortcutSpec::new("cmd-1", "ctrl-1")).to_string();
         let edit_hint = shortcut_hint(ShortcutSpec::new("cmd-2", "ctrl-2")).to_string();
         let review_feed_hint = shortcut_hint(ShortcutSpec::new("cmd-3", "ctrl-3")).to_string();
+        let journals_hint = shortcut_hint(ShortcutSpec::new("cmd-4", "ctrl-4")).to_string();
         items.push(PaletteItem {
             id: "switch-capture".to_string(),
             label: "Switch to Capture mode".to_string(),
@@ -336,6 +353,23 @@ impl AppStore {
             hint: Some(review_feed_hint),
             action: PaletteAction::SwitchMode(Mode::Review),
         });
+        items.push(PaletteItem {
+            id: "switch-journals".to_string(),
+            label: "Switch to Journals".to_string(),
+            hint: Some(journals_hint),
+            action: PaletteAction::SwitchMode(Mode::Journals),
+        });
+
+        if self.app.mode == Mode::Editor && self.editor.active_page.is_some() {
+            for page in self.template_pages() {
+                items.push(PaletteItem {
+                    id: format!("insert-template-{}", page.uid),
+                    label: format!("Insert template: {}", page.title),
+                    hint: Some("Template".to_string()),
+                    action: PaletteAction::InsertTemplate(page.uid),
+                });
+            }
+        }
 
         if self.app.db.is_some() {
             items.push(PaletteItem {
@@ -463,25 +497,56 @@ impl AppStore {
         ordered
     }
 
-    fn page_snippets_for_query(&self, query: &str) -> HashMap<String, String> {
+    fn palette_search_hits(&self, query: &str) -> Vec<SearchHit> {
         let Some(db) = self.app.db.as_ref() else {
-            return HashMap::new();
+            return Vec::new();
         };
+        db.parse_search_query(query)
+            .ok()
+            .and_then(|query| db.search(&query, 50).ok())
+            .unwrap_or_default()
+    }
+
+    fn page_snippets_for_query(hits: &[SearchHit]) -> HashMap<String, String> {
         let mut snippets = HashMap::new();
-        let results = db
-            .search_block_page_summaries(query, 50)
-            .unwrap_or_default();
-        for record in results {
-            snippets
-                .entry(record.page_uid)
-                .or_insert_with(|| helpers::format_snippet(&record.text, 80));
+        for hit in hits {
+            snippets.entry(hit.page_uid.clone()).or_insert_with(|| {
+                helpers::format_snippet(&snippet_highlights(&hit.snippet).0, 80)
+            });
         }
         snippets
     }
 
-    fn page_palette_items_for_query(&self, query: &str) -> Vec<(i64, PaletteItem)> {
+    fn block_palette_items_for_query(hits: &[SearchHit]) -> Vec<PaletteItem> {
+        hits.iter()
+            .take(8)
+            .map(|hit| PaletteItem {
+                id: format!("block-{}", hit.block_uid),
+                label: helpers::format_snippet(&snippet_highlights(&hit.snippet).0, 80),
+                hint: Some(hit.page_title.clone()),
+                action: PaletteAction::OpenBlock {
+                    page_uid: hit.page_uid.clone(),
+                    block_uid: hit.block_uid.clone(),
+                },
+            })
+            .collect()
+    }
+
+    fn page_palette_items_for_query(
+        &self,
+        query: &str,
+        hits: &[SearchHit],
+    ) -> Vec<(i64, PaletteItem)> {
         let query = query.trim();
-        let snippets = self.page_snippets_for_query(query);
+        let snippets = Self::page_snippets_for_query(hits);
+        // Recency is added by `score_palette_page`, so the index ranks on the title alone.
+        let title_scores: HashMap<String, i64> = self
+            .editor
+            .title_index
+            .search(query, &[], usize::MAX)
+            .into_iter()
+            .map(|found| (found.uid, found.score))
+            .collect();
         let mut scored: Vec<(i64, PaletteItem)> = Vec::new();
         for page in self.editor.pages.iter() {
             let title = page.title.trim();
@@ -496,7 +561,9 @@ impl AppStore {
                 .recent_pages
                 .iter()
                 .position(|uid| uid == &page.uid);
-            let Some(score) = helpers::score_palette_page(query, &label, &snippet, recent_rank)
+            let title_score = title_scores.get(&page.uid).copied();
+            let Some(score) =
+                helpers::score_palette_page(query, title_score, &snippet, recent_rank)
             else {
                 continue;
             };
@@ -565,23 +632,35 @@ impl AppStore {
             return rows;
         }
 
-        let mut pages_scored = self.page_palette_items_for_query(query);
-        let pages = pages_scored
-            .drain(..)
-            .map(|(_, item)| item)
-            .collect::<Vec<_>>();
-        push_palette_section(&mut rows, PaletteSection::Pages, pages);
+        let hits = self.palette_search_hits(query);
+        let parsed = self
+            .app
+            .db
+            .as_ref()
+            .and_then(|db| db.parse_search_query(query).ok())
+            .unwrap_or_else(|| SearchQuery::parse(query));
+        // Scoped queries (tag:, type:, ...) list blocks only.
+        if !parsed.has_filters() {
+            let mut pages_scored = self.page_palette_items_for_query(query, &hits);
+            let pages = pages_scored
+                .drain(..)
+                .map(|(_, item)| item)
+                .collect::<Vec<_>>();
+            push_palette_section(&mut rows, PaletteSection::Pages, pages);
+        }
+        push_palette_section(
+            &mut rows,
+            PaletteSection::Blocks,
+            Self::block_palette_items_for_query(&hits),
+        );
 
         let mut commands_scored: Vec<(i64, PaletteItem)> = Vec::new();
         let mut plugins_scored: Vec<(i64, PaletteItem)> = Vec::new();
         let mut settings_scored: Vec<(i64, PaletteItem)> = Vec::new();
 
         for item in self.build_palette_items() {
-            let label_score = helpers::fuzzy_score(query, &item.label);
-            let hint_score = item
-                .hint
-                .as_ref()
-                .and_then(|hint| helpers::fuzzy_score(query, hint));
+            let label_score = fuzzy_score(query, &item.label);
+            let hint_score = item.hint.as_ref().and_then(|hint| fuzzy_score(query, hint));
             let score = match (label_score, hint_score) {
                 (Some(label), Some(hint)) => label.max(hint.saturating_sub(2)),
                 (Some(label), None) => label,
@@ -748,6 +827,15 @@ impl AppStore {
                 self.open_page(&uid, cx);
                 window.focus(&self.editor.block_input.focus_handle(cx), cx);
             }
+            PaletteAction::OpenBlock {
+                page_uid,
+                block_uid,
+            } => {
+                self.open_page_and_focus_block(&page_uid, &block_uid, window, cx);
+            }
+            PaletteAction::InsertTemplate(page_uid) => {
+                self.insert_template(&page_uid, window, cx);
+            }
             PaletteAction::ToggleFocusMode => {
                 self.settings.focus_mode = !self.settings.focus_mode;
                 self.persist_settings();
@@ -319,12 +319,18 @@ impl AppStore {
                     return;
                 }
 
+                if this.plugins.plugin_runtime.is_none() {
+                    return;
+                }
+                this.save_before_plugin_call(&renderer.plugin_id, cx);
                 let result = match this.plugins.plugin_runtime.as_mut() {
                     Some(runtime) => {
                         runtime.render_block(&renderer.plugin_id, &renderer.id, &block_uid, &text)
                     }
                     None => return,
                 };
+                this.apply_plugin_data_changes(cx);
+                this.disable_over_budget_plugins(cx);
 
                 let mut next_text_to_apply: Option<String> = None;
                 let mut next_key: Option<String> = None;
@@ -403,7 +409,7 @@ impl AppStore {
     ) {
         let Some(block) = self
             .editor_for_pane(pane)
-            .and_then(|editor| editor.blocks.iter().find(|block| block.uid == block_uid))
+            .and_then(|editor| editor.blocks().iter().find(|block| block.uid == block_uid))
             .cloned()
         else {
             return;
@@ -442,6 +448,7 @@ impl AppStore {
                 }
 
                 let action_value = value.clone().map(Value::String);
+                this.save_before_plugin_call(&renderer.plugin_id, cx);
                 let result = match this.plugins.plugin_runtime.as_mut() {
                     Some(runtime) => runtime.handle_block_action(
                         &renderer.plugin_id,
@@ -455,6 +462,8 @@ impl AppStore {
                         return;
                     }
                 };
+                this.apply_plugin_data_changes(cx);
+                this.disable_over_budget_plugins(cx);
 
                 let mut next_text_to_apply: Option<String> = None;
                 let mut next_key: Option<String> = None;
@@ -642,7 +651,7 @@ impl AppStore {
                 return false;
             };
             let Some(ix) = editor
-                .blocks
+                .blocks()
                 .iter()
                 .position(|block| block.uid == block_uid)
             else {
@@ -690,22 +699,20 @@ impl AppStore {
                 return;
             };
             let Some(ix) = editor
-                .blocks
+                .blocks()
                 .iter()
                 .position(|block| block.uid == block_uid)
             else {
                 return;
             };
-            if editor.blocks[ix].text == next_text {
+            let block_type = editor.blocks()[ix].block_type;
+            if !editor.set_text(ix, next_text) {
                 return;
             }
 
-            let block_type = editor.blocks[ix].block_type;
-            editor.blocks[ix].text = next_text.to_string();
-
             let sync_input = active_pane == pane
                 && editor
-                    .blocks
+                    .blocks()
                     .get(editor.active_ix)
                     .is_some_and(|block| block.uid == block_uid);
             (ix, block_type, sync_input)
@@ -1,7 +1,10 @@
 use super::*;
 use crate::ui::tokens;
 use rfd::FileDialog;
-use sandpaper_core::plugins::{PluginError, PluginErrorContext};
+use sandpaper_core::plugin_events::PluginEvent;
+use sandpaper_core::plugins::{
+    PluginCommandBlock, PluginCommandContext, PluginCommandPage, PluginError, PluginErrorContext,
+};
 use serde_json::Value;
 
 pub(crate) fn plugin_registry_for_vault(vault_root: &std::path::Path) -> PluginRegistry {
@@ -288,6 +291,9 @@ impl AppStore {
         self.plugins.plugin_busy = false;
         self.plugins.plugin_runtime = None;
         self.plugins.plugin_active_panel = None;
+        self.plugins.plugin_panel_view = None;
+        self.plugins.plugin_panel_error = None;
+        self.plugins.plugin_panel_inputs.clear();
         self.plugins.plugin_permission_prompt = None;
         self.plugins.plugin_installing = false;
         self.plugins.plugin_install_status = None;
@@ -544,7 +550,7 @@ impl AppStore {
         }
     }
 
-    pub(crate) fn load_plugins(&mut self, window: Option<&mut Window>, cx: &mut Context<Self>) {
+    pub(crate) fn load_plugins(&mut self, mut window: Option<&mut Window>, cx: &mut Context<Self>) {
         self.plugins.plugin_busy = true;
         self.plugins.plugin_error = None;
         self.plugins.plugin_error_details = None;
@@ -594,7 +600,7 @@ impl AppStore {
         self.plugins.plugin_settings_saved = saved;
         self.plugins.plugin_settings_status = status;
         self.plugins.plugin_settings_dirty.clear();
-        self.on_plugin_list_changed(window, cx);
+        self.on_plugin_list_changed(window.as_deref_mut(), cx);
 
         let mut settings_by_plugin = HashMap::new();
         for plugin in allowed.iter() {
@@ -609,6 +615,23 @@ impl AppStore {
             settings_by_plugin,
         ) {
             Ok(result) => {
+                if let Some(runtime) = self.plugins.plugin_runtime.as_mut() {
+                    // `api.data` gets its own connection to the vault database.
+                    let data_db = self
+                        .app
+                        .active_vault_root
+                        .as_deref()
+                        .and_then(|root| app::open_vault_database(root).ok());
+                    runtime.set_database(data_db);
+                }
+                // A plugin that failed to load is left out; the others still run.
+                for err in result.errors {
+                    tracing::warn!(error = %format_runtime_error(&err), "plugin load failed");
+                    let message: SharedString = format_runtime_error(&err).into();
+                    self.plugins.plugin_error_details = Some(err.clone());
+                    self.plugins.plugin_error = Some(message.clone());
+                    self.push_plugin_error_notification(message, Some(err));
+                }
                 self.plugins.plugin_status = Some(PluginRuntimeStatus {
                     loaded: result.loaded,
                     blocked,
@@ -627,6 +650,8 @@ impl AppStore {
                 self.plugins.plugin_status = None;
             }
         }
+        // Strikes charged while registering count the same as ones from later calls.
+        self.disable_over_budget_plugins(cx);
 
         if let Some(active_panel) = self.plugins.plugin_active_panel.clone() {
             let panel_exists = self.plugins.plugin_status.as_ref().is_some_and(|status| {
@@ -634,8 +659,10 @@ impl AppStore {
                     panel.plugin_id == active_panel.plugin_id && panel.id == active_panel.id
                 })
             });
-            if !panel_exists {
-                self.plugins.plugin_active_panel = None;
+            if panel_exists {
+                self.refresh_plugin_panel(window, cx);
+            } else {
+                self.close_plugin_panel(cx);
             }
         }
 
@@ -651,6 +678,152 @@ impl AppStore {
         self.load_plugins(window, cx);
     }
 
+    /// Saves pending edits before a plugin that may write through `api.data` runs, so it
+    /// sees them and they are not lost when its changes are reloaded.
+    pub(crate) fn save_before_plugin_call(&mut self, plugin_id: &str, cx: &mut Context<Self>) {
+        if self.needs_save_before_plugin_call(plugin_id) {
+            self.save(cx);
+        }
+    }
+
+    fn needs_save_before_plugin_call(&self, plugin_id: &str) -> bool {
+        self.plugins.has_permission(plugin_id, "data.write")
+            && matches!(self.app.save_state, SaveState::Dirty)
+    }
+
+    /// Reloads open panes whose pages a plugin changed through `api.data`. The reload is one
+    /// undoable step and leaves the panes clean, since the changes are already saved.
+    pub(crate) fn apply_plugin_data_changes(&mut self, cx: &mut Context<Self>) {
+        let Some(runtime) = self.plugins.plugin_runtime.as_mut() else {
+            return;
+        };
+        let changed = runtime.take_data_changes();
+        self.reload_plugin_changed_pages(changed, cx);
+    }
+
+    /// Turns off plugins the runtime disabled for hitting their limits too often, then reloads
+    /// the others without them.
+    pub(crate) fn disable_over_budget_plugins(&mut self, cx: &mut Context<Self>) {
+        let disabled = self
+            .plugins
+            .plugin_runtime
+            .as_mut()
+            .map(|runtime| runtime.take_disabled_plugins())
+            .unwrap_or_default();
+        if disabled.is_empty() {
+            return;
+        }
+        let Some(vault_root) = self.app.active_vault_root.clone() else {
+            return;
+        };
+        let registry = plugin_registry_for_vault(&vault_root);
+        for plugin_id in disabled {
+            let message = match registry.set_enabled(&plugin_id, false) {
+                Ok(_) => {
+                    format!("Disabled {plugin_id} after it kept hitting its time or memory limits.")
+                }
+                Err(err) => {
+                    let err = describe_plugin_error(&err);
+                    format!("Disable {plugin_id} failed: {}", format_runtime_error(&err))
+                }
+            };
+            self.plugins
+                .plugin_manage_status
+                .insert(plugin_id, "Disabled after repeated limit errors.".into());
+            self.push_plugin_error_notification(message.into(), None);
+        }
+        // The call that hit the limit may still be unwinding through the store.
+        cx.defer(|this, cx| this.load_plugins(None, cx));
+        cx.notify();
+    }
+
+    /// Reloads open panes showing any of `changed` from the database.
+    fn reload_plugin_changed_pages(&mut self, changed: Vec<String>, cx: &mut Context<Self>) {
+        if changed.is_empty() {
+            return;
+        }
+        if let Some(pages) = self.app.db.as_ref().and_then(|db| db.list_pages().ok()) {
+            self.editor.set_pages(pages);
+        }
+        for pane in [EditorPane::Primary, EditorPane::Secondary] {
+            let Some(page) = self.page_for_pane(pane).cloned() else {
+                continue;
+            };
+            if !changed.contains(&page.uid) {
+                continue;
+            }
+            let Some(blocks) = self
+                .app
+                .db
+                .as_ref()
+                .and_then(|db| db.load_blocks_for_page(page.id).ok())
+            else {
+                continue;
+            };
+            let history_before = self.pane_snapshot(pane, cx);
+            let Some(editor) = self.editor_for_pane_mut(pane) else {
+                continue;
+            };
+            editor.replace_blocks(blocks);
+            editor.active_ix = editor
+                .active_ix
+                .min(editor.blocks().len().saturating_sub(1));
+            self.update_block_list_for_pane(pane);
+            if self.editor.active_pane == pane {
+                self.sync_block_input_from_active_for_pane(pane, None, cx);
+            }
+            self.record_structural_history_if_changed(pane, history_before, cx);
+        }
+        self.refresh_references();
+        cx.notify();
+    }
+
+    /// Whether any loaded plugin listens for one of `events`.
+    pub(crate) fn has_plugin_event_handlers(&self, events: &[&str]) -> bool {
+        self.plugins
+            .plugin_runtime
+            .as_ref()
+            .is_some_and(|runtime| events.iter().any(|event| runtime.has_event_handlers(event)))
+    }
+
+    /// Hands `events` to subscribed plugins and writes the edits their handlers return. The
+    /// edits go straight to the database and open panes are reloaded clean, so they raise no
+    /// events of their own.
+    pub(crate) fn dispatch_plugin_events(
+        &mut self,
+        events: Vec<PluginEvent>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(runtime) = self.plugins.plugin_runtime.as_mut() else {
+            return;
+        };
+        let mut errors = Vec::new();
+        for event in events {
+            if !runtime.has_event_handlers(event.name) {
+                continue;
+            }
+            let outcome = runtime.dispatch_event(&event);
+            errors.extend(outcome.errors);
+            for (plugin_id, result) in outcome.results {
+                if result.edits.is_empty() {
+                    continue;
+                }
+                if let Err(err) = runtime.apply_event_edits(&plugin_id, &event, result.edits) {
+                    errors.push(err);
+                }
+            }
+        }
+        let changed = runtime.take_data_changes();
+        for err in errors {
+            let err = describe_plugin_error(&err);
+            let message: SharedString = format_runtime_error(&err).into();
+            self.push_plugin_error_notification(message, Some(err));
+        }
+        self.reload_plugin_changed_pages(changed, cx);
+        self.disable_over_budget_plugins(cx);
+        cx.notify();
+    }
+
     pub(crate) fn clear_plugin_install_status(&mut self, cx: &mut Context<Self>) {
         self.plugins.plugin_install_status = None;
         cx.notify();
@@ -835,6 +1008,11 @@ impl AppStore {
                         "Disabled.".into()
                     },
                 );
+                if enabled {
+                    if let Some(runtime) = self.plugins.plugin_runtime.as_mut() {
+                        runtime.enable_plugin(&plugin_id);
+                    }
+                }
                 self.load_plugins(Some(window), cx);
             }
             Err(err) => {
@@ -1080,18 +1258,207 @@ impl AppStore {
         }
 
         self.plugins.plugin_active_panel = Some(panel);
+        self.plugins.plugin_panel_view = None;
         self.settings.context_panel_open = true;
         self.ui.context_panel_epoch += 1;
         self.settings.context_panel_tab = WorkspacePanel::Plugins;
         self.persist_settings();
-        cx.notify();
+        self.refresh_plugin_panel(Some(window), cx);
     }
 
     pub(crate) fn close_plugin_panel(&mut self, cx: &mut Context<Self>) {
         self.plugins.plugin_active_panel = None;
+        self.plugins.plugin_panel_view = None;
+        self.plugins.plugin_panel_error = None;
+        self.plugins.plugin_panel_inputs.clear();
+        cx.notify();
+    }
+
+    /// Renders the active plugin panel again for the active pane.
+    pub(crate) fn refresh_plugin_panel(
+        &mut self,
+        window: Option<&mut Window>,
+        cx: &mut Context<Self>,
+    ) {
+        self.call_plugin_panel(None, window, cx);
+    }
+
+    /// Routes a button click or input submit back to the active panel's plugin.
+    pub(crate) fn run_plugin_panel_action(
+        &mut self,
+        action_id: &str,
+        value: Option<Value>,
+        window: Option<&mut Window>,
+        cx: &mut Context<Self>,
+    ) {
+        self.call_plugin_panel(Some((action_id, value)), window, cx);
+    }
+
+    fn call_plugin_panel(
+        &mut self,
+        action: Option<(&str, Option<Value>)>,
+        window: Option<&mut Window>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(panel) = self.plugins.plugin_active_panel.clone() else {
+            return;
+        };
+        // `save` would render the panel again before this call does.
+        if self.needs_save_before_plugin_call(&panel.plugin_id) {
+            self.save_dirty_panes(cx);
+        }
+        let context = self.plugin_command_context(self.editor.active_pane, cx);
+        let Some(runtime) = self.plugins.plugin_runtime.as_mut() else {
+            return;
+        };
+        let result = match action {
+            Some((action_id, value)) => {
+                runtime.handle_panel_action(&panel.plugin_id, &panel.id, &context, action_id, value)
+            }
+            None => runtime.render_panel(&panel.plugin_id, &panel.id, &context),
+        };
+        self.apply_plugin_data_changes(cx);
+        self.disable_over_budget_plugins(cx);
+        match result {
+            Ok(view) => {
+                self.plugins.plugin_panel_error = None;
+                self.sync_plugin_panel_inputs(&view, window, cx);
+                self.plugins.plugin_panel_view = Some(view);
+            }
+            Err(err) => {
+                let err = describe_plugin_error(&err);
+                self.plugins.plugin_panel_error = Some(format_runtime_error(&err).into());
+            }
+        }
         cx.notify();
     }
 
+    /// Keeps one input per `input` node of `view`, reusing the ones already typed into.
+    fn sync_plugin_panel_inputs(
+        &mut self,
+        view: &PluginPanelView,
+        mut window: Option<&mut Window>,
+        cx: &mut Context<Self>,
+    ) {
+        fn collect(nodes: &[PluginPanelNode], inputs: &mut Vec<(String, String, Option<String>)>) {
+            for node in nodes {
+                match node {
+                    PluginPanelNode::Input {
+                        id,
+                        placeholder,
+                        value,
+                    } => inputs.push((
+                        id.clone(),
+                        placeholder.clone().unwrap_or_default(),
+                        value.clone(),
+                    )),
+                    PluginPanelNode::List { items } => collect(items, inputs),
+                    _ => {}
+                }
+            }
+        }
+        let mut wanted = Vec::new();
+        collect(&view.children, &mut wanted);
+        self.plugins
+            .plugin_panel_inputs
+            .retain(|id, _| wanted.iter().any(|(wanted_id, _, _)| wanted_id == id));
+
+        for (id, placeholder, value) in wanted {
+            let existing = self
+                .plugins
+                .plugin_panel_inputs
+                .get(&id)
+                .map(|(input, _)| input.clone());
+            let input = match existing {
+                Some(input) => input,
+                None => {
+                    let mut created = None;
+                    self.update_with_window(window.as_deref_mut(), cx, |window, cx| {
+                        created =
+                            Some(cx.new(|cx| InputState::new(window, cx).placeholder(placeholder)));
+                    });
+                    let Some(input) = created else {
+                        continue;
+                    };
+                    let action_id = id.clone();
+                    let subscription = cx.subscribe(
+                        &input,
+                        move |this, input, event: &gpui_component::input::InputEvent, cx| {
+                            if !matches!(
+                                event,
+                                gpui_component::input::InputEvent::PressEnter { .. }
+                            ) {
+                                return;
+                            }
+                            let text = input.read(cx).value().to_string();
+                            let action_id = action_id.clone();
+                            // Deferred so the window is free again when the panel re-renders
+                            // and updates its inputs.
+                            cx.defer(move |this, cx| {
+                                this.run_plugin_panel_action(
+                                    &action_id,
+                                    Some(Value::String(text)),
+                                    None,
+                                    cx,
+                                );
+                            });
+                        },
+                    );
+                    self.plugins
+                        .plugin_panel_inputs
+                        .insert(id, (input.clone(), subscription));
+                    input
+                }
+            };
+            // A panel that sends a value back sets the field, e.g. clearing it after submit.
+            if let Some(value) = value {
+                self.update_with_window(window.as_deref_mut(), cx, |window, cx| {
+                    input.update(cx, |input, cx| input.set_value(value, window, cx));
+                });
+            }
+        }
+    }
+
+    /// Runs `f` with `window` when the caller has it, or looks the window up.
+    fn update_with_window(
+        &self,
+        window: Option<&mut Window>,
+        cx: &mut Context<Self>,
+        f: impl FnOnce(&mut Window, &mut App),
+    ) {
+        match window {
+            Some(window) => f(window, cx),
+            None => self.with_window(cx, f),
+        }
+    }
+
+    /// Follows a panel `link` node to a page, or to the page holding a block.
+    pub(crate) fn open_plugin_panel_link(
+        &mut self,
+        page: Option<&str>,
+        block: Option<&str>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(block_uid) = block {
+            if self.focus_block_by_uid(block_uid, Some(&mut *window), cx) {
+                return;
+            }
+            let page_uid = self.app.db.as_ref().and_then(|db| {
+                let record = db.get_block_by_uid(block_uid).ok()??;
+                db.get_page_by_id(record.page_id).ok()?.map(|page| page.uid)
+            });
+            if let Some(page_uid) = page_uid {
+                self.open_page_and_focus_block(&page_uid, block_uid, window, cx);
+            }
+            return;
+        }
+        if let Some(page) = page {
+            self.set_mode(Mode::Editor, cx);
+            self.open_page(page, cx);
+        }
+    }
+
     pub(crate) fn run_plugin_command(
         &mut self,
         command: PluginCommand,
@@ -1113,29 +1480,125 @@ impl AppStore {
         if self.app.mode != Mode::Editor {
             self.set_mode(Mode::Editor, cx);
         }
+        self.save_before_plugin_call(&plugin_id, cx);
 
-        let Some(editor) = self.editor.editor.as_mut() else {
+        let pane = self.editor.active_pane;
+        let context = self.plugin_command_context(pane, cx);
+        let Some(runtime) = self.plugins.plugin_runtime.as_mut() else {
             return;
         };
-
-        let new_block = BlockSnapshot {
-            uid: Uuid::new_v4().to_string(),
-            text: format!("Plugin action: {}", command.title),
-            indent: 0,
-            block_type: BlockType::Text,
+        let result = runtime.run_command(&plugin_id, &command.id, &context);
+        self.apply_plugin_data_changes(cx);
+        self.disable_over_budget_plugins(cx);
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                let err = describe_plugin_error(&err);
+                let message: SharedString = format_runtime_error(&err).into();
+                self.plugins.plugin_error_details = Some(err.clone());
+                self.plugins.plugin_error = Some(message.clone());
+                self.push_plugin_error_notification(message, Some(err));
+                cx.notify();
+                return;
+            }
         };
 
-        let next_active = editor.active_ix.saturating_add(1);
-        editor.blocks.insert(0, new_block);
-        editor.active_ix = next_active.min(editor.blocks.len().saturating_sub(1));
+        if !result.edits.is_empty() {
+            let history_before = self.pane_snapshot(pane, cx);
+            let changed = match self.editor_for_pane_mut(pane) {
+                Some(editor) => result
+                    .edits
+                    .into_iter()
+                    .fold(false, |changed, edit| edit.apply(editor) || changed),
+                None => false,
+            };
+            if changed {
+                self.update_block_list_for_pane(pane);
+                self.sync_block_input_from_active_for_pane(pane, Some(window), cx);
+                self.mark_dirty_for_pane(pane, cx);
+                self.schedule_references_refresh(cx);
+                self.record_structural_history_if_changed(pane, history_before, cx);
+            }
+        }
 
-        self.update_block_list_for_pane(EditorPane::Primary);
-        self.mark_dirty_for_pane(EditorPane::Primary, cx);
-        self.schedule_references_refresh(cx);
-        window.focus(&self.editor.block_input.focus_handle(cx), cx);
+        if let Some(panel_id) = result.open_panel {
+            let panel = self.plugins.plugin_status.as_ref().and_then(|status| {
+                status
+                    .panels
+                    .iter()
+                    .find(|panel| panel.plugin_id == plugin_id && panel.id == panel_id)
+                    .cloned()
+            });
+            if let Some(panel) = panel {
+                self.open_plugin_panel(panel, window, cx);
+            }
+        }
+
+        if let Some(notification) = result.notification {
+            use gpui_component::notification::NotificationType;
+            let kind = match notification.kind.as_deref() {
+                Some("success") => NotificationType::Success,
+                Some("warning") => NotificationType::Warning,
+                Some("error") => NotificationType::Error,
+                _ => NotificationType::Info,
+            };
+            let message: SharedString = notification.message.into();
+            if window.root::<Root>().flatten().is_some() {
+                window.push_notification((kind, message), cx);
+            }
+        }
         cx.notify();
     }
 
+    /// The page, cursor block and selection of `pane`, as command handlers see them.
+    fn plugin_command_context(&self, pane: EditorPane, cx: &App) -> PluginCommandContext {
+        let page = self.page_for_pane(pane).map(|page| PluginCommandPage {
+            uid: page.uid.clone(),
+            title: page.title.clone(),
+        });
+        let Some(editor) = self.editor_for_pane(pane) else {
+            return PluginCommandContext {
+                page,
+                ..Default::default()
+            };
+        };
+        let cursor = self.pane_cursor_for_snapshot(pane, cx);
+        let block = editor
+            .blocks()
+            .get(editor.active_ix)
+            .map(|block| PluginCommandBlock {
+                uid: block.uid.clone(),
+                text: block.text.clone(),
+                cursor,
+            });
+        let selected = self
+            .selection_for_pane(pane)
+            .and_then(|selection| selection.range)
+            .zip(self.list_state_for_pane(pane))
+            .map(|((start, end), list_state)| {
+                crate::app::store::outline::selected_actual_indexes_for_visible_range(
+                    &list_state.visible_to_actual,
+                    start,
+                    end,
+                )
+            })
+            .unwrap_or_default();
+        let selection = selected
+            .into_iter()
+            .filter_map(|ix| editor.blocks().get(ix))
+            .map(|block| PluginCommandBlock {
+                uid: block.uid.clone(),
+                text: block.text.clone(),
+                cursor: None,
+            })
+            .collect();
+        PluginCommandContext {
+            page,
+            block,
+            selection,
+        }
+    }
+
     pub(crate) fn run_plugin_toolbar_action(
         &mut self,
         action: PluginToolbarAction,
@@ -1170,8 +1633,8 @@ impl AppStore {
         };
 
         let next_active = editor.active_ix.saturating_add(1);
-        editor.blocks.insert(0, new_block);
-        editor.active_ix = next_active.min(editor.blocks.len().saturating_sub(1));
+        editor.insert_block(0, new_block);
+        editor.active_ix = next_active.min(editor.blocks().len().saturating_sub(1));
 
         self.update_block_list_for_pane(EditorPane::Primary);
         self.mark_dirty_for_pane(EditorPane::Primary, cx);
@@ -1807,7 +2270,7 @@ mod tests {
                 assert!(app.grant_plugin_permission_action(window, cx));
                 let editor = app.editor.editor.as_ref().expect("editor");
                 assert!(editor
-                    .blocks
+                    .blocks()
                     .iter()
                     .any(|block| { block.text.contains("Plugin toolbar:") }));
             });
@@ -0,0 +1,116 @@
+use super::*;
+use sandpaper_core::links::extract_query;
+
+const QUERY_RESULT_LIMIT: i64 = 50;
+const QUERY_HEADER_HEIGHT: f32 = 28.0;
+const QUERY_PADDING_HEIGHT: f32 = 16.0;
+const QUERY_RESULT_ROW_HEIGHT: f32 = 26.0;
+
+/// Results of one `{{query ...}}` block, keyed by block uid in `EditorState::query_results`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct QueryResults {
+    pub(crate) source: String,
+    pub(crate) hits: Vec<SearchHit>,
+}
+
+/// Runs a query block's filter, leaving out the block itself and other query blocks.
+pub(crate) fn run_block_query(db: &Database, block_uid: &str, source: &str) -> Vec<SearchHit> {
+    let query = db
+        .parse_search_query(source)
+        .unwrap_or_else(|_| SearchQuery::parse(source));
+    if query.is_empty() {
+        return Vec::new();
+    }
+    db.search(&query, QUERY_RESULT_LIMIT)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|hit| hit.block_uid != block_uid && extract_query(&hit.text).is_none())
+        .collect()
+}
+
+impl AppStore {
+    /// Re-runs every query block on the open pages. Called with the references refresh so
+    /// results follow saved edits.
+    pub(crate) fn refresh_query_blocks(&mut self) {
+        let queries = [EditorPane::Primary, EditorPane::Secondary]
+            .into_iter()
+            .filter_map(|pane| self.editor_for_pane(pane))
+            .flat_map(|editor| editor.blocks().iter())
+            .filter_map(|block| Some((block.uid.clone(), extract_query(&block.text)?)))
+            .collect::<Vec<_>>();
+        let Some(db) = self.app.db.as_ref() else {
+            return;
+        };
+        let results = queries
+            .into_iter()
+            .map(|(uid, source)| {
+                let hits = run_block_query(db, &uid, &source);
+                (uid, QueryResults { source, hits })
+            })
+            .collect::<HashMap<_, _>>();
+
+        if results != self.editor.query_results {
+            self.editor.query_results = results;
+            self.update_block_list_for_pane(EditorPane::Primary);
+            self.update_block_list_for_pane(EditorPane::Secondary);
+        }
+    }
+
+    /// Results for a query block, or `None` until its current text has been run.
+    pub(crate) fn query_results_for(&self, uid: &str, text: &str) -> Option<&QueryResults> {
+        let source = extract_query(text)?;
+        self.editor
+            .query_results
+            .get(uid)
+            .filter(|results| results.source == source)
+    }
+
+    pub(crate) fn query_block_extra_height(&self, uid: &str, text: &str) -> f32 {
+        if extract_query(text).is_none() {
+            return 0.0;
+        }
+        let rows = self
+            .query_results_for(uid, text)
+            .map(|results| results.hits.len())
+            .unwrap_or(0)
+            .max(1);
+        QUERY_HEADER_HEIGHT + QUERY_PADDING_HEIGHT + rows as f32 * QUERY_RESULT_ROW_HEIGHT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_block_query;
+    use sandpaper_core::db::Database;
+
+    #[test]
+    fn block_queries_skip_their_own_block_and_other_queries() {
+        let db = Database::new_in_memory().expect("db");
+        db.run_migrations().expect("migrations");
+        let page_id = db.insert_page("page-1", "Inbox").expect("page");
+        let blocks = [
+            ("q-1", "a", "{{query #errand}}"),
+            ("q-2", "b", "{{query #errand todo:open}}"),
+            ("b-1", "c", "[ ] Pick up #errand parcel"),
+            ("b-2", "d", "[x] Return #errand books"),
+        ];
+        for (uid, sort_key, text) in blocks {
+            db.insert_block(page_id, uid, None, sort_key, text, "{}")
+                .expect("block");
+        }
+
+        let all = run_block_query(&db, "q-1", "#errand");
+        let mut uids = all
+            .iter()
+            .map(|hit| hit.block_uid.as_str())
+            .collect::<Vec<_>>();
+        uids.sort();
+        assert_eq!(uids, vec!["b-1", "b-2"]);
+
+        let open = run_block_query(&db, "q-2", "#errand todo:open");
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].block_uid, "b-1");
+
+        assert!(run_block_query(&db, "q-1", "   ").is_empty());
+    }
+}
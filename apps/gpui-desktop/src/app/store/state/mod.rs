@@ -1,10 +1,11 @@
 use super::{
-    AppStore, BacklinkEntry, BlockClipboard, BlockInputBinding, DiagramPreviewState, EditorPane,
-    HistoryEntry, LayoutDensity, LinkPreviewCacheEntry, LinkPreviewState, Mode, NotificationItem,
-    OutlineMenuState, PageDialogMode, PaneListState, PaneSelection, PluginBlockCacheEntry,
-    PluginBlockPreviewState, PluginPanel, PluginPermissionInfo, PluginPermissionPrompt,
-    PluginRuntimeStatus, QuickAddTarget, ReviewDisplayItem, SaveState, SecondaryPane, SettingsTab,
-    SlashMenuState, ThemePreference, UnlinkedReference, WikilinkMenuState, WorkspacePanel,
+    AppStore, BacklinkEntry, BlockClipboard, BlockInputBinding, DatabaseCellEdit,
+    DiagramPreviewState, EditorPane, HistoryEntry, LayoutDensity, LinkPreviewCacheEntry,
+    LinkPreviewState, Mode, NotificationItem, OutlineMenuState, PageDialogMode, PaneListState,
+    PaneSelection, PluginBlockCacheEntry, PluginBlockPreviewState, PluginPanel,
+    PluginPermissionInfo, PluginPermissionPrompt, PluginRuntimeStatus, QuickAddTarget,
+    ReviewDisplayItem, SaveState, SecondaryPane, SettingsTab, SlashMenuState, ThemePreference,
+    UnlinkedReference, WikilinkMenuState, WorkspacePanel,
 };
 use crate::app::prelude::*;
 
@@ -39,6 +40,7 @@ impl AppState {
 pub(crate) struct EditorState {
     pub(crate) pages_loading: bool,
     pub(crate) pages: Vec<PageRecord>,
+    pub(crate) title_index: TitleIndex,
     pub(crate) active_page: Option<PageRecord>,
     pub(crate) editor: Option<EditorModel>,
     pub(crate) page_cursors: HashMap<String, super::helpers::PageCursor>,
@@ -48,9 +50,13 @@ pub(crate) struct EditorState {
     pub(crate) sidebar_search_query: String,
     pub(crate) sidebar_search_input: Entity<InputState>,
     pub(crate) search_pages: Vec<PageRecord>,
-    pub(crate) search_blocks: Vec<BlockPageRecord>,
+    pub(crate) search_blocks: Vec<SearchHit>,
     pub(crate) backlinks: Vec<BacklinkEntry>,
     pub(crate) block_backlinks: Vec<BacklinkEntry>,
+    pub(crate) block_refs: super::block_refs::BlockRefState,
+    pub(crate) embed_input: Entity<InputState>,
+    pub(crate) query_results: HashMap<String, super::query_blocks::QueryResults>,
+    pub(crate) tagged_blocks: Vec<BacklinkEntry>,
     pub(crate) unlinked_references: Vec<UnlinkedReference>,
     pub(crate) references_epoch: u64,
     pub(crate) secondary_pane: Option<SecondaryPane>,
@@ -66,6 +72,11 @@ pub(crate) struct EditorState {
     pub(crate) capture_move_destination_input: Entity<InputState>,
     pub(crate) review_items: Vec<ReviewDisplayItem>,
     pub(crate) review_selected_index: usize,
+    pub(crate) review_stats: ReviewStats,
+    /// Review items whose answer side is showing.
+    pub(crate) review_revealed: HashSet<i64>,
+    /// Items due on each of the next days, starting today.
+    pub(crate) review_forecast: Vec<i64>,
     pub(crate) link_preview: Option<LinkPreviewState>,
     pub(crate) link_preview_epoch: u64,
     pub(crate) link_preview_close_epoch: u64,
@@ -85,8 +96,13 @@ pub(crate) struct EditorState {
     pub(crate) related_pages: Vec<super::connections::RelatedPage>,
     pub(crate) random_pages: Vec<PageRecord>,
     pub(crate) connections_epoch: u64,
+    pub(crate) page_history: super::history::PageHistoryState,
+    pub(crate) agenda: super::agenda::AgendaState,
+    pub(crate) journals: super::journals::JournalState,
     pub(crate) page_properties: Vec<PagePropertyRecord>,
     pub(crate) properties_open: bool,
+    pub(crate) database_cell_edit: Option<DatabaseCellEdit>,
+    pub(crate) database_cell_input: Entity<InputState>,
     // Capture mode state
     pub(crate) capture_move_item_uid: Option<String>,
     // Review mode / feed state
@@ -118,10 +134,13 @@ impl EditorState {
         });
         let capture_move_destination_input =
             cx.new(|cx| InputState::new(window, cx).placeholder("Move to page..."));
+        let database_cell_input = cx.new(|cx| InputState::new(window, cx));
+        let embed_input = cx.new(|cx| InputState::new(window, cx));
 
         Self {
             pages_loading: true,
             pages: Vec::new(),
+            title_index: TitleIndex::new(),
             active_page: None,
             editor: None,
             page_cursors: HashMap::new(),
@@ -134,6 +153,10 @@ impl EditorState {
             search_blocks: Vec::new(),
             backlinks: Vec::new(),
             block_backlinks: Vec::new(),
+            block_refs: super::block_refs::BlockRefState::default(),
+            embed_input,
+            query_results: HashMap::new(),
+            tagged_blocks: Vec::new(),
             unlinked_references: Vec::new(),
             references_epoch: 0,
             secondary_pane: None,
@@ -149,6 +172,9 @@ impl EditorState {
             capture_move_destination_input,
             review_items: Vec::new(),
             review_selected_index: 0,
+            review_stats: ReviewStats::default(),
+            review_revealed: HashSet::new(),
+            review_forecast: Vec::new(),
             link_preview: None,
             link_preview_epoch: 0,
             link_preview_close_epoch: 0,
@@ -168,8 +194,13 @@ impl EditorState {
             related_pages: Vec::new(),
             random_pages: Vec::new(),
             connections_epoch: 0,
+            page_history: super::history::PageHistoryState::default(),
+            agenda: super::agenda::AgendaState::default(),
+            journals: super::journals::JournalState::default(),
             page_properties: Vec::new(),
             properties_open: false,
+            database_cell_edit: None,
+            database_cell_input,
             capture_move_item_uid: None,
             feed_items: Vec::new(),
             feed_selected_index: 0,
@@ -182,6 +213,12 @@ impl EditorState {
             is_replaying_history: false,
         }
     }
+
+    /// Replaces the page list and syncs the title index with it.
+    pub(crate) fn set_pages(&mut self, pages: Vec<PageRecord>) {
+        self.title_index.sync(&pages);
+        self.pages = pages;
+    }
 }
 
 pub(crate) struct SettingsState {
@@ -438,6 +475,10 @@ pub(crate) struct UiState {
     pub(crate) page_dialog_mode: PageDialogMode,
     pub(crate) page_dialog_input: Entity<InputState>,
     pub(crate) page_dialog_error: Option<SharedString>,
+    /// Template pages offered when creating a page, and the one picked.
+    pub(crate) page_dialog_templates: Vec<PageRecord>,
+    pub(crate) page_dialog_template: Option<String>,
+    pub(crate) template_dialog: Option<super::templates::TemplateDialogState>,
     pub(crate) palette_input: Entity<InputState>,
     pub(crate) palette_open: bool,
     pub(crate) palette_query: String,
@@ -488,6 +529,9 @@ impl UiState {
             page_dialog_mode: PageDialogMode::Create,
             page_dialog_input,
             page_dialog_error: None,
+            page_dialog_templates: Vec::new(),
+            page_dialog_template: None,
+            template_dialog: None,
             palette_input,
             palette_open: false,
             palette_query: String::new(),
@@ -561,6 +605,11 @@ pub(crate) struct PluginsState {
     pub(crate) plugin_busy: bool,
     pub(crate) plugin_runtime: Option<PluginRuntime>,
     pub(crate) plugin_active_panel: Option<PluginPanel>,
+    /// What the active panel last rendered.
+    pub(crate) plugin_panel_view: Option<PluginPanelView>,
+    pub(crate) plugin_panel_error: Option<SharedString>,
+    /// Inputs for the panel's `input` nodes, by node id.
+    pub(crate) plugin_panel_inputs: HashMap<String, (Entity<InputState>, Subscription)>,
     pub(crate) plugin_permission_prompt: Option<PluginPermissionPrompt>,
     pub(crate) plugin_installing: bool,
     pub(crate) plugin_install_status: Option<SharedString>,
@@ -583,6 +632,9 @@ impl PluginsState {
             plugin_busy: false,
             plugin_runtime: None,
             plugin_active_panel: None,
+            plugin_panel_view: None,
+            plugin_panel_error: None,
+            plugin_panel_inputs: HashMap::new(),
             plugin_permission_prompt: None,
             plugin_installing: false,
             plugin_install_status: None,
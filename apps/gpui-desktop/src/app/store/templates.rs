@@ -0,0 +1,248 @@
+use super::*;
+use sandpaper_core::templates::{expand_template, template_fields, template_pages, TemplateVars};
+
+/// A template on its way into a pane, waiting on the template dialog.
+pub(crate) struct TemplateDialogState {
+    pub(crate) pane: EditorPane,
+    /// Block the template lands on; `None` uses the pane's active block.
+    pub(crate) block_uid: Option<String>,
+    pub(crate) templates: Vec<PageRecord>,
+    pub(crate) chosen: Option<ChosenTemplate>,
+}
+
+pub(crate) struct ChosenTemplate {
+    pub(crate) page: PageRecord,
+    pub(crate) blocks: Vec<BlockSnapshot>,
+    /// One input per `{{prompt:Name}}` field, in template order.
+    pub(crate) fields: Vec<(String, Entity<InputState>)>,
+}
+
+impl AppStore {
+    /// Pages tagged `#template` in the open vault.
+    pub(crate) fn template_pages(&self) -> Vec<PageRecord> {
+        self.app
+            .db
+            .as_ref()
+            .and_then(|db| template_pages(db).ok())
+            .unwrap_or_default()
+    }
+
+    /// Opens the template picker for the active block of `pane`.
+    pub(crate) fn open_template_dialog(
+        &mut self,
+        pane: EditorPane,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let templates = self.template_pages();
+        if templates.is_empty() {
+            window.push_notification(
+                (
+                    gpui_component::notification::NotificationType::Info,
+                    "No templates yet. Tag a page #template to insert it here.",
+                ),
+                cx,
+            );
+            return;
+        }
+        let block_uid = self
+            .editor_for_pane(pane)
+            .map(|editor| editor.active().uid.clone());
+        self.ui.template_dialog = Some(TemplateDialogState {
+            pane,
+            block_uid,
+            templates,
+            chosen: None,
+        });
+        self.show_template_dialog(window, cx);
+    }
+
+    /// Inserts the template page `page_uid` at the active block of the active pane, asking for
+    /// its prompt fields first when it has any.
+    pub(crate) fn insert_template(
+        &mut self,
+        page_uid: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let pane = self.editor.active_pane;
+        let block_uid = self
+            .editor_for_pane(pane)
+            .map(|editor| editor.active().uid.clone());
+        self.ui.template_dialog = Some(TemplateDialogState {
+            pane,
+            block_uid,
+            templates: Vec::new(),
+            chosen: None,
+        });
+        if self.choose_template(page_uid, window, cx) {
+            self.show_template_dialog(window, cx);
+        }
+    }
+
+    /// Picks the template to insert. Templates without prompt fields go in right away; returns
+    /// whether the dialog is still needed to fill in fields.
+    pub(crate) fn choose_template(
+        &mut self,
+        page_uid: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let loaded = self.app.db.as_ref().and_then(|db| {
+            let page = db.get_page_by_uid(page_uid).ok()??;
+            let blocks = db.load_blocks_for_page(page.id).ok()?;
+            Some((page, blocks))
+        });
+        let Some((page, blocks)) = loaded else {
+            self.close_template_dialog(cx);
+            return false;
+        };
+        let fields = template_fields(&blocks);
+        if fields.is_empty() {
+            self.apply_template(&blocks, HashMap::new(), window, cx);
+            self.close_template_dialog(cx);
+            return false;
+        }
+        let fields = fields
+            .into_iter()
+            .map(|name| {
+                let placeholder = name.clone();
+                let input = cx.new(|cx| InputState::new(window, cx).placeholder(placeholder));
+                (name, input)
+            })
+            .collect::<Vec<_>>();
+        if let Some((_, input)) = fields.first() {
+            window.focus(&input.focus_handle(cx), cx);
+        }
+        if let Some(dialog) = self.ui.template_dialog.as_mut() {
+            dialog.chosen = Some(ChosenTemplate {
+                page,
+                blocks,
+                fields,
+            });
+        }
+        cx.notify();
+        true
+    }
+
+    /// Inserts the chosen template with the entered fields. Returns `false` to keep the dialog
+    /// open while nothing has been picked.
+    pub(crate) fn confirm_template_dialog(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(chosen) = self
+            .ui
+            .template_dialog
+            .as_ref()
+            .and_then(|dialog| dialog.chosen.as_ref())
+        else {
+            return false;
+        };
+        let blocks = chosen.blocks.clone();
+        let fields = chosen
+            .fields
+            .iter()
+            .map(|(name, input)| (name.clone(), input.read(cx).value().trim().to_string()))
+            .collect::<HashMap<_, _>>();
+        self.apply_template(&blocks, fields, window, cx);
+        self.close_template_dialog(cx);
+        true
+    }
+
+    pub(crate) fn close_template_dialog(&mut self, cx: &mut Context<Self>) {
+        if self.ui.template_dialog.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn show_template_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let app = cx.entity();
+        let view = cx.new(|cx| crate::ui::dialogs::TemplateDialogView::new(app.clone(), cx));
+        window.open_dialog(cx, move |dialog, _window, _cx| {
+            dialog
+                .title("Insert template")
+                .w(px(420.0))
+                .confirm()
+                .button_props(
+                    gpui_component::dialog::DialogButtonProps::default()
+                        .ok_text("Insert")
+                        .cancel_text("Cancel"),
+                )
+                .child(view.clone())
+                .on_ok({
+                    let app = app.clone();
+                    move |_event, window, cx| {
+                        app.update(cx, |app, cx| app.confirm_template_dialog(window, cx))
+                    }
+                })
+                .on_cancel({
+                    let app = app.clone();
+                    move |_event, _window, cx| {
+                        app.update(cx, |app, cx| app.close_template_dialog(cx));
+                        true
+                    }
+                })
+                .on_close({
+                    let app = app.clone();
+                    move |_event, _window, cx| {
+                        app.update(cx, |app, cx| app.close_template_dialog(cx));
+                    }
+                })
+        });
+        cx.notify();
+    }
+
+    /// Expands `blocks` into the dialog's target block through the pane's `EditorModel`, so the
+    /// insert is one undoable step.
+    fn apply_template(
+        &mut self,
+        blocks: &[BlockSnapshot],
+        fields: HashMap<String, String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(dialog) = self.ui.template_dialog.as_ref() else {
+            return;
+        };
+        let pane = dialog.pane;
+        let block_uid = dialog.block_uid.clone();
+        let Some(title) = self.page_for_pane(pane).map(|page| page.title.clone()) else {
+            return;
+        };
+        let expanded = expand_template(
+            blocks,
+            &TemplateVars {
+                now: Local::now().naive_local(),
+                title,
+                fields,
+            },
+        );
+        let history_before = self.pane_snapshot(pane, cx);
+        let cursor = {
+            let Some(editor) = self.editor_for_pane_mut(pane) else {
+                return;
+            };
+            let ix = block_uid
+                .and_then(|uid| editor.blocks().iter().position(|block| block.uid == uid))
+                .unwrap_or(editor.active_ix);
+            editor.insert_template(ix, &expanded)
+        };
+        let Some(cursor) = cursor else {
+            return;
+        };
+        self.set_active_pane(pane, cx);
+        self.update_block_list_for_pane(pane);
+        self.sync_block_input_from_active_with_cursor_for_pane(
+            pane,
+            cursor.offset,
+            Some(window),
+            cx,
+        );
+        window.focus(&self.editor.block_input.focus_handle(cx), cx);
+        self.mark_dirty_for_pane(pane, cx);
+        self.schedule_references_refresh(cx);
+        self.record_structural_history_if_changed(pane, history_before, cx);
+    }
+}
@@ -20,6 +20,7 @@ fn empty_load_result() -> PluginRuntimeLoadResult {
         panels: Vec::new(),
         toolbar_actions: Vec::new(),
         renderers: Vec::new(),
+        errors: Vec::new(),
     }
 }
 
@@ -23,6 +23,9 @@ impl AppStore {
         ctx: BlockRenderCtx,
         cx: &mut Context<Self>,
     ) -> gpui::Div {
+        if sandpaper_core::links::extract_query(&block.text).is_some() {
+            return self.render_query_block_inner(block, ctx, cx);
+        }
         match block.block_type {
             BlockType::Heading1 | BlockType::Heading2 | BlockType::Heading3 => self
                 .render_heading_inner(
@@ -59,9 +62,7 @@ impl AppStore {
                 ctx.base_bg,
                 cx,
             ),
-            BlockType::DatabaseView => {
-                self.render_database_view_inner(ctx.indent_px, ctx.base_bg, cx)
-            }
+            BlockType::DatabaseView => self.render_database_view_inner(block, ctx, cx),
             BlockType::ColumnLayout => self.render_column_layout_inner(block, ctx, cx),
             BlockType::Toggle => self.render_toggle_inner(block, ctx, cx),
             _ => self.render_text_inner(block, ctx, cx),
@@ -256,14 +257,15 @@ impl AppStore {
             .child(div().flex_1().min_w_0().h(px(1.0)).bg(theme.border))
     }
 
-    /// Todo: checkbox before text
+    /// Todo: checkbox before text, task state, priority and date chips after it
     fn render_todo_inner(
         &mut self,
         block: &BlockSnapshot,
         ctx: BlockRenderCtx,
         cx: &mut Context<Self>,
     ) -> gpui::Div {
-        let checked = block.text.starts_with("- [x] ") || block.text.starts_with("[x] ");
+        let task = parse_task(&block.text);
+        let checked = task.as_ref().is_some_and(|task| !task.state.is_open());
 
         let (check_border, check_bg, check_fg, todo_muted) = {
             let theme = cx.theme();
@@ -298,6 +300,7 @@ impl AppStore {
         } else {
             ctx.content_container
         };
+        let chips = task.map(|task| self.render_task_chips(&block.uid, &task, pane, actual_ix, cx));
         div()
             .flex()
             .text_size(tokens::FONT_BASE)
@@ -321,8 +324,7 @@ impl AppStore {
                     .justify_center()
                     .hover(move |s| s.cursor_pointer())
                     .on_click(cx.listener(move |this, _event, _window, cx| {
-                        this.toggle_todo_checked(pane, actual_ix);
-                        cx.notify();
+                        this.toggle_todo_checked(pane, actual_ix, cx);
                     }))
                     .when(checked, |this| {
                         this.child(
@@ -333,9 +335,85 @@ impl AppStore {
                     }),
             )
             .child(styled_content)
+            .children(chips)
             .child(ctx.actions)
     }
 
+    /// State badge (click to cycle), priority, dates and repeat rule of a todo block
+    fn render_task_chips(
+        &mut self,
+        block_uid: &str,
+        task: &Task,
+        pane: EditorPane,
+        actual_ix: usize,
+        cx: &mut Context<Self>,
+    ) -> gpui::Div {
+        let theme = cx.theme();
+        let muted = theme.muted_foreground;
+        let secondary = theme.secondary;
+        let accent = theme.accent;
+        let warning = theme.warning;
+        let danger = theme.danger;
+        let today = chrono::Local::now().date_naive();
+
+        let chip = |label: String, color: gpui::Hsla| {
+            div()
+                .px_1()
+                .rounded_sm()
+                .bg(secondary)
+                .text_xs()
+                .text_color(color)
+                .child(label)
+        };
+        let state_color = match task.state {
+            TaskState::Doing => accent,
+            TaskState::Waiting => warning,
+            _ => muted,
+        };
+        let mut chips = div().flex().flex_shrink_0().items_center().gap_1().child(
+            chip(task.state.label().to_string(), state_color)
+                .id(format!("task-state-{block_uid}"))
+                .cursor_pointer()
+                .on_click(cx.listener(move |this, _event, _window, cx| {
+                    this.cycle_task_state(pane, actual_ix, cx);
+                })),
+        );
+        if let Some(priority) = task.priority {
+            let color = if priority == sandpaper_core::tasks::TaskPriority::A {
+                danger
+            } else {
+                muted
+            };
+            chips = chips.child(chip(format!("#{}", priority.as_str()), color));
+        }
+        let open = task.state.is_open();
+        if let Some(scheduled) = task.scheduled {
+            let color = if open && scheduled <= today {
+                accent
+            } else {
+                muted
+            };
+            chips = chips.child(chip(
+                format!("Scheduled {}", scheduled.format("%b %d")),
+                color,
+            ));
+        }
+        if let Some(repeat) = task.repeat {
+            chips = chips.child(chip(format!("↻ {}", repeat.to_text()), muted));
+        }
+        if let Some(deadline) = task.deadline {
+            let color = if open && deadline < today {
+                danger
+            } else if open && deadline == today {
+                warning
+            } else {
+                muted
+            };
+            chips = chips.child(chip(format!("Due {}", deadline.format("%b %d")), color));
+        }
+        chips
+    }
+
     /// Code block: monospace background
     fn render_code_block_inner(
         &self,
@@ -448,44 +526,310 @@ impl AppStore {
         }
     }
 
-    /// Database view: inline table of pages with properties
+    /// Database view: pages matching the block's query, as a table, board or list
     fn render_database_view_inner(
         &mut self,
-        indent_px: gpui::Pixels,
-        base_bg: gpui::Hsla,
+        block: &BlockSnapshot,
+        ctx: BlockRenderCtx,
         cx: &mut Context<Self>,
     ) -> gpui::Div {
         let theme = cx.theme();
         let border = theme.border;
-        let border_subtle = cx.global::<SandpaperTheme>().colors(cx).border_subtle;
         let muted = theme.muted_foreground;
-        let fg = theme.foreground;
-        let header_bg = theme.secondary;
-        let hover_bg = theme.list_hover;
+        let pane = ctx.pane;
+        let config = DatabaseViewConfig::from_block_text(&block.text);
 
-        // Collect property definitions for column headers
         let prop_defs: Vec<PropertyDefinition> = self
             .app
             .db
             .as_ref()
             .and_then(|db| db.list_property_definitions().ok())
             .unwrap_or_default();
-
-        // Query all pages with properties
-        let pages_with_props: Vec<(PageRecord, Vec<PagePropertyRecord>)> = self
+        let rows: Vec<PageQueryRow> = self
             .app
             .db
             .as_ref()
-            .and_then(|db| db.query_pages_with_properties(None, None).ok())
+            .and_then(|db| db.query_pages(&config.query).ok())
             .unwrap_or_default();
 
-        // Column keys: "title" is always first, then property definitions
+        // Column keys: the title is always first, then the configured or defined properties
+        let property_keys: Vec<String> = if config.columns.is_empty() {
+            prop_defs.iter().map(|def| def.key.clone()).collect()
+        } else {
+            config.columns.clone()
+        };
         let col_keys: Vec<(String, String)> =
-            std::iter::once(("__title".to_string(), "Title".to_string()))
-                .chain(prop_defs.iter().map(|d| (d.key.clone(), d.label.clone())))
+            std::iter::once((TITLE_KEY.to_string(), "Title".to_string()))
+                .chain(property_keys.iter().map(|key| {
+                    let label = prop_defs
+                        .iter()
+                        .find(|def| &def.key == key)
+                        .map(|def| def.label.clone())
+                        .unwrap_or_else(|| key.clone());
+                    (key.clone(), label)
+                }))
                 .collect();
 
-        // Header row
+        let body = if rows.is_empty() {
+            div()
+                .p_4()
+                .border_1()
+                .border_color(border)
+                .rounded_md()
+                .text_size(tokens::FONT_SM)
+                .text_color(muted)
+                .child("No pages match this view.")
+        } else {
+            match config.layout {
+                ViewLayout::Table => self
+                    .render_database_table(pane, block, &config, &col_keys, &prop_defs, &rows, cx),
+                ViewLayout::Board => {
+                    self.render_database_board(block, &config, &col_keys, &prop_defs, &rows, cx)
+                }
+                ViewLayout::List => self.render_database_list(&col_keys, &rows, cx),
+            }
+        };
+
+        let selected_layout = match config.layout {
+            ViewLayout::Table => 0,
+            ViewLayout::Board => 1,
+            ViewLayout::List => 2,
+        };
+        let layout_block_uid = block.uid.clone();
+        let label = div()
+            .flex()
+            .items_center()
+            .justify_between()
+            .mb_1()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(Icon::new(SandpaperIcon::Grid).size_3p5().text_color(muted))
+                    .child(
+                        div()
+                            .text_size(tokens::FONT_SM)
+                            .font_weight(gpui::FontWeight::MEDIUM)
+                            .text_color(muted)
+                            .child(format!("Database View · {}", rows.len())),
+                    ),
+            )
+            .child(
+                TabBar::new(SharedString::from(format!(
+                    "db-view-layout-tabs-{}",
+                    block.uid
+                )))
+                .xsmall()
+                .pill()
+                .selected_index(selected_layout)
+                .on_click(cx.listener(move |this, ix: &usize, _window, cx| {
+                    let layout = match *ix {
+                        0 => ViewLayout::Table,
+                        1 => ViewLayout::Board,
+                        _ => ViewLayout::List,
+                    };
+                    this.update_database_view_config(
+                        pane,
+                        &layout_block_uid,
+                        |config| config.layout = layout,
+                        cx,
+                    );
+                }))
+                .child(Tab::new().label("Table"))
+                .child(Tab::new().label("Board"))
+                .child(Tab::new().label("List")),
+            );
+
+        div()
+            .flex()
+            .flex_col()
+            .py_2()
+            .px_2()
+            .child(div().w(ctx.indent_px).h(px(1.0)).bg(ctx.base_bg))
+            .child(label)
+            .child(body)
+    }
+
+    /// Query block: the `{{query ...}}` source row followed by the blocks it currently matches
+    fn render_query_block_inner(
+        &mut self,
+        block: &BlockSnapshot,
+        ctx: BlockRenderCtx,
+        cx: &mut Context<Self>,
+    ) -> gpui::Div {
+        let theme = cx.theme();
+        let border = theme.border;
+        let muted = theme.muted_foreground;
+        let fg = theme.foreground;
+        let hover_bg = theme.list_hover;
+        let indent_px = ctx.indent_px;
+        let base_bg = ctx.base_bg;
+        let hits = self
+            .query_results_for(&block.uid, &block.text)
+            .map(|results| results.hits.clone());
+        let source_row = self.render_text_inner(block, ctx, cx);
+
+        let label = div()
+            .flex()
+            .items_center()
+            .gap_1()
+            .mb_1()
+            .child(
+                Icon::new(SandpaperIcon::Search)
+                    .size_3p5()
+                    .text_color(muted),
+            )
+            .child(
+                div()
+                    .text_size(tokens::FONT_SM)
+                    .font_weight(gpui::FontWeight::MEDIUM)
+                    .text_color(muted)
+                    .child(match &hits {
+                        Some(hits) => format!("Query · {}", hits.len()),
+                        None => "Query".to_string(),
+                    }),
+            );
+
+        let mut list = div().w_full().flex().flex_col();
+        match hits {
+            Some(hits) if !hits.is_empty() => {
+                for hit in hits {
+                    let page_uid = hit.page_uid.clone();
+                    let block_uid = hit.block_uid.clone();
+                    let text = strip_block_properties(&hit.text);
+                    list = list.child(
+                        div()
+                            .id(SharedString::from(format!(
+                                "query-hit-{}-{}",
+                                block.uid, hit.block_uid
+                            )))
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .hover(move |s| s.bg(hover_bg))
+                            .on_mouse_down(MouseButton::Left, |_event, _window, cx| {
+                                cx.stop_propagation();
+                            })
+                            .on_click(cx.listener(move |this, _event, window, cx| {
+                                this.open_page_and_focus_block(&page_uid, &block_uid, window, cx);
+                                cx.stop_propagation();
+                            }))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_w_0()
+                                    .overflow_hidden()
+                                    .text_size(tokens::FONT_SM)
+                                    .text_color(fg)
+                                    .child(crate::app::store::helpers::single_line_text(&text)),
+                            )
+                            .child(
+                                div()
+                                    .flex_shrink_0()
+                                    .text_xs()
+                                    .text_color(muted)
+                                    .child(hit.page_title.clone()),
+                            ),
+                    );
+                }
+            }
+            Some(_) => {
+                list = list.child(
+                    div()
+                        .px_2()
+                        .py_1()
+                        .text_size(tokens::FONT_SM)
+                        .text_color(muted)
+                        .child("No blocks match this query."),
+                );
+            }
+            None => {
+                list = list.child(
+                    div()
+                        .px_2()
+                        .py_1()
+                        .text_size(tokens::FONT_SM)
+                        .text_color(muted)
+                        .child("Results update when the block is saved."),
+                );
+            }
+        }
+
+        div().flex().flex_col().child(source_row).child(
+            div()
+                .flex()
+                .child(div().w(indent_px).h(px(1.0)).bg(base_bg))
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .mx_2()
+                        .mb_2()
+                        .p_2()
+                        .rounded_md()
+                        .border_1()
+                        .border_color(border)
+                        .child(label)
+                        .child(list),
+                ),
+        )
+    }
+
+    fn database_value_type(
+        key: &str,
+        props: &[PagePropertyRecord],
+        prop_defs: &[PropertyDefinition],
+    ) -> String {
+        props
+            .iter()
+            .find(|prop| prop.key == key)
+            .map(|prop| prop.value_type.clone())
+            .or_else(|| {
+                prop_defs
+                    .iter()
+                    .find(|def| def.key == key)
+                    .map(|def| def.value_type.clone())
+            })
+            .unwrap_or_else(|| "text".to_string())
+    }
+
+    fn database_cell_value(key: &str, page: &PageRecord, props: &[PagePropertyRecord]) -> String {
+        if key == TITLE_KEY {
+            return page.title.clone();
+        }
+        props
+            .iter()
+            .find(|prop| prop.key == key)
+            .map(|prop| prop.value.clone())
+            .unwrap_or_default()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_database_table(
+        &mut self,
+        pane: EditorPane,
+        block: &BlockSnapshot,
+        config: &DatabaseViewConfig,
+        col_keys: &[(String, String)],
+        prop_defs: &[PropertyDefinition],
+        rows: &[PageQueryRow],
+        cx: &mut Context<Self>,
+    ) -> gpui::Div {
+        let theme = cx.theme();
+        let border = theme.border;
+        let border_subtle = cx.global::<SandpaperTheme>().colors(cx).border_subtle;
+        let muted = theme.muted_foreground;
+        let fg = theme.foreground;
+        let header_bg = theme.secondary;
+        let hover_bg = theme.list_hover;
+
+        // Header row: clicking a column cycles its sort
         let mut header_row = div()
             .flex()
             .items_center()
@@ -494,75 +838,113 @@ impl AppStore {
             .bg(header_bg)
             .px_2()
             .py_1();
-
-        for (_, label) in &col_keys {
+        for (key, label) in col_keys {
+            let indicator = match config.query.sorts.iter().find(|sort| &sort.key == key) {
+                Some(sort) if sort.descending => " ↓",
+                Some(_) => " ↑",
+                None => "",
+            };
+            let sort_block_uid = block.uid.clone();
+            let sort_key = key.clone();
             header_row = header_row.child(
                 div()
+                    .id(SharedString::from(format!("db-col-{}-{key}", block.uid)))
                     .flex_1()
                     .min_w(px(80.0))
+                    .cursor_pointer()
                     .text_size(tokens::FONT_SM)
                     .font_weight(gpui::FontWeight::MEDIUM)
                     .text_color(muted)
-                    .child(label.clone()),
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.update_database_view_config(
+                            pane,
+                            &sort_block_uid,
+                            |config| config.toggle_sort(&sort_key),
+                            cx,
+                        );
+                    }))
+                    .child(format!("{label}{indicator}")),
             );
         }
 
-        // Data rows
+        // Data rows: the title opens the page, property cells edit in place
         let mut data_rows = div().flex().flex_col();
-        for (page, props) in &pages_with_props {
-            let page_uid = page.uid.clone();
+        for (page, props) in rows {
             let mut row = div()
-                .id(SharedString::from(format!("db-row-{}", page.uid)))
+                .id(SharedString::from(format!(
+                    "db-row-{}-{}",
+                    block.uid, page.uid
+                )))
                 .flex()
                 .items_center()
                 .px_2()
                 .py(px(3.0))
                 .border_b_1()
                 .border_color(border_subtle)
-                .cursor_pointer()
-                .hover(move |s| s.bg(hover_bg))
-                .on_click(cx.listener(move |this, _event, _window, cx| {
-                    this.open_page(&page_uid, cx);
-                }));
+                .hover(move |s| s.bg(hover_bg));
 
-            for (key, _) in &col_keys {
-                let cell_text = if key == "__title" {
-                    page.title.clone()
-                } else {
-                    props
-                        .iter()
-                        .find(|p| &p.key == key)
-                        .map(|p| p.value.clone())
-                        .unwrap_or_default()
+            for (key, _) in col_keys {
+                let cell_id =
+                    SharedString::from(format!("db-cell-{}-{}-{key}", block.uid, page.uid));
+                let cell = div()
+                    .id(cell_id)
+                    .flex_1()
+                    .min_w(px(80.0))
+                    .text_size(tokens::FONT_SM)
+                    .text_color(fg)
+                    .overflow_hidden()
+                    .cursor_pointer();
+                if key == TITLE_KEY {
+                    let page_uid = page.uid.clone();
+                    row = row.child(
+                        cell.on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.open_page(&page_uid, cx);
+                        }))
+                        .child(crate::app::store::helpers::single_line_text(&page.title)),
+                    );
+                    continue;
+                }
+
+                let edit = DatabaseCellEdit {
+                    page_id: page.id,
+                    key: key.clone(),
+                    value_type: Self::database_value_type(key, props, prop_defs),
                 };
-                let cell_text = crate::app::store::helpers::single_line_text(&cell_text);
+                let value = Self::database_cell_value(key, page, props);
+                if self.editor.database_cell_edit.as_ref() == Some(&edit) {
+                    row = row.child(
+                        cell.capture_key_down(cx.listener(
+                            |this, event: &KeyDownEvent, _window, cx| {
+                                match event.keystroke.key.as_str() {
+                                    "enter" => this.commit_database_cell_edit(cx),
+                                    "escape" => this.cancel_database_cell_edit(cx),
+                                    _ => return,
+                                }
+                                cx.stop_propagation();
+                            },
+                        ))
+                        .child(
+                            Input::new(&self.editor.database_cell_input)
+                                .appearance(false)
+                                .small(),
+                        ),
+                    );
+                    continue;
+                }
+                let display = crate::app::store::helpers::single_line_text(&value);
                 row = row.child(
-                    div()
-                        .flex_1()
-                        .min_w(px(80.0))
-                        .text_size(tokens::FONT_SM)
-                        .text_color(fg)
-                        .overflow_hidden()
-                        .child(cell_text),
+                    cell.on_click(cx.listener(move |this, _event, window, cx| {
+                        this.commit_database_cell_edit(cx);
+                        this.begin_database_cell_edit(edit.clone(), value.clone(), window, cx);
+                    }))
+                    .child(display),
                 );
             }
 
             data_rows = data_rows.child(row);
         }
 
-        let empty_state = if pages_with_props.is_empty() {
-            Some(
-                div()
-                    .p_4()
-                    .text_size(tokens::FONT_SM)
-                    .text_color(muted)
-                    .child("No pages found. Add pages to see them here."),
-            )
-        } else {
-            None
-        };
-
-        let table = div()
+        div()
             .w_full()
             .border_1()
             .border_color(border)
@@ -570,45 +952,173 @@ impl AppStore {
             .overflow_hidden()
             .child(header_row)
             .child(data_rows)
-            .when_some(empty_state, |this, empty| this.child(empty));
+    }
 
-        let label = div()
+    fn render_database_board(
+        &mut self,
+        block: &BlockSnapshot,
+        config: &DatabaseViewConfig,
+        col_keys: &[(String, String)],
+        prop_defs: &[PropertyDefinition],
+        rows: &[PageQueryRow],
+        cx: &mut Context<Self>,
+    ) -> gpui::Div {
+        let theme = cx.theme();
+        let border = theme.border;
+        let muted = theme.muted_foreground;
+        let fg = theme.foreground;
+        let card_bg = theme.background;
+        let column_bg = theme.secondary;
+        let hover_bg = theme.list_hover;
+
+        // Group by the configured key, else by the first select property
+        let group_def = match config.group_by.as_deref() {
+            Some(key) => prop_defs.iter().find(|def| def.key == key),
+            None => prop_defs.iter().find(|def| def.value_type == "select"),
+        };
+        let Some(group_key) = config
+            .group_by
+            .clone()
+            .or_else(|| group_def.map(|def| def.key.clone()))
+        else {
+            return div()
+                .p_4()
+                .border_1()
+                .border_color(border)
+                .rounded_md()
+                .text_size(tokens::FONT_SM)
+                .text_color(muted)
+                .child("Add a select property to group this view into a board.");
+        };
+        let options = parse_select_options(group_def.and_then(|def| def.options.as_deref()));
+
+        let mut board = div()
+            .w_full()
             .flex()
-            .items_center()
-            .justify_between()
-            .mb_1()
-            .child(
+            .items_start()
+            .gap_2()
+            .overflow_hidden();
+        for (group, members) in group_rows(rows, &group_key, &options) {
+            let title = if group.is_empty() {
+                "No value".to_string()
+            } else {
+                group.clone()
+            };
+            let mut column = div()
+                .flex_1()
+                .min_w(px(140.0))
+                .flex()
+                .flex_col()
+                .gap_1()
+                .p_2()
+                .rounded_md()
+                .bg(column_bg)
+                .child(
+                    div()
+                        .text_size(tokens::FONT_SM)
+                        .font_weight(gpui::FontWeight::MEDIUM)
+                        .text_color(muted)
+                        .child(format!("{title} · {}", members.len())),
+                );
+            for (page, props) in members {
+                let page_uid = page.uid.clone();
+                let details = col_keys
+                    .iter()
+                    .filter(|(key, _)| key != TITLE_KEY && *key != group_key)
+                    .filter_map(|(key, label)| {
+                        let value = Self::database_cell_value(key, page, props);
+                        (!value.trim().is_empty()).then(|| format!("{label}: {value}"))
+                    })
+                    .collect::<Vec<_>>();
+                column = column.child(
+                    div()
+                        .id(SharedString::from(format!(
+                            "db-card-{}-{}",
+                            block.uid, page.uid
+                        )))
+                        .p_2()
+                        .rounded_md()
+                        .border_1()
+                        .border_color(border)
+                        .bg(card_bg)
+                        .cursor_pointer()
+                        .hover(move |s| s.bg(hover_bg))
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.open_page(&page_uid, cx);
+                        }))
+                        .child(
+                            div()
+                                .text_size(tokens::FONT_SM)
+                                .text_color(fg)
+                                .child(page.title.clone()),
+                        )
+                        .children(details.into_iter().map(|detail| {
+                            div()
+                                .text_xs()
+                                .text_color(muted)
+                                .child(crate::app::store::helpers::single_line_text(&detail))
+                        })),
+                );
+            }
+            board = board.child(column);
+        }
+        board
+    }
+
+    fn render_database_list(
+        &mut self,
+        col_keys: &[(String, String)],
+        rows: &[PageQueryRow],
+        cx: &mut Context<Self>,
+    ) -> gpui::Div {
+        let theme = cx.theme();
+        let muted = theme.muted_foreground;
+        let fg = theme.foreground;
+        let hover_bg = theme.list_hover;
+
+        let mut list = div().w_full().flex().flex_col();
+        for (page, props) in rows {
+            let page_uid = page.uid.clone();
+            let summary = col_keys
+                .iter()
+                .filter(|(key, _)| key != TITLE_KEY)
+                .filter_map(|(key, _)| {
+                    let value = Self::database_cell_value(key, page, props);
+                    (!value.trim().is_empty()).then_some(value)
+                })
+                .collect::<Vec<_>>()
+                .join(" · ");
+            list = list.child(
                 div()
+                    .id(SharedString::from(format!("db-list-{}", page.uid)))
                     .flex()
                     .items_center()
-                    .gap_1()
-                    .child(Icon::new(SandpaperIcon::Grid).size_3p5().text_color(muted))
+                    .justify_between()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(move |s| s.bg(hover_bg))
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.open_page(&page_uid, cx);
+                    }))
                     .child(
                         div()
                             .text_size(tokens::FONT_SM)
-                            .font_weight(gpui::FontWeight::MEDIUM)
+                            .text_color(fg)
+                            .child(page.title.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
                             .text_color(muted)
-                            .child("Database View"),
+                            .overflow_hidden()
+                            .child(crate::app::store::helpers::single_line_text(&summary)),
                     ),
-            )
-            .child(
-                TabBar::new("db-view-layout-tabs")
-                    .xsmall()
-                    .pill()
-                    .selected_index(0)
-                    .child(Tab::new().label("Table"))
-                    .child(Tab::new().label("Kanban"))
-                    .child(Tab::new().label("Gallery")),
             );
-
-        div()
-            .flex()
-            .flex_col()
-            .py_2()
-            .px_2()
-            .child(div().w(indent_px).h(px(1.0)).bg(base_bg))
-            .child(label)
-            .child(table)
+        }
+        list
     }
 
     /// Column layout: render child Column blocks side-by-side
@@ -660,8 +1170,8 @@ impl AppStore {
             let mut columns: Vec<ColumnPreview> = Vec::new();
             let parent_indent = block.indent;
             let mut ix = actual_ix + 1;
-            while ix < editor.blocks.len() {
-                let current = &editor.blocks[ix];
+            while ix < editor.blocks().len() {
+                let current = &editor.blocks()[ix];
                 if current.indent <= parent_indent {
                     break;
                 }
@@ -679,8 +1189,8 @@ impl AppStore {
                     let column_indent = current.indent;
                     let mut rows: Vec<ColumnRowPreview> = Vec::new();
                     let mut row_ix = ix + 1;
-                    while row_ix < editor.blocks.len() {
-                        let child = &editor.blocks[row_ix];
+                    while row_ix < editor.blocks().len() {
+                        let child = &editor.blocks()[row_ix];
                         if child.indent <= column_indent {
                             break;
                         }
@@ -927,22 +1437,61 @@ impl AppStore {
     }
 
     /// Toggle a todo block's checked state
-    pub(crate) fn toggle_todo_checked(&mut self, pane: EditorPane, block_ix: usize) {
+    pub(crate) fn toggle_todo_checked(
+        &mut self,
+        pane: EditorPane,
+        block_ix: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let now = chrono::Local::now().naive_local();
+        let next = self
+            .editor_for_pane(pane)
+            .and_then(|editor| editor.blocks().get(block_ix))
+            .and_then(|block| toggle_task(&block.text, now));
+        if let Some(next) = next {
+            self.set_task_block_text(pane, block_ix, &next, cx);
+        }
+    }
+
+    /// Move a todo block to the next task state (TODO → DOING → WAITING → DONE → CANCELED)
+    pub(crate) fn cycle_task_state(
+        &mut self,
+        pane: EditorPane,
+        block_ix: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let now = chrono::Local::now().naive_local();
+        let next = self
+            .editor_for_pane(pane)
+            .and_then(|editor| editor.blocks().get(block_ix))
+            .and_then(|block| {
+                let task = parse_task(&block.text)?;
+                Some(set_task_state(&block.text, task.state.next(), now))
+            });
+        if let Some(next) = next {
+            self.set_task_block_text(pane, block_ix, &next, cx);
+        }
+    }
+
+    fn set_task_block_text(
+        &mut self,
+        pane: EditorPane,
+        block_ix: usize,
+        text: &str,
+        cx: &mut Context<Self>,
+    ) {
         let Some(editor) = self.editor_for_pane_mut(pane) else {
             return;
         };
-        if block_ix >= editor.blocks.len() {
+        if !editor.set_text(block_ix, text) {
             return;
         }
-        let text = &mut editor.blocks[block_ix].text;
-        if text.starts_with("- [x] ") {
-            *text = format!("- [ ] {}", &text[6..]);
-        } else if text.starts_with("- [ ] ") {
-            *text = format!("- [x] {}", &text[6..]);
-        } else if text.starts_with("[x] ") {
-            *text = format!("[ ] {}", &text[4..]);
-        } else if text.starts_with("[ ] ") {
-            *text = format!("[x] {}", &text[4..]);
+        let is_active = editor.active_ix == block_ix;
+        self.update_block_list_for_pane(pane);
+        if is_active {
+            self.sync_block_input_from_active_for_pane(pane, None, cx);
         }
+        self.mark_dirty_for_pane(pane, cx);
+        cx.notify();
     }
 }
@@ -190,18 +190,18 @@ impl AppStore {
         let Some(editor) = self.editor_for_pane(pane) else {
             return Vec::new();
         };
-        if editor.blocks.is_empty() {
+        if editor.blocks().is_empty() {
             return Vec::new();
         }
         let mut chain = Vec::new();
-        let mut current_ix = editor.active_ix.min(editor.blocks.len() - 1);
-        let mut current_indent = editor.blocks[current_ix].indent;
+        let mut current_ix = editor.active_ix.min(editor.blocks().len() - 1);
+        let mut current_indent = editor.blocks()[current_ix].indent;
         chain.push(current_ix);
 
         while current_ix > 0 {
             let mut found = None;
             for ix in (0..current_ix).rev() {
-                let indent = editor.blocks[ix].indent;
+                let indent = editor.blocks()[ix].indent;
                 if indent < current_indent {
                     found = Some(ix);
                     current_ix = ix;
@@ -220,7 +220,7 @@ impl AppStore {
             .into_iter()
             .rev()
             .map(|ix| {
-                let block = &editor.blocks[ix];
+                let block = &editor.blocks()[ix];
                 let label = if block.text.trim().is_empty() {
                     "Untitled".to_string()
                 } else {
@@ -247,7 +247,7 @@ impl AppStore {
             .editor
             .editor
             .as_ref()
-            .map(|editor| editor.blocks.len())
+            .map(|editor| editor.blocks().len())
             .unwrap_or(0);
         let title = if active_page.title.trim().is_empty() {
             "Untitled"
@@ -569,7 +569,9 @@ impl AppStore {
     }
 
     fn render_backlinks_toggle(&mut self, cx: &mut Context<Self>) -> gpui::AnyElement {
-        let total = self.editor.backlinks.len() + self.editor.block_backlinks.len();
+        let total = self.editor.backlinks.len()
+            + self.editor.block_backlinks.len()
+            + self.editor.tagged_blocks.len();
         let is_open = self.settings.context_panel_open
             && self.settings.context_panel_tab == WorkspacePanel::Backlinks;
         let tooltip = if total > 0 {
@@ -922,7 +924,13 @@ impl AppStore {
                     .py(px(10.0))
                     .text_xs()
                     .text_color(theme.muted_foreground)
-                    .child("Link suggestions"),
+                    .child(if self.editor.wikilink_menu.tag {
+                        "Tag suggestions"
+                    } else if self.editor.wikilink_menu.block_ref {
+                        "Block references"
+                    } else {
+                        "Link suggestions"
+                    }),
             );
 
         let hover_bg = theme.list_hover;
@@ -944,16 +952,38 @@ impl AppStore {
                 } else {
                     theme.popover
                 };
-                let (label, create, query) = match item {
+                let (label, create, query, tag, block_ref) = match item {
                     WikilinkMenuItem::Page(page) => {
                         let title = if page.title.trim().is_empty() {
                             page.uid
                         } else {
                             page.title
                         };
-                        (title, false, String::new())
+                        (title, false, String::new(), None, None)
+                    }
+                    WikilinkMenuItem::Alias { page, alias } => {
+                        let label = format!("{alias} → {}", page.title);
+                        (label, false, alias, None, None)
                     }
-                    WikilinkMenuItem::Create { label, query } => (label, true, query),
+                    WikilinkMenuItem::Create { label, query } => (label, true, query, None, None),
+                    WikilinkMenuItem::Tag(tag) => (
+                        format!("{} ({})", format_tag(&tag.name), tag.block_count),
+                        false,
+                        String::new(),
+                        Some(tag.name),
+                        None,
+                    ),
+                    WikilinkMenuItem::Block(record) => (
+                        format!(
+                            "{} · {}",
+                            format_snippet(&record.text, 60),
+                            record.page_title
+                        ),
+                        false,
+                        String::new(),
+                        None,
+                        Some(record.block_uid),
+                    ),
                 };
                 let label_clone = label.clone();
                 menu = menu.child(
@@ -980,8 +1010,15 @@ impl AppStore {
                             },
                         ))
                         .on_click(cx.listener(move |this, _event, window, cx| {
-                            if create {
+                            if let Some(tag) = tag.as_deref() {
+                                this.apply_tag_suggestion(tag, window, cx);
+                            } else if let Some(uid) = block_ref.as_deref() {
+                                this.apply_block_ref_suggestion(uid, window, cx);
+                            } else if create {
                                 this.apply_wikilink_suggestion(&query, true, window, cx);
+                            } else if !query.is_empty() {
+                                // Alias rows insert the alias itself, not the arrow label.
+                                this.apply_wikilink_suggestion(&query, false, window, cx);
                             } else {
                                 this.apply_wikilink_suggestion(&label_clone, false, window, cx);
                             }
@@ -1173,7 +1210,7 @@ impl AppStore {
             .h(px(320.0))
             .overflow_scroll();
         for (ix, actual_ix) in list_state.visible_to_actual.iter().copied().enumerate() {
-            let Some(block) = editor.blocks.get(actual_ix) else {
+            let Some(block) = editor.blocks().get(actual_ix) else {
                 continue;
             };
             let label = if block.text.trim().is_empty() {
@@ -1379,7 +1416,7 @@ impl AppStore {
     }
 
     #[allow(clippy::only_used_in_recursion)] // window is threaded through for GPUI API
-    fn render_inline_markdown_text(
+    pub(super) fn render_inline_markdown_text(
         &mut self,
         pane: EditorPane,
         block_uid: &str,
@@ -1451,6 +1488,7 @@ impl AppStore {
 
         enum InlineAction {
             Wikilink(String),
+            BlockRef(String),
             External(String),
         }
 
@@ -1467,6 +1505,7 @@ impl AppStore {
             color: Some(theme.muted_foreground),
         };
         let code_bg = theme.secondary.opacity(0.9);
+        let block_ref_bg = theme.accent.opacity(0.12);
 
         for token in tokens {
             match token {
@@ -1490,6 +1529,29 @@ impl AppStore {
                         ));
                     }
                 }
+                crate::app::store::markdown::InlineMarkdownToken::BlockRef { uid, label } => {
+                    let resolved = label.or_else(|| self.block_ref_text(&uid));
+                    let start = display.len();
+                    match resolved.as_deref().map(str::trim) {
+                        Some(text) if !text.is_empty() => display.push_str(text),
+                        _ => display.push_str(&format!("(({uid}))")),
+                    }
+                    let end = display.len();
+                    interactive_ranges.push(start..end);
+                    highlight_ranges.push((
+                        start..end,
+                        HighlightStyle {
+                            color: Some(if resolved.is_some() {
+                                theme.foreground
+                            } else {
+                                theme.muted_foreground
+                            }),
+                            background_color: Some(block_ref_bg),
+                            ..Default::default()
+                        },
+                    ));
+                    actions.push(InlineAction::BlockRef(uid));
+                }
                 crate::app::store::markdown::InlineMarkdownToken::Link { href, label } => {
                     let start = display.len();
                     display.push_str(&label);
@@ -1594,6 +1656,7 @@ impl AppStore {
         let actions = Rc::new(actions);
         let hover_actions = actions.clone();
         let click_actions = actions.clone();
+        let link_block_uid = block_uid.to_string();
         let hover_ranges = Rc::new(interactive_ranges);
         let click_ranges = hover_ranges.as_ref().clone();
         let hover_ranges_clone = hover_ranges.clone();
@@ -1604,13 +1667,23 @@ impl AppStore {
 
         let interactive =
             InteractiveText::new(format!("inline-md-{id_prefix}-{block_uid}"), styled)
-                .on_click(click_ranges, move |idx, _window, cx| {
+                .on_click(click_ranges, move |idx, window, cx| {
                     if let Some(action) = click_actions.get(idx) {
                         match action {
                             InlineAction::Wikilink(target) => {
                                 let target = target.clone();
                                 click_entity.update(cx, |this, cx| {
-                                    this.open_page(&target, cx);
+                                    if this.app.mode == Mode::Journals {
+                                        this.set_mode(Mode::Editor, cx);
+                                    }
+                                    this.open_link(&target, &link_block_uid, cx);
+                                    this.close_link_preview();
+                                });
+                            }
+                            InlineAction::BlockRef(uid) => {
+                                let uid = uid.clone();
+                                click_entity.update(cx, |this, cx| {
+                                    this.open_block_ref(&uid, window, cx);
                                     this.close_link_preview();
                                 });
                             }
@@ -1791,6 +1864,166 @@ impl AppStore {
         )
     }
 
+    fn render_block_embed_preview(
+        &mut self,
+        pane: EditorPane,
+        host_uid: &str,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<gpui::AnyElement> {
+        let (target, embed) = self.embed_for_text(text)?;
+        let embed = embed.clone();
+        let is_page_embed = matches!(target, sandpaper_core::links::EmbedTarget::Page(_));
+        let (border, list_bg, hover_bg, muted_fg, accent) = {
+            let theme = cx.theme();
+            (
+                theme.border,
+                theme.colors.list,
+                theme.list_hover,
+                theme.muted_foreground,
+                theme.accent,
+            )
+        };
+
+        let page_uid = embed.page_uid.clone();
+        let source_title: SharedString = if embed.page_title.trim().is_empty() {
+            embed.page_uid.clone().into()
+        } else {
+            embed.page_title.clone().into()
+        };
+        let header = div()
+            .id(format!("block-embed-source-{host_uid}"))
+            .flex()
+            .items_center()
+            .gap_1()
+            .text_xs()
+            .text_color(muted_fg)
+            .cursor_pointer()
+            .child(if is_page_embed {
+                "Embedded page"
+            } else {
+                "Embedded from"
+            })
+            .child(div().text_color(accent).child(source_title))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                this.open_page(&page_uid, cx);
+                cx.stop_propagation();
+            }));
+
+        let mut rows = div().flex().flex_col().mt_1();
+        if embed.blocks.is_empty() {
+            rows = rows.child(
+                div()
+                    .min_h(px(BLOCK_ROW_HEIGHT))
+                    .text_xs()
+                    .text_color(muted_fg)
+                    .child("This page has no blocks yet."),
+            );
+        }
+        let hidden = embed
+            .blocks
+            .len()
+            .saturating_sub(crate::app::store::block_refs::EMBED_MAX_ROWS);
+        for (ix, block) in embed
+            .blocks
+            .iter()
+            .take(crate::app::store::block_refs::EMBED_MAX_ROWS)
+            .enumerate()
+        {
+            let edit = crate::app::store::block_refs::EmbedEdit {
+                host_uid: host_uid.to_string(),
+                block_uid: block.uid.clone(),
+            };
+            let row_indent = px(block.indent.max(0) as f32 * 18.0);
+            let row = div()
+                .id(format!("block-embed-{host_uid}-{ix}"))
+                .min_h(px(BLOCK_ROW_HEIGHT))
+                .pl(row_indent)
+                .flex()
+                .items_start()
+                .gap_2()
+                .rounded_sm()
+                .on_mouse_down(MouseButton::Left, |_event, _window, cx| {
+                    cx.stop_propagation();
+                })
+                .child(
+                    div()
+                        .pt(tokens::SPACE_2)
+                        .text_sm()
+                        .text_color(muted_fg)
+                        .child("•"),
+                );
+            if self.editor.block_refs.edit.as_ref() == Some(&edit) {
+                rows = rows.child(
+                    row.capture_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
+                        match event.keystroke.key.as_str() {
+                            "enter" => this.commit_embed_edit(cx),
+                            "escape" => this.cancel_embed_edit(cx),
+                            _ => return,
+                        }
+                        cx.stop_propagation();
+                    }))
+                    .child(
+                        div().flex_1().min_w_0().child(
+                            Input::new(&self.editor.embed_input)
+                                .appearance(false)
+                                .small(),
+                        ),
+                    ),
+                );
+                continue;
+            }
+            let live_text = self
+                .live_block_text(&block.uid)
+                .unwrap_or_else(|| block.text.clone());
+            // Nested embeds are not expanded to keep cycles out of the outline.
+            let content = if crate::app::store::block_refs::block_embed_target(&live_text).is_some()
+            {
+                div()
+                    .text_sm()
+                    .text_color(muted_fg)
+                    .child(live_text.trim().to_string())
+                    .into_any_element()
+            } else {
+                let body = strip_block_properties(&live_text);
+                let row_id = format!("embed-{host_uid}-{}", block.uid);
+                self.render_inline_markdown_text(pane, &row_id, &body, window, cx)
+            };
+            rows = rows.child(
+                row.hover(move |s| s.bg(hover_bg))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.commit_embed_edit(cx);
+                        this.begin_embed_edit(edit.clone(), window, cx);
+                        cx.stop_propagation();
+                    }))
+                    .child(div().flex_1().min_w_0().py(tokens::SPACE_1).child(content)),
+            );
+        }
+        if hidden > 0 {
+            rows = rows.child(
+                div()
+                    .min_h(px(BLOCK_ROW_HEIGHT))
+                    .pt(tokens::SPACE_2)
+                    .text_xs()
+                    .text_color(muted_fg)
+                    .child(format!("{hidden} more blocks")),
+            );
+        }
+
+        Some(
+            div()
+                .p_2()
+                .rounded_md()
+                .border_1()
+                .border_color(border)
+                .bg(list_bg)
+                .child(header)
+                .child(rows)
+                .into_any_element(),
+        )
+    }
+
     fn render_plugin_block_preview(
         &mut self,
         pane: EditorPane,
@@ -2398,6 +2631,93 @@ impl AppStore {
         )
     }
 
+    fn render_backlink_entry(
+        &self,
+        entry: &BacklinkEntry,
+        id_prefix: &'static str,
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        let list_hover = cx.theme().list_hover;
+        let muted = cx.theme().muted_foreground;
+        let foreground = cx.theme().foreground;
+        let snippet = format_snippet(&entry.text, 90);
+        let page_uid = entry.page_uid.clone();
+        let block_uid = entry.block_uid.clone();
+        let open_block_uid = block_uid.clone();
+        let split_page_uid = entry.page_uid.clone();
+        let split_block_uid = block_uid.clone();
+        div()
+            .id(format!("{id_prefix}-{}", entry.block_uid))
+            .mx_3()
+            .px_2()
+            .py_2()
+            .mb_1()
+            .rounded_md()
+            .hover(move |s| s.bg(list_hover))
+            .child(
+                div()
+                    .flex()
+                    .items_start()
+                    .justify_between()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .flex_1()
+                            .min_w_0()
+                            .child(div().text_sm().text_color(foreground).child(snippet))
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(muted)
+                                    .child(entry.page_title.clone()),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_1()
+                            .opacity(0.0)
+                            .hover(move |s| s.opacity(1.0))
+                            .child(
+                                Button::new(format!("{id_prefix}-open-{}", block_uid))
+                                    .xsmall()
+                                    .ghost()
+                                    .icon(SandpaperIcon::ArrowRight)
+                                    .tooltip("Open")
+                                    .on_click(cx.listener(move |this, _event, window, cx| {
+                                        this.open_page_and_focus_block(
+                                            &page_uid,
+                                            &open_block_uid,
+                                            window,
+                                            cx,
+                                        );
+                                    })),
+                            )
+                            .child(
+                                Button::new(format!("{id_prefix}-split-{}", block_uid))
+                                    .xsmall()
+                                    .ghost()
+                                    .icon(SandpaperIcon::SplitVertical)
+                                    .tooltip("Open in split")
+                                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                                        this.open_secondary_pane_for_page(&split_page_uid, cx);
+                                        this.focus_block_by_uid_in_pane(
+                                            EditorPane::Secondary,
+                                            &split_block_uid,
+                                            None,
+                                            cx,
+                                        );
+                                    })),
+                            ),
+                    ),
+            )
+            .into_any_element()
+    }
+
     pub(super) fn render_backlinks_panel(
         &mut self,
         cx: &mut Context<Self>,
@@ -2417,10 +2737,11 @@ impl AppStore {
             .editor
             .editor
             .as_ref()
-            .and_then(|editor| editor.blocks.get(editor.active_ix))
+            .and_then(|editor| editor.blocks().get(editor.active_ix))
             .map(|block| block.text.clone())
             .unwrap_or_default();
 
+        let has_tagged_blocks = !self.editor.tagged_blocks.is_empty();
         let has_page_backlinks = !self.editor.backlinks.is_empty();
         let has_block_backlinks = !self.editor.block_backlinks.is_empty();
         let has_unlinked_refs = !self.editor.unlinked_references.is_empty();
@@ -2445,7 +2766,7 @@ impl AppStore {
             .min_h_0()
             .overflow_scroll();
 
-        if !has_page_backlinks && !has_block_backlinks && !has_unlinked_refs {
+        if !has_tagged_blocks && !has_page_backlinks && !has_block_backlinks && !has_unlinked_refs {
             use crate::ui::components::empty_state::EmptyState;
             body = body.child(
                 EmptyState::new(
@@ -2456,6 +2777,31 @@ impl AppStore {
             );
         }
 
+        if has_tagged_blocks {
+            let tag_label = self
+                .editor
+                .active_page
+                .as_ref()
+                .map(|page| format_tag(&page.title.trim().to_lowercase()))
+                .unwrap_or_default();
+            body = body.child(
+                div()
+                    .px_3()
+                    .pt_3()
+                    .pb_1()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::MEDIUM)
+                    .text_color(muted)
+                    .child(format!("Tagged {tag_label}")),
+            );
+            body = body.children(
+                self.editor
+                    .tagged_blocks
+                    .iter()
+                    .map(|entry| self.render_backlink_entry(entry, "backlinks-tag", cx)),
+            );
+        }
+
         if has_page_backlinks {
             body = body.child(
                 div()
@@ -2468,90 +2814,12 @@ impl AppStore {
 
                     .child("Page backlinks"),
             );
-            body = body.children(self.editor.backlinks.iter().map(|entry| {
-                let snippet = format_snippet(&entry.text, 90);
-                let page_uid = entry.page_uid.clone();
-                let block_uid = entry.block_uid.clone();
-                let open_block_uid = block_uid.clone();
-                let split_page_uid = entry.page_uid.clone();
-                let split_block_uid = block_uid.clone();
-                div()
-                    .id(format!("backlinks-page-{}", entry.block_uid))
-                    .mx_3()
-                    .px_2()
-                    .py_2()
-                    .mb_1()
-                    .rounded_md()
-                    .hover(move |s| s.bg(list_hover))
-                    .child(
-                        div()
-                            .flex()
-                            .items_start()
-                            .justify_between()
-                            .gap_2()
-                            .child(
-                                div()
-                                    .flex()
-                                    .flex_col()
-                                    .gap_1()
-                                    .flex_1()
-                                    .min_w_0()
-                                    .child(div().text_sm().text_color(foreground).child(snippet))
-                                    .child(
-                                        div()
-                                            .text_xs()
-                                            .text_color(muted)
-                                            .child(entry.page_title.clone()),
-                                    ),
-                            )
-                            .child(
-                                div()
-                                    .flex()
-                                    .items_center()
-                                    .gap_1()
-                                    .opacity(0.0)
-                                    .hover(move |s| s.opacity(1.0))
-                                    .child(
-                                        Button::new(format!("backlinks-open-{}", block_uid))
-                                            .xsmall()
-                                            .ghost()
-                                            .icon(SandpaperIcon::ArrowRight)
-                                            .tooltip("Open")
-                                            .on_click(cx.listener(
-                                                move |this, _event, window, cx| {
-                                                    this.open_page_and_focus_block(
-                                                        &page_uid,
-                                                        &open_block_uid,
-                                                        window,
-                                                        cx,
-                                                    );
-                                                },
-                                            )),
-                                    )
-                                    .child(
-                                        Button::new(format!("backlinks-split-{}", block_uid))
-                                            .xsmall()
-                                            .ghost()
-                                            .icon(SandpaperIcon::SplitVertical)
-                                            .tooltip("Open in split")
-                                            .on_click(cx.listener(
-                                                move |this, _event, _window, cx| {
-                                                    this.open_secondary_pane_for_page(
-                                                        &split_page_uid,
-                                                        cx,
-                                                    );
-                                                    this.focus_block_by_uid_in_pane(
-                                                        EditorPane::Secondary,
-                                                        &split_block_uid,
-                                                        None,
-                                                        cx,
-                                                    );
-                                                },
-                                            )),
-                                    ),
-                            ),
-                    )
-            }));
+            body = body.children(
+                self.editor
+                    .backlinks
+                    .iter()
+                    .map(|entry| self.render_backlink_entry(entry, "backlinks-page", cx)),
+            );
         }
 
         if has_block_backlinks {
@@ -2583,90 +2851,12 @@ impl AppStore {
                             .child(format!("Linked to {block_label}")),
                     ),
             );
-            body = body.children(self.editor.block_backlinks.iter().map(|entry| {
-                let snippet = format_snippet(&entry.text, 90);
-                let page_uid = entry.page_uid.clone();
-                let block_uid = entry.block_uid.clone();
-                let open_block_uid = block_uid.clone();
-                let split_page_uid = entry.page_uid.clone();
-                let split_block_uid = block_uid.clone();
-                div()
-                    .id(format!("backlinks-block-{}", entry.block_uid))
-                    .mx_3()
-                    .px_2()
-                    .py_2()
-                    .mb_1()
-                    .rounded_md()
-                    .hover(move |s| s.bg(list_hover))
-                    .child(
-                        div()
-                            .flex()
-                            .items_start()
-                            .justify_between()
-                            .gap_2()
-                            .child(
-                                div()
-                                    .flex()
-                                    .flex_col()
-                                    .gap_1()
-                                    .flex_1()
-                                    .min_w_0()
-                                    .child(div().text_sm().text_color(foreground).child(snippet))
-                                    .child(
-                                        div()
-                                            .text_xs()
-                                            .text_color(muted)
-                                            .child(entry.page_title.clone()),
-                                    ),
-                            )
-                            .child(
-                                div()
-                                    .flex()
-                                    .items_center()
-                                    .gap_1()
-                                    .opacity(0.0)
-                                    .hover(move |s| s.opacity(1.0))
-                                    .child(
-                                        Button::new(format!("backlinks-block-open-{}", block_uid))
-                                            .xsmall()
-                                            .ghost()
-                                            .icon(SandpaperIcon::ArrowRight)
-                                            .tooltip("Open")
-                                            .on_click(cx.listener(
-                                                move |this, _event, window, cx| {
-                                                    this.open_page_and_focus_block(
-                                                        &page_uid,
-                                                        &open_block_uid,
-                                                        window,
-                                                        cx,
-                                                    );
-                                                },
-                                            )),
-                                    )
-                                    .child(
-                                        Button::new(format!("backlinks-block-split-{}", block_uid))
-                                            .xsmall()
-                                            .ghost()
-                                            .icon(SandpaperIcon::SplitVertical)
-                                            .tooltip("Open in split")
-                                            .on_click(cx.listener(
-                                                move |this, _event, _window, cx| {
-                                                    this.open_secondary_pane_for_page(
-                                                        &split_page_uid,
-                                                        cx,
-                                                    );
-                                                    this.focus_block_by_uid_in_pane(
-                                                        EditorPane::Secondary,
-                                                        &split_block_uid,
-                                                        None,
-                                                        cx,
-                                                    );
-                                                },
-                                            )),
-                                    ),
-                            ),
-                    )
-            }));
+            body = body.children(
+                self.editor
+                    .block_backlinks
+                    .iter()
+                    .map(|entry| self.render_backlink_entry(entry, "backlinks-block", cx)),
+            );
         }
 
         if has_unlinked_refs {
@@ -2989,16 +3179,16 @@ impl AppStore {
         let Some(actual_ix) = list_state.visible_to_actual.get(visible_ix).copied() else {
             return div().into_any_element();
         };
-        if actual_ix >= editor.blocks.len() {
+        if actual_ix >= editor.blocks().len() {
             return div().into_any_element();
         }
         let next_visible_actual_ix = list_state
             .visible_to_actual
             .get(visible_ix.saturating_add(1))
             .copied()
-            .unwrap_or(editor.blocks.len());
+            .unwrap_or(editor.blocks().len());
 
-        let block = editor.blocks[actual_ix].clone();
+        let block = editor.blocks()[actual_ix].clone();
         let is_active = editor.active_ix == actual_ix;
         let selection = self.selection_for_pane(pane);
         let is_selected = selection.is_some_and(|selection| selection.contains(visible_ix));
@@ -3034,8 +3224,8 @@ impl AppStore {
             .filter(|target| target.pane == pane)
             .map(|target| target.insert_before_ix);
         let is_drop_target_before_row = drop_insert_before_ix == Some(actual_ix);
-        let is_drop_target_end = drop_insert_before_ix == Some(editor.blocks.len())
-            && next_visible_actual_ix == editor.blocks.len();
+        let is_drop_target_end = drop_insert_before_ix == Some(editor.blocks().len())
+            && next_visible_actual_ix == editor.blocks().len();
         let handle_visible = is_drag_source
             || is_active
             || is_selected
@@ -3080,6 +3270,15 @@ impl AppStore {
         } else if matches!(block.block_type, BlockType::Image) {
             div().into_any_element()
         } else {
+            let mut block_properties = parse_block_properties(&block.text);
+            let body_text = if block_properties.is_empty() {
+                block.text.clone()
+            } else {
+                strip_block_properties(&block.text)
+            };
+            if matches!(block.block_type, BlockType::Todo) {
+                block_properties.retain(|(key, _)| !is_task_property(key));
+            }
             let display_text = match block.block_type {
                 BlockType::Heading1
                 | BlockType::Heading2
@@ -3087,15 +3286,65 @@ impl AppStore {
                 | BlockType::Quote
                 | BlockType::Todo
                 | BlockType::Divider => crate::app::store::helpers::clean_text_for_block_type(
-                    &block.text,
+                    &body_text,
                     block.block_type,
                 ),
-                _ => block.text.clone(),
+                _ => body_text,
             };
-            self.render_inline_markdown_text(pane, &block.uid, &display_text, window, cx)
+            let embed_target = crate::app::store::block_refs::block_embed_target(&display_text);
+            let text = if let Some(target) = embed_target {
+                let label = match target {
+                    sandpaper_core::links::EmbedTarget::Block(_) => "Block embed",
+                    sandpaper_core::links::EmbedTarget::Page(_) => "Page embed",
+                };
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(label)
+                    .into_any_element()
+            } else {
+                self.render_inline_markdown_text(pane, &block.uid, &display_text, window, cx)
+            };
+            if block_properties.is_empty() {
+                text
+            } else {
+                div()
+                    .flex()
+                    .flex_col()
+                    .child(text)
+                    .child(self.render_block_properties(&block.uid, &block_properties, cx))
+                    .into_any_element()
+            }
         };
 
         let mut content_container = div().flex_1().min_w_0().relative().child(content);
+        let ref_count = self.block_ref_count(&block.uid);
+        if ref_count > 0 && !show_input {
+            let (badge_bg, badge_fg) = {
+                let theme = cx.theme();
+                (theme.secondary, theme.muted_foreground)
+            };
+            let block_uid = block.uid.clone();
+            content_container = content_container.child(
+                div()
+                    .id(format!("block-ref-count-{}", block.uid))
+                    .absolute()
+                    .top(tokens::SPACE_2)
+                    .right(px(0.0))
+                    .px_1()
+                    .rounded_sm()
+                    .bg(badge_bg)
+                    .text_xs()
+                    .text_color(badge_fg)
+                    .cursor_pointer()
+                    .child(ref_count.to_string())
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.focus_block_by_uid_in_pane(pane, &block_uid, Some(window), cx);
+                        this.set_context_panel_tab(WorkspacePanel::Backlinks, cx);
+                        cx.stop_propagation();
+                    })),
+            );
+        }
         if show_input
             && self.editor.slash_menu.open
             && self.editor.slash_menu.pane == pane
@@ -3395,6 +3644,7 @@ impl AppStore {
             .render_plugin_block_preview(pane, &block.uid, &block.text, cx)
             .or_else(|| self.render_image_preview(block.block_type, &block.text, cx))
             .or_else(|| self.render_code_preview(&block.uid, &block.text, cx))
+            .or_else(|| self.render_block_embed_preview(pane, &block.uid, &block.text, window, cx))
             .or_else(|| self.render_diagram_preview(&block.uid, &block.text, cx));
         if let Some(preview) = preview {
             container = container.child(
@@ -3426,6 +3676,45 @@ impl AppStore {
         container.into_any_element()
     }
 
+    fn render_block_properties(
+        &self,
+        block_uid: &str,
+        properties: &[(String, String)],
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        let muted = cx.theme().muted_foreground;
+        let fg = cx.theme().foreground;
+        let mut rows = div().flex().flex_col().pl_2();
+        for (key, value) in properties {
+            rows = rows.child(
+                div()
+                    .id(SharedString::from(format!("block-prop-{block_uid}-{key}")))
+                    .h(px(COMPACT_ROW_HEIGHT))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .w(px(100.0))
+                            .text_xs()
+                            .text_color(muted)
+                            .overflow_hidden()
+                            .child(key.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .text_xs()
+                            .text_color(fg)
+                            .overflow_hidden()
+                            .child(value.clone()),
+                    ),
+            );
+        }
+        rows.into_any_element()
+    }
+
     fn render_block_actions_for_pane(
         &mut self,
         pane: EditorPane,
@@ -3525,8 +3814,14 @@ impl AppStore {
                 let block_uid = item.block_uid.clone();
                 let page_uid = item.page_uid.clone();
                 let item_id = item.id;
-                let snippet = format_snippet(&item.text, 96);
+                let snippet = format_snippet(
+                    item.flashcard
+                        .as_ref()
+                        .map_or(item.text.as_str(), |flashcard| flashcard.front.as_str()),
+                    96,
+                );
                 let page_title = item.page_title.clone();
+                let card = item.card;
                 let due_label = chrono::Local
                     .timestamp_millis_opt(item.due_at)
                     .single()
@@ -3575,30 +3870,26 @@ impl AppStore {
                                                     },
                                                 )),
                                         )
-                                        .child(
-                                            Button::new(format!("review-snooze-day-{item_id}"))
-                                                .icon(SandpaperIcon::Subtract)
-                                                .with_size(tokens::FONT_XL)
-                                                .ghost()
-                                                .tooltip("Snooze 1 day (S)")
-                                                .on_click(cx.listener(
-                                                    move |this, _event, _window, cx| {
-                                                        this.review_snooze_day(item_id, cx);
-                                                    },
-                                                )),
-                                        )
-                                        .child(
-                                            Button::new(format!("review-snooze-week-{item_id}"))
-                                                .label("1w")
+                                        .children(ReviewGrade::ALL.into_iter().enumerate().map(
+                                            |(key, grade)| {
+                                                let interval = format_review_interval(
+                                                    card.review(grade).interval_days,
+                                                );
+                                                Button::new(format!(
+                                                    "review-{}-{item_id}",
+                                                    grade.as_str()
+                                                ))
+                                                .label(interval)
                                                 .xsmall()
                                                 .ghost()
-                                                .tooltip("Snooze 1 week (W)")
+                                                .tooltip(format!("{} ({})", grade.label(), key + 1))
                                                 .on_click(cx.listener(
                                                     move |this, _event, _window, cx| {
-                                                        this.review_snooze_week(item_id, cx);
+                                                        this.review_grade(item_id, grade, cx);
                                                     },
-                                                )),
-                                        ),
+                                                ))
+                                            },
+                                        )),
                                 ),
                         )
                         .on_click(cx.listener(move |this, _event, window, cx| {
@@ -3657,15 +3948,15 @@ impl AppStore {
                             cx.stop_propagation();
                         }
                     }
-                    "s" => {
-                        if let Some(item) = this.editor.review_items.get(selected) {
-                            this.review_snooze_day(item.id, cx);
-                            cx.stop_propagation();
-                        }
-                    }
-                    "w" => {
-                        if let Some(item) = this.editor.review_items.get(selected) {
-                            this.review_snooze_week(item.id, cx);
+                    key @ ("1" | "2" | "3" | "4") => {
+                        let grade = key
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|number| ReviewGrade::ALL.get(number - 1).copied());
+                        if let (Some(item), Some(grade)) =
+                            (this.editor.review_items.get(selected), grade)
+                        {
+                            this.review_grade(item.id, grade, cx);
                             cx.stop_propagation();
                         }
                     }
@@ -3680,9 +3971,90 @@ impl AppStore {
                     .text_xs()
                     .text_color(foreground_faint)
                     .child(format!(
-                        "{count} due  ·  j/k navigate  ·  d done  ·  s snooze"
+                        "{count} due  ·  j/k navigate  ·  1–4 again/hard/good/easy  ·  d done"
                     )),
             )
+            .child(self.render_review_stats(cx))
             .child(div().px_3().pb_3().flex_1().min_h_0().child(list))
     }
+
+    /// Recall over the last month and how many items fall due each day this week.
+    fn render_review_stats(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let foreground = cx.theme().foreground;
+        let accent = cx.theme().accent;
+        let stats = &self.editor.review_stats;
+        let mut summary = vec![format!("{} pending", stats.pending)];
+        if let Some(retention) = stats.retention {
+            summary.push(format!(
+                "{:.0}% recalled of {} reviews",
+                retention * 100.0,
+                stats.reviewed
+            ));
+        }
+        if let Some(ease) = stats.average_ease {
+            summary.push(format!("ease {ease:.2}"));
+        }
+
+        let peak = self
+            .editor
+            .review_forecast
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let today = Local::now().date_naive();
+        let days = self
+            .editor
+            .review_forecast
+            .iter()
+            .enumerate()
+            .map(|(offset, count)| {
+                let label = if offset == 0 {
+                    "Today".to_string()
+                } else {
+                    (today + chrono::Days::new(offset as u64))
+                        .format("%a")
+                        .to_string()
+                };
+                let fill = 4.0 + 28.0 * (*count as f32 / peak as f32);
+                div()
+                    .flex_1()
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(foreground)
+                            .child(count.to_string()),
+                    )
+                    .child(
+                        div()
+                            .h(px(32.0))
+                            .w_full()
+                            .flex()
+                            .flex_col()
+                            .justify_end()
+                            .child(div().h(px(fill)).rounded_sm().bg(accent)),
+                    )
+                    .child(div().text_xs().text_color(muted).child(label))
+            });
+
+        div()
+            .px_3()
+            .pb(tokens::SPACE_3)
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(muted)
+                    .child(summary.join("  ·  ")),
+            )
+            .child(div().flex().items_end().gap_1().children(days))
+    }
 }
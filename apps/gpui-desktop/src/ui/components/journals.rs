@@ -0,0 +1,377 @@
+use crate::app::prelude::*;
+use crate::app::store::journals::JournalDay;
+use crate::app::store::*;
+use crate::ui::sandpaper_theme::SandpaperTheme;
+use crate::ui::tokens;
+use chrono::{Datelike, NaiveDate};
+use gpui_component::IconName;
+use sandpaper_core::journals::month_grid;
+
+const CALENDAR_WIDTH: f32 = 280.0;
+const CALENDAR_CELL: f32 = 32.0;
+const WEEKDAY_LABELS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+impl AppStore {
+    pub(super) fn render_journals(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        let (fg, muted_fg) = {
+            let theme = cx.theme();
+            (theme.foreground, theme.muted_foreground)
+        };
+        let today = Local::now().date_naive();
+        let following_today = self.editor.journals.anchor.is_none();
+
+        let mut feed = div()
+            .w_full()
+            .max_w(px(720.0))
+            .mx_auto()
+            .flex()
+            .flex_col()
+            .gap_4();
+
+        feed = feed.child(
+            div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(
+                    div()
+                        .text_size(tokens::FONT_LG)
+                        .font_weight(gpui::FontWeight::MEDIUM)
+                        .text_color(fg)
+                        .child("Journals"),
+                )
+                .when(!following_today, |el| {
+                    el.child(
+                        Button::new("journals-today")
+                            .xsmall()
+                            .ghost()
+                            .label("Back to today")
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.jump_to_journal_date(None, cx);
+                            })),
+                    )
+                }),
+        );
+
+        let days = self.editor.journals.days.clone();
+        if days.is_empty() || (following_today && days[0].date != today) {
+            feed = feed.child(
+                div()
+                    .id("journals-start-today")
+                    .px_3()
+                    .py_2()
+                    .rounded_md()
+                    .text_size(tokens::FONT_SM)
+                    .text_color(muted_fg)
+                    .cursor_pointer()
+                    .hover(|s| s.text_color(fg))
+                    .child(format!(
+                        "Start today's page · {}",
+                        self.editor.journals.settings.title(today)
+                    ))
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.open_journal_day(today, cx);
+                    })),
+            );
+        }
+
+        for day in &days {
+            feed = feed.child(self.render_journal_day(day, today, window, cx));
+        }
+
+        if self.editor.journals.has_more {
+            feed = feed.child(
+                div().flex().justify_center().child(
+                    Button::new("journals-load-older")
+                        .small()
+                        .ghost()
+                        .label("Load older days")
+                        .on_click(cx.listener(|this, _event, _window, cx| {
+                            this.load_older_journals(cx);
+                        })),
+                ),
+            );
+        }
+
+        div()
+            .flex()
+            .flex_1()
+            .min_w_0()
+            .h_full()
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .h_full()
+                    .overflow_y_scrollbar()
+                    .p_6()
+                    .child(feed),
+            )
+            .child(self.render_journal_calendar(today, cx))
+            .into_any_element()
+    }
+
+    fn render_journal_day(
+        &mut self,
+        day: &JournalDay,
+        today: NaiveDate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        let (fg, muted_fg, border, hover_bg, accent) = {
+            let theme = cx.theme();
+            (
+                theme.foreground,
+                theme.muted_foreground,
+                theme.border,
+                theme.list_hover,
+                theme.accent,
+            )
+        };
+        let date = day.date;
+        let relative = match (date - today).num_days() {
+            0 => Some("Today"),
+            -1 => Some("Yesterday"),
+            1 => Some("Tomorrow"),
+            _ => None,
+        };
+        let weekday: SharedString = date.format("%A").to_string().into();
+        let title: SharedString = day.page.title.clone().into();
+
+        let header =
+            div()
+                .id(SharedString::from(format!("journal-title-{date}")))
+                .flex()
+                .items_center()
+                .gap_2()
+                .cursor_pointer()
+                .child(
+                    div()
+                        .text_size(tokens::FONT_XL)
+                        .font_weight(gpui::FontWeight::SEMIBOLD)
+                        .text_color(fg)
+                        .hover(move |s| s.text_color(accent))
+                        .child(title),
+                )
+                .child(div().text_size(tokens::FONT_SM).text_color(muted_fg).child(
+                    match relative {
+                        Some(relative) => format!("{relative} · {weekday}"),
+                        None => weekday.to_string(),
+                    },
+                ))
+                .on_click(cx.listener(move |this, _event, _window, cx| {
+                    this.open_journal_day(date, cx);
+                }));
+
+        let mut rows = div().flex().flex_col();
+        let visible = day
+            .blocks
+            .iter()
+            .filter(|block| !strip_block_properties(&block.text).trim().is_empty())
+            .collect::<Vec<_>>();
+        if visible.is_empty() {
+            rows = rows.child(
+                div()
+                    .id(SharedString::from(format!("journal-empty-{date}")))
+                    .min_h(px(BLOCK_ROW_HEIGHT))
+                    .flex()
+                    .items_center()
+                    .text_size(tokens::FONT_SM)
+                    .text_color(muted_fg)
+                    .cursor_pointer()
+                    .child("Nothing written yet. Click to start.")
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.open_journal_day(date, cx);
+                    })),
+            );
+        }
+        for block in visible {
+            let page_uid = day.page.uid.clone();
+            let block_uid = block.uid.clone();
+            let body = strip_block_properties(&block.text);
+            let row_id = format!("journal-{}", block.uid);
+            let content =
+                self.render_inline_markdown_text(EditorPane::Primary, &row_id, &body, window, cx);
+            rows = rows.child(
+                div()
+                    .id(SharedString::from(row_id))
+                    .min_h(px(BLOCK_ROW_HEIGHT))
+                    .pl(px(block.indent.max(0) as f32 * 18.0))
+                    .flex()
+                    .items_start()
+                    .gap_2()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(move |s| s.bg(hover_bg))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.open_page_and_focus_block(&page_uid, &block_uid, window, cx);
+                    }))
+                    .child(
+                        div()
+                            .pt(tokens::SPACE_2)
+                            .text_sm()
+                            .text_color(muted_fg)
+                            .child("•"),
+                    )
+                    .child(div().flex_1().min_w_0().pt(tokens::SPACE_2).child(content)),
+            );
+        }
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .pb_4()
+            .border_b_1()
+            .border_color(border)
+            .child(header)
+            .child(rows)
+            .into_any_element()
+    }
+
+    fn render_journal_calendar(
+        &mut self,
+        today: NaiveDate,
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        let theme = cx.theme();
+        let semantic = cx.global::<SandpaperTheme>().colors(cx);
+        let accent_subtle = semantic.accent_subtle;
+        let fg = theme.foreground;
+        let muted_fg = theme.muted_foreground;
+        let border = theme.border;
+        let sidebar_bg = theme.sidebar;
+        let hover_bg = theme.list_hover;
+        let accent = theme.accent;
+
+        let journals = &self.editor.journals;
+        let month = journals.calendar_month(today);
+        let selected = journals.anchor.unwrap_or(today);
+        let month_label: SharedString = month.format("%B %Y").to_string().into();
+
+        let header = div()
+            .flex()
+            .items_center()
+            .justify_between()
+            .child(
+                Button::new("journal-calendar-prev")
+                    .xsmall()
+                    .ghost()
+                    .icon(SandpaperIcon::ArrowLeft)
+                    .tooltip("Previous month")
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.shift_journal_calendar(-1, cx);
+                    })),
+            )
+            .child(
+                div()
+                    .text_size(tokens::FONT_SM)
+                    .font_weight(gpui::FontWeight::MEDIUM)
+                    .text_color(fg)
+                    .child(month_label),
+            )
+            .child(
+                Button::new("journal-calendar-next")
+                    .xsmall()
+                    .ghost()
+                    .icon(SandpaperIcon::ArrowRight)
+                    .tooltip("Next month")
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.shift_journal_calendar(1, cx);
+                    })),
+            );
+
+        let mut weekdays = div().flex();
+        for label in WEEKDAY_LABELS {
+            weekdays = weekdays.child(
+                div()
+                    .w(px(CALENDAR_CELL))
+                    .flex()
+                    .justify_center()
+                    .text_size(tokens::FONT_XS)
+                    .text_color(muted_fg)
+                    .child(label),
+            );
+        }
+
+        let mut grid = div().flex().flex_col().gap_1();
+        for week in month_grid(month).chunks(7) {
+            let mut row = div().flex();
+            for cell in week {
+                let Some(date) = *cell else {
+                    row = row.child(div().w(px(CALENDAR_CELL)).h(px(CALENDAR_CELL)));
+                    continue;
+                };
+                let has_page = journals.dates.contains(&date);
+                let is_selected = date == selected;
+                let mut day_cell = div()
+                    .id(SharedString::from(format!("journal-calendar-{date}")))
+                    .w(px(CALENDAR_CELL))
+                    .h(px(CALENDAR_CELL))
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .justify_center()
+                    .rounded_md()
+                    .text_size(tokens::FONT_SM)
+                    .text_color(if has_page { fg } else { muted_fg })
+                    .cursor_pointer()
+                    .hover(move |s| s.bg(hover_bg))
+                    .child(date.day().to_string())
+                    .child(
+                        div()
+                            .size(px(4.0))
+                            .rounded_full()
+                            .when(has_page, |el| el.bg(accent)),
+                    )
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        if has_page {
+                            this.jump_to_journal_date(Some(date), cx);
+                        } else {
+                            this.open_journal_day(date, cx);
+                        }
+                    }));
+                if is_selected {
+                    day_cell = day_cell.bg(accent_subtle).text_color(accent);
+                }
+                if date == today {
+                    day_cell = day_cell.border_1().border_color(accent);
+                }
+                row = row.child(day_cell);
+            }
+            grid = grid.child(row);
+        }
+
+        div()
+            .w(px(CALENDAR_WIDTH))
+            .flex_shrink_0()
+            .h_full()
+            .border_l_1()
+            .border_color(border)
+            .bg(sidebar_bg)
+            .p_4()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .child(header)
+            .child(div().flex().flex_col().gap_1().child(weekdays).child(grid))
+            .child(div().text_size(tokens::FONT_XS).text_color(muted_fg).child(
+                "Dotted days have a page. Pick one to jump there, or any other day to start it.",
+            ))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .text_size(tokens::FONT_XS)
+                    .text_color(muted_fg)
+                    .child(Icon::new(IconName::Calendar).size_3p5())
+                    .child(format!("{} daily pages", journals.dates.len())),
+            )
+            .into_any_element()
+    }
+}
@@ -53,6 +53,7 @@ impl AppStore {
             (Mode::Capture, "Capture"),
             (Mode::Editor, "Edit"),
             (Mode::Review, "Review"),
+            (Mode::Journals, "Journals"),
         ] {
             let mut btn = Button::new(format!("mode-{label}")).label(label);
             btn = if Self::topbar_mode_switch_uses_small_buttons() {
@@ -269,7 +270,9 @@ impl AppStore {
             WorkspacePanel::Review => 0,
             WorkspacePanel::Backlinks => 1,
             WorkspacePanel::Connections => 2,
-            WorkspacePanel::Plugins => 3,
+            WorkspacePanel::History => 3,
+            WorkspacePanel::Agenda => 4,
+            WorkspacePanel::Plugins => 5,
         };
         let tabs = TabBar::new("context-panel-tabs")
             .underline()
@@ -280,6 +283,8 @@ impl AppStore {
                     0 => WorkspacePanel::Review,
                     1 => WorkspacePanel::Backlinks,
                     2 => WorkspacePanel::Connections,
+                    3 => WorkspacePanel::History,
+                    4 => WorkspacePanel::Agenda,
                     _ => WorkspacePanel::Plugins,
                 };
                 this.set_context_panel_tab(tab, cx);
@@ -287,6 +292,8 @@ impl AppStore {
             .child(Tab::new().label("Review"))
             .child(Tab::new().label("Backlinks"))
             .child(Tab::new().label("Connections"))
+            .child(Tab::new().label("History"))
+            .child(Tab::new().label("Agenda"))
             .child(Tab::new().label("Plugins"));
 
         div()
@@ -488,6 +495,311 @@ impl AppStore {
             .into_any_element()
     }
 
+    fn render_history_panel(&mut self, cx: &mut Context<Self>) -> Option<gpui::AnyElement> {
+        use crate::app::store::helpers::format_snippet;
+        use sandpaper_core::revisions::BlockDiffKind;
+
+        self.editor.active_page.as_ref()?;
+        let theme = cx.theme();
+        let border = theme.border;
+        let sidebar_bg = theme.sidebar;
+        let fg = theme.foreground;
+        let muted_fg = theme.muted_foreground;
+        let hover_bg = theme.list_hover;
+        let active_bg = theme.list_active;
+        let added = theme.accent;
+        let removed = theme.danger;
+        let changed = theme.warning;
+        let header = self.render_context_panel_header(cx);
+
+        let revisions = self.editor.page_history.revisions.clone();
+        let preview = self.editor.page_history.preview.clone();
+        let selected_id = preview.as_ref().map(|preview| preview.revision.id);
+        let format_time = |millis: i64| {
+            chrono::Local
+                .timestamp_millis_opt(millis)
+                .single()
+                .map(|dt| dt.format("%b %d, %H:%M").to_string())
+                .unwrap_or_default()
+        };
+
+        let mut body = div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_4()
+            .flex_1()
+            .min_h_0()
+            .overflow_y_scrollbar();
+
+        if revisions.is_empty() {
+            use crate::ui::components::empty_state::EmptyState;
+            body = body.child(
+                EmptyState::new(
+                    "No history yet",
+                    "Versions are saved as you edit this page.",
+                )
+                .icon(IconName::File),
+            );
+        }
+
+        for revision in &revisions {
+            let revision_id = revision.id;
+            let is_selected = selected_id == Some(revision_id);
+            let label: SharedString = format_time(revision.created_at).into();
+            let count: SharedString = format!("{} blocks", revision.block_count).into();
+            body = body.child(
+                div()
+                    .id(SharedString::from(format!(
+                        "history-revision-{revision_id}"
+                    )))
+                    .rounded_md()
+                    .px_3()
+                    .py(tokens::SPACE_2)
+                    .cursor_pointer()
+                    .when(is_selected, |s| s.bg(active_bg))
+                    .hover(move |s| s.bg(hover_bg))
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.preview_page_revision(revision_id, cx);
+                    }))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        div()
+                            .text_size(tokens::FONT_BASE)
+                            .text_color(fg)
+                            .child(label),
+                    )
+                    .child(
+                        div()
+                            .text_size(tokens::FONT_XS)
+                            .text_color(muted_fg)
+                            .child(count),
+                    ),
+            );
+        }
+
+        if let Some(preview) = preview {
+            body = body.child(
+                div()
+                    .mt_3()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        div()
+                            .text_size(tokens::FONT_XS)
+                            .font_weight(gpui::FontWeight::MEDIUM)
+                            .text_color(muted_fg)
+                            .child("Changes since this version"),
+                    )
+                    .child(
+                        Button::new("history-restore-revision")
+                            .xsmall()
+                            .label("Restore version")
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.restore_previewed_revision(cx);
+                            })),
+                    ),
+            );
+            for row in preview.diff {
+                let (marker, color, text) = match row.kind {
+                    BlockDiffKind::Unchanged => (" ", muted_fg, row.after.map(|block| block.text)),
+                    BlockDiffKind::Added => ("+", added, row.after.map(|block| block.text)),
+                    BlockDiffKind::Removed => ("−", removed, row.before.map(|block| block.text)),
+                    BlockDiffKind::Changed => ("~", changed, row.before.map(|block| block.text)),
+                };
+                let text: SharedString = format_snippet(&text.unwrap_or_default(), 120).into();
+                let restorable =
+                    matches!(row.kind, BlockDiffKind::Removed | BlockDiffKind::Changed);
+                let uid = row.uid.clone();
+                let mut line = div()
+                    .flex()
+                    .items_start()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .text_size(tokens::FONT_SM)
+                    .child(div().w(px(10.0)).text_color(color).child(marker))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .text_color(if restorable { fg } else { muted_fg })
+                            .child(text),
+                    );
+                if restorable {
+                    line = line.child(
+                        Button::new(SharedString::from(format!("history-restore-block-{uid}")))
+                            .xsmall()
+                            .ghost()
+                            .label("Restore")
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.restore_revision_block(&uid, cx);
+                            })),
+                    );
+                }
+                body = body.child(line);
+            }
+        }
+
+        Some(
+            div()
+                .w(tokens::CONTEXT_PANEL_WIDTH)
+                .h_full()
+                .border_l_1()
+                .border_color(border)
+                .bg(sidebar_bg)
+                .flex()
+                .flex_col()
+                .min_h_0()
+                .child(header)
+                .child(body)
+                .into_any_element(),
+        )
+    }
+
+    fn render_agenda_panel(&mut self, cx: &mut Context<Self>) -> gpui::AnyElement {
+        let theme = cx.theme();
+        let border = theme.border;
+        let sidebar_bg = theme.sidebar;
+        let fg = theme.foreground;
+        let muted_fg = theme.muted_foreground;
+        let hover_bg = theme.list_hover;
+        let danger = theme.danger;
+        let accent = theme.accent;
+        let header = self.render_context_panel_header(cx);
+
+        let agenda = self.editor.agenda.agenda.clone();
+        let open_count = self.editor.agenda.open_count;
+
+        let mut body = div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_4()
+            .flex_1()
+            .min_h_0()
+            .overflow_y_scrollbar();
+
+        if agenda.is_empty() {
+            use crate::ui::components::empty_state::EmptyState;
+            let message = if open_count == 0 {
+                "Todo blocks with a scheduled or deadline date show up here."
+            } else {
+                "None of your open tasks are scheduled for the next two weeks."
+            };
+            body = body.child(EmptyState::new("Nothing due", message).icon(IconName::Inbox));
+        }
+
+        let sections = [
+            ("Overdue", agenda.overdue, danger),
+            ("Today", agenda.today, accent),
+            ("Upcoming", agenda.upcoming, muted_fg),
+        ];
+        for (title, records, date_color) in sections {
+            if records.is_empty() {
+                continue;
+            }
+            body = body.child(
+                div()
+                    .mt_2()
+                    .text_size(tokens::FONT_XS)
+                    .font_weight(gpui::FontWeight::MEDIUM)
+                    .text_color(muted_fg)
+                    .child(format!("{title} · {}", records.len())),
+            );
+            for record in records {
+                let task = &record.task;
+                let date_label: SharedString = task
+                    .agenda_date()
+                    .map(|date| date.format("%b %d").to_string())
+                    .unwrap_or_default()
+                    .into();
+                let state_label: SharedString = task.state.label().into();
+                let title: SharedString = if task.title.is_empty() {
+                    "Untitled task".into()
+                } else {
+                    task.title.clone().into()
+                };
+                let page_title: SharedString = record.page_title.clone().into();
+                let done_record = record.clone();
+                let mark_done = cx.listener(move |this, _event: &gpui::ClickEvent, _window, cx| {
+                    this.set_agenda_task_state(&done_record, TaskState::Done, cx);
+                    cx.stop_propagation();
+                });
+                let row_id = SharedString::from(format!("agenda-task-{}", record.block_uid));
+                let page_uid = record.page_uid.clone();
+                let block_uid = record.block_uid.clone();
+                body = body.child(
+                    div()
+                        .id(row_id)
+                        .flex()
+                        .items_start()
+                        .gap_2()
+                        .px_2()
+                        .py(tokens::SPACE_2)
+                        .rounded_md()
+                        .cursor_pointer()
+                        .hover(move |s| s.bg(hover_bg))
+                        .on_click(cx.listener(move |this, _event, window, cx| {
+                            this.open_page_and_focus_block(&page_uid, &block_uid, window, cx);
+                        }))
+                        .child(
+                            Button::new(SharedString::from(format!(
+                                "agenda-done-{}",
+                                record.block_uid
+                            )))
+                            .xsmall()
+                            .ghost()
+                            .icon(SandpaperIcon::Checkmark)
+                            .tooltip("Mark done")
+                            .on_click(mark_done),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .flex_1()
+                                .min_w_0()
+                                .child(div().text_size(tokens::FONT_SM).text_color(fg).child(title))
+                                .child(
+                                    div()
+                                        .flex()
+                                        .items_center()
+                                        .gap_2()
+                                        .text_size(tokens::FONT_XS)
+                                        .text_color(muted_fg)
+                                        .child(state_label)
+                                        .child(page_title),
+                                ),
+                        )
+                        .child(
+                            div()
+                                .flex_shrink_0()
+                                .text_size(tokens::FONT_XS)
+                                .text_color(date_color)
+                                .child(date_label),
+                        ),
+                );
+            }
+        }
+
+        div()
+            .w(tokens::CONTEXT_PANEL_WIDTH)
+            .h_full()
+            .border_l_1()
+            .border_color(border)
+            .bg(sidebar_bg)
+            .flex()
+            .flex_col()
+            .min_h_0()
+            .child(header)
+            .child(body)
+            .into_any_element()
+    }
+
     fn render_context_panel_content(&mut self, cx: &mut Context<Self>) -> gpui::AnyElement {
         match self.settings.context_panel_tab {
             WorkspacePanel::Review => self.render_review_pane(cx).into_any_element(),
@@ -508,6 +820,15 @@ impl AppStore {
                 )
             }),
             WorkspacePanel::Connections => self.render_connections_panel(cx),
+            WorkspacePanel::History => self.render_history_panel(cx).unwrap_or_else(|| {
+                self.render_empty_context_panel(
+                    "Page History",
+                    "Open a page to browse and restore its earlier versions.",
+                    IconName::File,
+                    cx,
+                )
+            }),
+            WorkspacePanel::Agenda => self.render_agenda_panel(cx),
         }
     }
 
@@ -607,6 +928,7 @@ impl AppStore {
             .on_action(cx.listener(Self::switch_to_capture_action))
             .on_action(cx.listener(Self::switch_to_edit_action))
             .on_action(cx.listener(Self::switch_to_review_action))
+            .on_action(cx.listener(Self::switch_to_journals_action))
             .on_action(cx.listener(Self::open_keyboard_shortcuts_action))
             .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _window, cx| {
                 if this.ui.sidebar_resize.is_some() {
@@ -680,6 +1002,15 @@ impl AppStore {
                 }
                 body = body.child(self.render_review_feed(cx));
             }
+            Mode::Journals => {
+                if show_sidebar {
+                    body = body.child(self.render_sidebar(cx));
+                    if !self.settings.sidebar_collapsed {
+                        body = body.child(self.render_sidebar_resizer(cx));
+                    }
+                }
+                body = body.child(self.render_journals(window, cx));
+            }
         }
 
         root = root.child(body);
@@ -1009,7 +1340,6 @@ impl AppStore {
         let border = theme.border;
         let accent = theme.accent;
         let hover_bg = theme.list_hover;
-        let warning = theme.warning;
 
         let mut feed = div()
             .w_full()
@@ -1075,105 +1405,7 @@ impl AppStore {
                     );
                 }
                 FeedItem::ReviewDue(review) => {
-                    let snippet: SharedString = if review.text.len() > 96 {
-                        format!("{}...", &review.text[..96]).into()
-                    } else {
-                        review.text.clone().into()
-                    };
-                    let page_title: SharedString = review.page_title.clone().into();
-                    let page_uid = review.page_uid.clone();
-                    let item_id = review.id;
-                    feed = feed.child(
-                        div()
-                            .id(SharedString::from(format!("feed-review-{i}")))
-                            .rounded_md()
-                            .border_1()
-                            .border_color(border)
-                            .bg(sidebar_bg)
-                            .overflow_hidden()
-                            .flex()
-                            .flex_row()
-                            .child(
-                                div()
-                                    .w(px(3.0))
-                                    .flex_shrink_0()
-                                    .bg(warning),
-                            )
-                            .child(
-                                div()
-                                    .flex_1()
-                                    .p_3()
-                                    .flex()
-                                    .flex_col()
-                                    .gap_2()
-                                    .child(
-                                        div()
-                                            .text_size(tokens::FONT_BASE)
-                                            .text_color(fg)
-                                            .child(snippet),
-                                    )
-                                    .child(
-                                        div()
-                                            .flex()
-                                            .items_center()
-                                            .justify_between()
-                                            .child(
-                                                div()
-                                                    .text_size(tokens::FONT_SM)
-                                                    .text_color(muted_fg)
-                                                    .child(page_title),
-                                            )
-                                            .child(
-                                                div()
-                                                    .flex()
-                                                    .items_center()
-                                                    .gap_1()
-                                                    .child(
-                                                        Button::new(format!("feed-done-{i}"))
-                                                            .xsmall()
-                                                            .ghost()
-                                                            .icon(SandpaperIcon::Checkmark)
-                                                            .tooltip("Mark done")
-                                                            .on_click(cx.listener(
-                                                                move |this,
-                                                                      _event,
-                                                                      _window,
-                                                                      cx| {
-                                                                    this.review_mark_done(
-                                                                        item_id, cx,
-                                                                    );
-                                                                    this.refresh_feed(cx);
-                                                                },
-                                                            )),
-                                                    )
-                                                    .child(
-                                                        Button::new(format!("feed-snooze-{i}"))
-                                                            .xsmall()
-                                                            .ghost()
-                                                            .icon(SandpaperIcon::Subtract)
-                                                            .tooltip("Snooze")
-                                                            .on_click(cx.listener(
-                                                                move |this,
-                                                                      _event,
-                                                                      _window,
-                                                                      cx| {
-                                                                    this.review_snooze_day(
-                                                                        item_id, cx,
-                                                                    );
-                                                                    this.refresh_feed(cx);
-                                                                },
-                                                            )),
-                                                    ),
-                                            ),
-                                    ),
-                            )
-                            .cursor_pointer()
-                            .hover(move |s| s.bg(hover_bg))
-                            .on_click(cx.listener(move |this, _event, _window, cx| {
-                                this.open_page(&page_uid, cx);
-                                this.set_mode(Mode::Editor, cx);
-                            })),
-                    );
+                    feed = feed.child(self.render_feed_review_item(i, review, cx));
                 }
                 FeedItem::RelatedPage(related) => {
                     let title: SharedString = related.page_title.clone().into();
@@ -1324,6 +1556,147 @@ impl AppStore {
             .child(feed)
             .into_any_element()
     }
+
+    /// A due review item in the feed. Flashcards show their front until revealed, then the
+    /// answer and the grade buttons.
+    fn render_feed_review_item(
+        &mut self,
+        ix: usize,
+        review: &ReviewDisplayItem,
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        use crate::app::store::helpers::format_snippet;
+
+        let theme = cx.theme();
+        let fg = theme.foreground;
+        let muted_fg = theme.muted_foreground;
+        let sidebar_bg = theme.sidebar;
+        let border = theme.border;
+        let hover_bg = theme.list_hover;
+        let warning = theme.warning;
+        let item_id = review.id;
+        let page_uid = review.page_uid.clone();
+        let page_title: SharedString = review.page_title.clone().into();
+        let revealed = self.editor.review_revealed.contains(&item_id);
+
+        let prompt = review
+            .flashcard
+            .as_ref()
+            .map(|card| card.front.clone())
+            .unwrap_or_else(|| format_snippet(&review.text, 96));
+        let mut content = div().flex_1().p_3().flex().flex_col().gap_2().child(
+            div()
+                .text_size(tokens::FONT_BASE)
+                .text_color(fg)
+                .child(prompt),
+        );
+        if let (Some(card), true) = (review.flashcard.as_ref(), revealed) {
+            let answer_style = HighlightStyle {
+                color: Some(warning),
+                font_weight: Some(gpui::FontWeight::SEMIBOLD),
+                ..Default::default()
+            };
+            let answer = StyledText::new(card.back.clone()).with_highlights(
+                card.reveals
+                    .iter()
+                    .map(|range| (range.clone(), answer_style)),
+            );
+            content = content.child(
+                div()
+                    .pt_2()
+                    .border_t_1()
+                    .border_color(border)
+                    .text_size(tokens::FONT_BASE)
+                    .text_color(fg)
+                    .child(answer),
+            );
+        }
+
+        let mut actions = div().flex().items_center().gap_1();
+        match (review.flashcard.is_some(), revealed) {
+            (true, false) => {
+                actions = actions.child(
+                    Button::new(format!("feed-reveal-{ix}"))
+                        .xsmall()
+                        .label("Show answer")
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.reveal_review_answer(item_id, cx);
+                        })),
+                );
+            }
+            (true, true) => {
+                let card = review.card;
+                actions = actions.children(ReviewGrade::ALL.into_iter().map(|grade| {
+                    let interval = format_review_interval(card.review(grade).interval_days);
+                    Button::new(format!("feed-{}-{ix}", grade.as_str()))
+                        .xsmall()
+                        .ghost()
+                        .label(format!("{} · {interval}", grade.label()))
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.review_grade(item_id, grade, cx);
+                            this.refresh_feed(cx);
+                        }))
+                }));
+            }
+            (false, _) => {
+                actions = actions
+                    .child(
+                        Button::new(format!("feed-done-{ix}"))
+                            .xsmall()
+                            .ghost()
+                            .icon(SandpaperIcon::Checkmark)
+                            .tooltip("Mark done")
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.review_mark_done(item_id, cx);
+                                this.refresh_feed(cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(format!("feed-snooze-{ix}"))
+                            .xsmall()
+                            .ghost()
+                            .icon(SandpaperIcon::Subtract)
+                            .tooltip("Review again soon")
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.review_grade(item_id, ReviewGrade::Hard, cx);
+                                this.refresh_feed(cx);
+                            })),
+                    );
+            }
+        }
+        content = content.child(
+            div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(
+                    div()
+                        .text_size(tokens::FONT_SM)
+                        .text_color(muted_fg)
+                        .child(page_title),
+                )
+                .child(actions),
+        );
+
+        div()
+            .id(SharedString::from(format!("feed-review-{ix}")))
+            .rounded_md()
+            .border_1()
+            .border_color(border)
+            .bg(sidebar_bg)
+            .overflow_hidden()
+            .flex()
+            .flex_row()
+            .child(div().w(px(3.0)).flex_shrink_0().bg(warning))
+            .child(content)
+            .cursor_pointer()
+            .hover(move |s| s.bg(hover_bg))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                this.open_page(&page_uid, cx);
+                this.set_mode(Mode::Editor, cx);
+            }))
+            .into_any_element()
+    }
 }
 
 #[cfg(test)]
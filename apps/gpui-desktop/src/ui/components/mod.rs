@@ -3,6 +3,7 @@ mod editor;
 pub(crate) mod empty_state;
 pub(crate) mod error_display;
 mod helpers;
+mod journals;
 mod layout;
 mod modals;
 mod plugins;
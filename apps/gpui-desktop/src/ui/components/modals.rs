@@ -1,4 +1,5 @@
 use crate::app::prelude::*;
+use crate::app::store::journals::JOURNAL_TITLE_FORMATS;
 use crate::app::store::*;
 use crate::ui::tokens;
 use super::helpers::{segmented_button, segmented_button_group};
@@ -234,6 +235,95 @@ impl AppStore {
             cx,
         ));
 
+        let today = Local::now().date_naive();
+        let journal_format = self.editor.journals.settings.title_format.clone();
+        let mut format_buttons = div().flex().items_center().gap(px(1.0));
+        for (ix, format) in JOURNAL_TITLE_FORMATS.into_iter().enumerate() {
+            format_buttons = format_buttons.child(
+                segmented_button(
+                    format!("settings-journal-format-{ix}"),
+                    today.format(format).to_string(),
+                    journal_format == format,
+                )
+                .on_click(cx.listener(move |this, _event, _window, cx| {
+                    this.set_journal_title_format(format, cx);
+                })),
+            );
+        }
+        let template_label: SharedString = match self.editor.journals.settings.template.as_deref() {
+            Some(template) => self
+                .editor
+                .pages
+                .iter()
+                .find(|page| page.uid == template)
+                .map(|page| page.title.clone())
+                .unwrap_or_else(|| template.to_string())
+                .into(),
+            None => "None".into(),
+        };
+        let active_page = self.editor.active_page.clone();
+        content = content.child(self.render_settings_section_card(
+            div()
+                .flex()
+                .flex_col()
+                .gap_3()
+                .child(self.render_settings_section_card_header(
+                    "Journals",
+                    "Daily page names and what new days start with.",
+                    cx,
+                ))
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .child(self.render_settings_row(
+                            "Title format",
+                            "How daily pages are named. Existing pages keep their titles.",
+                            segmented_button_group(
+                                "settings-journal-format-group",
+                                format_buttons.into_any_element(),
+                                cx,
+                            ),
+                            true,
+                            cx,
+                        ))
+                        .child(self.render_settings_row(
+                            "Daily template",
+                            "Blocks copied into each new daily page.",
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(div().text_size(tokens::FONT_SM).child(template_label))
+                                .child(
+                                    Button::new("settings-journal-template-use")
+                                        .xsmall()
+                                        .ghost()
+                                        .label("Use current page")
+                                        .disabled(active_page.is_none())
+                                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                                            this.set_journal_template(active_page.clone(), cx);
+                                        })),
+                                )
+                                .child(
+                                    Button::new("settings-journal-template-clear")
+                                        .xsmall()
+                                        .ghost()
+                                        .label("Clear")
+                                        .disabled(self.editor.journals.settings.template.is_none())
+                                        .on_click(cx.listener(|this, _event, _window, cx| {
+                                            this.set_journal_template(None, cx);
+                                        })),
+                                )
+                                .into_any_element(),
+                            false,
+                            cx,
+                        )),
+                )
+                .into_any_element(),
+            cx,
+        ));
+
         content.into_any_element()
     }
 
@@ -43,9 +43,80 @@ impl AppStore {
         let border = cx.theme().border;
         let sidebar_bg = cx.theme().sidebar;
         let muted_fg = cx.theme().muted_foreground;
-        let fg = cx.theme().foreground;
-        let plugin_id = panel.plugin_id.clone();
+        let danger_fg = cx.theme().danger_foreground;
         let header = self.render_context_panel_header(cx);
+        let view = self
+            .plugins
+            .plugin_panel_view
+            .clone()
+            .filter(|view| view.plugin_id == panel.plugin_id && view.panel_id == panel.id);
+        let title = view
+            .as_ref()
+            .and_then(|view| view.title.clone())
+            .unwrap_or_else(|| panel.title.clone());
+
+        let mut body = div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .p_4()
+            .flex_1()
+            .min_h_0()
+            .overflow_y_scrollbar()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .min_w_0()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(gpui::FontWeight::MEDIUM)
+                                    .child(title),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(muted_fg)
+                                    .child(format!("Plugin: {}", panel.plugin_id)),
+                            ),
+                    )
+                    .child(
+                        Button::new("plugin-panel-refresh")
+                            .label("Refresh")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, window, cx| {
+                                this.refresh_plugin_panel(Some(window), cx);
+                            })),
+                    ),
+            );
+
+        if let Some(error) = self.plugins.plugin_panel_error.clone() {
+            body = body.child(div().text_xs().text_color(danger_fg).child(error));
+        }
+        match view {
+            Some(view) if !view.children.is_empty() => {
+                for (ix, node) in view.children.iter().enumerate() {
+                    body = body.child(self.render_plugin_panel_node(node, format!("{ix}"), cx));
+                }
+            }
+            Some(_) => {
+                body = body.child(
+                    div()
+                        .text_sm()
+                        .text_color(muted_fg)
+                        .child("This panel has nothing to show."),
+                );
+            }
+            None => {}
+        }
 
         Some(
             div()
@@ -59,26 +130,89 @@ impl AppStore {
                 .flex_col()
                 .min_h_0()
                 .child(header)
-                .child(
-                    div()
-                        .px_4()
-                        .pt_3()
-                        .text_xs()
-                        .text_color(muted_fg)
-                        .child(format!("Plugin: {plugin_id}")),
-                )
-                .child(
-                    div()
-                        .px_4()
-                        .pt_2()
-                        .text_sm()
-                        .text_color(fg)
-                        .child("Active panel placeholder"),
-                )
+                .child(body)
                 .into_any_element(),
         )
     }
 
+    fn render_plugin_panel_node(
+        &mut self,
+        node: &PluginPanelNode,
+        path: String,
+        cx: &mut Context<Self>,
+    ) -> gpui::AnyElement {
+        let fg = cx.theme().foreground;
+        let muted_fg = cx.theme().muted_foreground;
+        let accent = cx.theme().accent;
+        let hover_bg = cx.theme().list_hover;
+        match node {
+            PluginPanelNode::Text { text, style } => {
+                let text = div().text_sm().text_color(fg).child(text.clone());
+                match style.as_deref() {
+                    Some("heading") => text.font_weight(gpui::FontWeight::SEMIBOLD),
+                    Some("muted") => text.text_xs().text_color(muted_fg),
+                    _ => text,
+                }
+                .into_any_element()
+            }
+            PluginPanelNode::List { items } => {
+                let mut list = div().flex().flex_col().gap_1();
+                for (ix, item) in items.iter().enumerate() {
+                    list =
+                        list.child(
+                            div()
+                                .flex()
+                                .items_start()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .w(tokens::SPACE_5)
+                                        .text_sm()
+                                        .text_color(muted_fg)
+                                        .child("•"),
+                                )
+                                .child(div().flex_1().min_w_0().child(
+                                    self.render_plugin_panel_node(item, format!("{path}-{ix}"), cx),
+                                )),
+                        );
+                }
+                list.into_any_element()
+            }
+            PluginPanelNode::Button { id, label, value } => {
+                let action_id = id.clone();
+                let value = value.clone();
+                Button::new(format!("plugin-panel-node-{path}"))
+                    .label(label.clone())
+                    .xsmall()
+                    .ghost()
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.run_plugin_panel_action(&action_id, value.clone(), Some(window), cx);
+                    }))
+                    .into_any_element()
+            }
+            PluginPanelNode::Input { id, .. } => match self.plugins.plugin_panel_inputs.get(id) {
+                Some((input, _)) => Input::new(input).small().into_any_element(),
+                None => div().into_any_element(),
+            },
+            PluginPanelNode::Link { label, page, block } => {
+                let page = page.clone();
+                let block = block.clone();
+                div()
+                    .id(SharedString::from(format!("plugin-panel-node-{path}")))
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(move |s| s.bg(hover_bg))
+                    .text_sm()
+                    .text_color(accent)
+                    .child(label.clone())
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.open_plugin_panel_link(page.as_deref(), block.as_deref(), window, cx);
+                    }))
+                    .into_any_element()
+            }
+        }
+    }
+
     fn render_plugin_setting_field(
         &mut self,
         plugin: &PluginPermissionInfo,
@@ -500,8 +500,22 @@ impl AppStore {
                     .text_color(theme.muted_foreground)
                     .child("Blocks"),
             );
+            let match_style = HighlightStyle {
+                color: Some(theme.accent),
+                font_weight: Some(gpui::FontWeight::SEMIBOLD),
+                ..Default::default()
+            };
             content = content.children(self.editor.search_blocks.iter().map(|block| {
-                let snippet = format_snippet(&block.text, 80);
+                let (snippet, ranges) = snippet_highlights(&block.snippet);
+                // Filter-only searches return the whole block; FTS snippets are already short
+                // and a same-length newline swap keeps their highlight ranges valid.
+                let snippet = if ranges.is_empty() {
+                    format_snippet(&snippet, 80)
+                } else {
+                    snippet.replace(['\r', '\n'], " ")
+                };
+                let snippet = StyledText::new(snippet)
+                    .with_highlights(ranges.into_iter().map(|range| (range, match_style)));
                 let page_uid = block.page_uid.clone();
                 let block_uid = block.block_uid.clone();
                 div()
@@ -44,6 +44,8 @@ fn command_palette_icon_for_action(action: &PaletteAction) -> SandpaperIcon {
         | PaletteAction::OpenPluginPanel(_)
         | PaletteAction::ClosePluginPanel => SandpaperIcon::Grid,
         PaletteAction::OpenPage(_) => SandpaperIcon::Open,
+        PaletteAction::OpenBlock { .. } => SandpaperIcon::Search,
+        PaletteAction::InsertTemplate(_) => SandpaperIcon::Copy,
         PaletteAction::ToggleFocusMode => SandpaperIcon::Eye,
         PaletteAction::OpenQuickCapture => SandpaperIcon::Alert,
         PaletteAction::SwitchMode(_) => SandpaperIcon::ArrowSwap,
@@ -937,6 +939,19 @@ impl Render for VaultDialogView {
     }
 }
 
+fn template_choice_button(
+    id: impl Into<gpui::ElementId>,
+    label: impl Into<SharedString>,
+    is_active: bool,
+) -> Button {
+    let button = Button::new(id).label(label).xsmall();
+    if is_active {
+        button.primary()
+    } else {
+        button.ghost()
+    }
+}
+
 pub(crate) struct PageDialogView {
     app: Entity<AppStore>,
     _subscription: Subscription,
@@ -957,12 +972,14 @@ impl PageDialogView {
 
 impl Render for PageDialogView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let (input, error, mode) = {
+        let (input, error, mode, templates, chosen_template) = {
             let app = self.app.read(cx);
             (
                 app.ui.page_dialog_input.clone(),
                 app.ui.page_dialog_error.clone(),
                 app.ui.page_dialog_mode,
+                app.ui.page_dialog_templates.clone(),
+                app.ui.page_dialog_template.clone(),
             )
         };
 
@@ -989,6 +1006,47 @@ impl Render for PageDialogView {
             )
             .child(Input::new(&input).small());
 
+        if mode == PageDialogMode::Create && !templates.is_empty() {
+            let mut choices = div().flex().flex_wrap().gap_1().child(
+                template_choice_button(
+                    "page-dialog-template-none",
+                    "Blank",
+                    chosen_template.is_none(),
+                )
+                .on_click({
+                    let app = self.app.clone();
+                    move |_event, _window, cx| {
+                        app.update(cx, |app, cx| app.set_page_dialog_template(None, cx));
+                    }
+                }),
+            );
+            for page in templates {
+                let uid = page.uid.clone();
+                choices = choices.child(
+                    template_choice_button(
+                        SharedString::from(format!("page-dialog-template-{}", page.uid)),
+                        page.title,
+                        chosen_template.as_deref() == Some(uid.as_str()),
+                    )
+                    .on_click({
+                        let app = self.app.clone();
+                        move |_event, _window, cx| {
+                            let uid = uid.clone();
+                            app.update(cx, |app, cx| app.set_page_dialog_template(Some(uid), cx));
+                        }
+                    }),
+                );
+            }
+            content = content
+                .child(
+                    div()
+                        .text_size(tokens::FONT_SM)
+                        .text_color(muted)
+                        .child("Start from"),
+                )
+                .child(choices);
+        }
+
         if let Some(error) = error {
             use crate::ui::components::error_display::InlineError;
             content = content.child(InlineError::new(format!(
@@ -1007,6 +1065,104 @@ impl Render for PageDialogView {
     }
 }
 
+pub(crate) struct TemplateDialogView {
+    app: Entity<AppStore>,
+    _subscription: Subscription,
+}
+
+impl TemplateDialogView {
+    pub(crate) fn new(app: Entity<AppStore>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.observe(&app, |_this, _app, cx| {
+            cx.notify();
+        });
+
+        Self {
+            app,
+            _subscription: subscription,
+        }
+    }
+}
+
+impl Render for TemplateDialogView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (templates, chosen) = {
+            let app = self.app.read(cx);
+            let Some(dialog) = app.ui.template_dialog.as_ref() else {
+                return div().id("template-dialog");
+            };
+            (
+                dialog.templates.clone(),
+                dialog
+                    .chosen
+                    .as_ref()
+                    .map(|chosen| (chosen.page.title.clone(), chosen.fields.clone())),
+            )
+        };
+
+        let (foreground, muted, hover_bg) = {
+            let theme = cx.theme();
+            (theme.foreground, theme.muted_foreground, theme.list_hover)
+        };
+
+        let mut content = div()
+            .id("template-dialog")
+            .flex()
+            .flex_col()
+            .gap_3()
+            .text_color(foreground);
+
+        if let Some((title, fields)) = chosen {
+            content = content.child(
+                div()
+                    .text_size(tokens::FONT_SM)
+                    .text_color(muted)
+                    .child(format!("Fill in the fields for “{title}”.")),
+            );
+            for (name, input) in fields {
+                content = content.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .child(div().text_size(tokens::FONT_SM).child(name))
+                        .child(Input::new(&input).small()),
+                );
+            }
+            return content;
+        }
+
+        content = content.child(
+            div()
+                .text_size(tokens::FONT_SM)
+                .text_color(muted)
+                .child("Pages tagged #template."),
+        );
+        for page in templates {
+            let page_uid = page.uid.clone();
+            let app = self.app.clone();
+            content = content.child(
+                div()
+                    .id(SharedString::from(format!("template-{}", page.uid)))
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .text_size(tokens::FONT_SM)
+                    .cursor_pointer()
+                    .hover(move |s| s.bg(hover_bg))
+                    .child(page.title)
+                    .on_click(move |_event, window, cx| {
+                        let needs_fields =
+                            app.update(cx, |app, cx| app.choose_template(&page_uid, window, cx));
+                        if !needs_fields {
+                            window.close_dialog(cx);
+                        }
+                    }),
+            );
+        }
+        content
+    }
+}
+
 pub(crate) struct KeyboardShortcutsDialogView {
     _app: Entity<AppStore>,
     _subscription: Subscription,
@@ -1088,6 +1244,10 @@ fn shortcut_groups() -> Vec<ShortcutGroup> {
                     description: "Review mode",
                     spec: ShortcutSpec::new("cmd-3", "ctrl-3"),
                 },
+                ShortcutEntry {
+                    description: "Journals",
+                    spec: ShortcutSpec::new("cmd-4", "ctrl-4"),
+                },
             ],
         },
         ShortcutGroup {
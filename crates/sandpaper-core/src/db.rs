@@ -1,16 +1,61 @@
+use crate::app;
 use crate::blocks::BlockType;
-use rusqlite::{params, Connection, OptionalExtension};
+use crate::flashcards;
+use crate::journals::{self, JournalSettings};
+use crate::links;
+use crate::properties::{self, PropertyValue, PropertyValueError};
+use crate::review::{ReviewCard, ReviewGrade, ReviewStats, DAY_MS};
+use crate::revisions::{self, PageRevision, PageRevisionRecord};
+use crate::search::{self, SearchHit, SearchQuery};
+use crate::sort_keys;
+use crate::tasks::{self, TaskFilter, TaskRecord, TaskState};
+use crate::views::{FilterOp, PageQuery, PageQueryRow, PageSource, PropertyFilter, TITLE_KEY};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::Path;
 
 pub struct Database {
     conn: Connection,
 }
 
+/// Failure of a call that validates property values before touching SQLite.
+#[derive(Debug)]
+pub enum DbError {
+    Sqlite(rusqlite::Error),
+    PropertyValue(PropertyValueError),
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Sqlite(err)
+    }
+}
+
+impl From<PropertyValueError> for DbError {
+    fn from(err: PropertyValueError) -> Self {
+        Self::PropertyValue(err)
+    }
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sqlite(err) => err.fmt(f),
+            Self::PropertyValue(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
 pub struct Migration {
     pub version: i64,
     pub name: &'static str,
     pub up: &'static str,
+    pub backfill: Option<fn(&Connection) -> rusqlite::Result<()>>,
 }
 
 const MIGRATIONS: &[Migration] = &[
@@ -175,11 +220,13 @@ const MIGRATIONS: &[Migration] = &[
             INSERT INTO pages_fts(rowid, title)
             VALUES (new.id, new.title);
         END;",
+        backfill: None,
     },
     Migration {
         version: 2,
         name: "assets-original-name",
         up: "ALTER TABLE assets ADD COLUMN original_name TEXT;",
+        backfill: None,
     },
     Migration {
         version: 3,
@@ -206,6 +253,151 @@ const MIGRATIONS: &[Migration] = &[
 
         CREATE INDEX IF NOT EXISTS page_properties_page
           ON page_properties(page_id, sort_order);",
+        backfill: None,
+    },
+    Migration {
+        version: 4,
+        name: "block-tree-parents",
+        up: "UPDATE blocks
+        SET parent_id = (
+            SELECT parent.id
+            FROM blocks parent
+            WHERE parent.page_id = blocks.page_id
+              AND parent.sort_key < blocks.sort_key
+              AND CASE WHEN json_valid(parent.props)
+                    THEN COALESCE(json_extract(parent.props, '$.indent'), 0)
+                    ELSE 0 END
+                < json_extract(blocks.props, '$.indent')
+            ORDER BY parent.sort_key DESC
+            LIMIT 1
+        )
+        WHERE parent_id IS NULL
+          AND json_valid(props)
+          AND COALESCE(json_extract(props, '$.indent'), 0) > 0;",
+        backfill: None,
+    },
+    Migration {
+        version: 5,
+        name: "blocks-fts-text-only-updates",
+        up: "DROP TRIGGER IF EXISTS blocks_fts_update;
+
+        CREATE TRIGGER IF NOT EXISTS blocks_fts_update AFTER UPDATE OF text ON blocks BEGIN
+            INSERT INTO blocks_fts(blocks_fts, rowid, text)
+            VALUES ('delete', old.id, old.text);
+            INSERT INTO blocks_fts(rowid, text)
+            VALUES (new.id, new.text);
+        END;",
+        backfill: None,
+    },
+    Migration {
+        version: 6,
+        name: "block-edges",
+        up: "CREATE INDEX IF NOT EXISTS edges_kind_to
+          ON edges(kind, to_block_uid);",
+        backfill: Some(rebuild_all_block_links),
+    },
+    Migration {
+        version: 7,
+        name: "block-tags",
+        up: "CREATE INDEX IF NOT EXISTS block_tags_block
+          ON block_tags(block_id);",
+        // The "block-edges" rebuild already fills `block_tags`.
+        backfill: None,
+    },
+    Migration {
+        version: 8,
+        name: "block-properties",
+        up: "CREATE TABLE IF NOT EXISTS block_properties (
+            id INTEGER PRIMARY KEY,
+            block_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            value_type TEXT NOT NULL DEFAULT 'text',
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (block_id) REFERENCES blocks(id) ON DELETE CASCADE,
+            UNIQUE(block_id, key)
+        );
+
+        CREATE INDEX IF NOT EXISTS block_properties_key
+          ON block_properties(key, value);",
+        backfill: Some(rebuild_all_block_properties),
+    },
+    Migration {
+        version: 9,
+        name: "page-aliases",
+        up: "CREATE TABLE IF NOT EXISTS page_aliases (
+            id INTEGER PRIMARY KEY,
+            page_id INTEGER NOT NULL,
+            alias TEXT NOT NULL,
+            alias_uid TEXT NOT NULL,
+            FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE,
+            UNIQUE(page_id, alias_uid)
+        );
+
+        CREATE INDEX IF NOT EXISTS page_aliases_alias_uid
+          ON page_aliases(alias_uid);",
+        backfill: Some(rebuild_all_page_aliases),
+    },
+    Migration {
+        version: 10,
+        name: "page-revisions",
+        up: "CREATE TABLE IF NOT EXISTS page_revisions (
+            id INTEGER PRIMARY KEY,
+            page_id INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            block_count INTEGER NOT NULL,
+            blocks TEXT NOT NULL,
+            FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS page_revisions_page
+          ON page_revisions(page_id, created_at);",
+        backfill: Some(backfill_page_revisions),
+    },
+    Migration {
+        version: 11,
+        name: "review-scheduling",
+        up: "ALTER TABLE review_queue ADD COLUMN ease REAL NOT NULL DEFAULT 2.5;
+        ALTER TABLE review_queue ADD COLUMN interval_days INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE review_queue ADD COLUMN repetitions INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE review_queue ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
+
+        CREATE TABLE IF NOT EXISTS review_log (
+            id INTEGER PRIMARY KEY,
+            queue_id INTEGER NOT NULL,
+            reviewed_at INTEGER NOT NULL,
+            grade TEXT NOT NULL,
+            interval_days INTEGER NOT NULL,
+            ease REAL NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS review_log_reviewed
+          ON review_log(reviewed_at);",
+        backfill: None,
+    },
+    Migration {
+        version: 12,
+        name: "flashcard-review-items",
+        up: "",
+        backfill: Some(backfill_flashcard_review_items),
+    },
+    Migration {
+        version: 13,
+        name: "journal-date-links",
+        up: "",
+        backfill: Some(rebuild_all_block_links),
+    },
+    Migration {
+        version: 14,
+        name: "page-revision-stale",
+        up: "ALTER TABLE page_revisions ADD COLUMN stale INTEGER NOT NULL DEFAULT 0;",
+        backfill: None,
+    },
+    Migration {
+        version: 15,
+        name: "fractional-sort-keys",
+        up: "",
+        backfill: Some(rekey_legacy_sort_keys),
     },
 ];
 
@@ -226,6 +418,23 @@ pub struct PagePropertyRecord {
     pub sort_order: i64,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageAliasRecord {
+    pub page_id: i64,
+    pub page_uid: String,
+    pub alias: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockPropertyRecord {
+    pub id: i64,
+    pub block_id: i64,
+    pub key: String,
+    pub value: String,
+    pub value_type: String,
+    pub sort_order: i64,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct PropertyDefinition {
     pub id: i64,
@@ -247,6 +456,17 @@ pub struct BlockRecord {
     pub props: String,
 }
 
+impl BlockRecord {
+    pub fn snapshot(&self) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: self.uid.clone(),
+            text: self.text.clone(),
+            indent: parse_indent(&self.props),
+            block_type: parse_block_type(&self.props),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize)]
 pub struct BlockSearchResult {
     pub id: i64,
@@ -263,6 +483,43 @@ pub struct BlockSnapshot {
     pub block_type: BlockType,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockInsert {
+    pub block: BlockSnapshot,
+    pub parent_uid: Option<String>,
+    pub sort_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMove {
+    pub uid: String,
+    pub parent_uid: Option<String>,
+    pub sort_key: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockChanges {
+    pub inserts: Vec<BlockInsert>,
+    pub updates: Vec<BlockSnapshot>,
+    pub moves: Vec<BlockMove>,
+    pub deletes: Vec<String>,
+}
+
+impl BlockChanges {
+    pub fn is_empty(&self) -> bool {
+        self.inserts.is_empty()
+            && self.updates.is_empty()
+            && self.moves.is_empty()
+            && self.deletes.is_empty()
+    }
+}
+
+struct StoredBlock {
+    snapshot: BlockSnapshot,
+    parent_uid: Option<String>,
+    sort_key: String,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct BlockPageRecord {
     pub block_uid: String,
@@ -271,12 +528,26 @@ pub struct BlockPageRecord {
     pub page_title: String,
 }
 
+/// The blocks shown by `{{embed ((uid))}}` (a block and its subtree) or `{{embed [[page]]}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockEmbed {
+    pub page_uid: String,
+    pub page_title: String,
+    pub blocks: Vec<BlockSnapshot>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct TagRecord {
     pub id: i64,
     pub name: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagSummary {
+    pub name: String,
+    pub block_count: i64,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct EdgeRecord {
     pub id: i64,
@@ -325,6 +596,7 @@ pub struct ReviewQueueItem {
     pub template: Option<String>,
     pub status: String,
     pub last_reviewed_at: Option<i64>,
+    pub card: ReviewCard,
 }
 
 impl Database {
@@ -338,6 +610,7 @@ impl Database {
              PRAGMA busy_timeout = 5000;
              PRAGMA cache_size = -64000;",
         )?;
+        register_functions(&conn)?;
         Ok(Self { conn })
     }
 
@@ -351,6 +624,7 @@ impl Database {
              PRAGMA busy_timeout = 5000;
              PRAGMA cache_size = -64000;",
         )?;
+        register_functions(&conn)?;
         Ok(Self { conn })
     }
 
@@ -376,6 +650,9 @@ impl Database {
             if migration.version > current_version {
                 let tx = self.conn.unchecked_transaction()?;
                 tx.execute_batch(migration.up)?;
+                if let Some(backfill) = migration.backfill {
+                    backfill(&tx)?;
+                }
                 tx.execute(
                     "INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)",
                     params![migration.version, migration.name],
@@ -441,6 +718,22 @@ impl Database {
             .optional()
     }
 
+    pub fn get_page_by_id(&self, page_id: i64) -> rusqlite::Result<Option<PageRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, uid, title FROM pages WHERE id = ?1",
+                [page_id],
+                |row| {
+                    Ok(PageRecord {
+                        id: row.get(0)?,
+                        uid: row.get(1)?,
+                        title: row.get(2)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
     pub fn list_pages(&self) -> rusqlite::Result<Vec<PageRecord>> {
         let mut stmt = self
             .conn
@@ -489,7 +782,9 @@ impl Database {
              VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![uid, page_id, parent_id, sort_key, text, props],
         )?;
-        Ok(self.conn.last_insert_rowid())
+        let block_id = self.conn.last_insert_rowid();
+        index_block_text(&self.conn, block_id, text)?;
+        Ok(block_id)
     }
 
     pub fn update_block_text(&self, block_id: i64, text: &str) -> rusqlite::Result<()> {
@@ -497,15 +792,24 @@ impl Database {
             "UPDATE blocks SET text = ?1, updated_at = strftime('%s','now') WHERE id = ?2",
             params![text, block_id],
         )?;
-        Ok(())
+        index_block_text(&self.conn, block_id, text)
     }
 
+    /// Rewrites one block's text the way a page save does: it is re-indexed, its flashcard
+    /// review item follows it, and the change folds into the page's recent revision.
     pub fn update_block_text_by_uid(&self, block_uid: &str, text: &str) -> rusqlite::Result<()> {
-        self.conn.execute(
-            "UPDATE blocks SET text = ?1, updated_at = strftime('%s','now') WHERE uid = ?2",
-            params![text, block_uid],
-        )?;
-        Ok(())
+        match self.get_block_by_uid(block_uid)? {
+            Some(block) => self.rewrite_block_text(&block, text),
+            None => Ok(()),
+        }
+    }
+
+    fn rewrite_block_text(&self, block: &BlockRecord, text: &str) -> rusqlite::Result<()> {
+        let snapshot = BlockSnapshot {
+            text: text.to_string(),
+            ..block.snapshot()
+        };
+        write_block_rows(&self.conn, block.page_id, &[], &[snapshot], &[], &[], true)
     }
 
     pub fn update_block_position(
@@ -527,21 +831,60 @@ impl Database {
             .query_row(
                 "SELECT id, uid, page_id, parent_id, sort_key, text, props FROM blocks WHERE id = ?1",
                 [block_id],
-                |row| {
-                    Ok(BlockRecord {
-                        id: row.get(0)?,
-                        uid: row.get(1)?,
-                        page_id: row.get(2)?,
-                        parent_id: row.get(3)?,
-                        sort_key: row.get(4)?,
-                        text: row.get(5)?,
-                        props: row.get(6)?,
-                    })
-                },
+                map_block_record,
             )
             .optional()
     }
 
+    pub fn get_block_by_uid(&self, block_uid: &str) -> rusqlite::Result<Option<BlockRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, uid, page_id, parent_id, sort_key, text, props FROM blocks WHERE uid = ?1",
+                [block_uid],
+                map_block_record,
+            )
+            .optional()
+    }
+
+    pub fn list_block_children(&self, parent_id: i64) -> rusqlite::Result<Vec<BlockRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, uid, page_id, parent_id, sort_key, text, props
+             FROM blocks
+             WHERE parent_id = ?1
+             ORDER BY sort_key ASC",
+        )?;
+        let rows = stmt.query_map([parent_id], map_block_record)?;
+        rows.collect()
+    }
+
+    pub fn list_root_blocks(&self, page_id: i64) -> rusqlite::Result<Vec<BlockRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, uid, page_id, parent_id, sort_key, text, props
+             FROM blocks
+             WHERE page_id = ?1 AND parent_id IS NULL
+             ORDER BY sort_key ASC",
+        )?;
+        let rows = stmt.query_map([page_id], map_block_record)?;
+        rows.collect()
+    }
+
+    /// Loads `block_id` and all of its descendants in outline order (root first).
+    pub fn load_block_subtree(&self, block_id: i64) -> rusqlite::Result<Vec<BlockRecord>> {
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM blocks WHERE id = ?1
+                UNION ALL
+                SELECT blocks.id FROM blocks JOIN subtree ON blocks.parent_id = subtree.id
+             )
+             SELECT b.id, b.uid, b.page_id, b.parent_id, b.sort_key, b.text, b.props
+             FROM blocks b
+             JOIN subtree ON b.id = subtree.id
+             ORDER BY b.sort_key ASC",
+        )?;
+        let rows = stmt.query_map([block_id], map_block_record)?;
+        rows.collect()
+    }
+
     pub fn delete_block(&self, block_id: i64) -> rusqlite::Result<()> {
         self.conn
             .execute("DELETE FROM blocks WHERE id = ?1", [block_id])?;
@@ -549,6 +892,9 @@ impl Database {
     }
 
     pub fn search_blocks(&self, query: &str) -> rusqlite::Result<Vec<i64>> {
+        let Some(query) = search::fts_match_expression(query) else {
+            return Ok(Vec::new());
+        };
         let mut stmt = self
             .conn
             .prepare("SELECT rowid FROM blocks_fts WHERE blocks_fts MATCH ?1 ORDER BY rowid")?;
@@ -561,6 +907,9 @@ impl Database {
         query: &str,
         limit: i64,
     ) -> rusqlite::Result<Vec<BlockSearchResult>> {
+        let Some(query) = search::fts_match_expression(query) else {
+            return Ok(Vec::new());
+        };
         let mut stmt = self.conn.prepare(
             "SELECT b.id, b.uid, b.text
              FROM blocks b
@@ -584,6 +933,9 @@ impl Database {
         query: &str,
         limit: i64,
     ) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        let Some(query) = search::fts_match_expression(query) else {
+            return Ok(Vec::new());
+        };
         let mut stmt = self.conn.prepare(
             "SELECT b.uid, b.text, p.uid, p.title
              FROM blocks b
@@ -604,57 +956,442 @@ impl Database {
         rows.collect()
     }
 
-    pub fn load_blocks_for_page(&self, page_id: i64) -> rusqlite::Result<Vec<BlockSnapshot>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT uid, text, props FROM blocks WHERE page_id = ?1 ORDER BY sort_key")?;
-        let rows = stmt.query_map([page_id], |row| {
-            let props: String = row.get(2)?;
-            Ok(BlockSnapshot {
-                uid: row.get(0)?,
-                text: row.get(1)?,
-                indent: parse_indent(&props),
-                block_type: parse_block_type(&props),
+    /// Parses a search box query, reading `key:value` as a property filter only for properties
+    /// that are defined or used on some block.
+    pub fn parse_search_query(&self, input: &str) -> rusqlite::Result<SearchQuery> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT key, value_type FROM property_definitions
+             UNION
+             SELECT DISTINCT key, 'text' FROM block_properties
+             WHERE key NOT IN (SELECT key FROM property_definitions)",
+        )?;
+        let properties = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok(SearchQuery::parse_with_properties(input, &properties))
+    }
+
+    /// Runs a parsed search: text terms through FTS5 (ranked by BM25, with a highlighted
+    /// snippet) and scoping filters as SQL predicates. Without text terms, hits are ordered by
+    /// most recently updated.
+    pub fn search(&self, query: &SearchQuery, limit: i64) -> Result<Vec<SearchHit>, DbError> {
+        let fts = query.fts_match();
+        let mut values: Vec<String> = Vec::new();
+        let snippet = if fts.is_some() {
+            format!(
+                "snippet(blocks_fts, 0, char({}), char({}), '…', 16)",
+                search::HIGHLIGHT_START as u32,
+                search::HIGHLIGHT_END as u32
+            )
+        } else {
+            "b.text".to_string()
+        };
+        let mut sql = format!(
+            "SELECT b.id, b.uid, p.uid, p.title, b.text, {snippet}
+             FROM blocks b
+             JOIN pages p ON p.id = b.page_id"
+        );
+        if let Some(fts) = &fts {
+            let param = bind_param(&mut values, fts);
+            sql.push_str(&format!(
+                " JOIN blocks_fts ON blocks_fts.rowid = b.id WHERE blocks_fts MATCH {param}"
+            ));
+        } else {
+            sql.push_str(" WHERE 1 = 1");
+        }
+        if let Some(excluded) = query.fts_excluded() {
+            let param = bind_param(&mut values, &excluded);
+            sql.push_str(&format!(
+                " AND b.id NOT IN (SELECT rowid FROM blocks_fts WHERE blocks_fts MATCH {param})"
+            ));
+        }
+        if !query.pages.is_empty() {
+            let params = query
+                .pages
+                .iter()
+                .map(|page| bind_param(&mut values, page))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let alias_params = query
+                .pages
+                .iter()
+                .map(|page| bind_param(&mut values, &app::sanitize_kebab(page)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            sql.push_str(&format!(
+                " AND (p.title COLLATE NOCASE IN ({params}) OR p.uid IN ({params})
+                   OR p.id IN (SELECT page_id FROM page_aliases
+                     WHERE alias_uid IN ({alias_params})))"
+            ));
+        }
+        if !query.block_types.is_empty() {
+            let params = query
+                .block_types
+                .iter()
+                .map(|block_type| {
+                    let name = serde_json::to_value(block_type)
+                        .ok()
+                        .and_then(|value| value.as_str().map(str::to_string))
+                        .unwrap_or_default();
+                    bind_param(&mut values, &name)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            sql.push_str(&format!(
+                " AND COALESCE(CASE WHEN json_valid(b.props)
+                    THEN json_extract(b.props, '$.block_type') END, 'text') IN ({params})"
+            ));
+        }
+        for tag in &query.tags {
+            let param = bind_param(&mut values, &tag.trim().to_lowercase());
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM block_tags bt JOIN tags t ON t.id = bt.tag_id
+                   WHERE bt.block_id = b.id AND t.name = {param})"
+            ));
+        }
+        for bound in &query.updated {
+            let (start, end) = bound.unix_range();
+            if let Some(start) = start {
+                let param = bind_param(&mut values, &start.to_string());
+                sql.push_str(&format!(" AND b.updated_at >= CAST({param} AS INTEGER)"));
+            }
+            if let Some(end) = end {
+                let param = bind_param(&mut values, &end.to_string());
+                sql.push_str(&format!(" AND b.updated_at < CAST({param} AS INTEGER)"));
+            }
+        }
+        if let Some(done) = query.todo_done {
+            let mark = if done { "[x]" } else { "[ ]" };
+            let bare = bind_param(&mut values, &format!("{mark}%"));
+            let bulleted = bind_param(&mut values, &format!("- {mark}%"));
+            sql.push_str(&format!(
+                " AND (ltrim(b.text) LIKE {bare} OR ltrim(b.text) LIKE {bulleted})"
+            ));
+        }
+        for filter in &query.properties {
+            let value_type = property_value_type(&self.conn, &filter.key)?;
+            let key = bind_param(&mut values, &filter.key);
+            let value_expr = format!(
+                "(SELECT bp.value FROM block_properties bp
+                  WHERE bp.block_id = b.id AND bp.key = {key})"
+            );
+            let predicate =
+                property_filter_predicate(&mut values, filter, &value_expr, &value_type)?;
+            sql.push_str(&format!(" AND {predicate}"));
+        }
+        if fts.is_some() {
+            sql.push_str(" ORDER BY bm25(blocks_fts)");
+        } else {
+            sql.push_str(" ORDER BY b.updated_at DESC, b.id DESC");
+        }
+        sql.push_str(&format!(" LIMIT {}", limit.max(0)));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(SearchHit {
+                block_id: row.get(0)?,
+                block_uid: row.get(1)?,
+                page_uid: row.get(2)?,
+                page_title: row.get(3)?,
+                text: row.get(4)?,
+                snippet: row.get(5)?,
             })
         })?;
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Loads every task block across all pages (or one page) that passes `filter`, in page
+    /// title and outline order. The filter runs against the task properties indexed in
+    /// `block_properties`; date bounds compare `YYYY-MM-DD` values.
+    pub fn query_tasks(&self, filter: &TaskFilter) -> rusqlite::Result<Vec<TaskRecord>> {
+        let mut values: Vec<String> = Vec::new();
+        let mut sql = format!(
+            "SELECT b.uid, b.text, p.uid, p.title
+             FROM blocks b
+             JOIN pages p ON p.id = b.page_id
+             WHERE ({OPEN_TASK_SQL} OR {DONE_TASK_SQL})"
+        );
+        if let Some(page_uid) = &filter.page_uid {
+            let param = bind_param(&mut values, page_uid);
+            sql.push_str(&format!(" AND p.uid = {param}"));
+        }
+        if !filter.states.is_empty() {
+            let states = filter
+                .states
+                .iter()
+                .map(|state| task_state_predicate(&mut values, *state))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            sql.push_str(&format!(" AND ({states})"));
+        }
+        if let Some(priority) = filter.priority {
+            let key = bind_param(&mut values, tasks::PRIORITY_KEY);
+            let aliases = bind_params(&mut values, priority.aliases());
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM block_properties bp
+                   WHERE bp.block_id = b.id AND bp.key = {key}
+                     AND lower(ltrim(bp.value, '#')) IN ({aliases}))"
+            ));
+        }
+        if filter.due_from.is_some() || filter.due_to.is_some() {
+            let scheduled = bind_param(&mut values, tasks::SCHEDULED_KEY);
+            let deadline = bind_param(&mut values, tasks::DEADLINE_KEY);
+            // Dates may be written as `[[YYYY-MM-DD]]` page links.
+            let date = "trim(CASE WHEN bp.value LIKE '[[%]]'
+                THEN substr(bp.value, 3, length(bp.value) - 4) ELSE bp.value END)";
+            // The agenda date: the earlier of the scheduled and deadline days.
+            let agenda_date = format!(
+                "(SELECT min({date}) FROM block_properties bp
+                  WHERE bp.block_id = b.id AND bp.key IN ({scheduled}, {deadline})
+                    AND date({date}) = {date})"
+            );
+            if let Some(from) = filter.due_from {
+                let param = bind_param(
+                    &mut values,
+                    &from.format(tasks::TASK_DATE_FORMAT).to_string(),
+                );
+                sql.push_str(&format!(" AND {agenda_date} >= {param}"));
+            }
+            if let Some(to) = filter.due_to {
+                let param =
+                    bind_param(&mut values, &to.format(tasks::TASK_DATE_FORMAT).to_string());
+                sql.push_str(&format!(" AND {agenda_date} <= {param}"));
+            }
+        }
+        sql.push_str(" ORDER BY p.title COLLATE NOCASE, b.sort_key");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(block_uid, text, page_uid, page_title)| {
+                Some(TaskRecord {
+                    block_uid,
+                    page_uid,
+                    page_title,
+                    task: tasks::parse_task(text.trim_start())?,
+                })
+            })
+            .collect())
+    }
+
+    pub fn load_blocks_for_page(&self, page_id: i64) -> rusqlite::Result<Vec<BlockSnapshot>> {
+        load_page_snapshots(&self.conn, page_id)
     }
 
     pub fn replace_blocks_for_page(
         &mut self,
         page_id: i64,
         blocks: &[BlockSnapshot],
+    ) -> rusqlite::Result<()> {
+        let changes = self.plan_block_changes(page_id, blocks)?;
+        self.apply_block_changes(
+            page_id,
+            &changes.inserts,
+            &changes.updates,
+            &changes.moves,
+            &changes.deletes,
+        )
+    }
+
+    /// Diffs `blocks` against the rows stored for `page_id`, reusing existing sort keys for
+    /// blocks that kept their relative order.
+    pub fn plan_block_changes(
+        &self,
+        page_id: i64,
+        blocks: &[BlockSnapshot],
+    ) -> rusqlite::Result<BlockChanges> {
+        let mut stmt = self.conn.prepare(
+            "SELECT b.uid, parent.uid, b.sort_key, b.text, b.props
+             FROM blocks b
+             LEFT JOIN blocks parent ON parent.id = b.parent_id
+             WHERE b.page_id = ?1
+             ORDER BY b.sort_key ASC, b.id ASC",
+        )?;
+        let rows = stmt.query_map([page_id], |row| {
+            let props: String = row.get(4)?;
+            Ok(StoredBlock {
+                snapshot: BlockSnapshot {
+                    uid: row.get(0)?,
+                    text: row.get(3)?,
+                    indent: parse_indent(&props),
+                    block_type: parse_block_type(&props),
+                },
+                parent_uid: row.get(1)?,
+                sort_key: row.get(2)?,
+            })
+        })?;
+        let existing = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(diff_block_rows(&existing, blocks))
+    }
+
+    pub fn apply_block_changes(
+        &mut self,
+        page_id: i64,
+        inserts: &[BlockInsert],
+        updates: &[BlockSnapshot],
+        moves: &[BlockMove],
+        deletes: &[String],
+    ) -> rusqlite::Result<()> {
+        self.write_block_changes(page_id, inserts, updates, moves, deletes, true)
+    }
+
+    /// Applies block changes in one transaction; see `write_block_rows`.
+    fn write_block_changes(
+        &mut self,
+        page_id: i64,
+        inserts: &[BlockInsert],
+        updates: &[BlockSnapshot],
+        moves: &[BlockMove],
+        deletes: &[String],
+        coalesce_revision: bool,
     ) -> rusqlite::Result<()> {
         let tx = self.conn.transaction()?;
-        tx.execute("DELETE FROM blocks WHERE page_id = ?1", [page_id])?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO blocks (uid, page_id, parent_id, sort_key, text, props)
-                 VALUES (?1, ?2, NULL, ?3, ?4, ?5)",
-            )?;
-            for (index, block) in blocks.iter().enumerate() {
-                let sort_key = format!("{:06}", index);
-                let props = serialize_block_props(block);
-                stmt.execute(params![block.uid, page_id, sort_key, block.text, props])?;
-            }
-        }
+        write_block_rows(
+            &tx,
+            page_id,
+            inserts,
+            updates,
+            moves,
+            deletes,
+            coalesce_revision,
+        )?;
+        tx.commit()
+    }
+
+    /// Creates the page `uid` titled `title` holding `blocks`, in one transaction, so a failed
+    /// block write leaves no page behind.
+    pub fn insert_page_with_blocks(
+        &mut self,
+        uid: &str,
+        title: &str,
+        blocks: &[BlockSnapshot],
+    ) -> rusqlite::Result<i64> {
+        let tx = self.conn.transaction()?;
         tx.execute(
-            "DELETE FROM review_queue
-             WHERE page_uid = (SELECT uid FROM pages WHERE id = ?1)
-               AND NOT EXISTS (
-                 SELECT 1
-                 FROM blocks
-                 WHERE blocks.page_id = ?1
-                   AND blocks.uid = review_queue.block_uid
-               )",
-            [page_id],
+            "INSERT INTO pages (uid, title) VALUES (?1, ?2)",
+            params![uid, title],
+        )?;
+        let page_id = tx.last_insert_rowid();
+        let changes = diff_block_rows(&[], blocks);
+        write_block_rows(
+            &tx,
+            page_id,
+            &changes.inserts,
+            &changes.updates,
+            &changes.moves,
+            &changes.deletes,
+            true,
         )?;
         tx.commit()?;
-        Ok(())
+        Ok(page_id)
     }
 
-    pub fn search_pages(&self, query: &str) -> rusqlite::Result<Vec<i64>> {
-        let mut stmt = self
+    // --- Page revisions ---
+
+    /// Snapshots the page's current blocks as of `now` (unix ms). Saves within
+    /// `REVISION_INTERVAL_MS` of the latest revision update it in place, and older revisions are
+    /// compacted. Block saves record revisions automatically.
+    pub fn record_page_revision_at(&self, page_id: i64, now: i64) -> rusqlite::Result<()> {
+        record_page_revision(&self.conn, page_id, now, true)
+    }
+
+    /// Lists the page's revisions, newest first.
+    pub fn list_page_revisions(&self, page_id: i64) -> rusqlite::Result<Vec<PageRevisionRecord>> {
+        if let Some((id, _, true)) = latest_page_revision(&self.conn, page_id)? {
+            refresh_page_revision(&self.conn, page_id, id)?;
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT id, page_id, created_at, block_count
+             FROM page_revisions
+             WHERE page_id = ?1
+             ORDER BY created_at DESC, id DESC",
+        )?;
+        let rows = stmt.query_map([page_id], |row| {
+            Ok(PageRevisionRecord {
+                id: row.get(0)?,
+                page_id: row.get(1)?,
+                created_at: row.get(2)?,
+                block_count: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn load_page_revision(&self, revision_id: i64) -> rusqlite::Result<Option<PageRevision>> {
+        let stale_page: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT page_id FROM page_revisions WHERE id = ?1 AND stale = 1",
+                [revision_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(page_id) = stale_page {
+            refresh_page_revision(&self.conn, page_id, revision_id)?;
+        }
+        let row = self
+            .conn
+            .query_row(
+                "SELECT id, page_id, created_at, blocks FROM page_revisions WHERE id = ?1",
+                [revision_id],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, i64>(2)?,
+                        row.get::<_, String>(3)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((id, page_id, created_at, blocks)) = row else {
+            return Ok(None);
+        };
+        let blocks = serde_json::from_str(&blocks).map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(err))
+        })?;
+        Ok(Some(PageRevision {
+            id,
+            page_id,
+            created_at,
+            blocks,
+        }))
+    }
+
+    /// Replaces the page's blocks with the revision's. The restore gets a revision of its own,
+    /// so the state it replaced stays in history.
+    pub fn restore_page_revision(&mut self, revision_id: i64) -> rusqlite::Result<bool> {
+        let Some(revision) = self.load_page_revision(revision_id)? else {
+            return Ok(false);
+        };
+        let changes = self.plan_block_changes(revision.page_id, &revision.blocks)?;
+        self.write_block_changes(
+            revision.page_id,
+            &changes.inserts,
+            &changes.updates,
+            &changes.moves,
+            &changes.deletes,
+            false,
+        )?;
+        Ok(true)
+    }
+
+    pub fn search_pages(&self, query: &str) -> rusqlite::Result<Vec<i64>> {
+        let Some(query) = search::fts_match_expression(query) else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = self
             .conn
             .prepare("SELECT rowid FROM pages_fts WHERE pages_fts MATCH ?1 ORDER BY rowid")?;
         let rows = stmt.query_map([query], |row| row.get(0))?;
@@ -758,12 +1495,31 @@ impl Database {
 
     // --- Page properties ---
 
+    /// Upserts a page property. Fails without writing when `value` does not parse as
+    /// `value_type`; empty values are always accepted.
     pub fn set_page_property(
         &self,
         page_id: i64,
         key: &str,
         value: &str,
         value_type: &str,
+    ) -> Result<(), DbError> {
+        if !properties::is_valid_property_value(value_type, value) {
+            return Err(invalid_property_value(key, value_type, value));
+        }
+        let value = PropertyValue::parse(value_type, value)
+            .map(|value| value.to_raw())
+            .unwrap_or_default();
+        Ok(self.write_page_property(page_id, key, &value, value_type)?)
+    }
+
+    /// Upserts a page property whose `value` is already in its stored form.
+    fn write_page_property(
+        &self,
+        page_id: i64,
+        key: &str,
+        value: &str,
+        value_type: &str,
     ) -> rusqlite::Result<()> {
         self.conn.execute(
             "INSERT INTO page_properties (page_id, key, value, value_type)
@@ -771,6 +1527,9 @@ impl Database {
              ON CONFLICT(page_id, key) DO UPDATE SET value = ?3, value_type = ?4",
             params![page_id, key, value, value_type],
         )?;
+        if key == properties::ALIAS_KEY {
+            sync_page_aliases(&self.conn, page_id, value)?;
+        }
         Ok(())
     }
 
@@ -799,9 +1558,237 @@ impl Database {
             "DELETE FROM page_properties WHERE page_id = ?1 AND key = ?2",
             params![page_id, key],
         )?;
+        if key == properties::ALIAS_KEY {
+            sync_page_aliases(&self.conn, page_id, "")?;
+        }
+        Ok(())
+    }
+
+    // --- Page aliases ---
+
+    pub fn list_page_aliases(&self, page_id: i64) -> rusqlite::Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT alias FROM page_aliases WHERE page_id = ?1 ORDER BY id ASC")?;
+        let rows = stmt.query_map([page_id], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    pub fn list_all_page_aliases(&self) -> rusqlite::Result<Vec<PageAliasRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.page_id, p.uid, a.alias
+             FROM page_aliases a
+             JOIN pages p ON p.id = a.page_id
+             ORDER BY a.page_id ASC, a.id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PageAliasRecord {
+                page_id: row.get(0)?,
+                page_uid: row.get(1)?,
+                alias: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Replaces the page's aliases, stored as its `alias` page property.
+    pub fn set_page_aliases(&self, page_id: i64, aliases: &[String]) -> rusqlite::Result<()> {
+        // Commas separate aliases in the property value; kebab-casing drops them anyway, so the
+        // alias still resolves the same way.
+        let aliases: Vec<String> = aliases
+            .iter()
+            .map(|alias| alias.replace(',', " ").trim().to_string())
+            .filter(|alias| !alias.is_empty())
+            .collect();
+        if aliases.is_empty() {
+            return self.delete_page_property(page_id, properties::ALIAS_KEY);
+        }
+        let value = properties::split_multi_select(&aliases.join(",")).join(", ");
+        self.write_page_property(page_id, properties::ALIAS_KEY, &value, "multi_select")
+    }
+
+    /// Adds `alias` to the page unless it already resolves there.
+    pub fn add_page_alias(&self, page_id: i64, alias: &str) -> rusqlite::Result<()> {
+        let alias_uid = app::sanitize_kebab(alias);
+        let mut aliases = self.list_page_aliases(page_id)?;
+        if alias.trim().is_empty()
+            || aliases
+                .iter()
+                .any(|existing| app::sanitize_kebab(existing) == alias_uid)
+        {
+            return Ok(());
+        }
+        aliases.push(alias.trim().to_string());
+        self.set_page_aliases(page_id, &aliases)
+    }
+
+    /// Renames a page, keeping the old title as an alias so existing links still resolve. An
+    /// alias matching the new title is dropped.
+    pub fn rename_page(&self, page_id: i64, title: &str) -> rusqlite::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.write_page_rename(page_id, title)?;
+        tx.commit()
+    }
+
+    /// Renames a page like [`Database::rename_page`] and rewrites `[[old title]]` links in
+    /// every block to the new title, all in one transaction. Returns the new text of each
+    /// rewritten block by uid.
+    pub fn rename_page_and_links(
+        &self,
+        page_id: i64,
+        title: &str,
+    ) -> rusqlite::Result<HashMap<String, String>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let old_title = self.write_page_rename(page_id, title)?;
+        let from = old_title.as_deref().unwrap_or_default().trim();
+        let to = title.trim();
+        let mut rewritten = HashMap::new();
+        if !from.is_empty()
+            && !to.is_empty()
+            && app::sanitize_kebab(from) != app::sanitize_kebab(to)
+        {
+            for record in self.list_blocks_with_wikilinks()? {
+                let next = links::replace_wikilinks_in_text(&record.text, from, to);
+                if next != record.text {
+                    self.update_block_text_by_uid(&record.block_uid, &next)?;
+                    rewritten.insert(record.block_uid, next);
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(rewritten)
+    }
+
+    /// Writes the new title and alias list; returns the old title.
+    fn write_page_rename(&self, page_id: i64, title: &str) -> rusqlite::Result<Option<String>> {
+        let old_title: Option<String> = self
+            .conn
+            .query_row("SELECT title FROM pages WHERE id = ?1", [page_id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        self.update_page_title(page_id, title)?;
+        let title_uid = app::sanitize_kebab(title);
+        let mut aliases: Vec<String> = self
+            .list_page_aliases(page_id)?
+            .into_iter()
+            .filter(|alias| app::sanitize_kebab(alias) != title_uid)
+            .collect();
+        if let Some(old_title) = old_title.as_deref().filter(|old| !old.trim().is_empty()) {
+            let old_uid = app::sanitize_kebab(old_title);
+            if old_uid != title_uid
+                && !aliases
+                    .iter()
+                    .any(|alias| app::sanitize_kebab(alias) == old_uid)
+            {
+                aliases.push(old_title.trim().to_string());
+            }
+        }
+        self.set_page_aliases(page_id, &aliases)?;
+        Ok(old_title)
+    }
+
+    /// Finds the page a link target names: by uid first, then by alias. When several pages
+    /// claim an alias the oldest wins.
+    pub fn resolve_page(&self, name: &str) -> rusqlite::Result<Option<PageRecord>> {
+        let uid = app::sanitize_kebab(name);
+        if let Some(page) = self.get_page_by_uid(&uid)? {
+            return Ok(Some(page));
+        }
+        self.conn
+            .query_row(
+                "SELECT p.id, p.uid, p.title
+                 FROM page_aliases a
+                 JOIN pages p ON p.id = a.page_id
+                 WHERE a.alias_uid = ?1
+                 ORDER BY p.id ASC
+                 LIMIT 1",
+                [uid],
+                |row| {
+                    Ok(PageRecord {
+                        id: row.get(0)?,
+                        uid: row.get(1)?,
+                        title: row.get(2)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// The day a date link like `[[next friday]]` in block `block_uid` points to, or `None`
+    /// when `name` is a page or not a date. Dates count from the daily page the block sits on,
+    /// else from the day the block was written, so the link keeps meaning the same day.
+    pub fn resolve_date_link(
+        &self,
+        name: &str,
+        block_uid: Option<&str>,
+    ) -> rusqlite::Result<Option<chrono::NaiveDate>> {
+        if self.resolve_page(name)?.is_some() {
+            return Ok(None);
+        }
+        let block_id = match block_uid {
+            Some(uid) => self.get_block_by_uid(uid)?.map(|block| block.id),
+            None => None,
+        };
+        let (_, origin) = date_link_origin(&self.conn, block_id)?;
+        Ok(journals::resolve_date_phrase(name, origin))
+    }
+
+    // --- Block properties ---
+
+    /// Writes `key:: value` into the block's text and re-indexes it. Fails without touching the
+    /// block when the value does not match the key's `PropertyDefinition.value_type`.
+    pub fn set_block_property(&self, block_id: i64, key: &str, value: &str) -> Result<(), DbError> {
+        let key = key.trim().to_ascii_lowercase();
+        let value_type = property_value_type(&self.conn, &key)?;
+        if !properties::is_valid_property_value(&value_type, value) {
+            return Err(invalid_property_value(&key, &value_type, value));
+        }
+        let block = self.require_block(block_id)?;
+        self.rewrite_block_text(
+            &block,
+            &properties::set_block_property_in_text(&block.text, &key, value),
+        )?;
         Ok(())
     }
 
+    pub fn get_block_properties(
+        &self,
+        block_id: i64,
+    ) -> rusqlite::Result<Vec<BlockPropertyRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, block_id, key, value, value_type, sort_order
+             FROM block_properties
+             WHERE block_id = ?1
+             ORDER BY sort_order ASC, key ASC",
+        )?;
+        let rows = stmt.query_map([block_id], |row| {
+            Ok(BlockPropertyRecord {
+                id: row.get(0)?,
+                block_id: row.get(1)?,
+                key: row.get(2)?,
+                value: row.get(3)?,
+                value_type: row.get(4)?,
+                sort_order: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn delete_block_property(&self, block_id: i64, key: &str) -> rusqlite::Result<()> {
+        let block = self.require_block(block_id)?;
+        self.rewrite_block_text(
+            &block,
+            &properties::remove_block_property_in_text(&block.text, key),
+        )
+    }
+
+    /// Loads a block, failing with `QueryReturnedNoRows` like the lookups it replaces.
+    fn require_block(&self, block_id: i64) -> rusqlite::Result<BlockRecord> {
+        self.get_block(block_id)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
     pub fn upsert_property_definition(
         &self,
         key: &str,
@@ -817,6 +1804,20 @@ impl Database {
         Ok(())
     }
 
+    /// Stores the choices of a `select` definition as a JSON array.
+    pub fn set_property_definition_options(
+        &self,
+        key: &str,
+        options: &[String],
+    ) -> rusqlite::Result<()> {
+        let encoded = serde_json::to_string(options).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "UPDATE property_definitions SET options = ?2 WHERE key = ?1",
+            params![key, encoded],
+        )?;
+        Ok(())
+    }
+
     pub fn list_property_definitions(&self) -> rusqlite::Result<Vec<PropertyDefinition>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, key, label, value_type, options, sort_order
@@ -840,19 +1841,106 @@ impl Database {
         &self,
         filter_key: Option<&str>,
         filter_value: Option<&str>,
-    ) -> rusqlite::Result<Vec<(PageRecord, Vec<PagePropertyRecord>)>> {
-        let pages = self.list_pages()?;
-        let mut results = Vec::new();
-        for page in pages {
-            let props = self.get_page_properties(page.id)?;
-            if let (Some(fk), Some(fv)) = (filter_key, filter_value) {
-                if !props.iter().any(|p| p.key == fk && p.value.contains(fv)) {
-                    continue;
-                }
+    ) -> Result<Vec<PageQueryRow>, DbError> {
+        let mut query = PageQuery::default();
+        if let (Some(key), Some(value)) = (filter_key, filter_value) {
+            query.filters.push(PropertyFilter {
+                key: key.to_string(),
+                op: FilterOp::Contains,
+                value: value.to_string(),
+                values: Vec::new(),
+            });
+        }
+        self.query_pages(&query)
+    }
+
+    /// Runs a database-view query in SQL and returns the matching pages with all of their
+    /// properties. The `title` key addresses the page title.
+    pub fn query_pages(&self, query: &PageQuery) -> Result<Vec<PageQueryRow>, DbError> {
+        let mut sql = String::from("SELECT p.id, p.uid, p.title FROM pages p WHERE 1 = 1");
+        let mut values: Vec<String> = Vec::new();
+
+        if let PageSource::Tag(tag) = &query.source {
+            let param = bind_param(&mut values, &tag.trim().to_lowercase());
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM blocks b
+                   JOIN block_tags bt ON bt.block_id = b.id
+                   JOIN tags t ON t.id = bt.tag_id
+                   WHERE b.page_id = p.id AND t.name = {param})"
+            ));
+        }
+
+        for filter in &query.filters {
+            let value_type = page_property_value_type(&self.conn, &filter.key)?;
+            let value_expr = page_property_expr(&mut values, &filter.key);
+            let predicate =
+                property_filter_predicate(&mut values, filter, &value_expr, &value_type)?;
+            sql.push_str(&format!(" AND {predicate}"));
+        }
+
+        let mut order_terms = Vec::new();
+        for sort in &query.sorts {
+            let direction = if sort.descending { "DESC" } else { "ASC" };
+            if sort.key == TITLE_KEY {
+                order_terms.push(format!("p.title COLLATE NOCASE {direction}"));
+                continue;
             }
-            results.push((page, props));
+            let value_type = page_property_value_type(&self.conn, &sort.key)?;
+            let value_expr = page_property_expr(&mut values, &sort.key);
+            order_terms.push(format!("COALESCE({value_expr}, '') = ''"));
+            order_terms.push(format!(
+                "{} {direction}",
+                typed_sql_expr(&value_expr, &value_type)
+            ));
+        }
+        order_terms.push("p.title COLLATE NOCASE ASC".to_string());
+        sql.push_str(&format!(" ORDER BY {}", order_terms.join(", ")));
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit.max(0)));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let pages = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok(PageRecord {
+                    id: row.get(0)?,
+                    uid: row.get(1)?,
+                    title: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let ids = serde_json::to_string(&pages.iter().map(|page| page.id).collect::<Vec<_>>())
+            .unwrap_or_else(|_| "[]".to_string());
+        let mut props_stmt = self.conn.prepare(
+            "SELECT id, page_id, key, value, value_type, sort_order
+             FROM page_properties
+             WHERE page_id IN (SELECT value FROM json_each(?1))
+             ORDER BY sort_order ASC, key ASC",
+        )?;
+        let mut props_by_page: HashMap<i64, Vec<PagePropertyRecord>> = HashMap::new();
+        let props = props_stmt.query_map([ids], |row| {
+            Ok(PagePropertyRecord {
+                id: row.get(0)?,
+                page_id: row.get(1)?,
+                key: row.get(2)?,
+                value: row.get(3)?,
+                value_type: row.get(4)?,
+                sort_order: row.get(5)?,
+            })
+        })?;
+        for prop in props {
+            let prop = prop?;
+            props_by_page.entry(prop.page_id).or_default().push(prop);
         }
-        Ok(results)
+
+        Ok(pages
+            .into_iter()
+            .map(|page| {
+                let props = props_by_page.remove(&page.id).unwrap_or_default();
+                (page, props)
+            })
+            .collect())
     }
 
     pub fn grant_plugin_permission(
@@ -949,6 +2037,263 @@ impl Database {
         rows.collect()
     }
 
+    /// Lists blocks on other pages that link to, embed or tag the page `page_uid`, matching
+    /// its uid, its current title and its aliases.
+    pub fn list_backlinks(&self, page_uid: &str) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        self.query_backlinks(page_uid, false)
+    }
+
+    /// Like `list_backlinks`, but keeps the links a page makes to itself.
+    pub fn list_backlinks_with_own_page(
+        &self,
+        page_uid: &str,
+    ) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        self.query_backlinks(page_uid, true)
+    }
+
+    fn query_backlinks(
+        &self,
+        page_uid: &str,
+        include_own_page: bool,
+    ) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        let title_uid = self
+            .get_page_by_uid(page_uid)?
+            .map(|page| app::sanitize_kebab(&page.title))
+            .unwrap_or_else(|| page_uid.to_string());
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT DISTINCT b.uid, b.text, p.uid, p.title, b.sort_key
+             FROM edges e
+             JOIN blocks b ON b.id = e.from_block_id
+             JOIN pages p ON p.id = b.page_id
+             WHERE e.kind IN ('wikilink', 'embed', 'tag')
+               AND (e.to_block_uid IN (?1, ?2) OR e.to_block_uid IN (
+                 SELECT a.alias_uid FROM page_aliases a
+                 JOIN pages ap ON ap.id = a.page_id
+                 WHERE ap.uid = ?1))
+               AND (?3 OR p.uid != ?1)
+             ORDER BY p.title ASC, b.sort_key ASC",
+        )?;
+        let rows = stmt.query_map(
+            params![page_uid, title_uid, include_own_page],
+            map_block_page_record,
+        )?;
+        rows.collect()
+    }
+
+    /// Lists every block tagged with the page `page_uid`, including blocks on the page itself.
+    pub fn list_tagged_blocks(&self, page_uid: &str) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        let title_uid = self
+            .get_page_by_uid(page_uid)?
+            .map(|page| app::sanitize_kebab(&page.title))
+            .unwrap_or_else(|| page_uid.to_string());
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT b.uid, b.text, p.uid, p.title, b.sort_key
+             FROM edges e
+             JOIN blocks b ON b.id = e.from_block_id
+             JOIN pages p ON p.id = b.page_id
+             WHERE e.kind = 'tag'
+               AND (e.to_block_uid IN (?1, ?2) OR e.to_block_uid IN (
+                 SELECT a.alias_uid FROM page_aliases a
+                 JOIN pages ap ON ap.id = a.page_id
+                 WHERE ap.uid = ?1))
+             ORDER BY p.title ASC, b.sort_key ASC",
+        )?;
+        let rows = stmt.query_map(params![page_uid, title_uid], map_block_page_record)?;
+        rows.collect()
+    }
+
+    pub fn list_block_backlinks(&self, block_uid: &str) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT b.uid, b.text, p.uid, p.title, b.sort_key
+             FROM edges e
+             JOIN blocks b ON b.id = e.from_block_id
+             JOIN pages p ON p.id = b.page_id
+             WHERE e.kind IN ('block_ref', 'embed')
+               AND e.to_block_uid = ?1
+               AND b.uid != ?1
+             ORDER BY p.title ASC, b.sort_key ASC",
+        )?;
+        let rows = stmt.query_map([block_uid], map_block_page_record)?;
+        rows.collect()
+    }
+
+    /// Looks up the current text and page of each block in `block_uids`, keyed by uid. Unknown
+    /// uids are left out.
+    pub fn lookup_blocks(
+        &self,
+        block_uids: &[String],
+    ) -> rusqlite::Result<HashMap<String, BlockPageRecord>> {
+        if block_uids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut values = Vec::new();
+        let placeholders: Vec<String> = block_uids
+            .iter()
+            .map(|uid| bind_param(&mut values, uid))
+            .collect();
+        let sql = format!(
+            "SELECT b.uid, b.text, p.uid, p.title
+             FROM blocks b
+             JOIN pages p ON p.id = b.page_id
+             WHERE b.uid IN ({})",
+            placeholders.join(", ")
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), map_block_page_record)?;
+        rows.map(|row| row.map(|record| (record.block_uid.clone(), record)))
+            .collect()
+    }
+
+    /// Counts the other blocks that reference or embed each block in `block_uids`. Blocks
+    /// nobody references are left out.
+    pub fn count_block_references(
+        &self,
+        block_uids: &[String],
+    ) -> rusqlite::Result<HashMap<String, i64>> {
+        if block_uids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut values = Vec::new();
+        let placeholders: Vec<String> = block_uids
+            .iter()
+            .map(|uid| bind_param(&mut values, uid))
+            .collect();
+        let sql = format!(
+            "SELECT e.to_block_uid, COUNT(DISTINCT e.from_block_id)
+             FROM edges e
+             JOIN blocks b ON b.id = e.from_block_id
+             WHERE e.kind IN ('block_ref', 'embed')
+               AND e.to_block_uid IN ({})
+               AND b.uid != e.to_block_uid
+             GROUP BY e.to_block_uid",
+            placeholders.join(", ")
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect()
+    }
+
+    /// Loads the block `block_uid` followed by its descendants, with indents rebased so the
+    /// block itself sits at zero.
+    pub fn load_block_embed(&self, block_uid: &str) -> rusqlite::Result<Option<BlockEmbed>> {
+        let Some(block) = self.get_block_by_uid(block_uid)? else {
+            return Ok(None);
+        };
+        let page: (String, String) = self.conn.query_row(
+            "SELECT uid, title FROM pages WHERE id = ?1",
+            [block.page_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let page_blocks = load_page_snapshots(&self.conn, block.page_id)?;
+        let Some(start) = page_blocks.iter().position(|b| b.uid == block_uid) else {
+            return Ok(None);
+        };
+        let base = page_blocks[start].indent;
+        let end = page_blocks[start + 1..]
+            .iter()
+            .position(|b| b.indent <= base)
+            .map(|offset| start + 1 + offset)
+            .unwrap_or(page_blocks.len());
+        let blocks = page_blocks[start..end]
+            .iter()
+            .cloned()
+            .map(|mut b| {
+                b.indent -= base;
+                b
+            })
+            .collect();
+        Ok(Some(BlockEmbed {
+            page_uid: page.0,
+            page_title: page.1,
+            blocks,
+        }))
+    }
+
+    /// Loads the whole outline of the page `name` (a title, uid or alias) for
+    /// `{{embed [[name]]}}`.
+    pub fn load_page_embed(&self, name: &str) -> rusqlite::Result<Option<BlockEmbed>> {
+        let Some(page) = self.resolve_page(name)? else {
+            return Ok(None);
+        };
+        Ok(Some(BlockEmbed {
+            blocks: load_page_snapshots(&self.conn, page.id)?,
+            page_uid: page.uid,
+            page_title: page.title,
+        }))
+    }
+
+    /// Lists tags that are attached to at least one block, most used first.
+    pub fn list_tags(&self) -> rusqlite::Result<Vec<TagSummary>> {
+        self.search_tags("", i64::MAX)
+    }
+
+    /// Lists tags whose name starts with `prefix` (case-insensitive), most used first.
+    pub fn search_tags(&self, prefix: &str, limit: i64) -> rusqlite::Result<Vec<TagSummary>> {
+        let pattern = format!("{}%", escape_like(&prefix.trim().to_lowercase()));
+        let mut stmt = self.conn.prepare(
+            "SELECT t.name, COUNT(bt.block_id) AS block_count
+             FROM tags t
+             JOIN block_tags bt ON bt.tag_id = t.id
+             WHERE t.name LIKE ?1 ESCAPE '\\'
+             GROUP BY t.id
+             ORDER BY block_count DESC, t.name ASC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![pattern, limit], |row| {
+            Ok(TagSummary {
+                name: row.get(0)?,
+                block_count: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn list_blocks_for_tag(&self, name: &str) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        let tags = [name.trim().to_lowercase()];
+        self.search_blocks_with_tags("", &tags, i64::MAX)
+    }
+
+    /// Full-text block search restricted to blocks carrying every tag in `tags`. An empty
+    /// `query` lists all blocks with those tags.
+    pub fn search_blocks_with_tags(
+        &self,
+        query: &str,
+        tags: &[String],
+        limit: i64,
+    ) -> rusqlite::Result<Vec<BlockPageRecord>> {
+        let query = search::fts_match_expression(query).unwrap_or_default();
+        let mut sql =
+            String::from("SELECT b.uid, b.text, p.uid, p.title, b.sort_key FROM blocks b");
+        if !query.is_empty() {
+            sql.push_str(" JOIN blocks_fts fts ON b.id = fts.rowid");
+        }
+        sql.push_str(" JOIN pages p ON b.page_id = p.id WHERE 1 = 1");
+        let mut values: Vec<String> = Vec::new();
+        if !query.is_empty() {
+            values.push(query.clone());
+            sql.push_str(&format!(" AND blocks_fts MATCH ?{}", values.len()));
+        }
+        for tag in tags {
+            values.push(tag.clone());
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM block_tags bt JOIN tags t ON t.id = bt.tag_id
+                   WHERE bt.block_id = b.id AND t.name = ?{})",
+                values.len()
+            ));
+        }
+        if query.is_empty() {
+            sql.push_str(" ORDER BY p.title ASC, b.sort_key ASC");
+        } else {
+            sql.push_str(" ORDER BY bm25(blocks_fts)");
+        }
+        sql.push_str(&format!(" LIMIT {limit}"));
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), map_block_page_record)?;
+        rows.collect()
+    }
+
     pub fn list_blocks_with_wikilinks(&self) -> rusqlite::Result<Vec<BlockPageRecord>> {
         let mut stmt = self.conn.prepare(
             "SELECT blocks.uid, blocks.text, pages.uid, pages.title
@@ -1115,33 +2460,136 @@ impl Database {
         now: i64,
         limit: i64,
     ) -> rusqlite::Result<Vec<ReviewQueueItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, page_uid, block_uid, added_at, due_at, template, status, last_reviewed_at
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {REVIEW_QUEUE_COLUMNS}
              FROM review_queue
              WHERE status = 'pending' AND due_at <= ?1
              ORDER BY due_at ASC, id ASC
-             LIMIT ?2",
-        )?;
-        let rows = stmt.query_map(params![now, limit], |row| {
-            Ok(ReviewQueueItem {
-                id: row.get(0)?,
-                page_uid: row.get(1)?,
-                block_uid: row.get(2)?,
-                added_at: row.get(3)?,
-                due_at: row.get(4)?,
-                template: row.get(5)?,
-                status: row.get(6)?,
-                last_reviewed_at: row.get(7)?,
-            })
-        })?;
+             LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![now, limit], map_review_queue_item)?;
         rows.collect()
     }
 
-    pub fn mark_review_queue_item(
-        &self,
-        id: i64,
-        status: &str,
-        reviewed_at: i64,
+    pub fn get_review_queue_item(&self, id: i64) -> rusqlite::Result<Option<ReviewQueueItem>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {REVIEW_QUEUE_COLUMNS} FROM review_queue WHERE id = ?1"),
+                [id],
+                map_review_queue_item,
+            )
+            .optional()
+    }
+
+    /// Answers a review item: reschedules it from its SM-2 state and logs the answer. Returns
+    /// the updated item, or `None` if it no longer exists.
+    pub fn grade_review_queue_item(
+        &self,
+        id: i64,
+        grade: ReviewGrade,
+        reviewed_at: i64,
+    ) -> rusqlite::Result<Option<ReviewQueueItem>> {
+        let Some(item) = self.get_review_queue_item(id)? else {
+            return Ok(None);
+        };
+        let card = item.card.review(grade);
+        let due_at = card.due_at(reviewed_at);
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE review_queue
+             SET status = 'pending',
+                 last_reviewed_at = ?1,
+                 due_at = ?2,
+                 ease = ?3,
+                 interval_days = ?4,
+                 repetitions = ?5,
+                 lapses = ?6
+             WHERE id = ?7",
+            params![
+                reviewed_at,
+                due_at,
+                card.ease,
+                card.interval_days,
+                card.repetitions,
+                card.lapses,
+                id
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO review_log (queue_id, reviewed_at, grade, interval_days, ease)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                reviewed_at,
+                grade.as_str(),
+                card.interval_days,
+                card.ease
+            ],
+        )?;
+        tx.commit()?;
+        Ok(Some(ReviewQueueItem {
+            due_at,
+            status: "pending".to_string(),
+            last_reviewed_at: Some(reviewed_at),
+            card,
+            ..item
+        }))
+    }
+
+    /// Queue totals as of `now`, with answer counts and retention from the log since `since`.
+    pub fn review_stats(&self, now: i64, since: i64) -> rusqlite::Result<ReviewStats> {
+        let (due, pending, done, average_ease) = self.conn.query_row(
+            "SELECT
+                COALESCE(SUM(status = 'pending' AND due_at <= ?1), 0),
+                COALESCE(SUM(status = 'pending'), 0),
+                COALESCE(SUM(status = 'done'), 0),
+                AVG(CASE WHEN status = 'pending' AND repetitions > 0 THEN ease END)
+             FROM review_queue",
+            [now],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+        let (reviewed, failed): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(grade = 'again'), 0)
+             FROM review_log
+             WHERE reviewed_at >= ?1",
+            [since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(ReviewStats {
+            due,
+            pending,
+            done,
+            reviewed,
+            failed,
+            retention: (reviewed > 0).then(|| (reviewed - failed) as f64 / reviewed as f64),
+            average_ease,
+        })
+    }
+
+    /// Pending items due on each of `days` days starting at `day_start`. Anything already
+    /// overdue counts towards the first day.
+    pub fn review_forecast(&self, day_start: i64, days: usize) -> rusqlite::Result<Vec<i64>> {
+        let mut forecast = vec![0; days];
+        if days == 0 {
+            return Ok(forecast);
+        }
+        let end = day_start + days as i64 * DAY_MS;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT due_at FROM review_queue WHERE status = 'pending' AND due_at < ?1")?;
+        let rows = stmt.query_map([end], |row| row.get::<_, i64>(0))?;
+        for due_at in rows {
+            let day = (due_at? - day_start).max(0) / DAY_MS;
+            forecast[day as usize] += 1;
+        }
+        Ok(forecast)
+    }
+
+    pub fn mark_review_queue_item(
+        &self,
+        id: i64,
+        status: &str,
+        reviewed_at: i64,
         next_due_at: Option<i64>,
     ) -> rusqlite::Result<()> {
         if let Some(next_due) = next_due_at {
@@ -1166,6 +2614,889 @@ impl Database {
     }
 }
 
+const REVIEW_QUEUE_COLUMNS: &str = "id, page_uid, block_uid, added_at, due_at, template, status,
+     last_reviewed_at, ease, interval_days, repetitions, lapses";
+
+fn map_review_queue_item(row: &rusqlite::Row<'_>) -> rusqlite::Result<ReviewQueueItem> {
+    Ok(ReviewQueueItem {
+        id: row.get(0)?,
+        page_uid: row.get(1)?,
+        block_uid: row.get(2)?,
+        added_at: row.get(3)?,
+        due_at: row.get(4)?,
+        template: row.get(5)?,
+        status: row.get(6)?,
+        last_reviewed_at: row.get(7)?,
+        card: ReviewCard {
+            ease: row.get(8)?,
+            interval_days: row.get(9)?,
+            repetitions: row.get(10)?,
+            lapses: row.get(11)?,
+        },
+    })
+}
+
+fn map_block_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<BlockRecord> {
+    Ok(BlockRecord {
+        id: row.get(0)?,
+        uid: row.get(1)?,
+        page_id: row.get(2)?,
+        parent_id: row.get(3)?,
+        sort_key: row.get(4)?,
+        text: row.get(5)?,
+        props: row.get(6)?,
+    })
+}
+
+fn map_block_page_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<BlockPageRecord> {
+    Ok(BlockPageRecord {
+        block_uid: row.get(0)?,
+        text: row.get(1)?,
+        page_uid: row.get(2)?,
+        page_title: row.get(3)?,
+    })
+}
+
+/// Rewrites the block's outgoing `edges` and its `block_tags` rows from its text.
+fn sync_block_links(conn: &Connection, block_id: i64, text: &str) -> rusqlite::Result<()> {
+    let mut clear = conn.prepare_cached(
+        "DELETE FROM edges
+         WHERE from_block_id = ?1 AND kind IN ('wikilink', 'block_ref', 'embed', 'tag')",
+    )?;
+    clear.execute([block_id])?;
+    let mut insert = conn.prepare_cached(
+        "INSERT INTO edges (from_block_id, to_block_uid, kind) VALUES (?1, ?2, ?3)",
+    )?;
+    let targets = links::extract_link_targets(text);
+    for link in &targets {
+        insert.execute(params![block_id, link.target, link.kind.as_str()])?;
+    }
+    // Date links like `[[tomorrow]]` also point at the daily page they mean, so that page
+    // lists them as backlinks.
+    let today = chrono::Local::now().date_naive();
+    let mut origin = None;
+    for link in &targets {
+        let is_date = link.kind == links::EdgeKind::Wikilink
+            && journals::resolve_date_phrase(&link.target, today).is_some();
+        if !is_date || names_page(conn, &link.target)? {
+            continue;
+        }
+        if origin.is_none() {
+            origin = Some(date_link_origin(conn, Some(block_id))?);
+        }
+        let Some((settings, day)) = origin.as_ref() else {
+            continue;
+        };
+        let Some(date) = journals::resolve_date_phrase(&link.target, *day) else {
+            continue;
+        };
+        let daily_uid = app::sanitize_kebab(&settings.title(date));
+        if daily_uid != link.target {
+            insert.execute(params![block_id, daily_uid, link.kind.as_str()])?;
+        }
+    }
+
+    let mut clear_tags = conn.prepare_cached("DELETE FROM block_tags WHERE block_id = ?1")?;
+    clear_tags.execute([block_id])?;
+    let mut upsert_tag = conn.prepare_cached("INSERT OR IGNORE INTO tags (name) VALUES (?1)")?;
+    let mut attach_tag = conn.prepare_cached(
+        "INSERT OR IGNORE INTO block_tags (block_id, tag_id)
+         SELECT ?1, id FROM tags WHERE name = ?2",
+    )?;
+    for tag in links::extract_tags(&links::strip_queries(text)) {
+        upsert_tag.execute([&tag])?;
+        attach_tag.execute(params![block_id, tag])?;
+    }
+    Ok(())
+}
+
+/// Whether `uid` is a page uid or alias, which wins over reading it as a date.
+fn names_page(conn: &Connection, uid: &str) -> rusqlite::Result<bool> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT EXISTS(SELECT 1 FROM pages WHERE uid = ?1)
+             OR EXISTS(SELECT 1 FROM page_aliases WHERE alias_uid = ?1)",
+    )?;
+    stmt.query_row([uid], |row| row.get(0))
+}
+
+/// The journal settings and the day date links in `block_id` count from: the date of the daily
+/// page it sits on, else the day the block was written. Without a block it is today.
+fn date_link_origin(
+    conn: &Connection,
+    block_id: Option<i64>,
+) -> rusqlite::Result<(JournalSettings, chrono::NaiveDate)> {
+    let settings = JournalSettings::load_with(|key| {
+        conn.query_row("SELECT value FROM kv WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()
+    })?;
+    let today = chrono::Local::now().date_naive();
+    let Some(block_id) = block_id else {
+        return Ok((settings, today));
+    };
+    let row: Option<(String, Option<i64>)> = conn
+        .query_row(
+            "SELECT p.title, b.created_at
+             FROM blocks b
+             JOIN pages p ON p.id = b.page_id
+             WHERE b.id = ?1",
+            [block_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let origin = row.and_then(|(title, created_at)| {
+        settings.parse_title(&title).or_else(|| {
+            let created = chrono::DateTime::from_timestamp(created_at?, 0)?;
+            Some(created.with_timezone(&chrono::Local).date_naive())
+        })
+    });
+    Ok((settings, origin.unwrap_or(today)))
+}
+
+fn invalid_property_value(key: &str, value_type: &str, value: &str) -> DbError {
+    DbError::PropertyValue(PropertyValueError {
+        key: key.to_string(),
+        value_type: value_type.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Registers the SQL functions the queries rely on.
+fn register_functions(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "multi_select_options",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let raw = ctx.get::<Option<String>>(0)?.unwrap_or_default();
+            Ok(multi_select_options_json(&raw))
+        },
+    )
+}
+
+/// A multi_select value as a JSON array of its lowercased options, so values written with
+/// different spacing or case compare equal option by option.
+fn multi_select_options_json(raw: &str) -> String {
+    let options: Vec<String> = properties::split_multi_select(raw)
+        .iter()
+        .map(|option| option.to_lowercase())
+        .collect();
+    serde_json::Value::from(options).to_string()
+}
+
+/// Type used to compare a page property in queries: its definition, else the type it was
+/// stored with.
+fn page_property_value_type(conn: &Connection, key: &str) -> rusqlite::Result<String> {
+    if key == TITLE_KEY {
+        return Ok("text".to_string());
+    }
+    let mut stmt = conn.prepare_cached(
+        "SELECT COALESCE(
+           (SELECT value_type FROM property_definitions WHERE key = ?1),
+           (SELECT value_type FROM page_properties WHERE key = ?1 LIMIT 1),
+           'text')",
+    )?;
+    stmt.query_row([key], |row| row.get(0))
+}
+
+/// SQL expression for a page property's raw value (or the page title) in `query_pages`.
+fn page_property_expr(values: &mut Vec<String>, key: &str) -> String {
+    if key == TITLE_KEY {
+        return "p.title".to_string();
+    }
+    let key = bind_param(values, key);
+    format!(
+        "(SELECT pp.value FROM page_properties pp
+          WHERE pp.page_id = p.id AND pp.key = {key})"
+    )
+}
+
+/// SQL predicate for a property filter over `value_expr`, comparing by `value_type`.
+fn property_filter_predicate(
+    values: &mut Vec<String>,
+    filter: &PropertyFilter,
+    value_expr: &str,
+    value_type: &str,
+) -> Result<String, DbError> {
+    let mut typed_param = |value: &str| -> Result<String, DbError> {
+        let value = match PropertyValue::parse(value_type, value) {
+            Some(value) => value.to_raw(),
+            None if value.trim().is_empty() => String::new(),
+            None => return Err(invalid_property_value(&filter.key, value_type, value)),
+        };
+        Ok(typed_sql_expr(&bind_param(values, &value), value_type))
+    };
+    let typed_expr = typed_sql_expr(value_expr, value_type);
+    let predicate = match filter.op {
+        FilterOp::Equals => format!("{typed_expr} = {}", typed_param(&filter.value)?),
+        FilterOp::NotEquals => {
+            let param = typed_param(&filter.value)?;
+            format!("({value_expr} IS NULL OR {typed_expr} != {param})")
+        }
+        FilterOp::Contains => {
+            let param = bind_param(values, &filter.value);
+            format!("instr(lower({value_expr}), lower({param})) > 0")
+        }
+        FilterOp::IsEmpty => format!("COALESCE(trim({value_expr}), '') = ''"),
+        FilterOp::IsNotEmpty => format!("COALESCE(trim({value_expr}), '') != ''"),
+        FilterOp::LessThan => format!(
+            "COALESCE(trim({value_expr}), '') != '' AND {typed_expr} < {}",
+            typed_param(&filter.value)?
+        ),
+        FilterOp::GreaterThan => format!(
+            "COALESCE(trim({value_expr}), '') != '' AND {typed_expr} > {}",
+            typed_param(&filter.value)?
+        ),
+        FilterOp::Between => {
+            let upper = filter.values.first().map(String::as_str).unwrap_or("");
+            format!(
+                "COALESCE(trim({value_expr}), '') != '' AND {typed_expr} BETWEEN {} AND {}",
+                typed_param(&filter.value)?,
+                typed_param(upper)?
+            )
+        }
+        FilterOp::In if value_type == "multi_select" => {
+            let wanted = bind_param(values, &multi_select_options_json(&filter.values.join(",")));
+            format!(
+                "EXISTS (SELECT 1 FROM json_each(multi_select_options({value_expr})) option
+                  WHERE option.value IN (SELECT value FROM json_each({wanted})))"
+            )
+        }
+        FilterOp::In => {
+            let params = filter
+                .values
+                .iter()
+                .map(|value| typed_param(value))
+                .collect::<Result<Vec<_>, _>>()?;
+            if params.is_empty() {
+                "0".to_string()
+            } else {
+                format!("{typed_expr} IN ({})", params.join(", "))
+            }
+        }
+    };
+    Ok(predicate)
+}
+
+/// Wraps a value expression so comparisons follow the property type.
+fn typed_sql_expr(expr: &str, value_type: &str) -> String {
+    match value_type {
+        "number" => format!("CAST({expr} AS REAL)"),
+        "date" | "checkbox" => format!("trim({expr})"),
+        _ => format!("{expr} COLLATE NOCASE"),
+    }
+}
+
+fn property_value_type(conn: &Connection, key: &str) -> rusqlite::Result<String> {
+    let mut stmt =
+        conn.prepare_cached("SELECT value_type FROM property_definitions WHERE key = ?1")?;
+    Ok(stmt
+        .query_row([key], |row| row.get(0))
+        .optional()?
+        .unwrap_or_else(|| "text".to_string()))
+}
+
+/// Rewrites the block's `block_properties` rows from its `key:: value` lines. Values that do not
+/// match their definition's type are kept as plain text.
+fn sync_block_properties(conn: &Connection, block_id: i64, text: &str) -> rusqlite::Result<()> {
+    let mut clear = conn.prepare_cached("DELETE FROM block_properties WHERE block_id = ?1")?;
+    clear.execute([block_id])?;
+    let mut insert = conn.prepare_cached(
+        "INSERT INTO block_properties (block_id, key, value, value_type, sort_order)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (ix, (key, value)) in properties::parse_block_properties(text)
+        .into_iter()
+        .enumerate()
+    {
+        let mut value_type = property_value_type(conn, &key)?;
+        if !properties::is_valid_property_value(&value_type, &value) {
+            value_type = "text".to_string();
+        }
+        insert.execute(params![block_id, key, value, value_type, ix as i64])?;
+    }
+    Ok(())
+}
+
+/// Refreshes every table derived from a block's text.
+fn index_block_text(conn: &Connection, block_id: i64, text: &str) -> rusqlite::Result<()> {
+    sync_block_links(conn, block_id, text)?;
+    sync_block_properties(conn, block_id, text)
+}
+
+fn rebuild_all_block_properties(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT id, text FROM blocks WHERE text LIKE '%::%'")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (block_id, text) in rows {
+        sync_block_properties(conn, block_id, &text)?;
+    }
+    Ok(())
+}
+
+fn load_page_snapshots(conn: &Connection, page_id: i64) -> rusqlite::Result<Vec<BlockSnapshot>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT uid, text, props FROM blocks WHERE page_id = ?1 ORDER BY sort_key",
+    )?;
+    let rows = stmt.query_map([page_id], |row| {
+        let props: String = row.get(2)?;
+        Ok(BlockSnapshot {
+            uid: row.get(0)?,
+            text: row.get(1)?,
+            indent: parse_indent(&props),
+            block_type: parse_block_type(&props),
+        })
+    })?;
+    rows.collect()
+}
+
+/// Writes block changes for `page_id` through `conn`, which callers hold a transaction on.
+/// `coalesce_revision` lets the save fold into the latest page revision when it is recent;
+/// otherwise the result gets a revision of its own. Folding only marks that revision stale, so
+/// saves don't re-encode the whole page; it is re-snapshotted when read or just before a newer
+/// revision supersedes it.
+fn write_block_rows(
+    conn: &Connection,
+    page_id: i64,
+    inserts: &[BlockInsert],
+    updates: &[BlockSnapshot],
+    moves: &[BlockMove],
+    deletes: &[String],
+    coalesce_revision: bool,
+) -> rusqlite::Result<()> {
+    let changed =
+        !(inserts.is_empty() && updates.is_empty() && moves.is_empty() && deletes.is_empty());
+    let now = chrono::Utc::now().timestamp_millis();
+    let fold_into = match latest_page_revision(conn, page_id)? {
+        Some((id, created_at, _))
+            if changed
+                && coalesce_revision
+                && now - created_at < revisions::REVISION_INTERVAL_MS =>
+        {
+            Some(id)
+        }
+        Some((id, _, true)) if changed => {
+            // Keep the state the stale revision stands for before it is superseded.
+            refresh_page_revision(conn, page_id, id)?;
+            None
+        }
+        _ => None,
+    };
+    {
+        let mut lookup = conn.prepare("SELECT id FROM blocks WHERE uid = ?1")?;
+        let mut resolve = |uid: Option<&str>| -> rusqlite::Result<Option<i64>> {
+            match uid {
+                Some(uid) => lookup.query_row([uid], |row| row.get(0)).optional(),
+                None => Ok(None),
+            }
+        };
+
+        let mut insert = conn.prepare(
+            "INSERT INTO blocks (uid, page_id, parent_id, sort_key, text, props)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(uid) DO UPDATE SET
+               parent_id = excluded.parent_id,
+               sort_key = excluded.sort_key,
+               text = excluded.text,
+               props = excluded.props,
+               updated_at = strftime('%s','now')
+             WHERE blocks.page_id = excluded.page_id",
+        )?;
+        for entry in inserts {
+            let parent_id = resolve(entry.parent_uid.as_deref())?;
+            let props = serialize_block_props(&entry.block);
+            let written = insert.execute(params![
+                entry.block.uid,
+                page_id,
+                parent_id,
+                entry.sort_key,
+                entry.block.text,
+                props
+            ])?;
+            if written == 0 {
+                return Err(block_uid_taken(&entry.block.uid));
+            }
+            if let Some(block_id) = resolve(Some(&entry.block.uid))? {
+                index_block_text(conn, block_id, &entry.block.text)?;
+            }
+        }
+
+        let mut reposition = conn.prepare(
+            "UPDATE blocks
+             SET parent_id = ?1, sort_key = ?2, updated_at = strftime('%s','now')
+             WHERE uid = ?3 AND page_id = ?4",
+        )?;
+        for entry in moves {
+            let parent_id = resolve(entry.parent_uid.as_deref())?;
+            reposition.execute(params![parent_id, entry.sort_key, entry.uid, page_id])?;
+        }
+
+        let mut update = conn.prepare(
+            "UPDATE blocks
+             SET text = ?1, props = ?2, updated_at = strftime('%s','now')
+             WHERE uid = ?3 AND page_id = ?4",
+        )?;
+        for block in updates {
+            let props = serialize_block_props(block);
+            update.execute(params![block.text, props, block.uid, page_id])?;
+            if let Some(block_id) = resolve(Some(&block.uid))? {
+                index_block_text(conn, block_id, &block.text)?;
+            }
+        }
+
+        let mut delete = conn.prepare("DELETE FROM blocks WHERE uid = ?1 AND page_id = ?2")?;
+        for uid in deletes {
+            delete.execute(params![uid, page_id])?;
+        }
+    }
+    conn.execute(
+        "DELETE FROM review_queue
+         WHERE page_uid = (SELECT uid FROM pages WHERE id = ?1)
+           AND NOT EXISTS (
+             SELECT 1
+             FROM blocks
+             WHERE blocks.page_id = ?1
+               AND blocks.uid = review_queue.block_uid
+           )",
+        [page_id],
+    )?;
+    if changed {
+        let page_uid: Option<String> = conn
+            .query_row("SELECT uid FROM pages WHERE id = ?1", [page_id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        if let Some(page_uid) = page_uid {
+            let edited = inserts
+                .iter()
+                .map(|entry| &entry.block)
+                .chain(updates)
+                .map(|block| (block.uid.as_str(), block.text.as_str()));
+            sync_flashcard_review_items(conn, &page_uid, edited, now)?;
+        }
+        match fold_into {
+            Some(id) => {
+                conn.execute("UPDATE page_revisions SET stale = 1 WHERE id = ?1", [id])?;
+            }
+            None => record_page_revision(conn, page_id, now, false)?,
+        }
+    }
+    Ok(())
+}
+
+/// Error for a block uid that already belongs to another page; saves never move blocks
+/// between pages.
+fn block_uid_taken(uid: &str) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE),
+        Some(format!("block uid {uid} belongs to another page")),
+    )
+}
+
+/// The page's newest revision as `(id, created_at, stale)`.
+fn latest_page_revision(
+    conn: &Connection,
+    page_id: i64,
+) -> rusqlite::Result<Option<(i64, i64, bool)>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, created_at, stale FROM page_revisions
+         WHERE page_id = ?1
+         ORDER BY created_at DESC, id DESC
+         LIMIT 1",
+    )?;
+    stmt.query_row([page_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .optional()
+}
+
+/// Re-snapshots the page into revision `id` and clears its stale mark.
+fn refresh_page_revision(conn: &Connection, page_id: i64, id: i64) -> rusqlite::Result<()> {
+    let blocks = load_page_snapshots(conn, page_id)?;
+    let encoded = serde_json::to_string(&blocks)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    conn.execute(
+        "UPDATE page_revisions SET blocks = ?1, block_count = ?2, stale = 0 WHERE id = ?3",
+        params![encoded, blocks.len() as i64, id],
+    )?;
+    Ok(())
+}
+
+fn record_page_revision(
+    conn: &Connection,
+    page_id: i64,
+    now: i64,
+    coalesce: bool,
+) -> rusqlite::Result<()> {
+    let blocks = load_page_snapshots(conn, page_id)?;
+    let encoded = serde_json::to_string(&blocks)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    let latest: Option<(i64, i64, String)> = conn
+        .query_row(
+            "SELECT id, created_at, blocks FROM page_revisions
+             WHERE page_id = ?1
+             ORDER BY created_at DESC, id DESC
+             LIMIT 1",
+            [page_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    match latest {
+        Some((_, _, latest_blocks)) if latest_blocks == encoded => return Ok(()),
+        Some((id, created_at, _))
+            if coalesce && now - created_at < revisions::REVISION_INTERVAL_MS =>
+        {
+            conn.execute(
+                "UPDATE page_revisions SET blocks = ?1, block_count = ?2, stale = 0 WHERE id = ?3",
+                params![encoded, blocks.len() as i64, id],
+            )?;
+            return Ok(());
+        }
+        _ => {}
+    }
+    conn.execute(
+        "INSERT INTO page_revisions (page_id, created_at, block_count, blocks)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![page_id, now, blocks.len() as i64, encoded],
+    )?;
+
+    let mut stmt =
+        conn.prepare_cached("SELECT id, created_at FROM page_revisions WHERE page_id = ?1")?;
+    let existing = stmt
+        .query_map([page_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<(i64, i64)>>>()?;
+    let mut delete = conn.prepare_cached("DELETE FROM page_revisions WHERE id = ?1")?;
+    for id in revisions::revisions_to_prune(&existing, now) {
+        delete.execute([id])?;
+    }
+    Ok(())
+}
+
+/// Re-keys pages that still hold the fixed-width `000000` sort keys, which leave no key
+/// before the first block and so made every save re-key the whole page.
+fn rekey_legacy_sort_keys(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT page_id FROM blocks
+         WHERE sort_key GLOB '[0-9][0-9][0-9][0-9][0-9][0-9]'",
+    )?;
+    let pages = stmt
+        .query_map([], |row| row.get::<_, i64>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut blocks =
+        conn.prepare("SELECT id FROM blocks WHERE page_id = ?1 ORDER BY sort_key, id")?;
+    let mut update = conn.prepare("UPDATE blocks SET sort_key = ?1 WHERE id = ?2")?;
+    for page_id in pages {
+        let ids = blocks
+            .query_map([page_id], |row| row.get::<_, i64>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let keys = sort_keys::keys_between(None, None, ids.len())
+            .expect("open bounds always yield sort keys");
+        for (id, key) in ids.into_iter().zip(keys) {
+            update.execute(params![key, id])?;
+        }
+    }
+    Ok(())
+}
+
+/// Seeds one revision per page from its current blocks, dated at the page's last update.
+fn backfill_page_revisions(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT id, COALESCE(updated_at, 0) FROM pages p
+         WHERE EXISTS (SELECT 1 FROM blocks b WHERE b.page_id = p.id)",
+    )?;
+    let pages = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (page_id, updated_at) in pages {
+        record_page_revision(conn, page_id, updated_at * 1000, false)?;
+    }
+    Ok(())
+}
+
+/// Queues the given blocks that are cards for review, due right away when new, and drops the
+/// card items of those that are no longer cards. Only items this created (template
+/// `flashcards::CARD_TEMPLATE`) are touched, so queue items added by hand are kept as they are.
+fn sync_flashcard_review_items<'a>(
+    conn: &Connection,
+    page_uid: &str,
+    blocks: impl IntoIterator<Item = (&'a str, &'a str)>,
+    now: i64,
+) -> rusqlite::Result<()> {
+    let mut insert = conn.prepare_cached(
+        "INSERT INTO review_queue (page_uid, block_uid, added_at, due_at, template, status)
+         VALUES (?1, ?2, ?3, ?3, ?4, 'pending')
+         ON CONFLICT(page_uid, block_uid) DO NOTHING",
+    )?;
+    let mut remove = conn.prepare_cached(
+        "DELETE FROM review_queue WHERE page_uid = ?1 AND block_uid = ?2 AND template = ?3",
+    )?;
+    for (block_uid, text) in blocks {
+        if flashcards::is_flashcard(text) {
+            insert.execute(params![page_uid, block_uid, now, flashcards::CARD_TEMPLATE])?;
+        } else {
+            remove.execute(params![page_uid, block_uid, flashcards::CARD_TEMPLATE])?;
+        }
+    }
+    Ok(())
+}
+
+fn backfill_flashcard_review_items(conn: &Connection) -> rusqlite::Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut stmt = conn.prepare("SELECT id, uid FROM pages")?;
+    let pages = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (page_id, page_uid) in pages {
+        let blocks = load_page_snapshots(conn, page_id)?;
+        let blocks = blocks
+            .iter()
+            .map(|block| (block.uid.as_str(), block.text.as_str()));
+        sync_flashcard_review_items(conn, &page_uid, blocks, now)?;
+    }
+    Ok(())
+}
+
+/// Rewrites the page's `page_aliases` rows from its `alias` property value.
+fn sync_page_aliases(conn: &Connection, page_id: i64, value: &str) -> rusqlite::Result<()> {
+    let mut clear = conn.prepare_cached("DELETE FROM page_aliases WHERE page_id = ?1")?;
+    clear.execute([page_id])?;
+    let mut insert = conn.prepare_cached(
+        "INSERT OR IGNORE INTO page_aliases (page_id, alias, alias_uid) VALUES (?1, ?2, ?3)",
+    )?;
+    for alias in properties::split_multi_select(value) {
+        insert.execute(params![page_id, alias, app::sanitize_kebab(&alias)])?;
+    }
+    Ok(())
+}
+
+fn rebuild_all_page_aliases(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT page_id, value FROM page_properties WHERE key = ?1")?;
+    let rows = stmt
+        .query_map([properties::ALIAS_KEY], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (page_id, value) in rows {
+        sync_page_aliases(conn, page_id, &value)?;
+    }
+    Ok(())
+}
+
+fn rebuild_all_block_links(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT id, text FROM blocks")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (block_id, text) in rows {
+        sync_block_links(conn, block_id, &text)?;
+    }
+    Ok(())
+}
+
+/// Appends a positional parameter and returns its placeholder.
+fn bind_param(values: &mut Vec<String>, value: &str) -> String {
+    values.push(value.to_string());
+    format!("?{}", values.len())
+}
+
+/// Binds each of `items`, returning the placeholders as an `IN` list.
+fn bind_params(values: &mut Vec<String>, items: &[&str]) -> String {
+    items
+        .iter()
+        .map(|item| bind_param(values, item))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Blocks starting with an open or a ticked task checkbox. `LIKE` ignores the case of `[X]`.
+const OPEN_TASK_SQL: &str = "(ltrim(b.text) LIKE '[ ] %' OR ltrim(b.text) LIKE '- [ ] %')";
+const DONE_TASK_SQL: &str = "(ltrim(b.text) LIKE '[x] %' OR ltrim(b.text) LIKE '- [x] %')";
+
+/// Matches task blocks in `state`, read as `tasks::parse_task` reads them: the checkbox decides
+/// open or closed, and `status::` picks a state within that, falling back to TODO or DONE.
+fn task_state_predicate(values: &mut Vec<String>, state: TaskState) -> String {
+    let (checkbox, fallback) = if state.is_open() {
+        (OPEN_TASK_SQL, TaskState::Todo)
+    } else {
+        (DONE_TASK_SQL, TaskState::Done)
+    };
+    if state != fallback {
+        let status = task_status_in(values, state.aliases());
+        return format!("({checkbox} AND {status})");
+    }
+    let others = TaskState::ALL
+        .into_iter()
+        .filter(|other| other.is_open() == state.is_open() && *other != fallback)
+        .flat_map(|other| other.aliases().iter().copied())
+        .collect::<Vec<_>>();
+    let status = task_status_in(values, &others);
+    format!("({checkbox} AND NOT {status})")
+}
+
+fn task_status_in(values: &mut Vec<String>, aliases: &[&str]) -> String {
+    let key = bind_param(values, tasks::STATUS_KEY);
+    let aliases = bind_params(values, aliases);
+    format!(
+        "EXISTS (SELECT 1 FROM block_properties bp
+          WHERE bp.block_id = b.id AND bp.key = {key} AND lower(bp.value) IN ({aliases}))"
+    )
+}
+
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Resolves each block's parent from the flat indent list: the nearest preceding block with a
+/// smaller indent.
+fn parent_indices(indents: &[i64]) -> Vec<Option<usize>> {
+    let mut stack: Vec<usize> = Vec::new();
+    let mut parents = Vec::with_capacity(indents.len());
+    for (ix, indent) in indents.iter().enumerate() {
+        while stack.last().is_some_and(|top| indents[*top] >= *indent) {
+            stack.pop();
+        }
+        parents.push(stack.last().copied());
+        stack.push(ix);
+    }
+    parents
+}
+
+fn diff_block_rows(existing: &[StoredBlock], next: &[BlockSnapshot]) -> BlockChanges {
+    let existing_ix: HashMap<&str, usize> = existing
+        .iter()
+        .enumerate()
+        .map(|(ix, row)| (row.snapshot.uid.as_str(), ix))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let next: Vec<&BlockSnapshot> = next
+        .iter()
+        .filter(|block| seen.insert(block.uid.as_str()))
+        .collect();
+    let indents: Vec<i64> = next.iter().map(|block| block.indent).collect();
+    let parents = parent_indices(&indents);
+
+    let kept: Vec<(usize, usize)> = next
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, block)| existing_ix.get(block.uid.as_str()).map(|ix| (pos, *ix)))
+        .collect();
+    let stable: HashSet<usize> = longest_increasing_run(&kept);
+
+    let sort_keys = assign_sort_keys(existing, &next, &existing_ix, &stable).unwrap_or_else(|| {
+        sort_keys::keys_between(None, None, next.len()).expect("open bounds always yield sort keys")
+    });
+
+    let mut changes = BlockChanges::default();
+    for (pos, block) in next.iter().enumerate() {
+        let parent_uid = parents[pos].map(|ix| next[ix].uid.clone());
+        let sort_key = sort_keys[pos].clone();
+        let Some(row) = existing_ix.get(block.uid.as_str()).map(|ix| &existing[*ix]) else {
+            changes.inserts.push(BlockInsert {
+                block: (*block).clone(),
+                parent_uid,
+                sort_key,
+            });
+            continue;
+        };
+        if row.sort_key != sort_key || row.parent_uid != parent_uid {
+            changes.moves.push(BlockMove {
+                uid: block.uid.clone(),
+                parent_uid,
+                sort_key,
+            });
+        }
+        if row.snapshot != **block {
+            changes.updates.push((*block).clone());
+        }
+    }
+    changes.deletes = existing
+        .iter()
+        .filter(|row| !seen.contains(row.snapshot.uid.as_str()))
+        .map(|row| row.snapshot.uid.clone())
+        .collect();
+    changes
+}
+
+/// Picks the positions (in `next` order) of kept blocks whose stored order can stay as-is:
+/// the longest run whose stored indexes are strictly increasing.
+fn longest_increasing_run(kept: &[(usize, usize)]) -> HashSet<usize> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; kept.len()];
+    for (ix, (_, stored_ix)) in kept.iter().enumerate() {
+        let slot = tails.partition_point(|tail| kept[*tail].1 < *stored_ix);
+        prev[ix] = slot.checked_sub(1).map(|before| tails[before]);
+        if slot == tails.len() {
+            tails.push(ix);
+        } else {
+            tails[slot] = ix;
+        }
+    }
+    let mut stable = HashSet::new();
+    let mut cursor = tails.last().copied();
+    while let Some(ix) = cursor {
+        stable.insert(kept[ix].0);
+        cursor = prev[ix];
+    }
+    stable
+}
+
+fn assign_sort_keys(
+    existing: &[StoredBlock],
+    next: &[&BlockSnapshot],
+    existing_ix: &HashMap<&str, usize>,
+    stable: &HashSet<usize>,
+) -> Option<Vec<String>> {
+    let stable_key = |pos: usize| -> Option<&str> {
+        if !stable.contains(&pos) {
+            return None;
+        }
+        let ix = existing_ix.get(next[pos].uid.as_str())?;
+        Some(existing[*ix].sort_key.as_str())
+    };
+
+    let mut keys = Vec::with_capacity(next.len());
+    let mut pos = 0;
+    while pos < next.len() {
+        if let Some(key) = stable_key(pos) {
+            if keys
+                .last()
+                .is_some_and(|last: &String| last.as_str() >= key)
+            {
+                return None;
+            }
+            keys.push(key.to_string());
+            pos += 1;
+            continue;
+        }
+        let run_end = (pos..next.len())
+            .find(|candidate| stable_key(*candidate).is_some())
+            .unwrap_or(next.len());
+        let lo = keys.last().map(String::as_str);
+        let hi = if run_end < next.len() {
+            stable_key(run_end)
+        } else {
+            None
+        };
+        keys.extend(sort_keys::keys_between(lo, hi, run_end - pos)?);
+        pos = run_end;
+    }
+    Some(keys)
+}
+
 fn parse_indent(props: &str) -> i64 {
     let parsed: serde_json::Value = match serde_json::from_str(props) {
         Ok(value) => value,
@@ -1198,8 +3529,25 @@ fn serialize_block_props(block: &BlockSnapshot) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{BlockSnapshot, Database};
+    use super::{BlockPageRecord, BlockSnapshot, Database, DbError};
     use crate::blocks::BlockType;
+    use crate::review::{ReviewCard, ReviewGrade, DAY_MS};
+    use crate::revisions::{diff_blocks, BlockDiffKind};
+    use crate::search::{self, SearchQuery};
+    use crate::tasks::{TaskFilter, TaskPriority, TaskState};
+    use crate::views::{
+        FilterOp, PageQuery, PageQueryRow, PageSource, PropertyFilter, PropertySort,
+    };
+    use chrono::NaiveDate;
+
+    fn snapshot(uid: &str, text: &str, indent: i64) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent,
+            block_type: BlockType::Text,
+        }
+    }
 
     fn table_exists(db: &Database, name: &str) -> bool {
         db.conn
@@ -1251,173 +3599,1215 @@ mod tests {
     }
 
     #[test]
-    fn migrations_create_schema() {
+    fn migrations_create_schema() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let expected_tables = [
+            "pages",
+            "blocks",
+            "edges",
+            "tags",
+            "block_tags",
+            "assets",
+            "kv",
+            "plugin_perms",
+            "review_queue",
+            "review_log",
+            "sync_ops",
+            "sync_inbox",
+            "blocks_fts",
+            "pages_fts",
+        ];
+
+        for table in expected_tables {
+            assert!(table_exists(&db, table), "missing table {table}");
+        }
+    }
+
+    #[test]
+    fn blocks_table_has_columns() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let columns = table_columns(&db, "blocks");
+        let expected = [
+            "id",
+            "uid",
+            "page_id",
+            "parent_id",
+            "sort_key",
+            "text",
+            "props",
+            "created_at",
+            "updated_at",
+        ];
+
+        for column in expected {
+            assert!(columns.contains(&column.to_string()), "missing {column}");
+        }
+    }
+
+    #[test]
+    fn fts_updates_on_insert_update_delete() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db
+            .insert_page("page-uid", "Test page")
+            .expect("insert page");
+        let block_id = db
+            .insert_block(page_id, "block-uid", None, "a", "hello world", "{}")
+            .expect("insert block");
+
+        let results = db.search_blocks("hello").expect("search");
+        assert_eq!(results, vec![block_id]);
+
+        db.update_block_text(block_id, "goodbye world")
+            .expect("update block");
+        let results = db.search_blocks("hello").expect("search after update");
+        assert!(results.is_empty());
+
+        let results = db.search_blocks("goodbye").expect("search after update");
+        assert_eq!(results, vec![block_id]);
+
+        db.delete_block(block_id).expect("delete block");
+        let results = db.search_blocks("goodbye").expect("search after delete");
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn update_block_text_by_uid_updates_fts() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db
+            .insert_page("page-uid", "Test page")
+            .expect("insert page");
+        let block_id = db
+            .insert_block(page_id, "block-uid", None, "a", "hello world", "{}")
+            .expect("insert block");
+
+        db.update_block_text_by_uid("block-uid", "updated title")
+            .expect("update block by uid");
+
+        let results = db.search_blocks("updated").expect("search updated");
+        assert_eq!(results, vec![block_id]);
+    }
+
+    #[test]
+    fn search_block_summaries_returns_text() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db
+            .insert_page("page-uid", "Search page")
+            .expect("insert page");
+        db.insert_block(page_id, "block-uid", None, "a", "alpha note", "{}")
+            .expect("insert block");
+        db.insert_block(page_id, "block-uid-2", None, "b", "beta note", "{}")
+            .expect("insert block");
+
+        let results = db
+            .search_block_summaries("alpha", 10)
+            .expect("search summaries");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uid, "block-uid");
+        assert_eq!(results[0].text, "alpha note");
+    }
+
+    #[test]
+    fn search_compiles_structured_queries() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let project = db.insert_page("project-x", "Project X").expect("page");
+        let other = db.insert_page("other", "Other").expect("page");
+        let todo = r#"{"block_type":"todo"}"#;
+        db.insert_block(
+            project,
+            "b1",
+            None,
+            "a",
+            "Ship the plan #urgent\npoints:: 5",
+            todo,
+        )
+        .expect("block");
+        db.insert_block(project, "b2", None, "b", "Plan review notes", "{}")
+            .expect("block");
+        db.insert_block(other, "b3", None, "a", "Old plan draft (v1)", todo)
+            .expect("block");
+        db.insert_block(other, "b4", None, "b", "- [x] Filed taxes", todo)
+            .expect("block");
+        db.insert_block(other, "b5", None, "c", "[ ] Book flights", todo)
+            .expect("block");
+        db.insert_block(
+            other,
+            "b6",
+            None,
+            "d",
+            "Scored points:many, see http://example.com",
+            "{}",
+        )
+        .expect("block");
+        db.conn
+            .execute(
+                "UPDATE blocks SET updated_at = 1735689600 WHERE uid = 'b3'",
+                [],
+            )
+            .expect("age block");
+        db.upsert_property_definition("points", "Points", "number")
+            .expect("definition");
+
+        let uids = |input: &str| -> Vec<String> {
+            let query = db.parse_search_query(input).expect("parse");
+            db.search(&query, 10)
+                .expect("search")
+                .into_iter()
+                .map(|hit| hit.block_uid)
+                .collect()
+        };
+
+        assert_eq!(uids(r#"page:"project x" type:todo plan"#), vec!["b1"]);
+        assert_eq!(uids("tag:urgent"), vec!["b1"]);
+        assert_eq!(uids("plan updated:<2025-01-02"), vec!["b3"]);
+        assert_eq!(uids("plan -draft -review"), vec!["b1"]);
+        assert_eq!(uids("\"review notes\""), vec!["b2"]);
+        assert_eq!(uids("points:>3"), vec!["b1"]);
+        // Unknown keys and values that do not parse are searched as words.
+        assert_eq!(uids("points:many"), vec!["b6"]);
+        assert_eq!(uids("http://example.com"), vec!["b6"]);
+        assert_eq!(uids("todo:done"), vec!["b4"]);
+        assert_eq!(uids("todo:open"), vec!["b5"]);
+        // Punctuation is searched as text instead of failing as FTS5 syntax.
+        assert_eq!(uids("(v1)"), vec!["b3"]);
+        assert!(db.search_block_summaries("\"unbalanced", 10).is_ok());
+
+        let hits = db
+            .search(&SearchQuery::parse("review"), 10)
+            .expect("search");
+        let (snippet, ranges) = search::snippet_highlights(&hits[0].snippet);
+        assert_eq!(&snippet[ranges[0].clone()], "review");
+    }
+
+    #[test]
+    fn query_tasks_filters_state_page_and_dates() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+        let work = db.insert_page("work", "Work").expect("page");
+        let home = db.insert_page("home", "Home").expect("page");
+        db.insert_block(
+            work,
+            "w1",
+            None,
+            "a",
+            "[ ] Send invoice\ndeadline:: 2026-03-01",
+            "{}",
+        )
+        .expect("block");
+        db.insert_block(work, "w2", None, "b", "- [ ] Review\nstatus:: doing", "{}")
+            .expect("block");
+        db.insert_block(work, "w3", None, "c", "[x] Shipped", "{}")
+            .expect("block");
+        db.insert_block(work, "w4", None, "d", "[[Not a task]]", "{}")
+            .expect("block");
+        db.insert_block(
+            work,
+            "w5",
+            None,
+            "e",
+            "[x] Dropped\nstatus:: Cancelled",
+            "{}",
+        )
+        .expect("block");
+        db.insert_block(
+            work,
+            "w6",
+            None,
+            "f",
+            "[ ] Renew lease\npriority:: #high\ndeadline:: [[2026-02-20]]\nscheduled:: 2026-03-10",
+            "{}",
+        )
+        .expect("block");
+        db.insert_block(
+            home,
+            "h1",
+            None,
+            "a",
+            "[ ] Water plants\nscheduled:: 2026-03-04",
+            "{}",
+        )
+        .expect("block");
+
+        let uids = |filter: &TaskFilter| -> Vec<String> {
+            db.query_tasks(filter)
+                .expect("tasks")
+                .into_iter()
+                .map(|record| record.block_uid)
+                .collect()
+        };
+        assert_eq!(
+            uids(&TaskFilter::default()),
+            vec!["h1", "w1", "w2", "w3", "w5", "w6"]
+        );
+        assert_eq!(uids(&TaskFilter::open()), vec!["h1", "w1", "w2", "w6"]);
+        let in_state = |state: TaskState| TaskFilter {
+            states: vec![state],
+            ..TaskFilter::default()
+        };
+        assert_eq!(uids(&in_state(TaskState::Todo)), vec!["h1", "w1", "w6"]);
+        assert_eq!(uids(&in_state(TaskState::Doing)), vec!["w2"]);
+        assert_eq!(uids(&in_state(TaskState::Done)), vec!["w3"]);
+        assert_eq!(uids(&in_state(TaskState::Canceled)), vec!["w5"]);
+        assert_eq!(
+            uids(&TaskFilter {
+                priority: Some(TaskPriority::A),
+                ..TaskFilter::default()
+            }),
+            vec!["w6"]
+        );
+        // The earlier of the scheduled and deadline days decides, in either date form.
+        assert_eq!(
+            uids(&TaskFilter {
+                page_uid: Some("work".to_string()),
+                due_to: NaiveDate::from_ymd_opt(2026, 3, 2),
+                ..TaskFilter::open()
+            }),
+            vec!["w1", "w6"]
+        );
+        assert_eq!(
+            uids(&TaskFilter {
+                due_from: NaiveDate::from_ymd_opt(2026, 3, 2),
+                ..TaskFilter::open()
+            }),
+            vec!["h1"]
+        );
+    }
+
+    #[test]
+    fn search_block_page_summaries_returns_page_info() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db
+            .insert_page("page-uid", "Search page")
+            .expect("insert page");
+        db.insert_block(page_id, "block-uid", None, "a", "alpha note", "{}")
+            .expect("insert block");
+        db.insert_block(page_id, "block-uid-2", None, "b", "beta note", "{}")
+            .expect("insert block");
+
+        let results = db
+            .search_block_page_summaries("alpha", 10)
+            .expect("search summaries");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].block_uid, "block-uid");
+        assert_eq!(results[0].page_uid, "page-uid");
+        assert_eq!(results[0].page_title, "Search page");
+        assert_eq!(results[0].text, "alpha note");
+    }
+
+    #[test]
+    fn replace_and_load_blocks_roundtrip() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("page-uid", "Inbox").expect("insert page");
+        let blocks = vec![
+            BlockSnapshot {
+                uid: "block-1".to_string(),
+                text: "First line".to_string(),
+                indent: 0,
+                block_type: BlockType::Text,
+            },
+            BlockSnapshot {
+                uid: "block-2".to_string(),
+                text: "Indented line".to_string(),
+                indent: 2,
+                block_type: BlockType::Text,
+            },
+        ];
+
+        db.replace_blocks_for_page(page_id, &blocks)
+            .expect("replace blocks");
+
+        let loaded = db.load_blocks_for_page(page_id).expect("load blocks");
+        assert_eq!(loaded, blocks);
+    }
+
+    #[test]
+    fn replace_blocks_for_page_writes_parent_links() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("page-uid", "Outline").expect("insert page");
+        let blocks = vec![
+            snapshot("root", "Root", 0),
+            snapshot("child-a", "Child A", 1),
+            snapshot("grand", "Grandchild", 2),
+            snapshot("child-b", "Child B", 1),
+            snapshot("sibling", "Sibling", 0),
+        ];
+        db.replace_blocks_for_page(page_id, &blocks)
+            .expect("replace blocks");
+
+        let root = db
+            .get_block_by_uid("root")
+            .expect("get root")
+            .expect("root");
+        assert_eq!(root.parent_id, None);
+
+        let children: Vec<String> = db
+            .list_block_children(root.id)
+            .expect("children")
+            .into_iter()
+            .map(|block| block.uid)
+            .collect();
+        assert_eq!(children, vec!["child-a".to_string(), "child-b".to_string()]);
+
+        let subtree: Vec<String> = db
+            .load_block_subtree(root.id)
+            .expect("subtree")
+            .into_iter()
+            .map(|block| block.uid)
+            .collect();
+        assert_eq!(subtree, vec!["root", "child-a", "grand", "child-b"]);
+
+        let roots: Vec<String> = db
+            .list_root_blocks(page_id)
+            .expect("roots")
+            .into_iter()
+            .map(|block| block.uid)
+            .collect();
+        assert_eq!(roots, vec!["root".to_string(), "sibling".to_string()]);
+    }
+
+    #[test]
+    fn replace_blocks_for_page_keeps_rows_for_unchanged_blocks() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("page-uid", "Stable").expect("insert page");
+        db.replace_blocks_for_page(
+            page_id,
+            &[snapshot("a", "Alpha", 0), snapshot("b", "Beta", 0)],
+        )
+        .expect("seed blocks");
+        let alpha = db.get_block_by_uid("a").expect("get").expect("alpha");
+        db.insert_edge(alpha.id, "target", "wikilink")
+            .expect("insert edge");
+        let tag = db.upsert_tag("keep").expect("tag");
+        db.attach_tag(alpha.id, tag.id).expect("attach tag");
+
+        db.replace_blocks_for_page(
+            page_id,
+            &[
+                snapshot("a", "Alpha", 0),
+                snapshot("c", "Gamma", 0),
+                snapshot("b", "Beta edited", 1),
+            ],
+        )
+        .expect("save blocks");
+
+        let alpha_after = db.get_block_by_uid("a").expect("get").expect("alpha");
+        assert_eq!(alpha_after.id, alpha.id);
+        assert_eq!(alpha_after.sort_key, alpha.sort_key);
+        assert_eq!(db.list_edges_from_block(alpha.id).expect("edges").len(), 1);
+        assert_eq!(db.list_tags_for_block(alpha.id).expect("tags").len(), 1);
+
+        let loaded = db.load_blocks_for_page(page_id).expect("load");
+        let uids: Vec<&str> = loaded.iter().map(|block| block.uid.as_str()).collect();
+        assert_eq!(uids, vec!["a", "c", "b"]);
+        let beta = db.get_block_by_uid("b").expect("get").expect("beta");
+        let gamma = db.get_block_by_uid("c").expect("get").expect("gamma");
+        assert_eq!(beta.parent_id, Some(gamma.id));
+        assert_eq!(beta.text, "Beta edited");
+    }
+
+    #[test]
+    fn plan_block_changes_touches_only_changed_rows() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("page-uid", "Large").expect("insert page");
+        let mut blocks: Vec<BlockSnapshot> = (0..10_000)
+            .map(|ix| snapshot(&format!("b{ix}"), &format!("line {ix}"), 0))
+            .collect();
+        db.replace_blocks_for_page(page_id, &blocks)
+            .expect("seed blocks");
+
+        blocks[5_000].text = "edited".to_string();
+        blocks.insert(2_500, snapshot("new", "inserted", 0));
+        let moved = blocks.remove(9_000);
+        blocks.insert(10, moved);
+        blocks.remove(7_000);
+
+        let changes = db.plan_block_changes(page_id, &blocks).expect("plan");
+        assert_eq!(changes.updates.len(), 1);
+        assert_eq!(changes.inserts.len(), 1);
+        assert_eq!(changes.moves.len(), 1);
+        assert_eq!(changes.deletes.len(), 1);
+
+        db.replace_blocks_for_page(page_id, &blocks)
+            .expect("save blocks");
+        let loaded = db.load_blocks_for_page(page_id).expect("load");
+        assert_eq!(loaded, blocks);
+        let unchanged = db.plan_block_changes(page_id, &blocks).expect("replan");
+        assert!(unchanged.is_empty());
+    }
+
+    #[test]
+    fn replace_blocks_for_page_rebalances_unusable_sort_keys() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("page-uid", "Legacy").expect("insert page");
+        db.insert_block(page_id, "a", None, "000000", "A", "{}")
+            .expect("a");
+        db.insert_block(page_id, "b", None, "000000", "B", "{}")
+            .expect("b");
+
+        let blocks = vec![
+            snapshot("first", "First", 0),
+            snapshot("a", "A", 0),
+            snapshot("b", "B", 0),
+        ];
+        db.replace_blocks_for_page(page_id, &blocks)
+            .expect("save blocks");
+        let loaded = db.load_blocks_for_page(page_id).expect("load");
+        assert_eq!(loaded, blocks);
+    }
+
+    #[test]
+    fn sort_key_migration_rekeys_legacy_pages() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let legacy = db.insert_page("legacy", "Legacy").expect("legacy");
+        for (ix, uid) in ["a", "b", "c"].into_iter().enumerate() {
+            db.insert_block(legacy, uid, None, &format!("{ix:06}"), uid, "{}")
+                .expect("legacy block");
+        }
+        let current = db.insert_page("current", "Current").expect("current");
+        db.insert_block(current, "x", None, "V", "x", "{}")
+            .expect("current block");
+
+        super::rekey_legacy_sort_keys(&db.conn).expect("backfill");
+        let keys = |page_id: i64| {
+            db.conn
+                .prepare("SELECT uid, sort_key FROM blocks WHERE page_id = ?1 ORDER BY sort_key")
+                .expect("prepare")
+                .query_map([page_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })
+                .expect("query")
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .expect("keys")
+        };
+        let rekeyed = keys(legacy);
+        let order = rekeyed
+            .iter()
+            .map(|(uid, _)| uid.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["a", "b", "c"]);
+        assert!(crate::sort_keys::key_between(None, Some(&rekeyed[0].1)).is_some());
+        assert_eq!(keys(current), vec![("x".to_string(), "V".to_string())]);
+    }
+
+    #[test]
+    fn saving_blocks_refuses_uids_of_other_pages() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let first = db.insert_page("first", "First").expect("first");
+        db.replace_blocks_for_page(first, &[snapshot("shared", "Mine", 0)])
+            .expect("seed first");
+        let second = db.insert_page("second", "Second").expect("second");
+        db.replace_blocks_for_page(second, &[snapshot("own", "Own", 0)])
+            .expect("seed second");
+
+        let err = db
+            .replace_blocks_for_page(
+                second,
+                &[snapshot("own", "Own", 0), snapshot("shared", "Stolen", 0)],
+            )
+            .expect_err("cross-page uid");
+        assert!(err.to_string().contains("belongs to another page"));
+        assert_eq!(
+            db.load_blocks_for_page(first).expect("first blocks"),
+            vec![snapshot("shared", "Mine", 0)]
+        );
+        assert_eq!(
+            db.load_blocks_for_page(second).expect("second blocks"),
+            vec![snapshot("own", "Own", 0)]
+        );
+    }
+
+    #[test]
+    fn backlinks_with_own_page_keep_self_links() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let target_id = db.insert_page("project-x", "Project X").expect("target");
+        db.replace_blocks_for_page(target_id, &[snapshot("t1", "See [[Project X]]", 0)])
+            .expect("seed target");
+        let source_id = db.insert_page("journal", "Journal").expect("source");
+        db.replace_blocks_for_page(source_id, &[snapshot("s1", "Met about [[project-x]]", 0)])
+            .expect("seed source");
+
+        let uids = |records: Vec<BlockPageRecord>| -> Vec<String> {
+            records.into_iter().map(|record| record.block_uid).collect()
+        };
+        assert_eq!(
+            uids(db.list_backlinks("project-x").expect("backlinks")),
+            vec!["s1".to_string()]
+        );
+        assert_eq!(
+            uids(
+                db.list_backlinks_with_own_page("project-x")
+                    .expect("backlinks")
+            ),
+            vec!["s1".to_string(), "t1".to_string()]
+        );
+    }
+
+    #[test]
+    fn saving_blocks_maintains_link_edges() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let target_id = db.insert_page("project-x", "Project X").expect("target");
+        db.replace_blocks_for_page(target_id, &[snapshot("target-block", "Goal", 0)])
+            .expect("seed target");
+        let source_id = db.insert_page("journal", "Journal").expect("source");
+        db.replace_blocks_for_page(
+            source_id,
+            &[
+                snapshot("s1", "Met about [[Project X|the project]]", 0),
+                snapshot("s2", "See ((target-block))", 0),
+                snapshot("s3", "{{embed [[project-x]]}}", 0),
+            ],
+        )
+        .expect("seed source");
+
+        let s1 = db.get_block_by_uid("s1").expect("get").expect("s1");
+        let edges = db.list_edges_from_block(s1.id).expect("edges");
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].kind, "wikilink");
+        assert_eq!(edges[0].to_block_uid, "project-x");
+
+        let backlinks: Vec<String> = db
+            .list_backlinks("project-x")
+            .expect("backlinks")
+            .into_iter()
+            .map(|record| record.block_uid)
+            .collect();
+        assert_eq!(backlinks, vec!["s1".to_string(), "s3".to_string()]);
+
+        let block_backlinks = db
+            .list_block_backlinks("target-block")
+            .expect("block backlinks");
+        assert_eq!(block_backlinks.len(), 1);
+        assert_eq!(block_backlinks[0].block_uid, "s2");
+        assert_eq!(block_backlinks[0].page_title, "Journal");
+
+        db.replace_blocks_for_page(
+            source_id,
+            &[
+                snapshot("s1", "No longer linked", 0),
+                snapshot("s3", "{{embed [[project-x]]}}", 0),
+            ],
+        )
+        .expect("edit source");
+        let backlinks = db.list_backlinks("project-x").expect("backlinks");
+        assert_eq!(backlinks.len(), 1);
+        assert!(db
+            .list_block_backlinks("target-block")
+            .expect("block backlinks")
+            .is_empty());
+    }
+
+    #[test]
+    fn block_refs_resolve_text_counts_and_embed_subtrees() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let target_id = db.insert_page("project-x", "Project X").expect("target");
+        db.replace_blocks_for_page(
+            target_id,
+            &[
+                snapshot("intro", "Intro", 0),
+                snapshot("goal", "Goal", 1),
+                snapshot("step-1", "Step one", 2),
+                snapshot("step-2", "Step two", 2),
+                snapshot("after", "After", 1),
+            ],
+        )
+        .expect("seed target");
+        let source_id = db.insert_page("journal", "Journal").expect("source");
+        db.replace_blocks_for_page(
+            source_id,
+            &[
+                snapshot("s1", "See ((goal)) and ((goal|again))", 0),
+                snapshot("s2", "{{embed ((goal))}}", 0),
+                snapshot("s3", "Also ((step-1))", 0),
+            ],
+        )
+        .expect("seed source");
+
+        let uids = vec!["goal".to_string(), "step-1".to_string(), "gone".to_string()];
+        let found = db.lookup_blocks(&uids).expect("lookup");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found["goal"].text, "Goal");
+        assert_eq!(found["goal"].page_title, "Project X");
+
+        let counts = db.count_block_references(&uids).expect("counts");
+        assert_eq!(counts.get("goal"), Some(&2));
+        assert_eq!(counts.get("step-1"), Some(&1));
+        assert_eq!(counts.get("gone"), None);
+
+        let embed = db.load_block_embed("goal").expect("embed").expect("goal");
+        assert_eq!(embed.page_uid, "project-x");
+        assert_eq!(
+            embed.blocks,
+            vec![
+                snapshot("goal", "Goal", 0),
+                snapshot("step-1", "Step one", 1),
+                snapshot("step-2", "Step two", 1),
+            ]
+        );
+        assert!(db.load_block_embed("gone").expect("embed").is_none());
+
+        let page = db
+            .load_page_embed("Project X")
+            .expect("page embed")
+            .expect("project");
+        assert_eq!(page.page_uid, "project-x");
+        assert_eq!(page.blocks.len(), 5);
+        assert!(db.load_page_embed("Missing").expect("embed").is_none());
+    }
+
+    #[test]
+    fn list_backlinks_matches_renamed_page_title() {
         let db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let expected_tables = [
-            "pages",
-            "blocks",
-            "edges",
-            "tags",
-            "block_tags",
-            "assets",
-            "kv",
-            "plugin_perms",
-            "review_queue",
-            "sync_ops",
-            "sync_inbox",
-            "blocks_fts",
-            "pages_fts",
-        ];
+        let page_id = db.insert_page("old-name", "Old Name").expect("page");
+        db.update_page_title(page_id, "New Name").expect("rename");
+        let other_id = db.insert_page("other", "Other").expect("other");
+        db.insert_block(other_id, "b1", None, "a", "Link [[New Name]]", "{}")
+            .expect("b1");
+        let block_id = db
+            .insert_block(other_id, "b2", None, "b", "Plain", "{}")
+            .expect("b2");
+        db.update_block_text(block_id, "Also [[old-name]]")
+            .expect("update");
 
-        for table in expected_tables {
-            assert!(table_exists(&db, table), "missing table {table}");
-        }
+        let backlinks = db.list_backlinks("old-name").expect("backlinks");
+        assert_eq!(backlinks.len(), 2);
     }
 
     #[test]
-    fn blocks_table_has_columns() {
+    fn aliases_resolve_pages_and_merge_backlinks() {
         let db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let columns = table_columns(&db, "blocks");
-        let expected = [
-            "id",
-            "uid",
-            "page_id",
-            "parent_id",
-            "sort_key",
-            "text",
-            "props",
-            "created_at",
-            "updated_at",
-        ];
+        let page_id = db
+            .insert_page("knowledge-management", "Knowledge Management")
+            .expect("page");
+        db.set_page_property(page_id, "alias", "KM, Second Brain", "multi_select")
+            .expect("aliases");
+        let other_id = db.insert_page("other", "Other").expect("other");
+        db.insert_block(other_id, "b1", None, "a", "See [[KM]]", "{}")
+            .expect("b1");
+        db.insert_block(other_id, "b2", None, "b", "Tagged #second-brain", "{}")
+            .expect("b2");
+        db.insert_block(other_id, "b3", None, "c", "[[Knowledge Management]]", "{}")
+            .expect("b3");
+
+        let resolved = db.resolve_page("km").expect("resolve").expect("page");
+        assert_eq!(resolved.uid, "knowledge-management");
+        assert!(db.resolve_page("Nope").expect("resolve").is_none());
+        assert_eq!(
+            db.list_backlinks("knowledge-management")
+                .expect("backlinks")
+                .len(),
+            3
+        );
+        db.insert_block(page_id, "b4", None, "a", "Inside", "{}")
+            .expect("b4");
+        let hits = db
+            .search(&SearchQuery::parse("page:KM"), 10)
+            .expect("search");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].block_uid, "b4");
+        let hits = db
+            .search(&SearchQuery::parse("page:\"second brain\""), 10)
+            .expect("search");
+        assert_eq!(hits[0].block_uid, "b4");
+
+        db.rename_page(page_id, "PKM").expect("rename");
+        assert_eq!(
+            db.list_page_aliases(page_id).expect("aliases"),
+            vec!["KM", "Second Brain", "Knowledge Management"]
+        );
+        db.rename_page(page_id, "Knowledge Management")
+            .expect("rename back");
+        assert_eq!(
+            db.list_page_aliases(page_id).expect("aliases"),
+            vec!["KM", "Second Brain", "PKM"]
+        );
 
-        for column in expected {
-            assert!(columns.contains(&column.to_string()), "missing {column}");
-        }
+        db.delete_page_property(page_id, "alias").expect("clear");
+        assert!(db.resolve_page("KM").expect("resolve").is_none());
     }
 
     #[test]
-    fn fts_updates_on_insert_update_delete() {
+    fn rename_page_and_links_rewrites_wikilinks() {
         let db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let page_id = db
-            .insert_page("page-uid", "Test page")
-            .expect("insert page");
-        let block_id = db
-            .insert_block(page_id, "block-uid", None, "a", "hello world", "{}")
-            .expect("insert block");
+        let page_id = db.insert_page("old-name", "Old Name").expect("page");
+        let other_id = db.insert_page("other", "Other").expect("other");
+        db.insert_block(other_id, "b1", None, "a", "Link [[Old Name]]", "{}")
+            .expect("b1");
+        db.insert_block(other_id, "b2", None, "b", "Other [[Elsewhere]]", "{}")
+            .expect("b2");
+
+        let rewritten = db
+            .rename_page_and_links(page_id, "New Name")
+            .expect("rename");
+        assert_eq!(
+            rewritten,
+            std::collections::HashMap::from([("b1".to_string(), "Link [[New Name]]".to_string())])
+        );
+        let b1 = db.get_block_by_uid("b1").expect("get").expect("b1");
+        assert_eq!(b1.text, "Link [[New Name]]");
+        assert_eq!(
+            db.list_page_aliases(page_id).expect("aliases"),
+            vec!["Old Name"]
+        );
+    }
 
-        let results = db.search_blocks("hello").expect("search");
-        assert_eq!(results, vec![block_id]);
+    #[test]
+    fn saving_blocks_records_compacted_revisions() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
 
-        db.update_block_text(block_id, "goodbye world")
-            .expect("update block");
-        let results = db.search_blocks("hello").expect("search after update");
-        assert!(results.is_empty());
+        let page_id = db.insert_page("notes", "Notes").expect("page");
+        db.replace_blocks_for_page(page_id, &[snapshot("a", "First draft", 0)])
+            .expect("save v1");
+        db.replace_blocks_for_page(
+            page_id,
+            &[snapshot("a", "First draft", 0), snapshot("b", "More", 0)],
+        )
+        .expect("save v2");
+        // Both saves land in the same revision window.
+        let revisions = db.list_page_revisions(page_id).expect("revisions");
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].block_count, 2);
 
-        let results = db.search_blocks("goodbye").expect("search after update");
-        assert_eq!(results, vec![block_id]);
+        db.conn
+            .execute(
+                "UPDATE page_revisions SET created_at = created_at - 3600000",
+                [],
+            )
+            .expect("age revision");
+        db.replace_blocks_for_page(page_id, &[snapshot("b", "More!", 0)])
+            .expect("save v3");
+        let revisions = db.list_page_revisions(page_id).expect("revisions");
+        assert_eq!(revisions.len(), 2);
+
+        let older = db
+            .load_page_revision(revisions[1].id)
+            .expect("load")
+            .expect("revision");
+        assert_eq!(older.blocks.len(), 2);
+        let newer = db
+            .load_page_revision(revisions[0].id)
+            .expect("load")
+            .expect("revision");
+        let kinds: Vec<BlockDiffKind> = diff_blocks(&older.blocks, &newer.blocks)
+            .into_iter()
+            .map(|row| row.kind)
+            .collect();
+        assert_eq!(kinds, vec![BlockDiffKind::Removed, BlockDiffKind::Changed]);
+
+        assert!(db.restore_page_revision(older.id).expect("restore"));
+        let texts: Vec<String> = db
+            .load_blocks_for_page(page_id)
+            .expect("blocks")
+            .into_iter()
+            .map(|block| block.text)
+            .collect();
+        assert_eq!(texts, vec!["First draft", "More"]);
+        // The restore gets its own revision; the state it replaced is kept.
+        let revisions = db.list_page_revisions(page_id).expect("revisions");
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].block_count, 2);
+        assert_eq!(revisions[1].block_count, 1);
+    }
 
-        db.delete_block(block_id).expect("delete block");
-        let results = db.search_blocks("goodbye").expect("search after delete");
-        assert!(results.is_empty());
+    #[test]
+    fn saves_in_a_revision_window_only_mark_it_stale() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("notes", "Notes").expect("page");
+        db.replace_blocks_for_page(page_id, &[snapshot("a", "One", 0)])
+            .expect("save v1");
+        db.replace_blocks_for_page(page_id, &[snapshot("a", "One two", 0)])
+            .expect("save v2");
+        let stored: (i64, String) = db
+            .conn
+            .query_row("SELECT stale, blocks FROM page_revisions", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .expect("revision");
+        assert_eq!(stored.0, 1);
+        assert!(!stored.1.contains("One two"));
+
+        // The window closes unread; the next save keeps its last state before moving on.
+        db.conn
+            .execute(
+                "UPDATE page_revisions SET created_at = created_at - 3600000",
+                [],
+            )
+            .expect("age revision");
+        db.replace_blocks_for_page(page_id, &[snapshot("a", "One two three", 0)])
+            .expect("save v3");
+        let texts: Vec<String> = db
+            .list_page_revisions(page_id)
+            .expect("revisions")
+            .into_iter()
+            .map(|revision| {
+                db.load_page_revision(revision.id)
+                    .expect("load")
+                    .expect("revision")
+                    .blocks[0]
+                    .text
+                    .clone()
+            })
+            .collect();
+        assert_eq!(texts, vec!["One two three", "One two"]);
     }
 
     #[test]
-    fn update_block_text_by_uid_updates_fts() {
-        let db = Database::new_in_memory().expect("db init");
+    fn saving_blocks_syncs_tags() {
+        let mut db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let page_id = db
-            .insert_page("page-uid", "Test page")
-            .expect("insert page");
-        let block_id = db
-            .insert_block(page_id, "block-uid", None, "a", "hello world", "{}")
-            .expect("insert block");
+        let page_id = db.insert_page("journal", "Journal").expect("page");
+        db.replace_blocks_for_page(
+            page_id,
+            &[
+                snapshot("t1", "Ship it #Project #[[Deep Work]]", 0),
+                snapshot("t2", "Plan #project", 0),
+                snapshot("t3", "# Heading only", 0),
+            ],
+        )
+        .expect("save");
+
+        let t1 = db.get_block_by_uid("t1").expect("get").expect("t1");
+        let names: Vec<String> = db
+            .list_tags_for_block(t1.id)
+            .expect("tags")
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["deep work".to_string(), "project".to_string()]);
+
+        let tags = db.list_tags().expect("list tags");
+        assert_eq!(tags[0].name, "project");
+        assert_eq!(tags[0].block_count, 2);
+        assert_eq!(db.search_tags("De", 10).expect("search").len(), 1);
+
+        let tagged: Vec<String> = db
+            .list_blocks_for_tag("Project")
+            .expect("tagged")
+            .into_iter()
+            .map(|record| record.block_uid)
+            .collect();
+        assert_eq!(tagged, vec!["t1".to_string(), "t2".to_string()]);
+
+        let hits = db
+            .search_blocks_with_tags("plan", &["project".to_string()], 10)
+            .expect("filtered search");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].block_uid, "t2");
+
+        db.insert_page("project", "Project").expect("tag page");
+        assert_eq!(db.list_tagged_blocks("project").expect("tag page").len(), 2);
 
-        db.update_block_text_by_uid("block-uid", "updated title")
-            .expect("update block by uid");
+        db.replace_blocks_for_page(
+            page_id,
+            &[
+                snapshot("t1", "Ship it #[[Deep Work]]", 0),
+                snapshot("t2", "Plan", 0),
+            ],
+        )
+        .expect("resave");
+        assert!(db
+            .list_blocks_for_tag("project")
+            .expect("tagged")
+            .is_empty());
+        let tags = db.list_tags().expect("list tags");
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "deep work");
+    }
 
-        let results = db.search_blocks("updated").expect("search updated");
-        assert_eq!(results, vec![block_id]);
+    #[test]
+    fn block_properties_follow_text_and_definitions() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+        db.upsert_property_definition("due", "Due", "date")
+            .expect("definition");
+
+        let page_id = db.insert_page("tasks", "Tasks").expect("page");
+        db.replace_blocks_for_page(
+            page_id,
+            &[snapshot(
+                "task",
+                "Write docs\nstatus:: doing\ndue:: soon",
+                0,
+            )],
+        )
+        .expect("save");
+        let task = db.get_block_by_uid("task").expect("get").expect("task");
+        let props = db.get_block_properties(task.id).expect("props");
+        assert_eq!(props.len(), 2);
+        assert_eq!(props[0].key, "status");
+        assert_eq!(props[0].value, "doing");
+        assert_eq!(props[1].key, "due");
+        assert_eq!(props[1].value_type, "text");
+
+        assert!(db.set_block_property(task.id, "due", "next week").is_err());
+        db.set_block_property(task.id, "due", "2026-03-01")
+            .expect("set due");
+        db.set_block_property(task.id, "Owner", "[[Alex]]")
+            .expect("set owner");
+        let task = db.get_block_by_uid("task").expect("get").expect("task");
+        assert_eq!(
+            task.text,
+            "Write docs\nstatus:: doing\ndue:: 2026-03-01\nowner:: [[Alex]]"
+        );
+        let props = db.get_block_properties(task.id).expect("props");
+        assert_eq!(props.len(), 3);
+        assert_eq!(props[1].value_type, "date");
+
+        db.delete_block_property(task.id, "status").expect("delete");
+        let keys: Vec<String> = db
+            .get_block_properties(task.id)
+            .expect("props")
+            .into_iter()
+            .map(|prop| prop.key)
+            .collect();
+        assert_eq!(keys, vec!["due".to_string(), "owner".to_string()]);
     }
 
     #[test]
-    fn search_block_summaries_returns_text() {
-        let db = Database::new_in_memory().expect("db init");
+    fn query_pages_filters_and_sorts_in_sql() {
+        let mut db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let page_id = db
-            .insert_page("page-uid", "Search page")
-            .expect("insert page");
-        db.insert_block(page_id, "block-uid", None, "a", "alpha note", "{}")
-            .expect("insert block");
-        db.insert_block(page_id, "block-uid-2", None, "b", "beta note", "{}")
-            .expect("insert block");
+        let alpha = db.insert_page("alpha", "Alpha").expect("alpha");
+        let beta = db.insert_page("beta", "Beta").expect("beta");
+        let gamma = db.insert_page("gamma", "Gamma").expect("gamma");
+        db.set_page_property(alpha, "status", "Doing", "select")
+            .expect("prop");
+        db.set_page_property(alpha, "owner", "Sam", "text")
+            .expect("prop");
+        db.set_page_property(beta, "status", "done", "select")
+            .expect("prop");
+        db.set_page_property(gamma, "owner", "Alex", "text")
+            .expect("prop");
+        db.replace_blocks_for_page(gamma, &[snapshot("g1", "Kickoff #project", 0)])
+            .expect("blocks");
+
+        let titles = |rows: Vec<PageQueryRow>| -> Vec<String> {
+            rows.into_iter().map(|(page, _)| page.title).collect()
+        };
 
-        let results = db
-            .search_block_summaries("alpha", 10)
-            .expect("search summaries");
+        let query = PageQuery {
+            filters: vec![PropertyFilter {
+                key: "status".to_string(),
+                op: FilterOp::NotEquals,
+                value: "DONE".to_string(),
+                values: Vec::new(),
+            }],
+            sorts: vec![PropertySort {
+                key: "owner".to_string(),
+                descending: false,
+            }],
+            ..PageQuery::default()
+        };
+        let rows = db.query_pages(&query).expect("query");
+        assert_eq!(rows[1].1.len(), 2);
+        assert_eq!(titles(rows), vec!["Gamma", "Alpha"]);
+
+        let query = PageQuery {
+            filters: vec![PropertyFilter {
+                key: "status".to_string(),
+                op: FilterOp::IsEmpty,
+                value: String::new(),
+                values: Vec::new(),
+            }],
+            ..PageQuery::default()
+        };
+        assert_eq!(
+            titles(db.query_pages(&query).expect("query")),
+            vec!["Gamma"]
+        );
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].uid, "block-uid");
-        assert_eq!(results[0].text, "alpha note");
+        let query = PageQuery {
+            source: PageSource::Tag("Project".to_string()),
+            ..PageQuery::default()
+        };
+        assert_eq!(
+            titles(db.query_pages(&query).expect("query")),
+            vec!["Gamma"]
+        );
+
+        let legacy = db
+            .query_pages_with_properties(Some("status"), Some("do"))
+            .expect("legacy query");
+        assert_eq!(titles(legacy), vec!["Alpha", "Beta"]);
     }
 
     #[test]
-    fn search_block_page_summaries_returns_page_info() {
+    fn query_pages_compares_typed_values() {
         let db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let page_id = db
-            .insert_page("page-uid", "Search page")
-            .expect("insert page");
-        db.insert_block(page_id, "block-uid", None, "a", "alpha note", "{}")
-            .expect("insert block");
-        db.insert_block(page_id, "block-uid-2", None, "b", "beta note", "{}")
-            .expect("insert block");
+        let pages = [
+            ("a", "A", "9", "2026-01-05", "red, Dark Blue"),
+            ("b", "B", "10", "2026-02-01", "green"),
+            ("c", "C", "", "", ""),
+        ];
+        for (uid, title, points, due, labels) in pages {
+            let id = db.insert_page(uid, title).expect("page");
+            db.set_page_property(id, "points", points, "number")
+                .expect("points");
+            db.set_page_property(id, "due", due, "date").expect("due");
+            db.set_page_property(id, "labels", labels, "multi_select")
+                .expect("labels");
+        }
+        let err = db
+            .set_page_property(1, "points", "lots", "number")
+            .expect_err("invalid number");
+        assert!(err.to_string().contains("invalid number value"));
+        assert!(matches!(err, DbError::PropertyValue(_)));
+
+        let run = |key: &str, op: FilterOp, value: &str, values: &[&str]| -> Vec<String> {
+            let query = PageQuery {
+                filters: vec![PropertyFilter {
+                    key: key.to_string(),
+                    op,
+                    value: value.to_string(),
+                    values: values.iter().map(|value| value.to_string()).collect(),
+                }],
+                ..PageQuery::default()
+            };
+            db.query_pages(&query)
+                .expect("query")
+                .into_iter()
+                .map(|(page, _)| page.title)
+                .collect()
+        };
 
-        let results = db
-            .search_block_page_summaries("alpha", 10)
-            .expect("search summaries");
+        // Numeric, not lexicographic: "9" < "10".
+        assert_eq!(run("points", FilterOp::GreaterThan, "9.5", &[]), vec!["B"]);
+        assert_eq!(run("points", FilterOp::LessThan, "10", &[]), vec!["A"]);
+        assert_eq!(
+            run("due", FilterOp::Between, "2026-01-01", &["2026-01-31"]),
+            vec!["A"]
+        );
+        assert_eq!(run("points", FilterOp::In, "", &["10", "11"]), vec!["B"]);
+        assert_eq!(run("labels", FilterOp::In, "", &["dark blue"]), vec!["A"]);
+        assert!(run("labels", FilterOp::In, "", &["Blue"]).is_empty());
+        assert!(run("labels", FilterOp::In, "", &["darkblue"]).is_empty());
+        assert_eq!(run("due", FilterOp::IsEmpty, "", &[]), vec!["C"]);
+        assert!(db
+            .query_pages(&PageQuery {
+                filters: vec![PropertyFilter {
+                    key: "due".to_string(),
+                    op: FilterOp::LessThan,
+                    value: "soon".to_string(),
+                    values: Vec::new(),
+                }],
+                ..PageQuery::default()
+            })
+            .is_err());
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].block_uid, "block-uid");
-        assert_eq!(results[0].page_uid, "page-uid");
-        assert_eq!(results[0].page_title, "Search page");
-        assert_eq!(results[0].text, "alpha note");
+        let query = PageQuery {
+            sorts: vec![PropertySort {
+                key: "points".to_string(),
+                descending: true,
+            }],
+            ..PageQuery::default()
+        };
+        let titles: Vec<String> = db
+            .query_pages(&query)
+            .expect("sorted")
+            .into_iter()
+            .map(|(page, _)| page.title)
+            .collect();
+        assert_eq!(titles, vec!["B", "A", "C"]);
     }
 
     #[test]
-    fn replace_and_load_blocks_roundtrip() {
-        let mut db = Database::new_in_memory().expect("db init");
+    fn block_edges_migration_backfills_existing_text() {
+        let db = Database::new_in_memory().expect("db init");
         db.run_migrations().expect("migrations");
 
-        let page_id = db.insert_page("page-uid", "Inbox").expect("insert page");
-        let blocks = vec![
-            BlockSnapshot {
-                uid: "block-1".to_string(),
-                text: "First line".to_string(),
-                indent: 0,
-                block_type: BlockType::Text,
-            },
-            BlockSnapshot {
-                uid: "block-2".to_string(),
-                text: "Indented line".to_string(),
-                indent: 2,
-                block_type: BlockType::Text,
-            },
-        ];
+        let page_id = db.insert_page("page", "Page").expect("page");
+        let block_id = db
+            .insert_block(page_id, "b1", None, "a", "Link [[Target]]", "{}")
+            .expect("block");
+        db.conn
+            .execute("DELETE FROM edges", [])
+            .expect("clear edges");
 
-        db.replace_blocks_for_page(page_id, &blocks)
-            .expect("replace blocks");
+        super::rebuild_all_block_links(&db.conn).expect("backfill");
+        let edges = db.list_edges_from_block(block_id).expect("edges");
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].to_block_uid, "target");
+    }
 
-        let loaded = db.load_blocks_for_page(page_id).expect("load blocks");
-        assert_eq!(loaded, blocks);
+    #[test]
+    fn block_tree_migration_backfills_parents_from_indent() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("page-uid", "Legacy").expect("insert page");
+        let root_id = db
+            .insert_block(page_id, "root", None, "000000", "Root", r#"{"indent":0}"#)
+            .expect("root");
+        let child_id = db
+            .insert_block(page_id, "child", None, "000001", "Child", r#"{"indent":1}"#)
+            .expect("child");
+        let grand_id = db
+            .insert_block(page_id, "grand", None, "000002", "Grand", r#"{"indent":2}"#)
+            .expect("grand");
+        let next_id = db
+            .insert_block(page_id, "next", None, "000003", "Next", r#"{"indent":1}"#)
+            .expect("next");
+        let broken_id = db
+            .insert_block(page_id, "broken", None, "000004", "Broken", "not json")
+            .expect("broken");
+
+        db.conn
+            .execute_batch(super::MIGRATIONS[3].up)
+            .expect("backfill");
+
+        let parent_of = |id: i64| db.get_block(id).expect("get").expect("block").parent_id;
+        assert_eq!(parent_of(root_id), None);
+        assert_eq!(parent_of(child_id), Some(root_id));
+        assert_eq!(parent_of(grand_id), Some(child_id));
+        assert_eq!(parent_of(next_id), Some(root_id));
+        assert_eq!(parent_of(broken_id), None);
     }
 
     #[test]
@@ -1732,6 +5122,88 @@ mod tests {
         assert!(remaining.is_empty());
     }
 
+    #[test]
+    fn grading_review_items_reschedules_and_logs_answers() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let start = 1_700_000_000_000;
+        let id = db
+            .upsert_review_queue_item("page-1", "b1", start, None)
+            .expect("insert review item");
+        let item = db.get_review_queue_item(id).expect("get").expect("item");
+        assert_eq!(item.card, ReviewCard::default());
+
+        let first = db
+            .grade_review_queue_item(id, ReviewGrade::Good, start)
+            .expect("grade")
+            .expect("graded");
+        assert_eq!(first.card.interval_days, 1);
+        assert_eq!(first.due_at, start + DAY_MS);
+        let second = db
+            .grade_review_queue_item(id, ReviewGrade::Good, first.due_at)
+            .expect("grade")
+            .expect("graded");
+        assert_eq!(second.card.interval_days, 6);
+        let lapsed = db
+            .grade_review_queue_item(id, ReviewGrade::Again, second.due_at)
+            .expect("grade")
+            .expect("graded");
+        assert_eq!(lapsed.card.lapses, 1);
+        assert_eq!(lapsed.card.repetitions, 0);
+        assert_eq!(
+            db.get_review_queue_item(id).expect("get").expect("item"),
+            lapsed
+        );
+        assert_eq!(
+            db.grade_review_queue_item(999, ReviewGrade::Good, start)
+                .expect("grade missing"),
+            None
+        );
+
+        let stats = db.review_stats(lapsed.due_at, start).expect("stats");
+        assert_eq!(stats.reviewed, 3);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.due, 1);
+        assert_eq!(stats.pending, 1);
+        let retention = stats.retention.expect("retention");
+        assert!((retention - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.average_ease, None);
+    }
+
+    #[test]
+    fn review_forecast_buckets_pending_items_by_day() {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let day_start = 1_700_000_000_000;
+        let items = [
+            ("b1", day_start - DAY_MS),
+            ("b2", day_start + 60_000),
+            ("b3", day_start + DAY_MS + 5),
+            ("b4", day_start + 2 * DAY_MS),
+            ("b5", day_start + 10 * DAY_MS),
+        ];
+        for (block_uid, due_at) in items {
+            db.upsert_review_queue_item("page-1", block_uid, due_at, None)
+                .expect("insert review item");
+        }
+        let done = db
+            .upsert_review_queue_item("page-1", "b6", day_start, None)
+            .expect("insert review item");
+        db.mark_review_queue_item(done, "done", day_start, None)
+            .expect("mark done");
+
+        assert_eq!(
+            db.review_forecast(day_start, 3).expect("forecast"),
+            vec![2, 1, 1]
+        );
+        assert!(db.review_forecast(day_start, 0).expect("empty").is_empty());
+        let stats = db.review_stats(day_start, day_start).expect("stats");
+        assert_eq!((stats.due, stats.pending, stats.done), (1, 5, 1));
+        assert_eq!(stats.retention, None);
+    }
+
     #[test]
     fn replace_blocks_for_page_removes_stale_review_queue_items() {
         let mut db = Database::new_in_memory().expect("db init");
@@ -1781,4 +5253,162 @@ mod tests {
         assert_eq!(due[0].page_uid, "inbox");
         assert_eq!(due[0].block_uid, "cap-2");
     }
+
+    #[test]
+    fn saving_card_blocks_queues_them_for_review() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("biology", "Biology").expect("page");
+        db.replace_blocks_for_page(
+            page_id,
+            &[
+                snapshot("cloze", "The {{cloze mitochondria}} makes ATP", 0),
+                snapshot("basic", "Cell wall :: plants", 0),
+                snapshot("outline", "Organelles #card", 0),
+                snapshot("answer", "Nucleus", 1),
+                snapshot("plain", "Just a note", 0),
+            ],
+        )
+        .expect("save");
+
+        let queued = |db: &Database| {
+            let mut uids = db
+                .list_review_queue_due(i64::MAX, 10)
+                .expect("list due")
+                .into_iter()
+                .filter(|item| item.template.as_deref() == Some("card"))
+                .map(|item| item.block_uid)
+                .collect::<Vec<_>>();
+            uids.sort();
+            uids
+        };
+        assert_eq!(queued(&db), vec!["basic", "cloze", "outline"]);
+
+        // Grading survives later saves; untagging a card drops its item.
+        let basic = db
+            .list_review_queue_due(i64::MAX, 10)
+            .expect("list due")
+            .into_iter()
+            .find(|item| item.block_uid == "basic")
+            .expect("basic item");
+        let graded = db
+            .grade_review_queue_item(basic.id, ReviewGrade::Good, 1_000)
+            .expect("grade")
+            .expect("graded");
+        db.replace_blocks_for_page(
+            page_id,
+            &[
+                snapshot("cloze", "The {{cloze mitochondria}} makes ATP", 0),
+                snapshot("basic", "Cell wall :: plants and fungi", 0),
+                snapshot("outline", "Organelles", 0),
+                snapshot("answer", "Nucleus", 1),
+            ],
+        )
+        .expect("save again");
+        assert_eq!(
+            db.get_review_queue_item(basic.id).expect("get"),
+            Some(graded)
+        );
+        assert_eq!(queued(&db), vec!["basic", "cloze"]);
+
+        db.update_block_text_by_uid("cloze", "The mitochondria makes ATP")
+            .expect("edit outside the editor");
+        assert_eq!(queued(&db), vec!["basic"]);
+
+        // Items queued by hand are never claimed or dropped by card syncing.
+        db.upsert_review_queue_item("biology", "answer", 5, None)
+            .expect("queue by hand");
+        let manual = |db: &Database| {
+            db.list_review_queue_due(i64::MAX, 10)
+                .expect("list due")
+                .into_iter()
+                .find(|item| item.block_uid == "answer")
+        };
+        db.update_block_text_by_uid("answer", "Nucleus #card")
+            .expect("tag");
+        let tagged = manual(&db).expect("manual item");
+        assert_eq!((tagged.template, tagged.due_at), (None, 5));
+        db.update_block_text_by_uid("answer", "Nucleus")
+            .expect("untag");
+        assert!(manual(&db).is_some());
+    }
+
+    #[test]
+    fn block_property_edits_sync_cards_and_revisions() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+
+        let page_id = db.insert_page("geography", "Geography").expect("page");
+        db.replace_blocks_for_page(
+            page_id,
+            &[snapshot("saved", "Capital of France? :: Paris", 0)],
+        )
+        .expect("save");
+        // Blocks written without a page save are not queued yet.
+        let inserted = db
+            .insert_block(
+                page_id,
+                "inserted",
+                None,
+                "b",
+                "Capital of Peru? :: Lima",
+                "{}",
+            )
+            .expect("block");
+        let saved = db
+            .get_block_by_uid("saved")
+            .expect("get")
+            .expect("saved block");
+        let item = db
+            .list_review_queue_due(i64::MAX, 10)
+            .expect("list due")
+            .into_iter()
+            .find(|item| item.block_uid == "saved")
+            .expect("saved item");
+        let graded = db
+            .grade_review_queue_item(item.id, ReviewGrade::Good, 1_000)
+            .expect("grade")
+            .expect("graded");
+
+        db.set_block_property(saved.id, "source", "atlas")
+            .expect("set property");
+        db.set_block_property(inserted, "source", "atlas")
+            .expect("set property");
+        db.delete_block_property(saved.id, "source")
+            .expect("delete property");
+
+        let mut queued = db
+            .list_review_queue_due(i64::MAX, 10)
+            .expect("list due")
+            .into_iter()
+            .map(|item| item.block_uid)
+            .collect::<Vec<_>>();
+        queued.sort();
+        assert_eq!(queued, vec!["inserted", "saved"]);
+        assert_eq!(
+            db.get_review_queue_item(item.id).expect("get"),
+            Some(graded)
+        );
+
+        // The edits fold into the revision the save recorded.
+        let revisions = db.list_page_revisions(page_id).expect("revisions");
+        assert_eq!(revisions.len(), 1);
+        let revision = db
+            .load_page_revision(revisions[0].id)
+            .expect("load")
+            .expect("revision");
+        let texts = revision
+            .blocks
+            .iter()
+            .map(|block| block.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                "Capital of France? :: Paris",
+                "Capital of Peru? :: Lima\nsource:: atlas"
+            ]
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use crate::blocks::BlockType;
 use crate::db::BlockSnapshot;
+use crate::ops::{diff_ops, EditorOp};
+use crate::templates::ExpandedTemplate;
 use uuid::Uuid;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -8,10 +10,20 @@ pub struct Cursor {
     pub offset: usize,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Block list of one open page plus the ops that changed it. Every mutation goes through
+/// [`EditorOp`]s so undo and plugin edits replay the same log.
+#[derive(Clone, Debug, Default)]
 pub struct EditorModel {
-    pub blocks: Vec<BlockSnapshot>,
+    blocks: Vec<BlockSnapshot>,
     pub active_ix: usize,
+    ops: Vec<EditorOp>,
+}
+
+// The pending op log is bookkeeping, not document state.
+impl PartialEq for EditorModel {
+    fn eq(&self, other: &Self) -> bool {
+        self.blocks == other.blocks && self.active_ix == other.active_ix
+    }
 }
 
 impl EditorModel {
@@ -19,49 +31,183 @@ impl EditorModel {
         let mut model = Self {
             blocks,
             active_ix: 0,
+            ops: Vec::new(),
         };
         model.ensure_non_empty();
+        model.ops.clear();
         model
     }
 
     pub fn ensure_non_empty(&mut self) {
         if self.blocks.is_empty() {
-            self.blocks.push(BlockSnapshot {
-                uid: Uuid::new_v4().to_string(),
-                text: String::new(),
-                indent: 0,
-                block_type: BlockType::Text,
-            });
+            self.insert_block(0, empty_block(0));
         }
         if self.active_ix >= self.blocks.len() {
             self.active_ix = self.blocks.len() - 1;
         }
     }
 
-    pub fn active(&self) -> &BlockSnapshot {
-        &self.blocks[self.active_ix]
+    pub fn blocks(&self) -> &[BlockSnapshot] {
+        &self.blocks
     }
 
-    pub fn active_mut(&mut self) -> &mut BlockSnapshot {
-        &mut self.blocks[self.active_ix]
+    pub fn active(&self) -> &BlockSnapshot {
+        &self.blocks[self.active_ix]
     }
 
     pub fn set_active_ix(&mut self, ix: usize) {
         self.active_ix = ix.min(self.blocks.len().saturating_sub(1));
     }
 
+    /// Ops recorded since the last [`EditorModel::take_ops`].
+    pub fn pending_ops(&self) -> &[EditorOp] {
+        &self.ops
+    }
+
+    pub fn take_ops(&mut self) -> Vec<EditorOp> {
+        std::mem::take(&mut self.ops)
+    }
+
+    /// Applies and records `op`; returns `false` if it does not fit the current blocks.
+    pub fn apply_op(&mut self, op: EditorOp) -> bool {
+        if !op.apply(&mut self.blocks) {
+            return false;
+        }
+        self.ops.push(op);
+        true
+    }
+
+    /// Applies ops in order, stopping at the first one that does not fit.
+    pub fn apply_ops(&mut self, ops: &[EditorOp]) -> bool {
+        let applied = ops.iter().all(|op| self.apply_op(op.clone()));
+        self.ensure_non_empty();
+        applied
+    }
+
+    pub fn set_text(&mut self, ix: usize, text: &str) -> bool {
+        let Some(block) = self.blocks.get(ix) else {
+            return false;
+        };
+        match EditorOp::edit_text(&block.uid, &block.text, text) {
+            Some(op) => self.apply_op(op),
+            None => false,
+        }
+    }
+
+    pub fn set_block_type(&mut self, ix: usize, block_type: BlockType) -> bool {
+        let Some(block) = self.blocks.get(ix) else {
+            return false;
+        };
+        if block.block_type == block_type {
+            return false;
+        }
+        let op = EditorOp::SetType {
+            uid: block.uid.clone(),
+            before: block.block_type,
+            after: block_type,
+        };
+        self.apply_op(op)
+    }
+
+    pub fn set_indent(&mut self, ix: usize, indent: i64) -> bool {
+        let Some(block) = self.blocks.get(ix) else {
+            return false;
+        };
+        if block.indent == indent {
+            return false;
+        }
+        let op = EditorOp::SetIndent {
+            uid: block.uid.clone(),
+            before: block.indent,
+            after: indent,
+        };
+        self.apply_op(op)
+    }
+
+    /// Sets text, type and indent of the block at `ix` to those of `block`, keeping its uid.
+    pub fn update_block(&mut self, ix: usize, block: &BlockSnapshot) -> bool {
+        let text = self.set_text(ix, &block.text);
+        let block_type = self.set_block_type(ix, block.block_type);
+        let indent = self.set_indent(ix, block.indent);
+        text || block_type || indent
+    }
+
+    pub fn insert_block(&mut self, ix: usize, block: BlockSnapshot) -> bool {
+        self.apply_op(EditorOp::InsertBlock {
+            index: ix.min(self.blocks.len()),
+            block,
+        })
+    }
+
+    pub fn insert_blocks(&mut self, ix: usize, blocks: Vec<BlockSnapshot>) {
+        let ix = ix.min(self.blocks.len());
+        for (offset, block) in blocks.into_iter().enumerate() {
+            self.insert_block(ix + offset, block);
+        }
+    }
+
+    /// Expands a template at block `ix`, nested at that block's indent. An empty block is
+    /// replaced by the first template block; otherwise the template goes after it. Returns
+    /// where `{{cursor}}` landed, or the end of the last inserted block.
+    pub fn insert_template(&mut self, ix: usize, template: &ExpandedTemplate) -> Option<Cursor> {
+        let last = template.blocks.len().checked_sub(1)?;
+        let ix = ix.min(self.blocks.len().saturating_sub(1));
+        let subtree = self.subtree_range_from(ix);
+        // An empty childless target is replaced; otherwise the template lands after its subtree.
+        let (indent, reuse) = match self.blocks.get(ix) {
+            Some(block) => (
+                block.indent,
+                block.text.trim().is_empty() && subtree.len() == 1,
+            ),
+            None => (0, false),
+        };
+        let start = if reuse { ix } else { subtree.end };
+        for (offset, block) in template.blocks.iter().enumerate() {
+            let mut block = block.clone();
+            block.indent += indent;
+            if reuse && offset == 0 {
+                self.update_block(ix, &block);
+            } else {
+                self.insert_block(start + offset, block);
+            }
+        }
+        let cursor = match template.cursor {
+            Some((block_ix, offset)) => Cursor {
+                block_ix: start + block_ix,
+                offset,
+            },
+            None => Cursor {
+                block_ix: start + last,
+                offset: self.blocks[start + last].text.len(),
+            },
+        };
+        self.active_ix = cursor.block_ix;
+        Some(cursor)
+    }
+
+    pub fn remove_block(&mut self, ix: usize) -> Option<BlockSnapshot> {
+        let block = self.blocks.get(ix)?.clone();
+        self.apply_op(EditorOp::DeleteBlock {
+            index: ix,
+            block: block.clone(),
+        })
+        .then_some(block)
+    }
+
+    /// Turns the block list into `blocks` with the fewest ops [`diff_ops`] finds.
+    pub fn replace_blocks(&mut self, blocks: Vec<BlockSnapshot>) {
+        for op in diff_ops(&self.blocks, &blocks) {
+            self.apply_op(op);
+        }
+        self.ensure_non_empty();
+    }
+
     pub fn insert_after_active(&mut self, text: String) -> Cursor {
         let indent = self.active().indent;
         let insert_ix = self.active_ix + 1;
-        self.blocks.insert(
-            insert_ix,
-            BlockSnapshot {
-                uid: Uuid::new_v4().to_string(),
-                text,
-                indent,
-                block_type: BlockType::Text,
-            },
-        );
+        let mut block = empty_block(indent);
+        block.text = text;
+        self.insert_block(insert_ix, block);
         self.active_ix = insert_ix;
         Cursor {
             block_ix: insert_ix,
@@ -76,10 +222,10 @@ impl EditorModel {
             cursor -= 1;
         }
 
-        let before = text[..cursor].to_string();
+        let before = &text[..cursor];
         let after = text[cursor..].to_string();
 
-        self.active_mut().text = before;
+        self.set_text(self.active_ix, before);
         self.insert_after_active(after)
     }
 
@@ -92,7 +238,7 @@ impl EditorModel {
         }
 
         let removed_ix = self.active_ix;
-        self.blocks.remove(removed_ix);
+        self.remove_block(removed_ix);
 
         if removed_ix < self.blocks.len() {
             self.active_ix = removed_ix;
@@ -114,9 +260,10 @@ impl EditorModel {
         let current_text = self.active().text.clone();
         let target_ix = self.active_ix - 1;
         let target_offset = self.blocks[target_ix].text.len();
-        self.blocks[target_ix].text.push_str(&current_text);
+        let merged = format!("{}{}", self.blocks[target_ix].text, current_text);
+        self.set_text(target_ix, &merged);
 
-        self.blocks.remove(self.active_ix);
+        self.remove_block(self.active_ix);
         self.active_ix = target_ix;
 
         Some(Cursor {
@@ -131,9 +278,9 @@ impl EditorModel {
             return None;
         }
 
-        let next_text = self.blocks[next_ix].text.clone();
-        self.active_mut().text.push_str(&next_text);
-        self.blocks.remove(next_ix);
+        let merged = format!("{}{}", self.active().text, self.blocks[next_ix].text);
+        self.set_text(self.active_ix, &merged);
+        self.remove_block(next_ix);
 
         Some(Cursor {
             block_ix: self.active_ix,
@@ -173,9 +320,8 @@ impl EditorModel {
         if range.start == 0 || prev_start >= range.start {
             return false;
         }
-        let removed: Vec<_> = self.blocks.drain(range.clone()).collect();
         let insert_at = prev_start;
-        self.blocks.splice(insert_at..insert_at, removed);
+        self.move_span(range, insert_at);
         self.active_ix = insert_at;
         true
     }
@@ -188,9 +334,9 @@ impl EditorModel {
         if next_start <= range.start {
             return false;
         }
-        let removed: Vec<_> = self.blocks.drain(range.clone()).collect();
-        let insert_at = self.subtree_range_from(next_start - removed.len()).end;
-        self.blocks.splice(insert_at..insert_at, removed);
+        let count = range.len();
+        let insert_at = self.subtree_range_from(next_start).end - count;
+        self.move_span(range, insert_at);
         self.active_ix = insert_at;
         true
     }
@@ -204,7 +350,7 @@ impl EditorModel {
             indent: block.indent,
             block_type: block.block_type,
         };
-        self.blocks.insert(insert_ix, clone);
+        self.insert_block(insert_ix, clone);
         self.active_ix = insert_ix;
         Cursor {
             block_ix: insert_ix,
@@ -234,7 +380,7 @@ impl EditorModel {
         }
         let insert_at = end;
         let count = clones.len();
-        self.blocks.splice(insert_at..insert_at, clones);
+        self.insert_blocks(insert_at, clones);
         self.active_ix = insert_at;
         Some(insert_at..insert_at + count)
     }
@@ -244,20 +390,18 @@ impl EditorModel {
             return None;
         }
         let end = range.end.min(self.blocks.len());
-        if end - range.start >= self.blocks.len() {
-            self.blocks = vec![BlockSnapshot {
-                uid: Uuid::new_v4().to_string(),
-                text: String::new(),
-                indent: 0,
-                block_type: BlockType::Text,
-            }];
+        let remove_all = end - range.start >= self.blocks.len();
+        for ix in (range.start..end).rev() {
+            self.remove_block(ix);
+        }
+        if remove_all {
+            self.insert_block(0, empty_block(0));
             self.active_ix = 0;
             return Some(Cursor {
                 block_ix: 0,
                 offset: 0,
             });
         }
-        self.blocks.drain(range.start..end);
         let next_ix = if range.start < self.blocks.len() {
             range.start
         } else {
@@ -290,22 +434,40 @@ impl EditorModel {
             if range.start == 0 {
                 return None;
             }
-            let removed: Vec<_> = self.blocks.drain(range.start..end).collect();
             let insert_at = range.start - 1;
-            self.blocks.splice(insert_at..insert_at, removed);
+            self.move_span(range.start..end, insert_at);
             self.active_ix = insert_at;
             return Some(insert_at..insert_at + count);
         }
         if end >= self.blocks.len() {
             return None;
         }
-        let removed: Vec<_> = self.blocks.drain(range.start..end).collect();
         let insert_at = end + 1 - count;
-        self.blocks.splice(insert_at..insert_at, removed);
+        self.move_span(range.start..end, insert_at);
         self.active_ix = insert_at;
         Some(insert_at..insert_at + count)
     }
 
+    /// Moves the blocks in `range` so the first lands at `insert_at`, counted with the range
+    /// taken out of the list.
+    pub fn move_span(&mut self, range: std::ops::Range<usize>, insert_at: usize) -> bool {
+        let end = range.end.min(self.blocks.len());
+        let count = end.saturating_sub(range.start);
+        if count == 0 || insert_at == range.start || insert_at + count > self.blocks.len() {
+            return false;
+        }
+        for offset in 0..count {
+            let (from, to) = if insert_at < range.start {
+                (range.start + offset, insert_at + offset)
+            } else {
+                (range.start, insert_at + count - 1)
+            };
+            let uid = self.blocks[from].uid.clone();
+            self.apply_op(EditorOp::Move { uid, from, to });
+        }
+        true
+    }
+
     pub fn adjust_range_indent(&mut self, range: std::ops::Range<usize>, delta: i64) -> bool {
         if delta == 0 {
             return false;
@@ -315,12 +477,9 @@ impl EditorModel {
         }
         let end = range.end.min(self.blocks.len());
         let mut changed = false;
-        for block in &mut self.blocks[range.start..end] {
-            let next = (block.indent + delta).max(0);
-            if next != block.indent {
-                block.indent = next;
-                changed = true;
-            }
+        for ix in range.start..end {
+            let next = (self.blocks[ix].indent + delta).max(0);
+            changed |= self.set_indent(ix, next);
         }
         changed
     }
@@ -337,9 +496,9 @@ impl EditorModel {
 
     fn adjust_subtree_indent(&mut self, delta: i64) {
         let range = self.active_subtree_range();
-        for block in &mut self.blocks[range] {
-            let next = (block.indent + delta).max(0);
-            block.indent = next;
+        for ix in range {
+            let next = (self.blocks[ix].indent + delta).max(0);
+            self.set_indent(ix, next);
         }
     }
 
@@ -398,11 +557,22 @@ impl EditorModel {
     }
 }
 
+fn empty_block(indent: i64) -> BlockSnapshot {
+    BlockSnapshot {
+        uid: Uuid::new_v4().to_string(),
+        text: String::new(),
+        indent,
+        block_type: BlockType::Text,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Cursor, EditorModel};
     use crate::blocks::BlockType;
     use crate::db::BlockSnapshot;
+    use crate::ops::{invert_ops, EditorOp};
+    use crate::templates::ExpandedTemplate;
 
     fn block(uid: &str, text: &str, indent: i64) -> BlockSnapshot {
         BlockSnapshot {
@@ -693,4 +863,108 @@ mod tests {
         assert_eq!(model.blocks[0].indent, 0);
         assert_eq!(model.blocks[1].indent, 0);
     }
+    #[test]
+    fn mutations_record_ops_that_undo_cleanly() {
+        let original = vec![
+            block("a", "hello", 0),
+            block("b", "world", 0),
+            block("b1", "child", 1),
+            block("c", "", 0),
+        ];
+        let mut model = EditorModel::new(original.clone());
+        assert!(model.pending_ops().is_empty());
+
+        model.split_active_and_insert_after(2);
+        model.set_active_ix(2);
+        assert!(model.move_active_down());
+        assert!(model.indent_active());
+        model.set_active_ix(0);
+        model.merge_next_into_active(0);
+        model.set_block_type(0, BlockType::Quote);
+        model.delete_range(1..2);
+
+        let ops = model.take_ops();
+        assert!(model.pending_ops().is_empty());
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, EditorOp::Move { uid, .. } if uid == "b" || uid == "c")));
+        assert!(matches!(
+            &ops[0],
+            EditorOp::EditText { uid, range, .. } if uid == "a" && *range == (2..5)
+        ));
+
+        let edited = model.blocks.clone();
+        assert!(model.apply_ops(&invert_ops(&ops)));
+        assert_eq!(model.blocks, original);
+        assert!(model.apply_ops(&ops));
+        assert_eq!(model.blocks, edited);
+    }
+
+    #[test]
+    fn insert_template_nests_under_the_target_block() {
+        let template = ExpandedTemplate {
+            blocks: vec![
+                BlockSnapshot {
+                    block_type: BlockType::Heading2,
+                    ..block("t1", "Agenda", 0)
+                },
+                block("t2", "Notes", 1),
+            ],
+            cursor: Some((1, 2)),
+        };
+
+        let mut model = EditorModel::new(vec![block("a", "", 1), block("b", "after", 0)]);
+        let cursor = model.insert_template(0, &template).expect("cursor");
+        assert_eq!(model.blocks[0].uid, "a");
+        assert_eq!(model.blocks[0].block_type, BlockType::Heading2);
+        let layout = model
+            .blocks
+            .iter()
+            .map(|block| (block.text.as_str(), block.indent))
+            .collect::<Vec<_>>();
+        assert_eq!(layout, vec![("Agenda", 1), ("Notes", 2), ("after", 0)]);
+        assert_eq!(
+            cursor,
+            Cursor {
+                block_ix: 1,
+                offset: 2
+            }
+        );
+
+        let mut model = EditorModel::new(vec![block("a", "kept", 0)]);
+        let template = ExpandedTemplate {
+            cursor: None,
+            ..template
+        };
+        let cursor = model.insert_template(0, &template).expect("cursor");
+        assert_eq!(model.blocks.len(), 3);
+        assert_eq!(model.blocks[0].text, "kept");
+        assert_eq!(model.active_ix, 2);
+        assert_eq!(cursor.offset, "Notes".len());
+
+        let mut model = EditorModel::new(vec![
+            block("p", "Parent", 0),
+            block("c", "Child", 1),
+            block("g", "Grandchild", 2),
+            block("s", "Sibling", 0),
+        ]);
+        let cursor = model.insert_template(0, &template).expect("cursor");
+        let layout = model
+            .blocks()
+            .iter()
+            .map(|block| (block.text.as_str(), block.indent))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layout,
+            vec![
+                ("Parent", 0),
+                ("Child", 1),
+                ("Grandchild", 2),
+                ("Agenda", 0),
+                ("Notes", 1),
+                ("Sibling", 0),
+            ]
+        );
+        assert_eq!(cursor.block_ix, 4);
+    }
 }
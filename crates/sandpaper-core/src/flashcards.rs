@@ -0,0 +1,207 @@
+use crate::db::BlockSnapshot;
+use crate::links::{extract_tags, strip_tag};
+use crate::properties::strip_block_properties;
+use std::ops::Range;
+
+/// Tag that turns a block into a card whose children are the answer.
+pub const CARD_TAG: &str = "card";
+/// `review_queue.template` of the items created for card blocks.
+pub const CARD_TEMPLATE: &str = "card";
+const CARD_SEPARATOR: &str = " :: ";
+const CLOZE_OPEN: &str = "{{cloze ";
+const CLOZE_PLACEHOLDER: &str = "[…]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashcardKind {
+    /// `question :: answer` on one line.
+    Basic,
+    /// `{{cloze text}}` deletions hidden on the front.
+    Cloze,
+    /// A `#card` block answered by its children.
+    Outline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flashcard {
+    pub kind: FlashcardKind,
+    pub front: String,
+    pub back: String,
+    /// Byte ranges in `back` holding the cloze answers.
+    pub reveals: Vec<Range<usize>>,
+}
+
+/// Reads a card from a block's text. `children` are the texts of the block's descendants, each
+/// with its depth below the block (1 for direct children), and only answer `#card` blocks.
+pub fn parse_flashcard(text: &str, children: &[(usize, &str)]) -> Option<Flashcard> {
+    let body = strip_block_properties(text);
+    let body = body.trim();
+    if body.contains(CLOZE_OPEN) {
+        if let Some(card) = parse_cloze(&strip_card_tag(body)) {
+            return Some(card);
+        }
+    }
+    if let Some((front, back)) = body.split_once(CARD_SEPARATOR) {
+        let front = strip_card_tag(front);
+        let back = strip_card_tag(back);
+        if !front.is_empty() && !back.is_empty() {
+            return Some(Flashcard {
+                kind: FlashcardKind::Basic,
+                front,
+                back,
+                reveals: Vec::new(),
+            });
+        }
+    }
+    if !extract_tags(body).iter().any(|tag| tag == CARD_TAG) {
+        return None;
+    }
+    let back = children
+        .iter()
+        .filter_map(|(depth, text)| {
+            let text = strip_block_properties(text);
+            let text = text.trim();
+            (!text.is_empty()).then(|| format!("{}{text}", "  ".repeat(depth.saturating_sub(1))))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(Flashcard {
+        kind: FlashcardKind::Outline,
+        front: strip_card_tag(body),
+        back,
+        reveals: Vec::new(),
+    })
+}
+
+/// The card for `blocks[ix]`, reading its descendants from the indented blocks that follow it.
+pub fn flashcard_at(blocks: &[BlockSnapshot], ix: usize) -> Option<Flashcard> {
+    let block = blocks.get(ix)?;
+    let children = blocks[ix + 1..]
+        .iter()
+        .take_while(|child| child.indent > block.indent)
+        .map(|child| ((child.indent - block.indent) as usize, child.text.as_str()))
+        .collect::<Vec<_>>();
+    parse_flashcard(&block.text, &children)
+}
+
+/// Whether a block with this text is a card. Children only fill in an outline card's answer,
+/// so the block's own text decides.
+pub fn is_flashcard(text: &str) -> bool {
+    parse_flashcard(text, &[]).is_some()
+}
+
+/// Uids of the card blocks on a page, in page order.
+pub fn flashcard_uids(blocks: &[BlockSnapshot]) -> Vec<&str> {
+    blocks
+        .iter()
+        .filter(|block| is_flashcard(&block.text))
+        .map(|block| block.uid.as_str())
+        .collect()
+}
+
+fn parse_cloze(text: &str) -> Option<Flashcard> {
+    let mut front = String::with_capacity(text.len());
+    let mut back = String::with_capacity(text.len());
+    let mut reveals = Vec::new();
+    let mut cursor = 0;
+    while let Some(start_rel) = text[cursor..].find(CLOZE_OPEN) {
+        let start = cursor + start_rel;
+        let inner_start = start + CLOZE_OPEN.len();
+        let Some(end_rel) = text[inner_start..].find("}}") else {
+            break;
+        };
+        let inner_end = inner_start + end_rel;
+        let answer = text[inner_start..inner_end].trim();
+        front.push_str(&text[cursor..start]);
+        back.push_str(&text[cursor..start]);
+        if answer.is_empty() {
+            front.push_str(&text[start..inner_end + 2]);
+            back.push_str(&text[start..inner_end + 2]);
+        } else {
+            front.push_str(CLOZE_PLACEHOLDER);
+            reveals.push(back.len()..back.len() + answer.len());
+            back.push_str(answer);
+        }
+        cursor = inner_end + 2;
+    }
+    if reveals.is_empty() {
+        return None;
+    }
+    front.push_str(&text[cursor..]);
+    back.push_str(&text[cursor..]);
+    Some(Flashcard {
+        kind: FlashcardKind::Cloze,
+        front,
+        back,
+        reveals,
+    })
+}
+
+/// Drops `#card` and `#[[card]]` from card text.
+fn strip_card_tag(text: &str) -> String {
+    strip_tag(text, CARD_TAG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flashcard_at, flashcard_uids, parse_flashcard, FlashcardKind};
+    use crate::blocks::BlockType;
+    use crate::db::BlockSnapshot;
+
+    fn block(uid: &str, text: &str, indent: i64) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent,
+            block_type: BlockType::Text,
+        }
+    }
+
+    #[test]
+    fn basic_cards_split_on_the_double_colon() {
+        let card = parse_flashcard("Capital of France :: Paris #card", &[]).expect("card");
+        assert_eq!(card.kind, FlashcardKind::Basic);
+        assert_eq!(card.front, "Capital of France");
+        assert_eq!(card.back, "Paris");
+
+        // Block properties use `key:: value` and are not cards.
+        assert_eq!(parse_flashcard("Notes\nsource:: book", &[]), None);
+        assert_eq!(parse_flashcard("Empty :: ", &[]), None);
+    }
+
+    #[test]
+    fn cloze_cards_hide_each_deletion_on_the_front() {
+        let card = parse_flashcard(
+            "The {{cloze mitochondria}} is the {{cloze powerhouse}} of the cell",
+            &[],
+        )
+        .expect("card");
+        assert_eq!(card.kind, FlashcardKind::Cloze);
+        assert_eq!(card.front, "The […] is the […] of the cell");
+        assert_eq!(card.back, "The mitochondria is the powerhouse of the cell");
+        let revealed = card
+            .reveals
+            .iter()
+            .map(|range| &card.back[range.clone()])
+            .collect::<Vec<_>>();
+        assert_eq!(revealed, vec!["mitochondria", "powerhouse"]);
+
+        assert_eq!(parse_flashcard("Unclosed {{cloze answer", &[]), None);
+    }
+
+    #[test]
+    fn outline_cards_take_their_children_as_the_answer() {
+        let blocks = [
+            block("q", "Three primary colours #card", 0),
+            block("a1", "Red", 1),
+            block("a2", "Blue\ncolor:: blue", 1),
+            block("a3", "Cyan in print", 2),
+            block("next", "Unrelated", 0),
+        ];
+        let card = flashcard_at(&blocks, 0).expect("card");
+        assert_eq!(card.kind, FlashcardKind::Outline);
+        assert_eq!(card.front, "Three primary colours");
+        assert_eq!(card.back, "Red\nBlue\n  Cyan in print");
+        assert_eq!(flashcard_at(&blocks, 4), None);
+        assert_eq!(flashcard_uids(&blocks), vec!["q"]);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::db::{PageAliasRecord, PageRecord};
+use std::collections::{HashMap, HashSet};
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 8;
+const START_BONUS: i64 = 10;
+const WORD_BOUNDARY_BONUS: i64 = 8;
+const CAMEL_CASE_BONUS: i64 = 6;
+const GAP_START_PENALTY: i64 = 3;
+const GAP_EXTEND_PENALTY: i64 = 1;
+const EXACT_MATCH_BONUS: i64 = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices in the text matched by each query char.
+    pub positions: Vec<usize>,
+}
+
+/// Scores `query` as a case-insensitive subsequence of `text`, preferring matches that start
+/// words and run consecutively. Returns `None` when `query` is not a subsequence or is empty.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let chars: Vec<char> = text.chars().collect();
+    let lower = lowercase_chars(&chars);
+    match_chars(&query, &chars, &lower)
+}
+
+/// Score-only form of `fuzzy_match`; an empty query matches everything with score 0.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    if query.trim().is_empty() {
+        return Some(0);
+    }
+    fuzzy_match(query, text).map(|found| found.score)
+}
+
+/// Bonus for a recently used item at `rank` (0 is the most recent).
+pub fn recency_bonus(rank: Option<usize>) -> i64 {
+    rank.map(|rank| (24 - rank as i64 * 3).max(0)).unwrap_or(0)
+}
+
+fn lowercase_chars(chars: &[char]) -> Vec<char> {
+    // One char per input char so positions line up with `chars`.
+    chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect()
+}
+
+fn boundary_bonus(chars: &[char], ix: usize) -> i64 {
+    if ix == 0 {
+        return START_BONUS;
+    }
+    let prev = chars[ix - 1];
+    let current = chars[ix];
+    if !prev.is_alphanumeric() && current.is_alphanumeric() {
+        WORD_BOUNDARY_BONUS
+    } else if prev.is_lowercase() && current.is_uppercase() {
+        CAMEL_CASE_BONUS
+    } else {
+        0
+    }
+}
+
+fn match_chars(query: &[char], chars: &[char], lower: &[char]) -> Option<FuzzyMatch> {
+    let (n, m) = (query.len(), lower.len());
+    if n == 0 || n > m {
+        return None;
+    }
+    // Cheap subsequence check before the full alignment.
+    let mut next = 0;
+    for q in query {
+        next += lower[next..].iter().position(|ch| ch == q)? + 1;
+    }
+
+    // best[i][j]: best score with query[i] matched at text[j]; from[i][j]: where query[i - 1]
+    // was matched on that path.
+    const NONE: i64 = i64::MIN / 4;
+    let mut best = vec![vec![NONE; m]; n];
+    let mut from = vec![vec![usize::MAX; m]; n];
+    for j in 0..m {
+        if lower[j] == query[0] {
+            best[0][j] = MATCH_SCORE + boundary_bonus(chars, j) - (j as i64).min(GAP_START_PENALTY);
+        }
+    }
+    for i in 1..n {
+        // Best predecessor at least two chars back, with its gap penalty applied.
+        let mut gap_best = NONE;
+        let mut gap_from = usize::MAX;
+        for j in i..m {
+            if j >= 2 && best[i - 1][j - 2] > NONE {
+                let opened = best[i - 1][j - 2] - GAP_START_PENALTY;
+                if opened >= gap_best - GAP_EXTEND_PENALTY {
+                    gap_best = opened;
+                    gap_from = j - 2;
+                } else {
+                    gap_best -= GAP_EXTEND_PENALTY;
+                }
+            } else if gap_best > NONE {
+                gap_best -= GAP_EXTEND_PENALTY;
+            }
+            if lower[j] != query[i] {
+                continue;
+            }
+            let base = MATCH_SCORE + boundary_bonus(chars, j);
+            let consecutive = best[i - 1][j - 1];
+            if consecutive > NONE && consecutive + CONSECUTIVE_BONUS >= gap_best {
+                best[i][j] = base + consecutive + CONSECUTIVE_BONUS;
+                from[i][j] = j - 1;
+            } else if gap_best > NONE {
+                best[i][j] = base + gap_best;
+                from[i][j] = gap_from;
+            }
+        }
+    }
+
+    let (mut end, mut score) = (usize::MAX, NONE);
+    for (j, value) in best[n - 1].iter().enumerate() {
+        if *value > score {
+            score = *value;
+            end = j;
+        }
+    }
+    if score <= NONE {
+        return None;
+    }
+    let mut positions = vec![0; n];
+    let mut j = end;
+    for i in (0..n).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+
+    score -= (m - n) as i64 / 4;
+    if n == m {
+        score += EXACT_MATCH_BONUS;
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+#[derive(Debug, Clone)]
+struct IndexedName {
+    text: String,
+    chars: Vec<char>,
+    lower: Vec<char>,
+}
+
+impl IndexedName {
+    fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let lower = lowercase_chars(&chars);
+        Self {
+            text: text.to_string(),
+            chars,
+            lower,
+        }
+    }
+
+    fn matches(&self, query: &[char]) -> Option<FuzzyMatch> {
+        match_chars(query, &self.chars, &self.lower)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleMatch {
+    pub uid: String,
+    pub title: String,
+    pub score: i64,
+    /// Char positions in `alias` when an alias matched, otherwise in `title`.
+    pub positions: Vec<usize>,
+    /// The alias that matched, when it scored better than the title.
+    pub alias: Option<String>,
+}
+
+/// In-memory page title index for fuzzy lookups. Titles are pre-split into chars once, so
+/// keystroke searches only pay for scoring.
+#[derive(Debug, Clone, Default)]
+pub struct TitleIndex {
+    entries: HashMap<String, IndexedName>,
+    aliases: HashMap<String, Vec<IndexedName>>,
+}
+
+impl TitleIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a page or updates its title after a rename.
+    pub fn upsert(&mut self, uid: &str, title: &str) {
+        if self
+            .entries
+            .get(uid)
+            .is_some_and(|entry| entry.text == title)
+        {
+            return;
+        }
+        self.entries
+            .insert(uid.to_string(), IndexedName::new(title));
+    }
+
+    pub fn remove(&mut self, uid: &str) {
+        self.entries.remove(uid);
+        self.aliases.remove(uid);
+    }
+
+    /// Replaces the aliases searched alongside the page's title.
+    pub fn set_aliases(&mut self, uid: &str, aliases: &[String]) {
+        if aliases.is_empty() {
+            self.aliases.remove(uid);
+            return;
+        }
+        let names = aliases
+            .iter()
+            .map(|alias| IndexedName::new(alias))
+            .collect();
+        self.aliases.insert(uid.to_string(), names);
+    }
+
+    /// Replaces every page's aliases.
+    pub fn sync_aliases(&mut self, aliases: &[PageAliasRecord]) {
+        self.aliases.clear();
+        for record in aliases {
+            self.aliases
+                .entry(record.page_uid.clone())
+                .or_default()
+                .push(IndexedName::new(&record.alias));
+        }
+    }
+
+    pub fn aliases(&self, uid: &str) -> Vec<String> {
+        self.aliases
+            .get(uid)
+            .map(|names| names.iter().map(|name| name.text.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Brings the index in line with `pages`, re-indexing only added or renamed pages.
+    pub fn sync(&mut self, pages: &[PageRecord]) {
+        let mut seen = HashSet::with_capacity(pages.len());
+        for page in pages {
+            self.upsert(&page.uid, &page.title);
+            seen.insert(page.uid.as_str());
+        }
+        self.entries.retain(|uid, _| seen.contains(uid.as_str()));
+        self.aliases.retain(|uid, _| seen.contains(uid.as_str()));
+    }
+
+    /// Ranks titles matching `query`, adding `recency_bonus` for uids in `recent` (most recent
+    /// first). A page matches on the better of its title and its aliases; blank titles match on
+    /// their uid.
+    pub fn search(&self, query: &str, recent: &[String], limit: usize) -> Vec<TitleMatch> {
+        let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+        let mut matches: Vec<TitleMatch> = self
+            .entries
+            .iter()
+            .filter_map(|(uid, entry)| {
+                let title_match = if entry.text.trim().is_empty() {
+                    IndexedName::new(uid).matches(&query)
+                } else {
+                    entry.matches(&query)
+                };
+                let mut best = title_match.map(|found| (found, None));
+                for alias in self.aliases.get(uid).into_iter().flatten() {
+                    let Some(found) = alias.matches(&query) else {
+                        continue;
+                    };
+                    if best
+                        .as_ref()
+                        .is_none_or(|(current, _)| found.score > current.score)
+                    {
+                        best = Some((found, Some(alias.text.clone())));
+                    }
+                }
+                let (found, alias) = best?;
+                let rank = recent.iter().position(|recent_uid| recent_uid == uid);
+                Some(TitleMatch {
+                    uid: uid.clone(),
+                    title: entry.text.clone(),
+                    score: found.score + recency_bonus(rank),
+                    positions: found.positions,
+                    alias,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+                .then_with(|| a.uid.cmp(&b.uid))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fuzzy_match, fuzzy_score, TitleIndex};
+    use crate::db::PageRecord;
+
+    fn page(id: i64, uid: &str, title: &str) -> PageRecord {
+        PageRecord {
+            id,
+            uid: uid.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn fuzzy_match_prefers_word_starts_and_runs() {
+        let found = fuzzy_match("prjplan", "Project Plan").expect("match");
+        assert_eq!(found.positions, vec![0, 1, 3, 8, 9, 10, 11]);
+        assert!(fuzzy_match("xyz", "Project Plan").is_none());
+
+        let tight = fuzzy_score("note", "notebook").unwrap();
+        let loose = fuzzy_score("note", "n o t e book").unwrap();
+        assert!(tight > loose);
+        // Runs and word starts beat the first occurrence of each char.
+        let found = fuzzy_match("pi", "apple pie").expect("match");
+        assert_eq!(found.positions, vec![6, 7]);
+        assert!(
+            fuzzy_score("roadmap", "Roadmap").unwrap()
+                > fuzzy_score("roadmap", "Roadmap 2026").unwrap()
+        );
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn title_index_tracks_renames_and_recency() {
+        let mut index = TitleIndex::new();
+        index.sync(&[
+            page(1, "project-plan", "Project Plan"),
+            page(2, "plans", "Plans"),
+        ]);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.search("prjplan", &[], 10)[0].uid, "project-plan");
+
+        let recent = vec!["project-plan".to_string()];
+        let ranked: Vec<String> = index
+            .search("plan", &recent, 10)
+            .into_iter()
+            .map(|found| found.uid)
+            .collect();
+        assert_eq!(ranked, vec!["project-plan", "plans"]);
+
+        index.sync(&[page(1, "project-plan", "Launch Checklist")]);
+        assert_eq!(index.len(), 1);
+        assert!(index.search("prjplan", &[], 10).is_empty());
+        assert_eq!(index.search("lchk", &[], 10)[0].title, "Launch Checklist");
+    }
+
+    #[test]
+    fn title_index_matches_aliases() {
+        let mut index = TitleIndex::new();
+        index.sync(&[
+            page(1, "knowledge-management", "Knowledge Management"),
+            page(2, "kitchen", "Kitchen"),
+        ]);
+        index.set_aliases("knowledge-management", &["PKM".to_string()]);
+
+        let found = index.search("pkm", &[], 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].uid, "knowledge-management");
+        assert_eq!(found[0].alias.as_deref(), Some("PKM"));
+        assert_eq!(found[0].positions, vec![0, 1, 2]);
+
+        let found = index.search("knowledge", &[], 10);
+        assert_eq!(found[0].alias, None);
+
+        index.sync(&[page(2, "kitchen", "Kitchen")]);
+        assert!(index.aliases("knowledge-management").is_empty());
+    }
+}
@@ -0,0 +1,483 @@
+use crate::app;
+use crate::db::{Database, PageRecord};
+use crate::templates::{expand_template, TemplateVars};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
+use std::collections::HashMap;
+
+pub const DEFAULT_TITLE_FORMAT: &str = "%Y-%m-%d";
+const TITLE_FORMAT_KEY: &str = "journals.title_format";
+const TEMPLATE_KEY: &str = "journals.template";
+
+/// How daily pages are named and what new ones start with. Stored in the vault's kv table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalSettings {
+    /// A chrono `strftime` format for daily page titles.
+    pub title_format: String,
+    /// Title or uid of the page copied into each new daily page.
+    pub template: Option<String>,
+}
+
+impl Default for JournalSettings {
+    fn default() -> Self {
+        Self {
+            title_format: DEFAULT_TITLE_FORMAT.to_string(),
+            template: None,
+        }
+    }
+}
+
+impl JournalSettings {
+    pub fn load(db: &Database) -> rusqlite::Result<Self> {
+        Self::load_with(|key| db.get_kv(key))
+    }
+
+    /// Reads the settings through `get_kv`, for callers holding only a connection.
+    pub(crate) fn load_with(
+        get_kv: impl Fn(&str) -> rusqlite::Result<Option<String>>,
+    ) -> rusqlite::Result<Self> {
+        let title_format = get_kv(TITLE_FORMAT_KEY)?
+            .filter(|format| is_valid_title_format(format))
+            .unwrap_or_else(|| DEFAULT_TITLE_FORMAT.to_string());
+        let template = get_kv(TEMPLATE_KEY)?.filter(|template| !template.trim().is_empty());
+        Ok(Self {
+            title_format,
+            template,
+        })
+    }
+
+    pub fn save(&self, db: &Database) -> rusqlite::Result<()> {
+        db.set_kv(TITLE_FORMAT_KEY, &self.title_format)?;
+        match self.template.as_deref() {
+            Some(template) => db.set_kv(TEMPLATE_KEY, template),
+            None => db.delete_kv(TEMPLATE_KEY),
+        }
+    }
+
+    pub fn title(&self, date: NaiveDate) -> String {
+        date.format(&self.title_format).to_string()
+    }
+
+    /// The date a daily page title stands for. ISO titles are always recognised so pages made
+    /// before the format changed stay in the journal.
+    pub fn parse_title(&self, title: &str) -> Option<NaiveDate> {
+        let title = title.trim();
+        NaiveDate::parse_from_str(title, &self.title_format)
+            .or_else(|_| NaiveDate::parse_from_str(title, DEFAULT_TITLE_FORMAT))
+            .ok()
+    }
+}
+
+/// Whether `format` names each day uniquely, so daily page titles can be read back as dates.
+pub fn is_valid_title_format(format: &str) -> bool {
+    if format.trim().is_empty() || StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return false;
+    }
+    let Some(sample) = NaiveDate::from_ymd_opt(2024, 11, 27) else {
+        return false;
+    };
+    let title = sample.format(format).to_string();
+    NaiveDate::parse_from_str(&title, format).ok() == Some(sample)
+}
+
+/// Resolves a link like `tomorrow`, `next friday`, `in 3 days` or `2 weeks ago` against
+/// `today`. Bare weekdays mean the next one after today. Page uids such as `next-friday`
+/// resolve too; weekdays must be spelled out so pages like `Sun` stay pages.
+pub fn resolve_date_phrase(phrase: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let phrase = phrase.trim().to_ascii_lowercase();
+    if let Ok(date) = NaiveDate::parse_from_str(&phrase, DEFAULT_TITLE_FORMAT) {
+        return Some(date);
+    }
+    let mut words = phrase.split_whitespace().collect::<Vec<_>>();
+    if let [uid] = words.as_slice() {
+        words = uid.split('-').collect();
+    }
+    match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => today.succ_opt(),
+        ["yesterday"] => today.pred_opt(),
+        [day] => next_weekday(today, parse_weekday(day)?),
+        ["next", "week"] => offset(today, 7, (1, Unit::Day)),
+        ["last", "week"] => offset(today, -7, (1, Unit::Day)),
+        ["next", "month"] => offset(today, 1, (1, Unit::Month)),
+        ["last", "month"] => offset(today, -1, (1, Unit::Month)),
+        ["next", day] => next_weekday(today, parse_weekday(day)?),
+        ["last", day] => {
+            let day = parse_weekday(day)?;
+            today
+                .iter_days()
+                .rev()
+                .skip(1)
+                .find(|date| date.weekday() == day)
+        }
+        ["this", day] => {
+            let day = parse_weekday(day)?;
+            let monday = today.week(Weekday::Mon).first_day();
+            monday.checked_add_days(Days::new(u64::from(day.num_days_from_monday())))
+        }
+        ["in", count, unit] => offset(today, parse_count(count)?, parse_unit(unit)?),
+        [count, unit, "ago"] => offset(today, -parse_count(count)?, parse_unit(unit)?),
+        _ => None,
+    }
+}
+
+/// The days of the month starting at `month_start`, laid out in Monday-first weeks with `None`
+/// padding before the first and after the last day.
+pub fn month_grid(month_start: NaiveDate) -> Vec<Option<NaiveDate>> {
+    let first = month_start.with_day(1).unwrap_or(month_start);
+    let mut grid = vec![None; first.weekday().num_days_from_monday() as usize];
+    grid.extend(
+        first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .map(Some),
+    );
+    while grid.len() % 7 != 0 {
+        grid.push(None);
+    }
+    grid
+}
+
+/// Every daily page in the vault with its date, newest first.
+pub fn journal_pages(
+    db: &Database,
+    settings: &JournalSettings,
+) -> rusqlite::Result<Vec<(NaiveDate, PageRecord)>> {
+    let mut pages = db
+        .list_pages()?
+        .into_iter()
+        .filter_map(|page| Some((settings.parse_title(&page.title)?, page)))
+        .collect::<Vec<_>>();
+    pages.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.id.cmp(&b.1.id)));
+    pages.dedup_by_key(|(date, _)| *date);
+    Ok(pages)
+}
+
+/// The daily page for `date`, if one exists under its title, its ISO title or its uid.
+pub fn find_journal_page(
+    db: &Database,
+    date: NaiveDate,
+    settings: &JournalSettings,
+) -> rusqlite::Result<Option<PageRecord>> {
+    let title = settings.title(date);
+    if let Some(page) = db.resolve_page(&title)? {
+        return Ok(Some(page));
+    }
+    Ok(db
+        .list_pages()?
+        .into_iter()
+        .find(|page| settings.parse_title(&page.title) == Some(date)))
+}
+
+/// Returns the daily page for `date`, creating it from the journal template when missing.
+/// Template variables such as `{{date}}` and `{{title}}` are filled in for that day.
+/// The flag is true when the page was created.
+pub fn ensure_journal_page(
+    db: &mut Database,
+    date: NaiveDate,
+    settings: &JournalSettings,
+) -> rusqlite::Result<(PageRecord, bool)> {
+    if let Some(page) = find_journal_page(db, date, settings)? {
+        return Ok((page, false));
+    }
+    let title = settings.title(date);
+    let uid = app::sanitize_kebab(&title);
+    let id = db.insert_page(&uid, &title)?;
+    let template = match settings.template.as_deref() {
+        Some(name) => db.resolve_page(name)?,
+        None => None,
+    };
+    if let Some(template) = template {
+        let vars = TemplateVars {
+            now: date.and_time(Local::now().time()),
+            title: title.clone(),
+            fields: HashMap::new(),
+        };
+        let expanded = expand_template(&db.load_blocks_for_page(template.id)?, &vars);
+        if !expanded.blocks.is_empty() {
+            db.replace_blocks_for_page(id, &expanded.blocks)?;
+        }
+    }
+    Ok((PageRecord { id, uid, title }, true))
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Day,
+    Month,
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    // chrono also reads `mon`, `sun` and so on; only full names count as dates here.
+    if value.len() <= 3 {
+        return None;
+    }
+    value.parse::<Weekday>().ok()
+}
+
+fn parse_count(value: &str) -> Option<i64> {
+    match value {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        _ => value.parse::<i64>().ok().filter(|count| *count >= 0),
+    }
+}
+
+fn parse_unit(value: &str) -> Option<(i64, Unit)> {
+    match value {
+        "day" | "days" => Some((1, Unit::Day)),
+        "week" | "weeks" => Some((7, Unit::Day)),
+        "month" | "months" => Some((1, Unit::Month)),
+        "year" | "years" => Some((12, Unit::Month)),
+        _ => None,
+    }
+}
+
+/// Moves `amount` steps of `scale` units from `today`; months clamp to the end of shorter months.
+fn offset(today: NaiveDate, amount: i64, (scale, unit): (i64, Unit)) -> Option<NaiveDate> {
+    let steps = amount.checked_mul(scale)?;
+    let magnitude = steps.unsigned_abs();
+    match (unit, steps >= 0) {
+        (Unit::Day, true) => today.checked_add_days(Days::new(magnitude)),
+        (Unit::Day, false) => today.checked_sub_days(Days::new(magnitude)),
+        (Unit::Month, true) => {
+            today.checked_add_months(Months::new(u32::try_from(magnitude).ok()?))
+        }
+        (Unit::Month, false) => {
+            today.checked_sub_months(Months::new(u32::try_from(magnitude).ok()?))
+        }
+    }
+}
+
+fn next_weekday(today: NaiveDate, day: Weekday) -> Option<NaiveDate> {
+    today.iter_days().skip(1).find(|date| date.weekday() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ensure_journal_page, is_valid_title_format, journal_pages, month_grid, resolve_date_phrase,
+        JournalSettings,
+    };
+    use crate::blocks::BlockType;
+    use crate::db::{BlockSnapshot, Database};
+    use chrono::NaiveDate;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").expect("date")
+    }
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+        db
+    }
+
+    #[test]
+    fn date_phrases_resolve_relative_to_today() {
+        // 2026-03-04 is a Wednesday.
+        let today = date("2026-03-04");
+        let cases = [
+            ("today", "2026-03-04"),
+            ("Tomorrow", "2026-03-05"),
+            ("yesterday", "2026-03-03"),
+            ("friday", "2026-03-06"),
+            ("wednesday", "2026-03-11"),
+            ("next friday", "2026-03-06"),
+            ("last friday", "2026-02-27"),
+            ("this monday", "2026-03-02"),
+            ("next week", "2026-03-11"),
+            ("last month", "2026-02-04"),
+            ("in 3 days", "2026-03-07"),
+            ("in 2 weeks", "2026-03-18"),
+            ("in a month", "2026-04-04"),
+            ("2 days ago", "2026-03-02"),
+            ("2026-12-25", "2026-12-25"),
+            ("next-friday", "2026-03-06"),
+            ("in-3-days", "2026-03-07"),
+        ];
+        for (phrase, expected) in cases {
+            assert_eq!(
+                resolve_date_phrase(phrase, today),
+                Some(date(expected)),
+                "{phrase}"
+            );
+        }
+        for phrase in [
+            "",
+            "someday",
+            "next fortnight",
+            "in -2 days",
+            "Project plan",
+            "sun",
+            "mon",
+            "next fri",
+        ] {
+            assert_eq!(resolve_date_phrase(phrase, today), None, "{phrase}");
+        }
+    }
+
+    #[test]
+    fn title_formats_must_round_trip_to_a_date() {
+        assert!(is_valid_title_format("%Y-%m-%d"));
+        assert!(is_valid_title_format("%b %-d, %Y"));
+        assert!(is_valid_title_format("%A, %-d %B %Y"));
+        assert!(!is_valid_title_format("%B %Y"));
+        assert!(!is_valid_title_format("Journal"));
+        assert!(!is_valid_title_format("%Q"));
+
+        let settings = JournalSettings {
+            title_format: "%b %-d, %Y".to_string(),
+            template: None,
+        };
+        assert_eq!(settings.title(date("2026-03-04")), "Mar 4, 2026");
+        assert_eq!(
+            settings.parse_title("Mar 4, 2026"),
+            Some(date("2026-03-04"))
+        );
+        assert_eq!(settings.parse_title("2026-03-04"), Some(date("2026-03-04")));
+        assert_eq!(settings.parse_title("Inbox"), None);
+    }
+
+    #[test]
+    fn month_grid_pads_to_whole_monday_first_weeks() {
+        let grid = month_grid(date("2026-03-15"));
+        assert_eq!(grid.len() % 7, 0);
+        // March 2026 starts on a Sunday.
+        assert!(grid[..6].iter().all(Option::is_none));
+        assert_eq!(grid[6], Some(date("2026-03-01")));
+        assert_eq!(grid.iter().flatten().count(), 31);
+    }
+
+    #[test]
+    fn settings_persist_in_the_vault() {
+        let db = setup_db();
+        assert_eq!(
+            JournalSettings::load(&db).expect("load"),
+            JournalSettings::default()
+        );
+        let settings = JournalSettings {
+            title_format: "%-d %B %Y".to_string(),
+            template: Some("daily-template".to_string()),
+        };
+        settings.save(&db).expect("save");
+        assert_eq!(JournalSettings::load(&db).expect("load"), settings);
+
+        JournalSettings::default().save(&db).expect("save");
+        assert_eq!(
+            JournalSettings::load(&db).expect("load"),
+            JournalSettings::default()
+        );
+    }
+
+    #[test]
+    fn new_daily_pages_copy_the_template() {
+        let mut db = setup_db();
+        let template_id = db
+            .insert_page("daily-template", "Daily template")
+            .expect("insert template");
+        let template_blocks = vec![
+            BlockSnapshot {
+                uid: "t1".to_string(),
+                text: "Plan for {{date:%A}}".to_string(),
+                indent: 0,
+                block_type: BlockType::Heading2,
+            },
+            BlockSnapshot {
+                uid: "t2".to_string(),
+                text: "TODO review inbox".to_string(),
+                indent: 1,
+                block_type: BlockType::Text,
+            },
+        ];
+        db.replace_blocks_for_page(template_id, &template_blocks)
+            .expect("template blocks");
+        let settings = JournalSettings {
+            title_format: "%b %-d, %Y".to_string(),
+            template: Some("Daily template".to_string()),
+        };
+
+        let (page, created) =
+            ensure_journal_page(&mut db, date("2026-03-04"), &settings).expect("ensure");
+        assert!(created);
+        assert_eq!(page.title, "Mar 4, 2026");
+        let blocks = db.load_blocks_for_page(page.id).expect("blocks");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].block_type, BlockType::Heading2);
+        assert_eq!(blocks[0].text, "Plan for Wednesday");
+        assert_eq!(blocks[1].text, "TODO review inbox");
+        assert_eq!(blocks[1].indent, 1);
+        assert!(blocks.iter().all(|block| !block.uid.starts_with('t')));
+
+        let (again, created) =
+            ensure_journal_page(&mut db, date("2026-03-04"), &settings).expect("ensure");
+        assert!(!created);
+        assert_eq!(again.id, page.id);
+
+        // Pages named in the old ISO format still count as that day's page.
+        db.insert_page("custom", "2026-03-02").expect("insert");
+        let (old, created) =
+            ensure_journal_page(&mut db, date("2026-03-02"), &settings).expect("ensure");
+        assert!(!created);
+        assert_eq!(old.uid, "custom");
+
+        let dates = journal_pages(&db, &settings)
+            .expect("journal pages")
+            .into_iter()
+            .map(|(date, _)| date)
+            .collect::<Vec<_>>();
+        assert_eq!(dates, vec![date("2026-03-04"), date("2026-03-02")]);
+    }
+
+    #[test]
+    fn date_links_count_from_the_page_they_are_on() {
+        let mut db = setup_db();
+        let settings = JournalSettings::default();
+        let (day, _) = ensure_journal_page(&mut db, date("2026-03-04"), &settings).expect("day");
+        let project = db.insert_page("project", "Project").expect("project");
+        db.insert_page("friday", "Friday").expect("friday");
+        let block = |uid: &str, text: &str| BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent: 0,
+            block_type: BlockType::Text,
+        };
+        db.replace_blocks_for_page(
+            day.id,
+            &[block("d1", "Call Bo [[tomorrow]], ship [[friday]]")],
+        )
+        .expect("day blocks");
+        db.replace_blocks_for_page(project, &[block("p1", "Due [[tomorrow]]")])
+            .expect("project blocks");
+
+        // `tomorrow` on the 4th means the 5th, whatever today is.
+        assert_eq!(
+            db.resolve_date_link("tomorrow", Some("d1"))
+                .expect("resolve"),
+            Some(date("2026-03-05"))
+        );
+        let backlinks = db.list_backlinks("2026-03-05").expect("backlinks");
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].block_uid, "d1");
+
+        // A page named like a weekday stays that page.
+        assert_eq!(
+            db.resolve_date_link("friday", Some("d1")).expect("resolve"),
+            None
+        );
+        assert!(db
+            .list_backlinks("2026-03-06")
+            .expect("backlinks")
+            .is_empty());
+        assert_eq!(db.resolve_date_link("sun", None).expect("resolve"), None);
+
+        // Elsewhere dates count from the day the block was written, here today.
+        let written = chrono::Local::now().date_naive();
+        assert_eq!(
+            db.resolve_date_link("tomorrow", Some("p1"))
+                .expect("resolve"),
+            written.succ_opt()
+        );
+    }
+}
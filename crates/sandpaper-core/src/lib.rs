@@ -3,6 +3,21 @@ pub mod assets;
 pub mod blocks;
 pub mod db;
 pub mod editor;
+pub mod flashcards;
+pub mod fuzzy;
+pub mod journals;
 pub mod links;
+pub mod ops;
+pub mod plugin_data;
+pub mod plugin_events;
 pub mod plugins;
+pub mod properties;
+pub mod recurrence;
+pub mod review;
+pub mod revisions;
+pub mod search;
+pub mod sort_keys;
+pub mod tasks;
+pub mod templates;
 pub mod vaults;
+pub mod views;
@@ -86,6 +86,232 @@ pub fn strip_block_refs(text: &str) -> String {
     output
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    Wikilink,
+    BlockRef,
+    Embed,
+    Tag,
+}
+
+impl EdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Wikilink => "wikilink",
+            EdgeKind::BlockRef => "block_ref",
+            EdgeKind::Embed => "embed",
+            EdgeKind::Tag => "tag",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EmbedTarget {
+    Page(String),
+    Block(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LinkTarget {
+    pub kind: EdgeKind,
+    pub target: String,
+}
+
+/// Returns the page title a raw wikilink points at, dropping any `|alias` or `#heading` suffix.
+pub fn wikilink_target(raw: &str) -> Option<&str> {
+    let before_alias = raw.split('|').next().unwrap_or(raw);
+    let title = before_alias
+        .split('#')
+        .next()
+        .unwrap_or(before_alias)
+        .trim();
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+pub fn extract_embeds(text: &str) -> Vec<EmbedTarget> {
+    let mut embeds = Vec::new();
+    let mut cursor = 0;
+    while let Some(start) = text[cursor..].find("{{") {
+        let start_ix = cursor + start + 2;
+        let Some(end_rel) = text[start_ix..].find("}}") else {
+            break;
+        };
+        let end_ix = start_ix + end_rel;
+        cursor = end_ix + 2;
+        let inner = text[start_ix..end_ix].trim();
+        let Some(arg) = inner.strip_prefix("embed") else {
+            continue;
+        };
+        let arg = arg.trim();
+        if let Some(title) = arg
+            .strip_prefix("[[")
+            .and_then(|rest| rest.strip_suffix("]]"))
+            .and_then(wikilink_target)
+        {
+            embeds.push(EmbedTarget::Page(title.to_string()));
+        } else if let Some(uid) = extract_block_refs(arg).into_iter().next() {
+            embeds.push(EmbedTarget::Block(uid));
+        }
+    }
+    embeds
+}
+
+/// Returns the filter text of a block that is a whole `{{query ...}}` macro.
+pub fn extract_query(text: &str) -> Option<String> {
+    let inner = text.trim().strip_prefix("{{")?.strip_suffix("}}")?;
+    let rest = inner.trim_start().strip_prefix("query")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim().to_string())
+}
+
+/// Drops `{{query ...}}` macros so the tags and page names they filter on are not read as
+/// references made by the block.
+pub fn strip_queries(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut cursor = 0;
+    while let Some(start_rel) = text[cursor..].find("{{") {
+        let start = cursor + start_rel;
+        let Some(end_rel) = text[start..].find("}}") else {
+            break;
+        };
+        let end = start + end_rel + 2;
+        output.push_str(&text[cursor..start]);
+        if extract_query(&text[start..end]).is_none() {
+            output.push_str(&text[start..end]);
+        }
+        cursor = end;
+    }
+    output.push_str(&text[cursor..]);
+    output
+}
+
+fn is_tag_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '-' | '_' | '/')
+}
+
+fn normalize_tag(raw: &str) -> Option<String> {
+    let name = raw.trim().trim_end_matches('/').to_lowercase();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Extracts `#tag` and `#[[multi word tag]]` names, lowercased and deduped. A `#` only starts a
+/// tag at the beginning of the text or after whitespace or an opening bracket, so headings, URL
+/// fragments and text inside inline code are skipped.
+pub fn extract_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut in_code = false;
+    let mut prev: Option<char> = None;
+    let mut cursor = 0;
+    while let Some(ch) = text[cursor..].chars().next() {
+        cursor += ch.len_utf8();
+        let at_boundary =
+            prev.is_none_or(|prev| prev.is_whitespace() || matches!(prev, '(' | '[' | ',' | ';'));
+        prev = Some(ch);
+        if ch == '`' {
+            in_code = !in_code;
+            continue;
+        }
+        if ch != '#' || in_code || !at_boundary {
+            continue;
+        }
+        let rest = &text[cursor..];
+        let (raw, consumed) = if let Some(inner) = rest.strip_prefix("[[") {
+            match inner.find("]]") {
+                Some(end) => (&inner[..end], end + 4),
+                None => continue,
+            }
+        } else {
+            let end = rest
+                .char_indices()
+                .find(|(_, ch)| !is_tag_char(*ch))
+                .map(|(ix, _)| ix)
+                .unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+        if let Some(name) = normalize_tag(raw) {
+            if !tags.contains(&name) {
+                tags.push(name);
+            }
+        }
+        cursor += consumed;
+        prev = text[..cursor].chars().next_back();
+    }
+    tags
+}
+
+/// Removes `#name` and `#[[name]]` words (ignoring case and trailing punctuation) from each
+/// line of `text`, trimming what is left.
+pub fn strip_tag(text: &str, name: &str) -> String {
+    let plain = format!("#{name}");
+    let bracketed = format!("#[[{name}]]");
+    text.lines()
+        .map(|line| {
+            line.split(' ')
+                .filter(|word| {
+                    let tag = word.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+                    !tag.eq_ignore_ascii_case(&plain) && !tag.eq_ignore_ascii_case(&bracketed)
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Formats a tag name for insertion into block text, bracketing names `#tag` cannot express.
+pub fn format_tag(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(is_tag_char) {
+        format!("#{name}")
+    } else {
+        format!("#[[{name}]]")
+    }
+}
+
+/// Collects every outgoing reference in `text`, normalized for storage in the `edges` table:
+/// page targets become page uids, block targets stay block uids.
+pub fn extract_link_targets(text: &str) -> Vec<LinkTarget> {
+    let text = &strip_queries(text);
+    let mut targets: Vec<LinkTarget> = Vec::new();
+    let mut push = |kind: EdgeKind, target: String| {
+        let link = LinkTarget { kind, target };
+        if !targets.contains(&link) {
+            targets.push(link);
+        }
+    };
+    for link in extract_wikilinks(text) {
+        if let Some(title) = wikilink_target(&link) {
+            push(EdgeKind::Wikilink, app::sanitize_kebab(title));
+        }
+    }
+    for uid in extract_block_refs(text) {
+        push(EdgeKind::BlockRef, uid);
+    }
+    for embed in extract_embeds(text) {
+        match embed {
+            EmbedTarget::Page(title) => push(EdgeKind::Embed, app::sanitize_kebab(&title)),
+            EmbedTarget::Block(uid) => push(EdgeKind::Embed, uid),
+        }
+    }
+    for tag in extract_tags(text) {
+        push(EdgeKind::Tag, app::sanitize_kebab(&tag));
+    }
+    targets
+}
+
 pub fn replace_wikilinks_in_text(text: &str, from_title: &str, to_title: &str) -> String {
     let normalized_from = app::sanitize_kebab(from_title);
     let normalized_to = app::sanitize_kebab(to_title);
@@ -157,8 +383,9 @@ pub fn replace_wikilinks_in_text(text: &str, from_title: &str, to_title: &str) -
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_block_refs, extract_wikilinks, replace_wikilinks_in_text, strip_block_refs,
-        strip_wikilinks,
+        extract_block_refs, extract_embeds, extract_link_targets, extract_query, extract_tags,
+        extract_wikilinks, format_tag, replace_wikilinks_in_text, strip_block_refs, strip_queries,
+        strip_wikilinks, EdgeKind, EmbedTarget, LinkTarget,
     };
 
     #[test]
@@ -185,6 +412,86 @@ mod tests {
         assert_eq!(stripped, "Link to block-1 and Alias.");
     }
 
+    #[test]
+    fn extract_embeds_reads_page_and_block_targets() {
+        let embeds =
+            extract_embeds("{{embed [[Project Plan|Plan]]}} then {{embed ((b-1))}} {{toc}}");
+        assert_eq!(
+            embeds,
+            vec![
+                EmbedTarget::Page("Project Plan".to_string()),
+                EmbedTarget::Block("b-1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_macros_are_read_and_not_treated_as_links() {
+        assert_eq!(
+            extract_query("  {{query #urgent todo:open}} "),
+            Some("#urgent todo:open".to_string())
+        );
+        assert_eq!(extract_query("{{query}}"), Some(String::new()));
+        assert_eq!(extract_query("{{queryx #a}}"), None);
+        assert_eq!(extract_query("see {{query #a}}"), None);
+
+        assert_eq!(
+            strip_queries("Due {{query #urgent}} for [[Plan]] {{toc}}"),
+            "Due  for [[Plan]] {{toc}}"
+        );
+        let targets = extract_link_targets("{{query #urgent page:Plan}} #real");
+        assert_eq!(
+            targets,
+            vec![LinkTarget {
+                kind: EdgeKind::Tag,
+                target: "real".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn extract_tags_reads_simple_and_bracketed_tags() {
+        let tags = extract_tags(
+            "#Project work on #[[Big Idea]] (#area/home) #project, not a#b or `#code`",
+        );
+        assert_eq!(tags, vec!["project", "big idea", "area/home"]);
+    }
+
+    #[test]
+    fn extract_tags_skips_headings_and_fragments() {
+        assert!(extract_tags("# Heading").is_empty());
+        assert!(extract_tags("## Heading").is_empty());
+        assert!(extract_tags("see https://example.com/#anchor").is_empty());
+        assert!(extract_tags("#[[unclosed").is_empty());
+    }
+
+    #[test]
+    fn format_tag_brackets_names_with_spaces() {
+        assert_eq!(format_tag("project"), "#project");
+        assert_eq!(format_tag("big idea"), "#[[big idea]]");
+    }
+
+    #[test]
+    fn extract_link_targets_normalizes_and_dedupes() {
+        let targets = extract_link_targets(
+            "[[Project Plan#Goals]] and [[project plan|plan]] cite ((b-1)) {{embed [[Notes]]}} #todo",
+        );
+        let link = |kind: EdgeKind, target: &str| LinkTarget {
+            kind,
+            target: target.to_string(),
+        };
+        assert_eq!(
+            targets,
+            vec![
+                link(EdgeKind::Wikilink, "project-plan"),
+                link(EdgeKind::Wikilink, "notes"),
+                link(EdgeKind::BlockRef, "b-1"),
+                link(EdgeKind::Embed, "notes"),
+                link(EdgeKind::Tag, "todo"),
+            ]
+        );
+    }
+
     #[test]
     fn replace_wikilinks_updates_matching_targets() {
         let text = "See [[Project Atlas|Alias]] and [[Project Atlas#Head]] plus [[Other]].";
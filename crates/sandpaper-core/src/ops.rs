@@ -0,0 +1,449 @@
+use crate::blocks::BlockType;
+use crate::db::BlockSnapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// A single reversible change to a page's block list. Positions are indices into the list as it
+/// was right before the op ran; block-local ops address their block by uid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EditorOp {
+    InsertBlock {
+        index: usize,
+        block: BlockSnapshot,
+    },
+    DeleteBlock {
+        index: usize,
+        block: BlockSnapshot,
+    },
+    /// Replaces the byte `range` of the block text, which held `deleted`, with `inserted`.
+    EditText {
+        uid: String,
+        range: Range<usize>,
+        deleted: String,
+        inserted: String,
+    },
+    /// Removes the block at `from` and reinserts it so it ends up at `to`.
+    Move {
+        uid: String,
+        from: usize,
+        to: usize,
+    },
+    SetType {
+        uid: String,
+        before: BlockType,
+        after: BlockType,
+    },
+    SetIndent {
+        uid: String,
+        before: i64,
+        after: i64,
+    },
+}
+
+impl EditorOp {
+    /// The smallest `EditText` turning `before` into `after`, or `None` when they are equal.
+    pub fn edit_text(uid: &str, before: &str, after: &str) -> Option<Self> {
+        if before == after {
+            return None;
+        }
+        let mut prefix = before
+            .bytes()
+            .zip(after.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !before.is_char_boundary(prefix) || !after.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let max_suffix = before.len().min(after.len()) - prefix;
+        let mut suffix = before
+            .bytes()
+            .rev()
+            .zip(after.bytes().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !before.is_char_boundary(before.len() - suffix)
+            || !after.is_char_boundary(after.len() - suffix)
+        {
+            suffix -= 1;
+        }
+        let range = prefix..before.len() - suffix;
+        Some(Self::EditText {
+            uid: uid.to_string(),
+            deleted: before[range.clone()].to_string(),
+            inserted: after[prefix..after.len() - suffix].to_string(),
+            range,
+        })
+    }
+
+    pub fn block_uid(&self) -> &str {
+        match self {
+            Self::InsertBlock { block, .. } | Self::DeleteBlock { block, .. } => &block.uid,
+            Self::EditText { uid, .. }
+            | Self::Move { uid, .. }
+            | Self::SetType { uid, .. }
+            | Self::SetIndent { uid, .. } => uid,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::InsertBlock { index, block } => Self::DeleteBlock { index, block },
+            Self::DeleteBlock { index, block } => Self::InsertBlock { index, block },
+            Self::EditText {
+                uid,
+                range,
+                deleted,
+                inserted,
+            } => Self::EditText {
+                uid,
+                range: range.start..range.start + inserted.len(),
+                deleted: inserted,
+                inserted: deleted,
+            },
+            Self::Move { uid, from, to } => Self::Move {
+                uid,
+                from: to,
+                to: from,
+            },
+            Self::SetType { uid, before, after } => Self::SetType {
+                uid,
+                before: after,
+                after: before,
+            },
+            Self::SetIndent { uid, before, after } => Self::SetIndent {
+                uid,
+                before: after,
+                after: before,
+            },
+        }
+    }
+
+    /// Applies the op, returning `false` without touching `blocks` when it does not fit them.
+    pub fn apply(&self, blocks: &mut Vec<BlockSnapshot>) -> bool {
+        let position =
+            |uid: &str, blocks: &[BlockSnapshot]| blocks.iter().position(|block| block.uid == uid);
+        match self {
+            Self::InsertBlock { index, block } => {
+                if *index > blocks.len() {
+                    return false;
+                }
+                blocks.insert(*index, block.clone());
+            }
+            Self::DeleteBlock { index, block } => {
+                if blocks
+                    .get(*index)
+                    .is_none_or(|existing| existing.uid != block.uid)
+                {
+                    return false;
+                }
+                blocks.remove(*index);
+            }
+            Self::EditText {
+                uid,
+                range,
+                deleted,
+                inserted,
+            } => {
+                let Some(ix) = position(uid, blocks) else {
+                    return false;
+                };
+                let text = &mut blocks[ix].text;
+                if text.get(range.clone()) != Some(deleted.as_str()) {
+                    return false;
+                }
+                text.replace_range(range.clone(), inserted);
+            }
+            Self::Move { uid, from, to } => {
+                if *to >= blocks.len() || blocks.get(*from).is_none_or(|block| &block.uid != uid) {
+                    return false;
+                }
+                let block = blocks.remove(*from);
+                blocks.insert(*to, block);
+            }
+            Self::SetType { uid, before, after } => {
+                let Some(ix) = position(uid, blocks) else {
+                    return false;
+                };
+                if blocks[ix].block_type != *before {
+                    return false;
+                }
+                blocks[ix].block_type = *after;
+            }
+            Self::SetIndent { uid, before, after } => {
+                let Some(ix) = position(uid, blocks) else {
+                    return false;
+                };
+                if blocks[ix].indent != *before {
+                    return false;
+                }
+                blocks[ix].indent = *after;
+            }
+        }
+        true
+    }
+}
+
+/// Inverses of `ops` in the order that undoes them.
+pub fn invert_ops(ops: &[EditorOp]) -> Vec<EditorOp> {
+    ops.iter().rev().map(EditorOp::inverse).collect()
+}
+
+/// Applies `ops` in order, stopping at the first one that does not fit. Returns how many ran.
+pub fn apply_ops(blocks: &mut Vec<BlockSnapshot>, ops: &[EditorOp]) -> usize {
+    ops.iter().take_while(|op| op.apply(blocks)).count()
+}
+
+/// Ops that turn `previous` into `next`, for callers that only have the two snapshots.
+pub fn diff_ops(previous: &[BlockSnapshot], next: &[BlockSnapshot]) -> Vec<EditorOp> {
+    let next_uids: HashSet<&str> = next.iter().map(|block| block.uid.as_str()).collect();
+    let mut working = previous.to_vec();
+    let mut ops = Vec::new();
+    for index in (0..previous.len()).rev() {
+        if !next_uids.contains(previous[index].uid.as_str()) {
+            ops.push(EditorOp::DeleteBlock {
+                index,
+                block: working.remove(index),
+            });
+        }
+    }
+
+    for (index, block) in next.iter().enumerate() {
+        let Some(from) = working[index..]
+            .iter()
+            .position(|existing| existing.uid == block.uid)
+            .map(|offset| index + offset)
+        else {
+            working.insert(index, block.clone());
+            ops.push(EditorOp::InsertBlock {
+                index,
+                block: block.clone(),
+            });
+            continue;
+        };
+        if from != index {
+            let moved = working.remove(from);
+            working.insert(index, moved);
+            ops.push(EditorOp::Move {
+                uid: block.uid.clone(),
+                from,
+                to: index,
+            });
+        }
+        let existing = &mut working[index];
+        if let Some(op) = EditorOp::edit_text(&block.uid, &existing.text, &block.text) {
+            existing.text = block.text.clone();
+            ops.push(op);
+        }
+        if existing.block_type != block.block_type {
+            ops.push(EditorOp::SetType {
+                uid: block.uid.clone(),
+                before: existing.block_type,
+                after: block.block_type,
+            });
+            existing.block_type = block.block_type;
+        }
+        if existing.indent != block.indent {
+            ops.push(EditorOp::SetIndent {
+                uid: block.uid.clone(),
+                before: existing.indent,
+                after: block.indent,
+            });
+            existing.indent = block.indent;
+        }
+    }
+    ops
+}
+
+/// Net effect of an op log on one block, in the shape sync peers exchange: whole block state
+/// rather than individual keystrokes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockChange {
+    Added {
+        index: usize,
+        block: BlockSnapshot,
+    },
+    Edited {
+        block: BlockSnapshot,
+    },
+    /// Position or indent changed. Emitted for every block whose index shifted, since peers
+    /// order blocks by position.
+    Moved {
+        index: usize,
+        block: BlockSnapshot,
+    },
+    Deleted {
+        uid: String,
+    },
+}
+
+/// Folds `ops` applied on top of `previous` into per-block changes: adds, edits and moves in
+/// final block order, then deletions in their original order.
+pub fn summarize_ops(previous: &[BlockSnapshot], ops: &[EditorOp]) -> Vec<BlockChange> {
+    let mut blocks = previous.to_vec();
+    apply_ops(&mut blocks, ops);
+    let before: HashMap<&str, (usize, &BlockSnapshot)> = previous
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (block.uid.as_str(), (index, block)))
+        .collect();
+    let edited: HashSet<&str> = ops
+        .iter()
+        .filter(|op| matches!(op, EditorOp::EditText { .. } | EditorOp::SetType { .. }))
+        .map(EditorOp::block_uid)
+        .collect();
+
+    let mut changes = Vec::new();
+    for (index, block) in blocks.iter().enumerate() {
+        let Some((previous_index, previous_block)) = before.get(block.uid.as_str()) else {
+            changes.push(BlockChange::Added {
+                index,
+                block: block.clone(),
+            });
+            continue;
+        };
+        if edited.contains(block.uid.as_str())
+            && (block.text != previous_block.text || block.block_type != previous_block.block_type)
+        {
+            changes.push(BlockChange::Edited {
+                block: block.clone(),
+            });
+        }
+        if *previous_index != index || block.indent != previous_block.indent {
+            changes.push(BlockChange::Moved {
+                index,
+                block: block.clone(),
+            });
+        }
+    }
+
+    let remaining: HashSet<&str> = blocks.iter().map(|block| block.uid.as_str()).collect();
+    changes.extend(
+        previous
+            .iter()
+            .filter(|block| !remaining.contains(block.uid.as_str()))
+            .map(|block| BlockChange::Deleted {
+                uid: block.uid.clone(),
+            }),
+    );
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_ops, diff_ops, invert_ops, summarize_ops, BlockChange, EditorOp};
+    use crate::blocks::BlockType;
+    use crate::db::BlockSnapshot;
+
+    fn block(uid: &str, text: &str, indent: i64) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent,
+            block_type: BlockType::Text,
+        }
+    }
+
+    #[test]
+    fn edit_text_covers_only_the_changed_span() {
+        let op = EditorOp::edit_text("a", "hello world", "hello brave world").expect("edit");
+        assert_eq!(
+            op,
+            EditorOp::EditText {
+                uid: "a".to_string(),
+                range: 6..6,
+                deleted: String::new(),
+                inserted: "brave ".to_string(),
+            }
+        );
+        let op = EditorOp::edit_text("a", "café", "cafè").expect("edit");
+        let EditorOp::EditText { range, .. } = &op else {
+            panic!("expected text edit");
+        };
+        assert_eq!(range, &(3..5));
+        assert_eq!(EditorOp::edit_text("a", "same", "same"), None);
+
+        let mut blocks = vec![block("a", "café", 0)];
+        assert!(op.apply(&mut blocks));
+        assert_eq!(blocks[0].text, "cafè");
+        assert!(op.inverse().apply(&mut blocks));
+        assert_eq!(blocks[0].text, "café");
+    }
+
+    #[test]
+    fn diff_ops_roundtrip_through_inverses() {
+        let previous = vec![
+            block("a", "one", 0),
+            block("b", "two", 0),
+            block("c", "three", 1),
+            block("d", "four", 0),
+        ];
+        let mut next = vec![
+            block("d", "four", 0),
+            block("a", "one!", 1),
+            block("e", "five", 0),
+            block("c", "three", 0),
+        ];
+        next[3].block_type = BlockType::Code;
+
+        let ops = diff_ops(&previous, &next);
+        let mut blocks = previous.clone();
+        assert_eq!(apply_ops(&mut blocks, &ops), ops.len());
+        assert_eq!(blocks, next);
+
+        let undo = invert_ops(&ops);
+        assert_eq!(apply_ops(&mut blocks, &undo), undo.len());
+        assert_eq!(blocks, previous);
+    }
+
+    #[test]
+    fn apply_rejects_ops_that_do_not_fit() {
+        let mut blocks = vec![block("a", "one", 0)];
+        let stale = EditorOp::EditText {
+            uid: "a".to_string(),
+            range: 0..3,
+            deleted: "two".to_string(),
+            inserted: "three".to_string(),
+        };
+        assert!(!stale.apply(&mut blocks));
+        assert!(!EditorOp::DeleteBlock {
+            index: 0,
+            block: block("b", "", 0),
+        }
+        .apply(&mut blocks));
+        assert_eq!(blocks, vec![block("a", "one", 0)]);
+    }
+
+    #[test]
+    fn summarize_ops_reports_net_block_changes() {
+        let previous = vec![block("b1", "First", 0), block("b2", "Second", 0)];
+        let next = vec![block("b1", "First updated", 1), block("b3", "Third", 0)];
+        let changes = summarize_ops(&previous, &diff_ops(&previous, &next));
+        assert_eq!(
+            changes,
+            vec![
+                BlockChange::Edited {
+                    block: next[0].clone(),
+                },
+                BlockChange::Moved {
+                    index: 0,
+                    block: next[0].clone(),
+                },
+                BlockChange::Added {
+                    index: 1,
+                    block: next[1].clone(),
+                },
+                BlockChange::Deleted {
+                    uid: "b2".to_string(),
+                },
+            ]
+        );
+
+        // Typing and then undoing it nets out to nothing.
+        let typed = EditorOp::edit_text("b1", "First", "First!").expect("edit");
+        let changes = summarize_ops(&previous, &[typed.clone(), typed.inverse()]);
+        assert!(changes.is_empty());
+    }
+}
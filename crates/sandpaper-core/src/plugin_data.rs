@@ -0,0 +1,445 @@
+use crate::app::resolve_unique_page_uid;
+use crate::blocks::BlockType;
+use crate::db::{BlockSnapshot, Database, PageRecord};
+use crate::plugin_events::{apply_event_edits, PluginEvent};
+use crate::plugins::PluginCommandEdit;
+use crate::properties::{
+    parse_block_properties, remove_block_property_in_text, set_block_property_in_text,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+/// Permission needed for the `api.data` reads.
+pub const DATA_READ: &str = "data.read";
+/// Permission needed for the `api.data` writes.
+pub const DATA_WRITE: &str = "data.write";
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 200;
+
+/// A block handed to `createPage` or `insertBlocks`: either its text or
+/// `{ text, indent, type }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NewBlock {
+    Text(String),
+    Block {
+        text: String,
+        #[serde(default)]
+        indent: i64,
+        #[serde(default, rename = "type")]
+        block_type: BlockType,
+    },
+}
+
+impl NewBlock {
+    /// A block with a fresh uid, indented `base_indent` levels further.
+    pub fn into_snapshot(self, base_indent: i64) -> BlockSnapshot {
+        let (text, indent, block_type) = match self {
+            NewBlock::Text(text) => (text, 0, BlockType::Text),
+            NewBlock::Block {
+                text,
+                indent,
+                block_type,
+            } => (text, indent.max(0), block_type),
+        };
+        BlockSnapshot {
+            uid: Uuid::new_v4().to_string(),
+            text,
+            indent: base_indent + indent,
+            block_type,
+        }
+    }
+}
+
+/// Vault access behind the plugin `api.data` namespace. Holds its own connection to the vault
+/// database, checks the plugin's granted permissions on every call and remembers which pages
+/// were written so the host can reload them in the editor.
+#[derive(Default)]
+pub struct PluginDataHost {
+    db: Option<Database>,
+    changed_pages: Vec<String>,
+}
+
+impl PluginDataHost {
+    pub fn set_database(&mut self, db: Option<Database>) {
+        self.db = db;
+        self.changed_pages.clear();
+    }
+
+    /// Uids of the pages written since the last call, in write order.
+    pub fn take_changed_pages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.changed_pages)
+    }
+
+    /// A page by uid or title (aliases included) with its blocks, or `null`.
+    pub fn get_page(&self, plugin_id: &str, name: &str) -> Result<Value, String> {
+        let db = self.database(plugin_id, DATA_READ)?;
+        let Some(page) = db.resolve_page(name).map_err(db_error)? else {
+            return Ok(Value::Null);
+        };
+        let blocks = db.load_blocks_for_page(page.id).map_err(db_error)?;
+        Ok(json!({
+            "uid": page.uid,
+            "title": page.title,
+            "blocks": blocks.iter().map(block_json).collect::<Vec<_>>(),
+        }))
+    }
+
+    pub fn list_pages(&self, plugin_id: &str) -> Result<Value, String> {
+        let db = self.database(plugin_id, DATA_READ)?;
+        let pages = db.list_pages().map_err(db_error)?;
+        Ok(Value::Array(pages.iter().map(page_json).collect()))
+    }
+
+    /// Blocks matching a search query, using the same syntax as the search box.
+    pub fn search_blocks(
+        &self,
+        plugin_id: &str,
+        query: &str,
+        limit: Option<i64>,
+    ) -> Result<Value, String> {
+        let db = self.database(plugin_id, DATA_READ)?;
+        let limit = limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT);
+        let query = db.parse_search_query(query).map_err(db_error)?;
+        let hits = db.search(&query, limit).map_err(db_error)?;
+        Ok(Value::Array(
+            hits.into_iter()
+                .map(|hit| {
+                    json!({
+                        "uid": hit.block_uid,
+                        "text": hit.text,
+                        "pageUid": hit.page_uid,
+                        "pageTitle": hit.page_title,
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    /// A block with its page uid, or `null`.
+    pub fn get_block(&self, plugin_id: &str, uid: &str) -> Result<Value, String> {
+        let db = self.database(plugin_id, DATA_READ)?;
+        let Some((page, block)) = find_block(db, uid)? else {
+            return Ok(Value::Null);
+        };
+        let mut value = block_json(&block);
+        value["pageUid"] = Value::String(page.uid);
+        Ok(value)
+    }
+
+    pub fn set_block_text(&mut self, plugin_id: &str, uid: &str, text: &str) -> Result<(), String> {
+        self.update_block(plugin_id, uid, |_| text.to_string())
+    }
+
+    /// Sets a `key:: value` property on a block, or removes it for `None`.
+    pub fn set_block_property(
+        &mut self,
+        plugin_id: &str,
+        uid: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), String> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err("invalid-property-key".to_string());
+        }
+        self.update_block(plugin_id, uid, |text| match value {
+            Some(value) => set_block_property_in_text(text, key, value),
+            None => remove_block_property_in_text(text, key),
+        })
+    }
+
+    /// Creates a page titled `title` holding `blocks`. Returns `{ uid, title }`.
+    pub fn create_page(
+        &mut self,
+        plugin_id: &str,
+        title: &str,
+        blocks: Vec<NewBlock>,
+    ) -> Result<Value, String> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err("title-required".to_string());
+        }
+        let db = self.database_mut(plugin_id, DATA_WRITE)?;
+        let uid = resolve_unique_page_uid(db, title).map_err(|err| format!("{err:?}"))?;
+        let snapshots = blocks
+            .into_iter()
+            .map(|block| block.into_snapshot(0))
+            .collect::<Vec<_>>();
+        let id = db
+            .insert_page_with_blocks(&uid, title, &snapshots)
+            .map_err(db_error)?;
+        let page = PageRecord {
+            id,
+            uid,
+            title: title.to_string(),
+        };
+        self.changed_pages.push(page.uid.clone());
+        Ok(page_json(&page))
+    }
+
+    /// Inserts blocks into a page after the block `after` (and its children), or at the end.
+    /// Returns the new block uids.
+    pub fn insert_blocks(
+        &mut self,
+        plugin_id: &str,
+        page: &str,
+        blocks: Vec<NewBlock>,
+        after: Option<&str>,
+    ) -> Result<Value, String> {
+        let db = self.database_mut(plugin_id, DATA_WRITE)?;
+        let page = db
+            .resolve_page(page)
+            .map_err(db_error)?
+            .ok_or_else(|| "page-not-found".to_string())?;
+        let mut existing = db.load_blocks_for_page(page.id).map_err(db_error)?;
+        let (at, base_indent) = match after {
+            Some(uid) => {
+                let ix = existing
+                    .iter()
+                    .position(|block| block.uid == uid)
+                    .ok_or_else(|| "block-not-found".to_string())?;
+                let indent = existing[ix].indent;
+                let end = existing[ix + 1..]
+                    .iter()
+                    .position(|block| block.indent <= indent)
+                    .map_or(existing.len(), |offset| ix + 1 + offset);
+                (end, indent)
+            }
+            None => (existing.len(), 0),
+        };
+        let inserted = blocks
+            .into_iter()
+            .map(|block| block.into_snapshot(base_indent))
+            .collect::<Vec<_>>();
+        let uids = inserted
+            .iter()
+            .map(|block| Value::String(block.uid.clone()))
+            .collect();
+        existing.splice(at..at, inserted);
+        db.replace_blocks_for_page(page.id, &existing)
+            .map_err(db_error)?;
+        self.changed_pages.push(page.uid);
+        Ok(Value::Array(uids))
+    }
+
+    fn update_block(
+        &mut self,
+        plugin_id: &str,
+        uid: &str,
+        edit: impl FnOnce(&str) -> String,
+    ) -> Result<(), String> {
+        let db = self.database_mut(plugin_id, DATA_WRITE)?;
+        let (page, mut block) =
+            find_block(db, uid)?.ok_or_else(|| "block-not-found".to_string())?;
+        let next = edit(&block.text);
+        if next == block.text {
+            return Ok(());
+        }
+        block.text = next;
+        db.apply_block_changes(page.id, &[], &[block], &[], &[])
+            .map_err(db_error)?;
+        self.changed_pages.push(page.uid);
+        Ok(())
+    }
+
+    /// Writes the edits a `plugin_id` event handler returned for `event`.
+    pub fn apply_event_edits(
+        &mut self,
+        plugin_id: &str,
+        event: &PluginEvent,
+        edits: Vec<PluginCommandEdit>,
+    ) -> Result<(), String> {
+        let db = self.database_mut(plugin_id, DATA_WRITE)?;
+        let pages = apply_event_edits(db, event, edits).map_err(db_error)?;
+        self.changed_pages.extend(pages);
+        Ok(())
+    }
+
+    fn database(&self, plugin_id: &str, permission: &str) -> Result<&Database, String> {
+        let db = self
+            .db
+            .as_ref()
+            .ok_or_else(|| "data-unavailable".to_string())?;
+        check_permission(db, plugin_id, permission)?;
+        Ok(db)
+    }
+
+    fn database_mut(&mut self, plugin_id: &str, permission: &str) -> Result<&mut Database, String> {
+        let db = self
+            .db
+            .as_mut()
+            .ok_or_else(|| "data-unavailable".to_string())?;
+        check_permission(db, plugin_id, permission)?;
+        Ok(db)
+    }
+}
+
+fn check_permission(db: &Database, plugin_id: &str, permission: &str) -> Result<(), String> {
+    let granted = db.list_plugin_permissions(plugin_id).map_err(db_error)?;
+    if granted.iter().any(|perm| perm == permission) {
+        Ok(())
+    } else {
+        Err(format!("missing-permission:{permission}"))
+    }
+}
+
+/// The page holding block `uid`, its blocks and the block's index among them.
+fn find_block(db: &Database, uid: &str) -> Result<Option<(PageRecord, BlockSnapshot)>, String> {
+    let Some(record) = db.get_block_by_uid(uid).map_err(db_error)? else {
+        return Ok(None);
+    };
+    let page = db.get_page_by_id(record.page_id).map_err(db_error)?;
+    Ok(page.map(|page| (page, record.snapshot())))
+}
+
+pub(crate) fn page_json(page: &PageRecord) -> Value {
+    json!({ "uid": page.uid, "title": page.title })
+}
+
+pub(crate) fn block_json(block: &BlockSnapshot) -> Value {
+    let properties = parse_block_properties(&block.text)
+        .into_iter()
+        .map(|(key, value)| (key, Value::String(value)))
+        .collect::<Map<_, _>>();
+    json!({
+        "uid": block.uid,
+        "text": block.text,
+        "indent": block.indent,
+        "type": block.block_type,
+        "properties": properties,
+    })
+}
+
+fn db_error(err: impl std::fmt::Debug) -> String {
+    format!("{err:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NewBlock, PluginDataHost, DATA_READ, DATA_WRITE};
+    use crate::blocks::BlockType;
+    use crate::db::{BlockSnapshot, Database};
+
+    fn host_with_page() -> PluginDataHost {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+        let page_id = db.insert_page("inbox", "Inbox").expect("insert page");
+        db.replace_blocks_for_page(
+            page_id,
+            &[
+                BlockSnapshot {
+                    uid: "a".to_string(),
+                    text: "Parent".to_string(),
+                    indent: 0,
+                    block_type: BlockType::Text,
+                },
+                BlockSnapshot {
+                    uid: "b".to_string(),
+                    text: "Child".to_string(),
+                    indent: 1,
+                    block_type: BlockType::Text,
+                },
+            ],
+        )
+        .expect("blocks");
+        let mut host = PluginDataHost::default();
+        host.set_database(Some(db));
+        host
+    }
+
+    fn grant(host: &PluginDataHost, permission: &str) {
+        host.db
+            .as_ref()
+            .expect("db")
+            .grant_plugin_permission("probe", permission)
+            .expect("grant");
+    }
+
+    #[test]
+    fn reads_and_writes_need_granted_permissions() {
+        let mut host = host_with_page();
+        assert_eq!(
+            host.get_page("probe", "Inbox"),
+            Err("missing-permission:data.read".to_string())
+        );
+        grant(&host, DATA_READ);
+        let page = host.get_page("probe", "inbox").expect("page");
+        assert_eq!(page["title"], "Inbox");
+        assert_eq!(page["blocks"][1]["indent"], 1);
+
+        assert_eq!(
+            host.set_block_text("probe", "a", "Changed"),
+            Err("missing-permission:data.write".to_string())
+        );
+        assert!(host.take_changed_pages().is_empty());
+    }
+
+    #[test]
+    fn writes_update_blocks_and_report_changed_pages() {
+        let mut host = host_with_page();
+        grant(&host, DATA_READ);
+        grant(&host, DATA_WRITE);
+
+        host.set_block_property("probe", "a", "status", Some("done"))
+            .expect("set property");
+        let block = host.get_block("probe", "a").expect("block");
+        assert_eq!(block["pageUid"], "inbox");
+        assert_eq!(block["properties"]["status"], "done");
+
+        let uids = host
+            .insert_blocks(
+                "probe",
+                "Inbox",
+                vec![
+                    NewBlock::Text("After parent".to_string()),
+                    NewBlock::Block {
+                        text: "Nested".to_string(),
+                        indent: 1,
+                        block_type: BlockType::Todo,
+                    },
+                ],
+                Some("a"),
+            )
+            .expect("insert");
+        assert_eq!(uids.as_array().map(Vec::len), Some(2));
+        let page = host.get_page("probe", "inbox").expect("page");
+        let layout = page["blocks"]
+            .as_array()
+            .expect("blocks")
+            .iter()
+            .map(|block| {
+                (
+                    block["text"].as_str().unwrap_or_default(),
+                    block["indent"].as_i64(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layout,
+            vec![
+                ("Parent\nstatus:: done", Some(0)),
+                ("Child", Some(1)),
+                ("After parent", Some(0)),
+                ("Nested", Some(1)),
+            ]
+        );
+        assert_eq!(page["blocks"][3]["type"], "todo");
+
+        let created = host
+            .create_page("probe", "Inbox", vec![NewBlock::Text("First".to_string())])
+            .expect("create");
+        assert_ne!(created["uid"], "inbox");
+        assert_eq!(
+            host.take_changed_pages(),
+            vec![
+                "inbox".to_string(),
+                "inbox".to_string(),
+                created["uid"].as_str().unwrap_or_default().to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use crate::db::{BlockSnapshot, Database, PageRecord, ReviewQueueItem};
+use crate::editor::EditorModel;
+use crate::plugin_data::{block_json, page_json};
+use crate::plugins::PluginCommandEdit;
+use crate::review::ReviewGrade;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+pub const PAGE_OPENED: &str = "page.opened";
+pub const PAGE_SAVED: &str = "page.saved";
+pub const BLOCK_CREATED: &str = "block.created";
+pub const BLOCK_CHANGED: &str = "block.changed";
+pub const BLOCK_DELETED: &str = "block.deleted";
+pub const VAULT_CHANGED: &str = "vault.changed";
+pub const REVIEW_COMPLETED: &str = "review.completed";
+
+/// Events plugins can subscribe to with `api.on`.
+pub const PLUGIN_EVENTS: [&str; 7] = [
+    PAGE_OPENED,
+    PAGE_SAVED,
+    BLOCK_CREATED,
+    BLOCK_CHANGED,
+    BLOCK_DELETED,
+    VAULT_CHANGED,
+    REVIEW_COMPLETED,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginEvent {
+    pub name: &'static str,
+    pub payload: Value,
+}
+
+impl PluginEvent {
+    pub fn page_opened(page: &PageRecord) -> Self {
+        Self {
+            name: PAGE_OPENED,
+            payload: json!({ "page": page_json(page) }),
+        }
+    }
+
+    pub fn page_saved(page: &PageRecord, blocks: &[BlockSnapshot]) -> Self {
+        Self {
+            name: PAGE_SAVED,
+            payload: json!({
+                "page": page_json(page),
+                "blocks": blocks.iter().map(block_json).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    pub fn vault_changed(vault_id: Option<&str>, root: &Path) -> Self {
+        Self {
+            name: VAULT_CHANGED,
+            payload: json!({
+                "vault": { "id": vault_id, "path": root.to_string_lossy() },
+            }),
+        }
+    }
+
+    /// The page the event is about, if any.
+    pub fn page_uid(&self) -> Option<&str> {
+        self.payload["page"]["uid"]
+            .as_str()
+            .or_else(|| self.payload["pageUid"].as_str())
+    }
+
+    /// The block the event is about, if any.
+    pub fn block_uid(&self) -> Option<&str> {
+        self.payload["block"]["uid"]
+            .as_str()
+            .or_else(|| self.payload["blockUid"].as_str())
+    }
+
+    /// A review answered with `grade`, or marked done when `grade` is `None`.
+    pub fn review_completed(item: &ReviewQueueItem, grade: Option<ReviewGrade>) -> Self {
+        Self {
+            name: REVIEW_COMPLETED,
+            payload: json!({
+                "itemId": item.id,
+                "pageUid": item.page_uid,
+                "blockUid": item.block_uid,
+                "grade": grade.map(ReviewGrade::as_str),
+                "dueAt": item.due_at,
+            }),
+        }
+    }
+}
+
+/// Block events for saving `after` over `before`: blocks that are new, gone, or whose text,
+/// type or indent changed. Moves alone are not reported. Each payload carries the page and
+/// the block, as it is now or, for deletions, as it was.
+pub fn block_events(
+    page: &PageRecord,
+    before: &[BlockSnapshot],
+    after: &[BlockSnapshot],
+) -> Vec<PluginEvent> {
+    let previous = before
+        .iter()
+        .map(|block| (block.uid.as_str(), block))
+        .collect::<HashMap<_, _>>();
+    let current = after
+        .iter()
+        .map(|block| block.uid.as_str())
+        .collect::<HashSet<_>>();
+    let event = |name, block: &BlockSnapshot| PluginEvent {
+        name,
+        payload: json!({ "page": page_json(page), "block": block_json(block) }),
+    };
+    let mut events = Vec::new();
+    for block in after {
+        match previous.get(block.uid.as_str()) {
+            None => events.push(event(BLOCK_CREATED, block)),
+            Some(old)
+                if old.text != block.text
+                    || old.block_type != block.block_type
+                    || old.indent != block.indent =>
+            {
+                events.push(event(BLOCK_CHANGED, block))
+            }
+            Some(_) => {}
+        }
+    }
+    for block in before {
+        if !current.contains(block.uid.as_str()) {
+            events.push(event(BLOCK_DELETED, block));
+        }
+    }
+    events
+}
+
+/// Writes the follow-up edits an event handler returned. Edits naming a block land on that
+/// block's page; the rest go to the event's page, after the event's block. Returns the uids
+/// of the pages written.
+pub fn apply_event_edits(
+    db: &mut Database,
+    event: &PluginEvent,
+    edits: Vec<PluginCommandEdit>,
+) -> rusqlite::Result<Vec<String>> {
+    let mut changed = Vec::new();
+    for edit in edits {
+        let anchor = match &edit {
+            PluginCommandEdit::SetText { uid, .. } | PluginCommandEdit::DeleteBlock { uid } => {
+                Some(uid.as_str())
+            }
+            PluginCommandEdit::InsertBlocks { after, .. } => after.as_deref(),
+        };
+        let page = match anchor {
+            Some(uid) => match db.get_block_by_uid(uid)? {
+                Some(record) => db.get_page_by_id(record.page_id)?,
+                None => None,
+            },
+            None => match event.page_uid() {
+                Some(uid) => db.get_page_by_uid(uid)?,
+                None => None,
+            },
+        };
+        let Some(page) = page else {
+            continue;
+        };
+        let mut editor = EditorModel::new(db.load_blocks_for_page(page.id)?);
+        editor.active_ix = event
+            .block_uid()
+            .and_then(|uid| editor.blocks().iter().position(|block| block.uid == uid))
+            .unwrap_or(editor.blocks().len().saturating_sub(1));
+        if edit.apply(&mut editor) {
+            db.replace_blocks_for_page(page.id, editor.blocks())?;
+            if !changed.contains(&page.uid) {
+                changed.push(page.uid);
+            }
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_event_edits, block_events, PluginEvent, BLOCK_CHANGED, BLOCK_CREATED, BLOCK_DELETED,
+    };
+    use crate::blocks::BlockType;
+    use crate::db::{BlockSnapshot, Database, PageRecord};
+    use crate::plugin_data::NewBlock;
+    use crate::plugins::PluginCommandEdit;
+
+    fn block(uid: &str, text: &str, indent: i64) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent,
+            block_type: BlockType::Text,
+        }
+    }
+
+    #[test]
+    fn block_events_report_created_changed_and_deleted_blocks() {
+        let page = PageRecord {
+            id: 1,
+            uid: "inbox".to_string(),
+            title: "Inbox".to_string(),
+        };
+        let before = [
+            block("a", "Keep", 0),
+            block("b", "Edit me", 0),
+            block("c", "Indent me", 0),
+            block("d", "Drop me", 0),
+        ];
+        let after = [
+            block("c", "Indent me", 1),
+            block("a", "Keep", 0),
+            block("b", "Edited", 0),
+            block("e", "New", 0),
+        ];
+        let events = block_events(&page, &before, &after);
+        let summary = events
+            .iter()
+            .map(|PluginEvent { name, payload }| {
+                (*name, payload["block"]["uid"].as_str().unwrap_or_default())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (BLOCK_CHANGED, "c"),
+                (BLOCK_CHANGED, "b"),
+                (BLOCK_CREATED, "e"),
+                (BLOCK_DELETED, "d"),
+            ]
+        );
+        assert_eq!(events[0].payload["page"]["uid"], "inbox");
+        assert_eq!(events[3].payload["block"]["text"], "Drop me");
+    }
+
+    #[test]
+    fn event_edits_are_written_to_the_event_page() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+        let page_id = db.insert_page("inbox", "Inbox").expect("page");
+        let blocks = [block("a", "First", 0), block("b", "Second", 0)];
+        db.replace_blocks_for_page(page_id, &blocks)
+            .expect("blocks");
+        let page = PageRecord {
+            id: page_id,
+            uid: "inbox".to_string(),
+            title: "Inbox".to_string(),
+        };
+        let event = block_events(&page, &[], &blocks[..1]).remove(0);
+
+        let changed = apply_event_edits(
+            &mut db,
+            &event,
+            vec![
+                PluginCommandEdit::InsertBlocks {
+                    after: None,
+                    blocks: vec![NewBlock::Text("Follow-up".to_string())],
+                },
+                PluginCommandEdit::SetText {
+                    uid: "b".to_string(),
+                    text: "Second!".to_string(),
+                },
+                PluginCommandEdit::DeleteBlock {
+                    uid: "missing".to_string(),
+                },
+            ],
+        )
+        .expect("apply");
+        assert_eq!(changed, vec!["inbox".to_string()]);
+        let texts = db
+            .load_blocks_for_page(page_id)
+            .expect("load")
+            .into_iter()
+            .map(|block| block.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["First", "Follow-up", "Second!"]);
+    }
+}
@@ -1,13 +1,21 @@
+use crate::db::Database;
+use crate::editor::EditorModel;
+use crate::plugin_data::{NewBlock, PluginDataHost};
+use crate::plugin_events::{PluginEvent, PLUGIN_EVENTS};
 use chrono::{DateTime, Duration, Utc};
+use rquickjs::allocator::{Allocator, RustAllocator};
 use rquickjs::{
-    function::Opt, CatchResultExt, CaughtError, Context, FromJs, Function, IntoJs, Object,
-    Persistent, Runtime, Value as JsValue,
+    function::Opt, CatchResultExt, CaughtError, Context, Exception, FromJs, Function, IntoJs,
+    Object, Persistent, Runtime, Value as JsValue,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
 
 const PLUGIN_API_VERSION: &str = "1.0.0";
 const HOST_APP_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -45,6 +53,9 @@ pub struct PluginRuntimeError {
     pub stack: Option<String>,
     #[serde(default)]
     pub context: Option<PluginErrorContext>,
+    /// `name` of the JS error object that was thrown, when one was.
+    #[serde(skip)]
+    error_name: Option<String>,
 }
 
 impl PluginRuntimeError {
@@ -53,6 +64,7 @@ impl PluginRuntimeError {
             message: message.into(),
             stack: None,
             context: None,
+            error_name: None,
         }
     }
 
@@ -769,6 +781,9 @@ pub struct PluginRuntimeLoadResult {
     pub toolbar_actions: Vec<PluginToolbarAction>,
     #[serde(default)]
     pub renderers: Vec<PluginRenderer>,
+    /// Why the plugins missing from `loaded` failed to load.
+    #[serde(default)]
+    pub errors: Vec<PluginRuntimeError>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -780,6 +795,126 @@ pub struct PluginCommand {
     pub description: Option<String>,
 }
 
+/// What a command handler sees of the editor when it runs. `settings` is filled in by the
+/// runtime.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PluginCommandContext {
+    #[serde(default)]
+    pub page: Option<PluginCommandPage>,
+    /// The block holding the cursor.
+    #[serde(default)]
+    pub block: Option<PluginCommandBlock>,
+    /// Selected blocks, in page order.
+    #[serde(default)]
+    pub selection: Vec<PluginCommandBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginCommandPage {
+    pub uid: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginCommandBlock {
+    pub uid: String,
+    pub text: String,
+    /// Byte offset of the cursor, for the cursor block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<usize>,
+}
+
+/// What a command handler returns; every field is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginCommandResult {
+    /// Edits to the page the command ran on, applied as one undoable step.
+    #[serde(default)]
+    pub edits: Vec<PluginCommandEdit>,
+    /// Id of one of the plugin's panels to open.
+    #[serde(default)]
+    pub open_panel: Option<String>,
+    #[serde(default)]
+    pub notification: Option<PluginNotification>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PluginCommandEdit {
+    SetText {
+        uid: String,
+        text: String,
+    },
+    /// Inserts after the block `after` and its children, or after the cursor block.
+    InsertBlocks {
+        #[serde(default)]
+        after: Option<String>,
+        blocks: Vec<NewBlock>,
+    },
+    DeleteBlock {
+        uid: String,
+    },
+}
+
+impl PluginCommandEdit {
+    /// Applies the edit through `editor`'s ops. Returns whether anything changed.
+    pub fn apply(self, editor: &mut EditorModel) -> bool {
+        let index_of = |editor: &EditorModel, uid: &str| {
+            editor.blocks().iter().position(|block| block.uid == uid)
+        };
+        match self {
+            PluginCommandEdit::SetText { uid, text } => {
+                index_of(editor, &uid).is_some_and(|ix| editor.set_text(ix, &text))
+            }
+            PluginCommandEdit::InsertBlocks { after, blocks } => {
+                let anchor = match after {
+                    Some(uid) => index_of(editor, &uid),
+                    None => Some(editor.active_ix),
+                };
+                let Some(anchor) = anchor.filter(|ix| *ix < editor.blocks().len()) else {
+                    return false;
+                };
+                let indent = editor.blocks()[anchor].indent;
+                let end = editor.blocks()[anchor + 1..]
+                    .iter()
+                    .position(|block| block.indent <= indent)
+                    .map_or(editor.blocks().len(), |offset| anchor + 1 + offset);
+                let blocks = blocks
+                    .into_iter()
+                    .map(|block| block.into_snapshot(indent))
+                    .collect::<Vec<_>>();
+                let inserted = !blocks.is_empty();
+                editor.insert_blocks(end, blocks);
+                inserted
+            }
+            PluginCommandEdit::DeleteBlock { uid } => {
+                let removed = index_of(editor, &uid)
+                    .and_then(|ix| editor.remove_block(ix))
+                    .is_some();
+                editor.ensure_non_empty();
+                removed
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginNotification {
+    pub message: String,
+    /// `info` (default), `success`, `warning` or `error`.
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+/// What the handlers of one event returned. Event handlers return the same shape as command
+/// handlers, so they can ask for follow-up edits.
+#[derive(Debug, Default)]
+pub struct PluginEventOutcome {
+    pub results: Vec<(String, PluginCommandResult)>,
+    /// Handlers that threw or returned something unreadable; the others still ran.
+    pub errors: Vec<PluginError>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PluginPanel {
     pub plugin_id: String,
@@ -789,6 +924,58 @@ pub struct PluginPanel {
     pub location: Option<String>,
 }
 
+/// What a panel's `render` or `onAction` handler returns: a title and a tree of nodes. A
+/// handler may also return just the array of nodes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginPanelView {
+    #[serde(default)]
+    pub plugin_id: String,
+    #[serde(default)]
+    pub panel_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub children: Vec<PluginPanelNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PluginPanelNode {
+    /// `style` is `heading`, `muted` or unset.
+    Text {
+        text: String,
+        #[serde(default)]
+        style: Option<String>,
+    },
+    List {
+        items: Vec<PluginPanelNode>,
+    },
+    /// Runs the panel action `id` with `value` when clicked.
+    Button {
+        id: String,
+        label: String,
+        #[serde(default)]
+        value: Option<Value>,
+    },
+    /// Runs the panel action `id` with the entered text on Enter.
+    Input {
+        id: String,
+        #[serde(default)]
+        placeholder: Option<String>,
+        #[serde(default)]
+        value: Option<String>,
+    },
+    /// Opens a page by uid or title, or the page holding `block`.
+    Link {
+        label: String,
+        #[serde(default)]
+        page: Option<String>,
+        #[serde(default)]
+        block: Option<String>,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PluginToolbarAction {
     pub plugin_id: String,
@@ -855,9 +1042,37 @@ struct PluginRuntimeRegistry {
     toolbar_actions: Vec<PluginToolbarAction>,
     renderers: Vec<PluginRenderer>,
     renderer_handlers: HashMap<(String, String), RendererHandlers>,
+    command_handlers: HashMap<(String, String), Persistent<Function<'static>>>,
+    /// Panel `render` and `onAction` handlers, keyed like renderers.
+    panel_handlers: HashMap<(String, String), RendererHandlers>,
+    /// `api.on` subscriptions as (plugin id, event, handler), in subscription order.
+    event_handlers: Vec<(String, String, Persistent<Function<'static>>)>,
     toolbar_action_handlers: HashMap<(String, String), Persistent<Function<'static>>>,
 }
 
+impl PluginRuntimeRegistry {
+    /// Forgets everything `plugin_id` registered.
+    fn remove_plugin(&mut self, plugin_id: &str) {
+        self.commands
+            .retain(|command| command.plugin_id != plugin_id);
+        self.panels.retain(|panel| panel.plugin_id != plugin_id);
+        self.toolbar_actions
+            .retain(|action| action.plugin_id != plugin_id);
+        self.renderers
+            .retain(|renderer| renderer.plugin_id != plugin_id);
+        self.renderer_handlers
+            .retain(|(owner, _), _| owner != plugin_id);
+        self.command_handlers
+            .retain(|(owner, _), _| owner != plugin_id);
+        self.panel_handlers
+            .retain(|(owner, _), _| owner != plugin_id);
+        self.event_handlers
+            .retain(|(owner, _, _)| owner != plugin_id);
+        self.toolbar_action_handlers
+            .retain(|(owner, _), _| owner != plugin_id);
+    }
+}
+
 struct PluginFence {
     lang: String,
     config_text: String,
@@ -867,26 +1082,157 @@ struct PluginFence {
 const CACHE_TTL_KEY: &str = "cache_ttl";
 const CACHE_TS_KEY: &str = "cache_ts";
 
-struct CacheMeta {
-    ttl_seconds: Option<u64>,
-    timestamp: Option<String>,
-    stale: bool,
+/// Budgets plugin code runs under, so a runaway plugin fails its call instead of freezing the
+/// app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginLimits {
+    /// Time one call into a plugin may take, including settling a returned promise.
+    pub call_timeout_ms: u64,
+    /// Heap each plugin may hold. Every plugin runs in its own QuickJS runtime, so what it
+    /// keeps between calls counts against its own limit only.
+    pub memory_per_plugin: usize,
+    /// Native stack JS code may use.
+    pub max_stack_size: usize,
+    /// Limits a plugin may hit before it is disabled.
+    pub max_strikes: u32,
 }
 
-pub struct PluginRuntime {
-    registry: std::rc::Rc<std::cell::RefCell<PluginRuntimeRegistry>>,
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            call_timeout_ms: 2_000,
+            memory_per_plugin: 32 * 1024 * 1024,
+            max_stack_size: 512 * 1024,
+            max_strikes: 3,
+        }
+    }
+}
+
+/// Deadline for the call in progress, checked by the runtime's interrupt handler.
+#[derive(Clone, Default)]
+struct CallBudget {
+    deadline: Rc<Cell<Option<Instant>>>,
+    expired: Rc<Cell<bool>>,
+}
+
+impl CallBudget {
+    fn start(&self, timeout_ms: u64) {
+        self.expired.set(false);
+        self.deadline.set(Some(
+            Instant::now() + std::time::Duration::from_millis(timeout_ms),
+        ));
+    }
+
+    /// Ends the call; returns whether it ran out of time.
+    fn finish(&self) -> bool {
+        self.deadline.set(None);
+        self.expired.replace(false)
+    }
+
+    /// Time left for the call in progress.
+    fn remaining(&self) -> std::time::Duration {
+        self.deadline
+            .get()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+            .unwrap_or_default()
+    }
+
+    fn should_interrupt(&self) -> bool {
+        if self
+            .deadline
+            .get()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.expired.set(true);
+        }
+        self.expired.get()
+    }
+}
+
+/// Bytes a sandbox's heap holds, and the most it held since the current call started.
+#[derive(Clone, Default)]
+struct HeapUsage {
+    used: Rc<Cell<usize>>,
+    peak: Rc<Cell<usize>>,
+}
+
+impl HeapUsage {
+    fn start_call(&self) {
+        self.peak.set(self.used.get());
+    }
+
+    fn grow(&self, bytes: usize) {
+        let used = self.used.get() + bytes;
+        self.used.set(used);
+        self.peak.set(self.peak.get().max(used));
+    }
+
+    fn shrink(&self, bytes: usize) {
+        self.used.set(self.used.get().saturating_sub(bytes));
+    }
+}
+
+/// Rust's allocator, counting into a `HeapUsage`. QuickJS checks the memory limit before it
+/// allocates, so a refused allocation never gets here; the peak shows how full the heap got.
+struct CountingAllocator(HeapUsage);
+
+unsafe impl Allocator for CountingAllocator {
+    fn alloc(&mut self, size: usize) -> *mut u8 {
+        let ptr = RustAllocator.alloc(size);
+        if !ptr.is_null() {
+            self.0.grow(unsafe { RustAllocator::usable_size(ptr) });
+        }
+        ptr
+    }
+
+    fn calloc(&mut self, count: usize, size: usize) -> *mut u8 {
+        let ptr = RustAllocator.calloc(count, size);
+        if !ptr.is_null() {
+            self.0.grow(unsafe { RustAllocator::usable_size(ptr) });
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8) {
+        self.0.shrink(RustAllocator::usable_size(ptr));
+        RustAllocator.dealloc(ptr);
+    }
+
+    unsafe fn realloc(&mut self, ptr: *mut u8, new_size: usize) -> *mut u8 {
+        let old_size = RustAllocator::usable_size(ptr);
+        let new_ptr = RustAllocator.realloc(ptr, new_size);
+        if !new_ptr.is_null() {
+            self.0.shrink(old_size);
+            self.0.grow(RustAllocator::usable_size(new_ptr));
+        }
+        new_ptr
+    }
+
+    unsafe fn usable_size(ptr: *mut u8) -> usize {
+        RustAllocator::usable_size(ptr)
+    }
+}
+
+/// A plugin's own QuickJS runtime and the helpers evaluated into it.
+struct PluginSandbox {
     load_plugin_fn: Persistent<Function<'static>>,
     to_json_fn: Persistent<Function<'static>>,
-    settings: HashMap<String, Value>,
-    permissions: HashMap<String, Vec<String>>,
-    settings_schema: HashMap<String, PluginSettingsSchema>,
     context: Context,
-    _runtime: Runtime,
+    runtime: Runtime,
+    heap: HeapUsage,
+    memory_limit: usize,
 }
 
-impl PluginRuntime {
-    pub fn new() -> Result<Self, PluginError> {
-        let runtime = Runtime::new()?;
+impl PluginSandbox {
+    fn new(limits: &PluginLimits, budget: &CallBudget) -> Result<Self, PluginError> {
+        let heap = HeapUsage::default();
+        let runtime = Runtime::new_with_alloc(CountingAllocator(heap.clone()))?;
+        runtime.set_interrupt_handler(Some(Box::new({
+            let budget = budget.clone();
+            move || budget.should_interrupt()
+        })));
+        runtime.set_memory_limit(limits.memory_per_plugin);
+        runtime.set_max_stack_size(limits.max_stack_size);
         let context = Context::full(&runtime)?;
         let (load_plugin_fn, to_json_fn) = context.with(|ctx| {
             ctx.eval::<(), _>(
@@ -906,19 +1252,142 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
                 Persistent::save(&ctx, to_json),
             ))
         })?;
-
         Ok(Self {
-            _runtime: runtime,
-            context,
-            registry: std::rc::Rc::new(std::cell::RefCell::new(PluginRuntimeRegistry::default())),
             load_plugin_fn,
             to_json_fn,
+            context,
+            runtime,
+            heap,
+            memory_limit: limits.memory_per_plugin,
+        })
+    }
+
+    /// Whether `error` is the engine running out of this sandbox's heap during the call. QuickJS
+    /// throws an `InternalError` when an allocation would pass the limit; the heap must also
+    /// have filled up, so a plugin throwing that error itself is not mistaken for it.
+    fn ran_out_of_memory(&self, error: &PluginRuntimeError) -> bool {
+        error.error_name.as_deref() == Some("InternalError")
+            && error.message == "out of memory"
+            && self.heap.peak.get() >= self.memory_limit / 2
+    }
+
+    fn parse_block_view<'js>(
+        &self,
+        ctx: rquickjs::Ctx<'js>,
+        value: JsValue<'js>,
+        plugin_id: &str,
+        renderer_id: &str,
+        block_uid: &str,
+    ) -> Result<PluginBlockView, PluginError> {
+        let json_value = js_to_json(ctx, self.to_json_fn.clone(), value)?;
+        let mut view: PluginBlockView =
+            serde_json::from_value(json_value).map_err(PluginError::Serde)?;
+        view.plugin_id = plugin_id.to_string();
+        view.renderer_id = renderer_id.to_string();
+        view.block_uid = block_uid.to_string();
+        Ok(view)
+    }
+}
+
+/// Whether `error` is QuickJS's own stack overflow `RangeError`.
+fn overflowed_stack(error: &PluginRuntimeError) -> bool {
+    error.error_name.as_deref() == Some("RangeError")
+        && error.message == "Maximum call stack size exceeded"
+}
+
+struct CacheMeta {
+    ttl_seconds: Option<u64>,
+    timestamp: Option<String>,
+    stale: bool,
+}
+
+pub struct PluginRuntime {
+    registry: std::rc::Rc<std::cell::RefCell<PluginRuntimeRegistry>>,
+    settings: HashMap<String, Value>,
+    permissions: HashMap<String, Vec<String>>,
+    settings_schema: HashMap<String, PluginSettingsSchema>,
+    data: std::rc::Rc<std::cell::RefCell<PluginDataHost>>,
+    limits: PluginLimits,
+    budget: CallBudget,
+    strikes: HashMap<String, u32>,
+    disabled: HashSet<String>,
+    newly_disabled: Vec<String>,
+    sandboxes: HashMap<String, PluginSandbox>,
+}
+
+impl Drop for PluginRuntime {
+    fn drop(&mut self) {
+        self.registry.take();
+    }
+}
+
+impl PluginRuntime {
+    pub fn new() -> Result<Self, PluginError> {
+        Self::with_limits(PluginLimits::default())
+    }
+
+    pub fn with_limits(limits: PluginLimits) -> Result<Self, PluginError> {
+        Ok(Self {
+            registry: std::rc::Rc::new(std::cell::RefCell::new(PluginRuntimeRegistry::default())),
             settings: HashMap::new(),
             permissions: HashMap::new(),
             settings_schema: HashMap::new(),
+            data: std::rc::Rc::new(std::cell::RefCell::new(PluginDataHost::default())),
+            limits,
+            budget: CallBudget::default(),
+            strikes: HashMap::new(),
+            disabled: HashSet::new(),
+            newly_disabled: Vec::new(),
+            sandboxes: HashMap::new(),
         })
     }
 
+    /// Plugins disabled for hitting their limits too often since the last call. They stay
+    /// unloaded in this runtime, across reloads, until `enable_plugin`; callers should also
+    /// turn them off in the registry.
+    pub fn take_disabled_plugins(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.newly_disabled)
+    }
+
+    /// Lets a plugin disabled for its limits load again with no strikes, for when the user
+    /// turns it back on.
+    pub fn enable_plugin(&mut self, plugin_id: &str) {
+        self.disabled.remove(plugin_id);
+        self.strikes.remove(plugin_id);
+    }
+
+    /// Opens `api.data` onto a vault database; `None` makes every data call fail.
+    pub fn set_database(&mut self, db: Option<Database>) {
+        self.data.borrow_mut().set_database(db);
+    }
+
+    /// Writes the edits a `plugin_id` event handler returned, when the plugin holds
+    /// `data.write`. Written pages show up in `take_data_changes`.
+    pub fn apply_event_edits(
+        &mut self,
+        plugin_id: &str,
+        event: &PluginEvent,
+        edits: Vec<PluginCommandEdit>,
+    ) -> Result<(), PluginError> {
+        self.data
+            .borrow_mut()
+            .apply_event_edits(plugin_id, event, edits)
+            .map_err(|message| {
+                PluginError::Runtime(Box::new(
+                    PluginRuntimeError::new(message).with_context(
+                        PluginErrorContext::new("event")
+                            .with_plugin(plugin_id)
+                            .with_action(event.name),
+                    ),
+                ))
+            })
+    }
+
+    /// Uids of the pages plugins wrote through `api.data` since the last call.
+    pub fn take_data_changes(&mut self) -> Vec<String> {
+        self.data.borrow_mut().take_changed_pages()
+    }
+
     pub fn load_plugins(
         &mut self,
         plugins: &[PluginDescriptor],
@@ -942,73 +1411,92 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
             })
             .collect();
         let registry = std::rc::Rc::new(std::cell::RefCell::new(PluginRuntimeRegistry::default()));
+        // Handlers that close over `api` keep the old registry alive through its
+        // register functions; clearing it lets the JS side be collected.
+        self.registry.take();
         self.registry = registry.clone();
-        let loaded_ids = plugins
-            .iter()
-            .map(|plugin| plugin.manifest.id.clone())
-            .collect::<Vec<_>>();
-
-        let load_plugin_fn = self.load_plugin_fn.clone();
-        self.context.with(|ctx| {
-            for plugin in plugins {
-                let api = Self::build_api(ctx.clone(), registry.clone(), &plugin.manifest.id)?;
-                let entry = plugin
-                    .manifest
-                    .main
-                    .clone()
-                    .unwrap_or_else(|| "index.js".to_string());
-                let entry_path = plugin.path.join(entry);
-                let source = fs::read_to_string(&entry_path)?;
-                let load_fn = load_plugin_fn.clone().restore(&ctx)?;
-                let load_context = PluginErrorContext::new("load").with_plugin(&plugin.manifest.id);
-                let exports: JsValue =
-                    load_fn
-                        .call((source, api.clone()))
-                        .catch(&ctx)
-                        .map_err(|err| {
-                            PluginError::Runtime(Box::new(runtime_error_from_caught(
-                                err,
-                                load_context.clone(),
-                            )))
-                        })?;
-                if let Ok(register_fn) = Function::from_value(exports.clone()) {
-                    let register_context =
-                        PluginErrorContext::new("register").with_plugin(&plugin.manifest.id);
-                    let _ = register_fn
-                        .call::<_, JsValue>((api.clone(),))
-                        .catch(&ctx)
-                        .map_err(|err| {
-                            PluginError::Runtime(Box::new(runtime_error_from_caught(
-                                err,
-                                register_context.clone(),
-                            )))
-                        })?;
-                } else if let Ok(exports_obj) = Object::from_value(exports.clone()) {
-                    if let Ok(default_fn) = exports_obj.get::<_, Function>("default") {
-                        let register_context =
-                            PluginErrorContext::new("register").with_plugin(&plugin.manifest.id);
-                        let _ = default_fn
-                            .call::<_, JsValue>((api.clone(),))
+        // Dropping the old sandboxes frees whatever the plugins kept in their heaps.
+        self.sandboxes.clear();
+
+        let data = self.data.clone();
+        let mut loaded = Vec::new();
+        let mut errors = Vec::new();
+        // Plugins disabled for their limits stay out until `enable_plugin`.
+        for plugin in plugins {
+            let plugin_id = &plugin.manifest.id;
+            if self.disabled.contains(plugin_id) {
+                continue;
+            }
+            let result = PluginSandbox::new(&self.limits, &self.budget).and_then(|sandbox| {
+                self.sandboxes.insert(plugin_id.clone(), sandbox);
+                self.run_limited(plugin_id, |_, sandbox| {
+                    sandbox.context.with(|ctx| {
+                        let api = Self::build_api(
+                            ctx.clone(),
+                            registry.clone(),
+                            data.clone(),
+                            plugin_id,
+                        )?;
+                        let entry = plugin
+                            .manifest
+                            .main
+                            .clone()
+                            .unwrap_or_else(|| "index.js".to_string());
+                        let entry_path = plugin.path.join(entry);
+                        let source = fs::read_to_string(&entry_path)?;
+                        let load_fn = sandbox.load_plugin_fn.clone().restore(&ctx)?;
+                        let load_context = PluginErrorContext::new("load").with_plugin(plugin_id);
+                        let exports: JsValue = load_fn
+                            .call((source, api.clone()))
                             .catch(&ctx)
                             .map_err(|err| {
                                 PluginError::Runtime(Box::new(runtime_error_from_caught(
                                     err,
-                                    register_context.clone(),
+                                    load_context.clone(),
                                 )))
                             })?;
-                    }
+                        let register_fn =
+                            Function::from_value(exports.clone()).ok().or_else(|| {
+                                Object::from_value(exports)
+                                    .ok()
+                                    .and_then(|exports| exports.get::<_, Function>("default").ok())
+                            });
+                        if let Some(register_fn) = register_fn {
+                            let register_context =
+                                PluginErrorContext::new("register").with_plugin(plugin_id);
+                            register_fn
+                                .call::<_, JsValue>((api,))
+                                .catch(&ctx)
+                                .map_err(|err| {
+                                    PluginError::Runtime(Box::new(runtime_error_from_caught(
+                                        err,
+                                        register_context,
+                                    )))
+                                })?;
+                        }
+                        Ok::<_, PluginError>(())
+                    })
+                })
+            });
+            match result {
+                Ok(()) => loaded.push(plugin_id.clone()),
+                Err(err) => {
+                    // Drop what the plugin registered before failing; its sandbox is freed
+                    // on the next load, once nothing refers to it.
+                    registry.borrow_mut().remove_plugin(plugin_id);
+                    errors.push(load_error(plugin_id, err));
                 }
             }
-            Ok::<_, PluginError>(())
-        })?;
+        }
 
         let registry = self.registry.borrow();
         Ok(PluginRuntimeLoadResult {
-            loaded: loaded_ids,
+            loaded,
             commands: registry.commands.clone(),
             panels: registry.panels.clone(),
             toolbar_actions: registry.toolbar_actions.clone(),
             renderers: registry.renderers.clone(),
+            errors,
         })
     }
 
@@ -1041,18 +1529,280 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
         )
     }
 
+    /// The plugin's stored settings with schema defaults filled in and `overrides` applied.
+    fn merged_settings(&self, plugin_id: &str, overrides: &HashMap<String, String>) -> Value {
+        let stored = self
+            .settings
+            .get(plugin_id)
+            .cloned()
+            .unwrap_or(Value::Object(serde_json::Map::new()));
+        merge_settings_with_overrides(&stored, self.settings_schema.get(plugin_id), overrides)
+    }
+
+    /// Runs a command's handler with `context`. Handlers may return a promise, which is
+    /// settled before this returns.
+    pub fn run_command(
+        &mut self,
+        plugin_id: &str,
+        command_id: &str,
+        context: &PluginCommandContext,
+    ) -> Result<PluginCommandResult, PluginError> {
+        let handler = {
+            let registry = self.registry.borrow();
+            if !registry
+                .commands
+                .iter()
+                .any(|command| command.plugin_id == plugin_id && command.id == command_id)
+            {
+                return Err(PluginError::Runtime(Box::new("command-not-found".into())));
+            }
+            registry
+                .command_handlers
+                .get(&(plugin_id.to_string(), command_id.to_string()))
+                .cloned()
+        }
+        .ok_or_else(|| PluginError::Runtime(Box::new("command-handler-missing".into())))?;
+
+        let mut context_json = serde_json::to_value(context)?;
+        context_json["settings"] = self.merged_settings(plugin_id, &HashMap::new());
+
+        let json_value = self.run_limited(plugin_id, |_, sandbox| {
+            sandbox.context.with(|ctx| {
+                let ctx_obj = json_to_js(ctx.clone(), &context_json)?;
+                let handler_fn = handler.restore(&ctx)?;
+                let error_context = PluginErrorContext::new("command")
+                    .with_plugin(plugin_id)
+                    .with_action(command_id);
+                let value = call_settled(&ctx, handler_fn, ctx_obj, error_context)?;
+                js_to_json(ctx, sandbox.to_json_fn.clone(), value)
+            })
+        })?;
+        if json_value.is_null() {
+            return Ok(PluginCommandResult::default());
+        }
+        serde_json::from_value(json_value).map_err(PluginError::Serde)
+    }
+
+    pub fn has_event_handlers(&self, event: &str) -> bool {
+        self.registry
+            .borrow()
+            .event_handlers
+            .iter()
+            .any(|(_, name, _)| name == event)
+    }
+
+    /// Runs every plugin's handlers for `event`, in subscription order.
+    pub fn dispatch_event(&mut self, event: &PluginEvent) -> PluginEventOutcome {
+        let mut outcome = PluginEventOutcome::default();
+        for (plugin_id, value) in self.call_event_handlers(None, event.name, &event.payload) {
+            let result = value.and_then(|value| {
+                if value.is_null() {
+                    Ok(PluginCommandResult::default())
+                } else {
+                    serde_json::from_value(value).map_err(PluginError::Serde)
+                }
+            });
+            match result {
+                Ok(result) => outcome.results.push((plugin_id, result)),
+                Err(err) => outcome.errors.push(err),
+            }
+        }
+        outcome
+    }
+
+    /// Runs one plugin's handlers for `event` and returns what they returned, as an array.
     pub fn emit_event(
         &mut self,
-        _plugin_id: &str,
-        _event: &str,
-        _payload: Value,
+        plugin_id: &str,
+        event: &str,
+        payload: Value,
     ) -> Result<Value, PluginError> {
-        Ok(Value::Null)
+        self.call_event_handlers(Some(plugin_id), event, &payload)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    }
+
+    /// Renders a panel for the editor state in `context`.
+    pub fn render_panel(
+        &mut self,
+        plugin_id: &str,
+        panel_id: &str,
+        context: &PluginCommandContext,
+    ) -> Result<PluginPanelView, PluginError> {
+        self.call_panel_handler(plugin_id, panel_id, context, None, None)
+    }
+
+    /// Runs a panel action from a button or input and returns the panel's next view. Panels
+    /// without `onAction` are rendered again with `ctx.action` set.
+    pub fn handle_panel_action(
+        &mut self,
+        plugin_id: &str,
+        panel_id: &str,
+        context: &PluginCommandContext,
+        action_id: &str,
+        value: Option<Value>,
+    ) -> Result<PluginPanelView, PluginError> {
+        self.call_panel_handler(plugin_id, panel_id, context, Some(action_id), value)
+    }
+
+    fn call_panel_handler(
+        &mut self,
+        plugin_id: &str,
+        panel_id: &str,
+        context: &PluginCommandContext,
+        action_id: Option<&str>,
+        action_value: Option<Value>,
+    ) -> Result<PluginPanelView, PluginError> {
+        let handler = {
+            let registry = self.registry.borrow();
+            let handlers = registry
+                .panel_handlers
+                .get(&(plugin_id.to_string(), panel_id.to_string()))
+                .ok_or_else(|| PluginError::Runtime(Box::new("panel-not-found".into())))?;
+            match action_id {
+                Some(_) => handlers.on_action.clone().or(handlers.render.clone()),
+                None => handlers.render.clone(),
+            }
+        }
+        .ok_or_else(|| PluginError::Runtime(Box::new("panel-render-missing".into())))?;
+
+        let mut context_json = serde_json::to_value(context)?;
+        context_json["settings"] = self.merged_settings(plugin_id, &HashMap::new());
+        context_json["panel"] = serde_json::json!({ "id": panel_id });
+        if let Some(action_id) = action_id {
+            context_json["action"] = serde_json::json!({ "id": action_id, "value": action_value });
+        }
+
+        let mut error_context = match action_id {
+            Some(action_id) => PluginErrorContext::new("panel-action").with_action(action_id),
+            None => PluginErrorContext::new("panel-render"),
+        }
+        .with_plugin(plugin_id);
+        let value = self.run_limited(plugin_id, |_, sandbox| {
+            sandbox.context.with(|ctx| {
+                let ctx_obj = json_to_js(ctx.clone(), &context_json)?;
+                let handler_fn = handler.restore(&ctx)?;
+                let value = call_settled(&ctx, handler_fn, ctx_obj, error_context.clone())?;
+                js_to_json(ctx, sandbox.to_json_fn.clone(), value)
+            })
+        })?;
+        let view = match value {
+            Value::Null => Ok(PluginPanelView::default()),
+            Value::Array(children) => {
+                serde_json::from_value(Value::Array(children)).map(|children| PluginPanelView {
+                    children,
+                    ..Default::default()
+                })
+            }
+            value => serde_json::from_value::<PluginPanelView>(value),
+        };
+        let mut view = view.map_err(|err| {
+            error_context.phase = "panel-view".to_string();
+            PluginError::Runtime(Box::new(
+                PluginRuntimeError::new(format!("panel-view-invalid: {err}"))
+                    .with_context(error_context),
+            ))
+        })?;
+        view.plugin_id = plugin_id.to_string();
+        view.panel_id = panel_id.to_string();
+        Ok(view)
+    }
+
+    fn call_event_handlers(
+        &mut self,
+        plugin_id: Option<&str>,
+        event: &str,
+        payload: &Value,
+    ) -> Vec<(String, Result<Value, PluginError>)> {
+        let handlers = self
+            .registry
+            .borrow()
+            .event_handlers
+            .iter()
+            .filter(|(owner, name, _)| {
+                name == event
+                    && plugin_id.is_none_or(|plugin_id| plugin_id == owner)
+                    && !self.disabled.contains(owner)
+            })
+            .map(|(owner, _, handler)| (owner.clone(), handler.clone()))
+            .collect::<Vec<_>>();
+        let mut results = Vec::with_capacity(handlers.len());
+        for (owner, handler) in handlers {
+            let error_context = PluginErrorContext::new("event")
+                .with_plugin(&owner)
+                .with_action(event);
+            let value = self.run_limited(&owner, |_, sandbox| {
+                sandbox.context.with(|ctx| {
+                    let handler_fn = handler.restore(&ctx)?;
+                    let payload = json_to_js(ctx.clone(), payload)?;
+                    let value = call_settled(&ctx, handler_fn, payload, error_context)?;
+                    js_to_json(ctx, sandbox.to_json_fn.clone(), value)
+                })
+            });
+            results.push((owner, value));
+        }
+        results
+    }
+
+    /// Runs a call into `plugin_id`'s sandbox under the call budget. Hitting a limit turns the
+    /// error into a `plugin-timeout`, `plugin-out-of-memory` or `plugin-stack-overflow` and
+    /// counts a strike; at `max_strikes` the plugin is disabled. Limits are told apart by the
+    /// interrupt handler's deadline and the sandbox's heap, not by what the plugin threw.
+    fn run_limited<R>(
+        &mut self,
+        plugin_id: &str,
+        call: impl FnOnce(&Self, &PluginSandbox) -> Result<R, PluginError>,
+    ) -> Result<R, PluginError> {
+        if self.disabled.contains(plugin_id) {
+            return Err(PluginError::Runtime(Box::new("plugin-disabled".into())));
+        }
+        let Some(sandbox) = self.sandboxes.get(plugin_id) else {
+            return Err(PluginError::Runtime(Box::new("plugin-not-loaded".into())));
+        };
+        sandbox.heap.start_call();
+        self.budget.start(self.limits.call_timeout_ms);
+        let result = call(self, sandbox);
+        let timed_out = self.budget.finish();
+        let Err(PluginError::Runtime(mut error)) = result else {
+            return result;
+        };
+        let message = if timed_out {
+            format!(
+                "plugin-timeout: took longer than {}ms",
+                self.limits.call_timeout_ms
+            )
+        } else if sandbox.ran_out_of_memory(&error) {
+            // Free what the failed call left behind before anything else runs.
+            sandbox.runtime.run_gc();
+            "plugin-out-of-memory".to_string()
+        } else if overflowed_stack(&error) {
+            "plugin-stack-overflow".to_string()
+        } else {
+            return Err(PluginError::Runtime(error));
+        };
+        error.message = message;
+        let strikes = self.strikes.entry(plugin_id.to_string()).or_default();
+        *strikes += 1;
+        if *strikes >= self.limits.max_strikes {
+            self.strikes.remove(plugin_id);
+            self.disabled.insert(plugin_id.to_string());
+            self.newly_disabled.push(plugin_id.to_string());
+            error
+                .message
+                .push_str(" (disabled after repeated limit errors)");
+        }
+        if error.context.is_none() {
+            error.context = Some(PluginErrorContext::new("limits").with_plugin(plugin_id));
+        }
+        Err(PluginError::Runtime(error))
     }
 
     fn build_api<'js>(
         ctx: rquickjs::Ctx<'js>,
         registry: std::rc::Rc<std::cell::RefCell<PluginRuntimeRegistry>>,
+        data: std::rc::Rc<std::cell::RefCell<PluginDataHost>>,
         plugin_id: &str,
     ) -> Result<Object<'js>, PluginError> {
         let api = Object::new(ctx.clone())?;
@@ -1101,16 +1851,23 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
         let register_command = Function::new(ctx.clone(), {
             let registry = registry.clone();
             let plugin_id = plugin_id.clone();
-            move |def: Object, _handler: Option<Function>| -> rquickjs::Result<()> {
+            move |def: Object, handler: Opt<Function>| -> rquickjs::Result<()> {
                 let id: String = def.get("id")?;
                 let title: String = def.get("title")?;
                 let description: Option<String> = def.get("description").ok();
-                registry.borrow_mut().commands.push(PluginCommand {
+                let mut registry = registry.borrow_mut();
+                registry.commands.push(PluginCommand {
                     plugin_id: plugin_id.clone(),
-                    id,
+                    id: id.clone(),
                     title,
                     description,
                 });
+                if let Some(handler) = handler.0 {
+                    let ctx = handler.ctx().clone();
+                    registry
+                        .command_handlers
+                        .insert((plugin_id.clone(), id), Persistent::save(&ctx, handler));
+                }
                 Ok(())
             }
         });
@@ -1119,16 +1876,44 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
         let register_panel = Function::new(ctx.clone(), {
             let registry = registry.clone();
             let plugin_id = plugin_id.clone();
-            move |def: Object, _handler: Option<Function>| -> rquickjs::Result<()> {
+            move |def: Object<'js>, handlers: Opt<JsValue<'js>>| -> rquickjs::Result<()> {
                 let id: String = def.get("id")?;
                 let title: String = def.get("title")?;
                 let location: Option<String> = def.get("location").ok();
-                registry.borrow_mut().panels.push(PluginPanel {
+                // Either a render function or `{ render, onAction }`.
+                let (render_fn, on_action_fn) = match handlers.0 {
+                    Some(value) => match Function::from_value(value.clone()) {
+                        Ok(render) => (Some(render), None),
+                        Err(_) => match Object::from_value(value) {
+                            Ok(handlers) => (
+                                handlers.get::<_, Function>("render").ok(),
+                                handlers.get::<_, Function>("onAction").ok(),
+                            ),
+                            Err(_) => (None, None),
+                        },
+                    },
+                    None => (None, None),
+                };
+                let handlers = RendererHandlers {
+                    render: render_fn.map(|func| {
+                        let ctx = func.ctx().clone();
+                        Persistent::save(&ctx, func)
+                    }),
+                    on_action: on_action_fn.map(|func| {
+                        let ctx = func.ctx().clone();
+                        Persistent::save(&ctx, func)
+                    }),
+                };
+                let mut registry = registry.borrow_mut();
+                registry.panels.push(PluginPanel {
                     plugin_id: plugin_id.clone(),
-                    id,
+                    id: id.clone(),
                     title,
                     location,
                 });
+                registry
+                    .panel_handlers
+                    .insert((plugin_id.clone(), id), handlers);
                 Ok(())
             }
         });
@@ -1165,9 +1950,137 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
         });
         api.set("registerToolbarAction", register_toolbar_action)?;
 
+        let on = Function::new(ctx.clone(), {
+            let registry = registry.clone();
+            let plugin_id = plugin_id.clone();
+            move |ctx: rquickjs::Ctx<'js>, event: String, handler: Function<'js>| {
+                if !PLUGIN_EVENTS.contains(&event.as_str()) {
+                    return Err(Exception::throw_type(
+                        &ctx,
+                        &format!("unknown-event:{event}"),
+                    ));
+                }
+                registry.borrow_mut().event_handlers.push((
+                    plugin_id.clone(),
+                    event,
+                    Persistent::save(&ctx, handler),
+                ));
+                Ok(())
+            }
+        });
+        api.set("on", on)?;
+        api.set("data", Self::build_data_api(ctx.clone(), data, &plugin_id)?)?;
+
         Ok(api)
     }
 
+    /// `api.data`: vault reads and writes, checked against the permissions granted to the
+    /// plugin. Failures are thrown as JS errors carrying the host's error code.
+    fn build_data_api<'js>(
+        ctx: rquickjs::Ctx<'js>,
+        data: std::rc::Rc<std::cell::RefCell<PluginDataHost>>,
+        plugin_id: &str,
+    ) -> Result<Object<'js>, PluginError> {
+        let data_obj = Object::new(ctx.clone())?;
+        let plugin_id = plugin_id.to_string();
+
+        let get_page = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>, name: String| -> rquickjs::Result<JsValue<'js>> {
+                data_result_to_js(&ctx, data.borrow().get_page(&plugin_id, &name))
+            }
+        });
+        data_obj.set("getPage", get_page)?;
+
+        let list_pages = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>| -> rquickjs::Result<JsValue<'js>> {
+                data_result_to_js(&ctx, data.borrow().list_pages(&plugin_id))
+            }
+        });
+        data_obj.set("listPages", list_pages)?;
+
+        let search_blocks = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>,
+                  query: String,
+                  limit: Opt<i64>|
+                  -> rquickjs::Result<JsValue<'js>> {
+                data_result_to_js(
+                    &ctx,
+                    data.borrow().search_blocks(&plugin_id, &query, limit.0),
+                )
+            }
+        });
+        data_obj.set("searchBlocks", search_blocks)?;
+
+        let get_block = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>, uid: String| -> rquickjs::Result<JsValue<'js>> {
+                data_result_to_js(&ctx, data.borrow().get_block(&plugin_id, &uid))
+            }
+        });
+        data_obj.set("getBlock", get_block)?;
+
+        let set_block_text = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>, uid: String, text: String| -> rquickjs::Result<()> {
+                data.borrow_mut()
+                    .set_block_text(&plugin_id, &uid, &text)
+                    .map_err(|err| Exception::throw_message(&ctx, &err))
+            }
+        });
+        data_obj.set("setBlockText", set_block_text)?;
+
+        let set_block_property = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>,
+                  uid: String,
+                  key: String,
+                  value: Opt<Option<String>>|
+                  -> rquickjs::Result<()> {
+                data.borrow_mut()
+                    .set_block_property(&plugin_id, &uid, &key, value.0.flatten().as_deref())
+                    .map_err(|err| Exception::throw_message(&ctx, &err))
+            }
+        });
+        data_obj.set("setBlockProperty", set_block_property)?;
+
+        let create_page = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>,
+                  title: String,
+                  blocks: Opt<JsValue<'js>>|
+                  -> rquickjs::Result<JsValue<'js>> {
+                let blocks = new_blocks_from_js(&ctx, blocks.0)?;
+                let result = data.borrow_mut().create_page(&plugin_id, &title, blocks);
+                data_result_to_js(&ctx, result)
+            }
+        });
+        data_obj.set("createPage", create_page)?;
+
+        let insert_blocks = Function::new(ctx.clone(), {
+            let (data, plugin_id) = (data.clone(), plugin_id.clone());
+            move |ctx: rquickjs::Ctx<'js>,
+                  page: String,
+                  blocks: JsValue<'js>,
+                  after: Opt<Option<String>>|
+                  -> rquickjs::Result<JsValue<'js>> {
+                let blocks = new_blocks_from_js(&ctx, Some(blocks))?;
+                let result = data.borrow_mut().insert_blocks(
+                    &plugin_id,
+                    &page,
+                    blocks,
+                    after.0.flatten().as_deref(),
+                );
+                data_result_to_js(&ctx, result)
+            }
+        });
+        data_obj.set("insertBlocks", insert_blocks)?;
+
+        Ok(data_obj)
+    }
+
     fn call_block_handler(
         &mut self,
         plugin_id: &str,
@@ -1227,196 +2140,178 @@ globalThis.__sandpaperToJson = (value) => JSON.stringify(value);"#,
         let summary = fence.as_ref().and_then(|f| f.summary.clone());
         let cache_meta = read_cache_meta(&config);
 
-        let base_settings = self
-            .settings
-            .get(plugin_id)
-            .cloned()
-            .unwrap_or(Value::Object(serde_json::Map::new()));
-        let settings = merge_settings_with_overrides(
-            &base_settings,
-            self.settings_schema.get(plugin_id),
-            &config,
-        );
-
-        self.context.with(|ctx| {
-            let ctx_obj = Object::new(ctx.clone())?;
-            let block_obj = Object::new(ctx.clone())?;
-            block_obj.set("uid", block_uid)?;
-            block_obj.set("text", text)?;
-            ctx_obj.set("block", block_obj)?;
-            if let Some(summary) = summary.clone() {
-                ctx_obj.set("summary", summary)?;
-            }
-            ctx_obj.set("config", config_to_js(ctx.clone(), &config)?)?;
-            ctx_obj.set("settings", json_to_js(ctx.clone(), &settings)?)?;
-            ctx_obj.set("cache", cache_meta_to_js(ctx.clone(), &cache_meta)?)?;
-            if renderer_permissions.iter().any(|perm| perm == "network") {
-                let network_obj = Object::new(ctx.clone())?;
-                let fetch_ctx = ctx.clone();
-                let fetch_fn = Function::new(
-                    ctx.clone(),
-                    move |url: String, options: Opt<Object>| -> rquickjs::Result<Object> {
-                        let mut method = "GET".to_string();
-                        let mut body: Option<String> = None;
-                        if let Some(opts) = options.0 {
-                            if let Ok(value) = opts.get::<_, String>("method") {
-                                method = value;
-                            }
-                            if let Ok(value) = opts.get::<_, String>("body") {
-                                body = Some(value);
-                            }
-                        }
-                        let request = ureq::request(method.as_str(), url.as_str());
-                        let result = if let Some(body) = body {
-                            request.send_string(&body)
-                        } else {
-                            request.call()
-                        };
-                        let response = Object::new(fetch_ctx.clone())?;
-                        match result {
-                            Ok(resp) => {
-                                let status = resp.status();
-                                let text = resp.into_string().unwrap_or_default();
-                                response.set("ok", (200..300).contains(&status))?;
-                                response.set("status", status)?;
-                                response.set("text", text)?;
+        let settings = self.merged_settings(plugin_id, &config);
+
+        self.run_limited(plugin_id, |this, sandbox| {
+            sandbox.context.with(|ctx| {
+                let ctx_obj = Object::new(ctx.clone())?;
+                let block_obj = Object::new(ctx.clone())?;
+                block_obj.set("uid", block_uid)?;
+                block_obj.set("text", text)?;
+                ctx_obj.set("block", block_obj)?;
+                if let Some(summary) = summary.clone() {
+                    ctx_obj.set("summary", summary)?;
+                }
+                ctx_obj.set("config", config_to_js(ctx.clone(), &config)?)?;
+                ctx_obj.set("settings", json_to_js(ctx.clone(), &settings)?)?;
+                ctx_obj.set("cache", cache_meta_to_js(ctx.clone(), &cache_meta)?)?;
+                if renderer_permissions.iter().any(|perm| perm == "network") {
+                    let network_obj = Object::new(ctx.clone())?;
+                    let fetch_ctx = ctx.clone();
+                    let budget = this.budget.clone();
+                    let fetch_fn = Function::new(
+                        ctx.clone(),
+                        move |url: String, options: Opt<Object>| -> rquickjs::Result<Object> {
+                            let mut method = "GET".to_string();
+                            let mut body: Option<String> = None;
+                            if let Some(opts) = options.0 {
+                                if let Ok(value) = opts.get::<_, String>("method") {
+                                    method = value;
+                                }
+                                if let Ok(value) = opts.get::<_, String>("body") {
+                                    body = Some(value);
+                                }
                             }
-                            Err(err) => {
-                                response.set("ok", false)?;
-                                response.set("status", 0)?;
-                                response.set("text", err.to_string())?;
+                            // The interrupt handler cannot stop a blocking request, so it
+                            // gets whatever is left of the call's budget.
+                            let request = ureq::request(method.as_str(), url.as_str())
+                                .timeout(budget.remaining());
+                            let result = if let Some(body) = body {
+                                request.send_string(&body)
+                            } else {
+                                request.call()
+                            };
+                            let response = Object::new(fetch_ctx.clone())?;
+                            match result {
+                                Ok(resp) => {
+                                    let status = resp.status();
+                                    let text = resp.into_string().unwrap_or_default();
+                                    response.set("ok", (200..300).contains(&status))?;
+                                    response.set("status", status)?;
+                                    response.set("text", text)?;
+                                }
+                                Err(err) => {
+                                    response.set("ok", false)?;
+                                    response.set("status", 0)?;
+                                    response.set("text", err.to_string())?;
+                                }
                             }
-                        }
-                        Ok(response)
-                    },
-                );
-                network_obj.set("fetch", fetch_fn)?;
-                ctx_obj.set("network", network_obj)?;
-            }
-
-            if let Some(action_id) = action_id {
-                let action_obj = Object::new(ctx.clone())?;
-                action_obj.set("id", action_id)?;
-                if let Some(value) = action_value {
-                    action_obj.set("value", json_to_js(ctx.clone(), &value)?)?;
+                            Ok(response)
+                        },
+                    );
+                    network_obj.set("fetch", fetch_fn)?;
+                    ctx_obj.set("network", network_obj)?;
                 }
-                ctx_obj.set("action", action_obj)?;
-            }
 
-            let handler_fn = handler.restore(&ctx)?;
-            let mut error_context = if action_id.is_some() {
-                PluginErrorContext::new("block-action")
-            } else {
-                PluginErrorContext::new("block-render")
-            };
-            error_context = error_context
-                .with_plugin(plugin_id)
-                .with_renderer(renderer_id)
-                .with_block(block_uid);
-            if let Some(action_id) = action_id {
-                error_context = error_context.with_action(action_id);
-            }
-            let value: JsValue = handler_fn.call((ctx_obj,)).catch(&ctx).map_err(|err| {
-                PluginError::Runtime(Box::new(runtime_error_from_caught(err, error_context)))
-            })?;
-            let mut view = self.parse_block_view(ctx, value, plugin_id, renderer_id, block_uid)?;
-            let has_clipboard_control = view.controls.iter().any(|control| {
-                control
-                    .get("type")
-                    .and_then(Value::as_str)
-                    .map(|kind| kind == "clipboard")
-                    .unwrap_or(false)
-            });
-            if has_clipboard_control && !renderer_permissions.iter().any(|perm| perm == "clipboard")
-            {
-                return Ok(permission_blocked_view(
-                    plugin_id,
-                    renderer_id,
-                    block_uid,
-                    &["clipboard".to_string()],
-                ));
-            }
-            let mut config_updated = false;
-            let mut cache_ttl = cache_meta.ttl_seconds;
-            if let Some(cache) = view.cache.as_ref() {
-                if let Some(ttl) = cache.ttl_seconds {
-                    cache_ttl = Some(ttl);
-                    let ttl_string = ttl.to_string();
-                    if config.get(CACHE_TTL_KEY).map(|value| value.as_str())
-                        != Some(ttl_string.as_str())
-                    {
-                        config.insert(CACHE_TTL_KEY.to_string(), ttl_string);
-                        config_updated = true;
+                if let Some(action_id) = action_id {
+                    let action_obj = Object::new(ctx.clone())?;
+                    action_obj.set("id", action_id)?;
+                    if let Some(value) = action_value {
+                        action_obj.set("value", json_to_js(ctx.clone(), &value)?)?;
                     }
+                    ctx_obj.set("action", action_obj)?;
                 }
-                if let Some(timestamp) = cache.timestamp.clone() {
-                    if config.get(CACHE_TS_KEY).map(|value| value.as_str())
-                        != Some(timestamp.as_str())
-                    {
-                        config.insert(CACHE_TS_KEY.to_string(), timestamp);
-                        config_updated = true;
-                    }
-                }
-            }
-            if cache_ttl.is_some()
-                && view.status.as_deref() != Some("error")
-                && view.cache.is_some()
-                && view
-                    .cache
-                    .as_ref()
-                    .and_then(|cache| cache.timestamp.as_ref())
-                    .is_none()
-            {
-                let now = Utc::now().to_rfc3339();
-                if config.get(CACHE_TS_KEY).map(|value| value.as_str()) != Some(now.as_str()) {
-                    config.insert(CACHE_TS_KEY.to_string(), now);
-                    config_updated = true;
-                }
-            }
-            if view.next_text.is_none() {
-                let next_summary = if view.status.as_deref() == Some("error") {
-                    summary.clone()
+
+                let handler_fn = handler.restore(&ctx)?;
+                let mut error_context = if action_id.is_some() {
+                    PluginErrorContext::new("block-action")
                 } else {
-                    view.summary.clone().or(summary.clone())
+                    PluginErrorContext::new("block-render")
                 };
-                if let Some(next_summary) = next_summary {
-                    if let Some(lang) = fence
+                error_context = error_context
+                    .with_plugin(plugin_id)
+                    .with_renderer(renderer_id)
+                    .with_block(block_uid);
+                if let Some(action_id) = action_id {
+                    error_context = error_context.with_action(action_id);
+                }
+                let value: JsValue = handler_fn.call((ctx_obj,)).catch(&ctx).map_err(|err| {
+                    PluginError::Runtime(Box::new(runtime_error_from_caught(err, error_context)))
+                })?;
+                let mut view =
+                    sandbox.parse_block_view(ctx, value, plugin_id, renderer_id, block_uid)?;
+                let has_clipboard_control = view.controls.iter().any(|control| {
+                    control
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .map(|kind| kind == "clipboard")
+                        .unwrap_or(false)
+                });
+                if has_clipboard_control
+                    && !renderer_permissions.iter().any(|perm| perm == "clipboard")
+                {
+                    return Ok(permission_blocked_view(
+                        plugin_id,
+                        renderer_id,
+                        block_uid,
+                        &["clipboard".to_string()],
+                    ));
+                }
+                let mut config_updated = false;
+                let mut cache_ttl = cache_meta.ttl_seconds;
+                if let Some(cache) = view.cache.as_ref() {
+                    if let Some(ttl) = cache.ttl_seconds {
+                        cache_ttl = Some(ttl);
+                        let ttl_string = ttl.to_string();
+                        if config.get(CACHE_TTL_KEY).map(|value| value.as_str())
+                            != Some(ttl_string.as_str())
+                        {
+                            config.insert(CACHE_TTL_KEY.to_string(), ttl_string);
+                            config_updated = true;
+                        }
+                    }
+                    if let Some(timestamp) = cache.timestamp.clone() {
+                        if config.get(CACHE_TS_KEY).map(|value| value.as_str())
+                            != Some(timestamp.as_str())
+                        {
+                            config.insert(CACHE_TS_KEY.to_string(), timestamp);
+                            config_updated = true;
+                        }
+                    }
+                }
+                if cache_ttl.is_some()
+                    && view.status.as_deref() != Some("error")
+                    && view.cache.is_some()
+                    && view
+                        .cache
                         .as_ref()
-                        .map(|item| item.lang.clone())
-                        .or_else(|| renderer_id.split('.').next().map(str::to_string))
-                    {
-                        let next_config_text = if config_updated {
-                            serialize_plugin_config(&config)
-                        } else {
-                            config_text.clone()
-                        };
-                        view.next_text = Some(format_plugin_fence(
-                            &lang,
-                            next_config_text.as_str(),
-                            &next_summary,
-                        ));
+                        .and_then(|cache| cache.timestamp.as_ref())
+                        .is_none()
+                {
+                    let now = Utc::now().to_rfc3339();
+                    if config.get(CACHE_TS_KEY).map(|value| value.as_str()) != Some(now.as_str()) {
+                        config.insert(CACHE_TS_KEY.to_string(), now);
+                        config_updated = true;
                     }
                 }
-            }
-            Ok(view)
-        })
-    }
-
-    fn parse_block_view<'js>(
-        &self,
-        ctx: rquickjs::Ctx<'js>,
-        value: JsValue<'js>,
-        plugin_id: &str,
-        renderer_id: &str,
-        block_uid: &str,
-    ) -> Result<PluginBlockView, PluginError> {
-        let json_value = js_to_json(ctx, self.to_json_fn.clone(), value)?;
-        let mut view: PluginBlockView =
-            serde_json::from_value(json_value).map_err(PluginError::Serde)?;
-        view.plugin_id = plugin_id.to_string();
-        view.renderer_id = renderer_id.to_string();
-        view.block_uid = block_uid.to_string();
-        Ok(view)
+                if view.next_text.is_none() {
+                    let next_summary = if view.status.as_deref() == Some("error") {
+                        summary.clone()
+                    } else {
+                        view.summary.clone().or(summary.clone())
+                    };
+                    if let Some(next_summary) = next_summary {
+                        if let Some(lang) = fence
+                            .as_ref()
+                            .map(|item| item.lang.clone())
+                            .or_else(|| renderer_id.split('.').next().map(str::to_string))
+                        {
+                            let next_config_text = if config_updated {
+                                serialize_plugin_config(&config)
+                            } else {
+                                config_text.clone()
+                            };
+                            view.next_text = Some(format_plugin_fence(
+                                &lang,
+                                next_config_text.as_str(),
+                                &next_summary,
+                            ));
+                        }
+                    }
+                }
+                Ok(view)
+            })
+        })
     }
 }
 
@@ -1658,6 +2553,47 @@ fn json_to_js<'js>(ctx: rquickjs::Ctx<'js>, value: &Value) -> Result<JsValue<'js
     }
 }
 
+/// Calls `handler` with `arg`, settling a returned promise before handing back its value.
+fn call_settled<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    handler: Function<'js>,
+    arg: JsValue<'js>,
+    error_context: PluginErrorContext,
+) -> Result<JsValue<'js>, PluginError> {
+    handler
+        .call((arg,))
+        .catch(ctx)
+        .and_then(|value: JsValue<'js>| match value.as_promise() {
+            Some(promise) => promise.finish::<JsValue>().catch(ctx),
+            None => Ok(value),
+        })
+        .map_err(|err| {
+            PluginError::Runtime(Box::new(runtime_error_from_caught(err, error_context)))
+        })
+}
+
+fn data_result_to_js<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    result: Result<Value, String>,
+) -> rquickjs::Result<JsValue<'js>> {
+    let value = result.map_err(|err| Exception::throw_message(ctx, &err))?;
+    json_to_js(ctx.clone(), &value)
+        .map_err(|err| Exception::throw_message(ctx, &format!("{err:?}")))
+}
+
+fn new_blocks_from_js<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    value: Option<JsValue<'js>>,
+) -> rquickjs::Result<Vec<NewBlock>> {
+    let Some(value) = value.filter(|value| !value.is_undefined() && !value.is_null()) else {
+        return Ok(Vec::new());
+    };
+    ctx.json_stringify(value)?
+        .and_then(|json| json.to_string().ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or_else(|| Exception::throw_type(ctx, "invalid-blocks"))
+}
+
 fn js_to_json<'js>(
     ctx: rquickjs::Ctx<'js>,
     to_json_fn: Persistent<Function<'static>>,
@@ -1706,9 +2642,12 @@ fn runtime_error_from_caught(
 ) -> PluginRuntimeError {
     match err {
         CaughtError::Exception(ex) => {
-            PluginRuntimeError::new(ex.message().unwrap_or_else(|| "js-exception".to_string()))
-                .with_stack(ex.stack())
-                .with_context(context)
+            let mut error =
+                PluginRuntimeError::new(ex.message().unwrap_or_else(|| "js-exception".to_string()))
+                    .with_stack(ex.stack())
+                    .with_context(context);
+            error.error_name = ex.get::<_, String>("name").ok();
+            error
         }
         CaughtError::Value(value) => {
             PluginRuntimeError::new(format!("js-exception: {value:?}")).with_context(context)
@@ -1719,6 +2658,19 @@ fn runtime_error_from_caught(
     }
 }
 
+/// Describes why `plugin_id` failed to load.
+fn load_error(plugin_id: &str, err: PluginError) -> PluginRuntimeError {
+    let mut error = match err {
+        PluginError::Runtime(error) => *error,
+        PluginError::Io(err) => PluginRuntimeError::new(format!("io: {err}")),
+        PluginError::Serde(err) => PluginRuntimeError::new(format!("serde: {err}")),
+    };
+    if error.context.is_none() {
+        error.context = Some(PluginErrorContext::new("load").with_plugin(plugin_id));
+    }
+    error
+}
+
 pub fn load_plugins_into_runtime(
     root: &Path,
     registry: &PluginRegistry,
@@ -1732,9 +2684,15 @@ pub fn load_plugins_into_runtime(
 mod tests {
     use super::{
         check_manifest_compatibility, discover_plugins, install_plugin, list_plugins,
-        parse_plugin_manifest, remove_plugin, update_plugin, PluginRegistry, PluginRuntime,
-        PluginState,
+        parse_plugin_manifest, remove_plugin, update_plugin, PluginCommandBlock,
+        PluginCommandContext, PluginCommandEdit, PluginCommandPage, PluginCommandResult,
+        PluginError, PluginLimits, PluginPanelNode, PluginRegistry, PluginRuntime, PluginState,
     };
+    use crate::blocks::BlockType;
+    use crate::db::{BlockSnapshot, Database, PageRecord};
+    use crate::editor::EditorModel;
+    use crate::plugin_data::NewBlock;
+    use crate::plugin_events::{block_events, PluginEvent};
     use std::collections::HashMap;
     use std::fs;
     use std::path::PathBuf;
@@ -2246,6 +3204,687 @@ mod tests {
         assert_eq!(view.summary.as_deref(), Some("has-network"));
     }
 
+    fn write_data_plugin(root: &std::path::Path) -> PathBuf {
+        let plugins_dir = root.join("plugins");
+        let plugin_dir = plugins_dir.join("notes");
+        fs::create_dir_all(&plugin_dir).expect("plugin dir");
+        fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{
+  "id": "notes",
+  "name": "Notes",
+  "version": "0.1.0",
+  "main": "index.js",
+  "permissions": ["data.read", "data.write"]
+}"#,
+        )
+        .expect("write manifest");
+        fs::write(
+            plugin_dir.join("index.js"),
+            r#"module.exports = (api) => {
+  api.registerRenderer(
+    { id: "notes.block", title: "Notes", kind: "block", languages: ["notes"] },
+    {
+      render: () => {
+        try {
+          const page = api.data.getPage("Inbox");
+          api.data.setBlockText(page.blocks[0].uid, "Updated");
+          api.data.insertBlocks("inbox", ["Added", { text: "Task", type: "todo" }]);
+          return { summary: api.data.getPage("inbox").blocks.map((b) => b.text).join("|") };
+        } catch (err) {
+          return { summary: err.message };
+        }
+      }
+    }
+  );
+};"#,
+        )
+        .expect("write entry");
+        plugins_dir
+    }
+
+    #[test]
+    fn runtime_data_api_checks_granted_permissions() {
+        let dir = tempdir().expect("tempdir");
+        let plugins_dir = write_data_plugin(dir.path());
+        let db_path = dir.path().join("sandpaper.db");
+        let db = Database::open(&db_path).expect("db");
+        db.run_migrations().expect("migrations");
+        let page_id = db.insert_page("inbox", "Inbox").expect("page");
+        db.insert_block(page_id, "b1", None, "a0", "Original", "{}")
+            .expect("block");
+
+        let registry = PluginRegistry::new(plugins_dir.join("state.json"));
+        let mut runtime = PluginRuntime::new().expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        runtime.set_database(Some(Database::open(&db_path).expect("runtime db")));
+
+        let view = runtime
+            .render_block("notes", "notes.block", "b0", "```notes")
+            .expect("render");
+        assert_eq!(
+            view.summary.as_deref(),
+            Some("missing-permission:data.read")
+        );
+
+        db.grant_plugin_permission("notes", "data.read")
+            .expect("grant read");
+        db.grant_plugin_permission("notes", "data.write")
+            .expect("grant write");
+        let view = runtime
+            .render_block("notes", "notes.block", "b0", "```notes")
+            .expect("render");
+        assert_eq!(view.summary.as_deref(), Some("Updated|Added|Task"));
+        assert_eq!(
+            runtime.take_data_changes(),
+            vec!["inbox".to_string(), "inbox".to_string()]
+        );
+        let record = db.get_block_by_uid("b1").expect("block").expect("exists");
+        assert_eq!(record.text, "Updated");
+    }
+
+    #[test]
+    fn runtime_renders_panels_and_routes_actions() {
+        let dir = tempdir().expect("tempdir");
+        let plugin_dir = dir.path().join("plugins").join("tasks");
+        fs::create_dir_all(&plugin_dir).expect("plugin dir");
+        fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{ "id": "tasks", "name": "Tasks", "version": "0.1.0" }"#,
+        )
+        .expect("write manifest");
+        fs::write(
+            plugin_dir.join("index.js"),
+            r#"module.exports = (api) => {
+  const items = ["Water plants"];
+  const render = (ctx) => ({
+    title: "Tasks for " + ctx.page.title,
+    children: [
+      { type: "text", text: items.length + " open", style: "muted" },
+      { type: "list", items: items.map((text) => ({ type: "link", label: text, page: text })) },
+      { type: "input", id: "add", placeholder: "New task" },
+      { type: "button", id: "clear", label: "Clear" }
+    ]
+  });
+  api.registerPanel({ id: "tasks.panel", title: "Tasks" }, {
+    render,
+    onAction: async (ctx) => {
+      if (ctx.action.id === "add") items.push(ctx.action.value);
+      if (ctx.action.id === "clear") items.length = 0;
+      return render(ctx);
+    }
+  });
+  api.registerPanel({ id: "tasks.plain", title: "Plain" }, (ctx) =>
+    ctx.action ? [{ type: "text", text: "did " + ctx.action.id }] : [{ type: "text", text: ctx.panel.id }]
+  );
+  api.registerPanel({ id: "tasks.broken", title: "Broken" }, () => [{ type: "marquee" }]);
+  api.registerPanel({ id: "tasks.bare", title: "Bare" });
+};"#,
+        )
+        .expect("write entry");
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::new().expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        let loaded = runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        assert_eq!(loaded.panels.len(), 4);
+
+        let context = PluginCommandContext {
+            page: Some(PluginCommandPage {
+                uid: "inbox".to_string(),
+                title: "Inbox".to_string(),
+            }),
+            ..Default::default()
+        };
+        let view = runtime
+            .render_panel("tasks", "tasks.panel", &context)
+            .expect("render");
+        assert_eq!(view.plugin_id, "tasks");
+        assert_eq!(view.panel_id, "tasks.panel");
+        assert_eq!(view.title.as_deref(), Some("Tasks for Inbox"));
+        assert_eq!(view.children.len(), 4);
+        assert_eq!(
+            view.children[1],
+            PluginPanelNode::List {
+                items: vec![PluginPanelNode::Link {
+                    label: "Water plants".to_string(),
+                    page: Some("Water plants".to_string()),
+                    block: None,
+                }],
+            }
+        );
+
+        let view = runtime
+            .handle_panel_action(
+                "tasks",
+                "tasks.panel",
+                &context,
+                "add",
+                Some(serde_json::Value::String("Call Ada".to_string())),
+            )
+            .expect("action");
+        assert_eq!(
+            view.children[0],
+            PluginPanelNode::Text {
+                text: "2 open".to_string(),
+                style: Some("muted".to_string()),
+            }
+        );
+
+        let plain = runtime
+            .render_panel("tasks", "tasks.plain", &context)
+            .expect("plain render");
+        assert_eq!(
+            plain.children,
+            vec![PluginPanelNode::Text {
+                text: "tasks.plain".to_string(),
+                style: None,
+            }]
+        );
+        let plain = runtime
+            .handle_panel_action("tasks", "tasks.plain", &context, "go", None)
+            .expect("plain action");
+        assert_eq!(
+            plain.children,
+            vec![PluginPanelNode::Text {
+                text: "did go".to_string(),
+                style: None,
+            }]
+        );
+
+        for (panel_id, expected) in [
+            ("tasks.broken", "panel-view-invalid"),
+            ("tasks.bare", "panel-render-missing"),
+            ("tasks.missing", "panel-not-found"),
+        ] {
+            match runtime.render_panel("tasks", panel_id, &context) {
+                Err(PluginError::Runtime(err)) => {
+                    assert!(err.message.starts_with(expected), "{}", err.message)
+                }
+                other => panic!("unexpected result for {panel_id}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn runtime_runs_command_handlers_with_context() {
+        let dir = tempdir().expect("tempdir");
+        let plugin_dir = dir.path().join("plugins").join("cmd");
+        fs::create_dir_all(&plugin_dir).expect("plugin dir");
+        fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{ "id": "cmd", "name": "Cmd", "version": "0.1.0", "main": "index.js" }"#,
+        )
+        .expect("write manifest");
+        fs::write(
+            plugin_dir.join("index.js"),
+            r#"module.exports = (api) => {
+  api.registerCommand({ id: "cmd.shout", title: "Shout" }, (ctx) => ({
+    edits: [
+      { type: "setText", uid: ctx.block.uid, text: ctx.block.text.toUpperCase() },
+      { type: "insertBlocks", blocks: [ctx.page.title + " " + ctx.selection.length] }
+    ],
+    openPanel: "cmd.panel",
+    notification: { message: "Done", kind: "success" }
+  }));
+  api.registerCommand({ id: "cmd.later", title: "Later" }, async () => {
+    await null;
+    return { notification: { message: "Later" } };
+  });
+  api.registerCommand({ id: "cmd.quiet", title: "Quiet" }, () => {});
+  api.registerCommand({ id: "cmd.bare", title: "Bare" });
+};"#,
+        )
+        .expect("write entry");
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::new().expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+
+        let context = PluginCommandContext {
+            page: Some(PluginCommandPage {
+                uid: "inbox".to_string(),
+                title: "Inbox".to_string(),
+            }),
+            block: Some(PluginCommandBlock {
+                uid: "b1".to_string(),
+                text: "hello".to_string(),
+                cursor: Some(5),
+            }),
+            selection: Vec::new(),
+        };
+        let result = runtime
+            .run_command("cmd", "cmd.shout", &context)
+            .expect("run");
+        assert_eq!(
+            result.edits,
+            vec![
+                PluginCommandEdit::SetText {
+                    uid: "b1".to_string(),
+                    text: "HELLO".to_string(),
+                },
+                PluginCommandEdit::InsertBlocks {
+                    after: None,
+                    blocks: vec![NewBlock::Text("Inbox 0".to_string())],
+                },
+            ]
+        );
+        assert_eq!(result.open_panel.as_deref(), Some("cmd.panel"));
+        assert_eq!(
+            result.notification.map(|notification| notification.message),
+            Some("Done".to_string())
+        );
+
+        let later = runtime
+            .run_command("cmd", "cmd.later", &context)
+            .expect("async run");
+        assert_eq!(
+            later.notification.map(|notification| notification.message),
+            Some("Later".to_string())
+        );
+        assert_eq!(
+            runtime
+                .run_command("cmd", "cmd.quiet", &context)
+                .expect("quiet"),
+            PluginCommandResult::default()
+        );
+        assert!(runtime.run_command("cmd", "cmd.bare", &context).is_err());
+        assert!(runtime.run_command("cmd", "cmd.missing", &context).is_err());
+    }
+
+    #[test]
+    fn command_edits_apply_through_the_editor() {
+        let block = |uid: &str, text: &str, indent: i64| BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent,
+            block_type: BlockType::Text,
+        };
+        let mut editor = EditorModel::new(vec![
+            block("a", "Parent", 0),
+            block("b", "Child", 1),
+            block("c", "Next", 0),
+        ]);
+        let edits = vec![
+            PluginCommandEdit::SetText {
+                uid: "c".to_string(),
+                text: "Renamed".to_string(),
+            },
+            PluginCommandEdit::InsertBlocks {
+                after: None,
+                blocks: vec![NewBlock::Text("Sibling".to_string())],
+            },
+            PluginCommandEdit::DeleteBlock {
+                uid: "b".to_string(),
+            },
+            PluginCommandEdit::DeleteBlock {
+                uid: "missing".to_string(),
+            },
+        ];
+        let applied = edits
+            .into_iter()
+            .map(|edit| edit.apply(&mut editor))
+            .collect::<Vec<_>>();
+        assert_eq!(applied, vec![true, true, true, false]);
+        let texts = editor
+            .blocks()
+            .iter()
+            .map(|block| block.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["Parent", "Sibling", "Renamed"]);
+        assert_eq!(editor.take_ops().len(), 3);
+    }
+
+    #[test]
+    fn runtime_dispatches_events_to_subscribers() {
+        let dir = tempdir().expect("tempdir");
+        for (id, entry) in [
+            (
+                "tidy",
+                r#"module.exports = (api) => {
+  api.on("block.changed", ({ block }) =>
+    block.text.endsWith(" ") ? { edits: [{ type: "setText", uid: block.uid, text: block.text.trim() }] } : null
+  );
+  api.on("page.opened", ({ page }) => page.title);
+};"#,
+            ),
+            (
+                "broken",
+                r#"module.exports = (api) => {
+  api.on("block.changed", () => { throw new Error("nope"); });
+};"#,
+            ),
+        ] {
+            let plugin_dir = dir.path().join("plugins").join(id);
+            fs::create_dir_all(&plugin_dir).expect("plugin dir");
+            fs::write(
+                plugin_dir.join("plugin.json"),
+                format!(r#"{{ "id": "{id}", "name": "{id}", "version": "0.1.0" }}"#),
+            )
+            .expect("write manifest");
+            fs::write(plugin_dir.join("index.js"), entry).expect("write entry");
+        }
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::new().expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        assert!(runtime.has_event_handlers("block.changed"));
+        assert!(!runtime.has_event_handlers("page.saved"));
+
+        let db_path = dir.path().join("sandpaper.db");
+        let db = Database::open(&db_path).expect("db");
+        db.run_migrations().expect("migrations");
+        let page_id = db.insert_page("inbox", "Inbox").expect("page");
+        db.insert_block(page_id, "b1", None, "a0", "Draft ", "{}")
+            .expect("block");
+        runtime.set_database(Some(Database::open(&db_path).expect("runtime db")));
+        let page = PageRecord {
+            id: page_id,
+            uid: "inbox".to_string(),
+            title: "Inbox".to_string(),
+        };
+        let before = [BlockSnapshot {
+            uid: "b1".to_string(),
+            text: "Draft".to_string(),
+            indent: 0,
+            block_type: BlockType::Text,
+        }];
+        let after = [BlockSnapshot {
+            text: "Draft ".to_string(),
+            ..before[0].clone()
+        }];
+        let events = block_events(&page, &before, &after);
+        let outcome = runtime.dispatch_event(&events[0]);
+        assert_eq!(outcome.errors.len(), 1);
+        assert_eq!(outcome.results.len(), 1);
+        assert_eq!(outcome.results[0].0, "tidy");
+        assert_eq!(
+            outcome.results[0].1.edits,
+            vec![PluginCommandEdit::SetText {
+                uid: "b1".to_string(),
+                text: "Draft".to_string(),
+            }]
+        );
+        let edits = outcome.results[0].1.edits.clone();
+        let err = runtime
+            .apply_event_edits("tidy", &events[0], edits.clone())
+            .expect_err("write needs data.write");
+        match err {
+            super::PluginError::Runtime(err) => {
+                assert_eq!(err.message, "missing-permission:data.write");
+            }
+            _ => panic!("unexpected error"),
+        }
+        db.grant_plugin_permission("tidy", "data.write")
+            .expect("grant write");
+        runtime
+            .apply_event_edits("tidy", &events[0], edits)
+            .expect("apply edits");
+        assert_eq!(runtime.take_data_changes(), vec!["inbox".to_string()]);
+        assert_eq!(
+            db.get_block_by_uid("b1")
+                .expect("load")
+                .expect("block")
+                .text,
+            "Draft"
+        );
+
+        let emitted = runtime
+            .emit_event(
+                "tidy",
+                "page.opened",
+                PluginEvent::page_opened(&page).payload,
+            )
+            .expect("emit");
+        assert_eq!(emitted, serde_json::json!(["Inbox"]));
+    }
+
+    #[test]
+    fn runtime_rejects_unknown_events() {
+        let dir = tempdir().expect("tempdir");
+        let plugin_dir = dir.path().join("plugins").join("typo");
+        fs::create_dir_all(&plugin_dir).expect("plugin dir");
+        fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{ "id": "typo", "name": "Typo", "version": "0.1.0" }"#,
+        )
+        .expect("write manifest");
+        fs::write(
+            plugin_dir.join("index.js"),
+            r#"module.exports = (api) => { api.on("page.open", () => {}); };"#,
+        )
+        .expect("write entry");
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::new().expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        let result = runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        assert!(result.loaded.is_empty());
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].message, "unknown-event:page.open");
+    }
+
+    #[test]
+    fn runtime_enforces_limits_and_disables_repeat_offenders() {
+        let dir = tempdir().expect("tempdir");
+        let plugin_dir = dir.path().join("plugins").join("hog");
+        fs::create_dir_all(&plugin_dir).expect("plugin dir");
+        fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{ "id": "hog", "name": "Hog", "version": "0.1.0" }"#,
+        )
+        .expect("write manifest");
+        fs::write(
+            plugin_dir.join("index.js"),
+            r#"module.exports = (api) => {
+  api.registerCommand({ id: "hog.spin", title: "Spin" }, () => { for (;;) {} });
+  api.registerCommand({ id: "hog.later", title: "Later" }, async () => {
+    await null;
+    for (;;) {}
+  });
+  api.registerCommand({ id: "hog.recurse", title: "Recurse" }, () => {
+    const dive = (depth) => dive(depth + 1) + 1;
+    return dive(0);
+  });
+  api.registerCommand({ id: "hog.hoard", title: "Hoard" }, () => {
+    const hoard = [];
+    for (;;) hoard.push(new Array(100000).fill(hoard.length));
+  });
+  api.registerCommand({ id: "hog.bluff", title: "Bluff" }, () => {
+    throw new InternalError("out of memory");
+  });
+  api.registerCommand({ id: "hog.ok", title: "Ok" }, () => ({ notification: { message: "ok" } }));
+};"#,
+        )
+        .expect("write entry");
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::with_limits(PluginLimits {
+            call_timeout_ms: 100,
+            memory_per_plugin: 16 * 1024 * 1024,
+            max_stack_size: 256 * 1024,
+            max_strikes: 4,
+        })
+        .expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        let context = PluginCommandContext::default();
+        let run = |runtime: &mut PluginRuntime, command: &str| match runtime
+            .run_command("hog", command, &context)
+        {
+            Err(PluginError::Runtime(err)) => {
+                let phase = err.context.map(|context| context.phase);
+                (err.message, phase)
+            }
+            other => panic!("{command} should fail, got {other:?}"),
+        };
+
+        let (message, phase) = run(&mut runtime, "hog.spin");
+        assert_eq!(message, "plugin-timeout: took longer than 100ms");
+        assert_eq!(phase.as_deref(), Some("command"));
+        assert!(run(&mut runtime, "hog.later")
+            .0
+            .starts_with("plugin-timeout"));
+        assert_eq!(run(&mut runtime, "hog.recurse").0, "plugin-stack-overflow");
+        // Throwing the engine's error without filling the heap is not a limit.
+        assert_eq!(run(&mut runtime, "hog.bluff").0, "out of memory");
+        assert!(runtime.take_disabled_plugins().is_empty());
+
+        let (message, _) = run(&mut runtime, "hog.hoard");
+        assert_eq!(
+            message,
+            "plugin-out-of-memory (disabled after repeated limit errors)"
+        );
+        assert_eq!(run(&mut runtime, "hog.ok").0, "plugin-disabled");
+        assert_eq!(runtime.take_disabled_plugins(), vec!["hog".to_string()]);
+        assert!(runtime.take_disabled_plugins().is_empty());
+
+        // Reloading keeps it out; turning it back on gives it a fresh start.
+        let reloaded = runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("reload");
+        assert!(reloaded.loaded.is_empty());
+        runtime.enable_plugin("hog");
+        runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("reload");
+        let result = runtime
+            .run_command("hog", "hog.ok", &context)
+            .expect("ok after reload");
+        assert!(result.notification.is_some());
+    }
+
+    fn write_test_plugins(root: &std::path::Path, plugins: &[(&str, &str)]) {
+        for (id, source) in plugins {
+            let plugin_dir = root.join("plugins").join(id);
+            fs::create_dir_all(&plugin_dir).expect("plugin dir");
+            fs::write(
+                plugin_dir.join("plugin.json"),
+                format!(r#"{{ "id": "{id}", "name": "{id}", "version": "0.1.0" }}"#),
+            )
+            .expect("write manifest");
+            fs::write(plugin_dir.join("index.js"), source).expect("write entry");
+        }
+    }
+
+    #[test]
+    fn runtime_charges_memory_to_the_plugin_that_keeps_it() {
+        let dir = tempdir().expect("tempdir");
+        write_test_plugins(
+            dir.path(),
+            &[
+                (
+                    "leaker",
+                    r#"module.exports = (api) => {
+  const kept = [];
+  api.registerCommand({ id: "leaker.stash", title: "Stash" }, () => {
+    kept.push(new Array(100000).fill(kept.length));
+  });
+};"#,
+                ),
+                (
+                    "steady",
+                    r#"module.exports = (api) => {
+  api.registerCommand({ id: "steady.sum", title: "Sum" }, () => {
+    const scratch = new Array(100000).fill(1);
+    return { notification: { message: String(scratch.length) } };
+  });
+};"#,
+                ),
+            ],
+        );
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::with_limits(PluginLimits {
+            memory_per_plugin: 4 * 1024 * 1024,
+            max_strikes: 2,
+            ..PluginLimits::default()
+        })
+        .expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        let context = PluginCommandContext::default();
+
+        let mut failures = Vec::new();
+        for _ in 0..40 {
+            if let Err(PluginError::Runtime(err)) =
+                runtime.run_command("leaker", "leaker.stash", &context)
+            {
+                failures.push(err.message);
+            }
+            runtime
+                .run_command("steady", "steady.sum", &context)
+                .expect("steady keeps working");
+        }
+        assert_eq!(failures[0], "plugin-out-of-memory");
+        assert!(failures
+            .contains(&"plugin-out-of-memory (disabled after repeated limit errors)".to_string()));
+        assert_eq!(failures.last().map(String::as_str), Some("plugin-disabled"));
+        assert_eq!(runtime.take_disabled_plugins(), vec!["leaker".to_string()]);
+    }
+
+    #[test]
+    fn runtime_loads_plugins_independently_and_disables_runaway_registers() {
+        let dir = tempdir().expect("tempdir");
+        write_test_plugins(
+            dir.path(),
+            &[
+                ("looper", r#"module.exports = () => { for (;;) {} };"#),
+                (
+                    "fine",
+                    r#"module.exports = (api) => {
+  api.registerCommand({ id: "fine.ok", title: "Ok" }, () => ({ notification: { message: "ok" } }));
+};"#,
+                ),
+            ],
+        );
+        let registry = PluginRegistry::new(dir.path().join("plugins/state.json"));
+        let mut runtime = PluginRuntime::with_limits(PluginLimits {
+            call_timeout_ms: 100,
+            max_strikes: 2,
+            ..PluginLimits::default()
+        })
+        .expect("runtime");
+        let plugins = discover_plugins(dir.path(), &registry).expect("discover");
+        let context = PluginCommandContext::default();
+
+        for _ in 0..2 {
+            let result = runtime
+                .load_plugins(&plugins, HashMap::new())
+                .expect("load");
+            assert_eq!(result.loaded, vec!["fine".to_string()]);
+            assert_eq!(result.errors.len(), 1);
+            assert!(result.errors[0].message.starts_with("plugin-timeout"));
+            let error_context = result.errors[0].context.as_ref().expect("error context");
+            assert_eq!(error_context.plugin_id.as_deref(), Some("looper"));
+            runtime
+                .run_command("fine", "fine.ok", &context)
+                .expect("fine loads");
+        }
+        assert_eq!(runtime.take_disabled_plugins(), vec!["looper".to_string()]);
+
+        // Later reloads skip it instead of spending its timeout again.
+        let result = runtime
+            .load_plugins(&plugins, HashMap::new())
+            .expect("load");
+        assert_eq!(result.loaded, vec!["fine".to_string()]);
+        assert!(result.errors.is_empty());
+        runtime
+            .run_command("fine", "fine.ok", &context)
+            .expect("fine still works");
+    }
+
     #[test]
     fn runtime_blocks_clipboard_control_without_permission() {
         let dir = tempdir().expect("tempdir");
@@ -0,0 +1,274 @@
+use chrono::NaiveDate;
+use std::fmt;
+
+/// Page property holding the page's aliases as a comma-separated list.
+pub const ALIAS_KEY: &str = "alias";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyValueError {
+    pub key: String,
+    pub value_type: String,
+    pub value: String,
+}
+
+impl fmt::Display for PropertyValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} value for property `{}`: {:?}",
+            self.value_type, self.key, self.value
+        )
+    }
+}
+
+impl std::error::Error for PropertyValueError {}
+
+fn is_property_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_')
+}
+
+/// Parses a single `key:: value` line, returning the lowercased key and the trimmed value.
+fn parse_property_line(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.trim().split_once("::")?;
+    if key.is_empty() || !key.chars().all(is_property_key_char) {
+        return None;
+    }
+    Some((key.to_ascii_lowercase(), value.trim().to_string()))
+}
+
+/// Collects Logseq-style `key:: value` lines from block text, in order. Later lines win when a
+/// key repeats.
+pub fn parse_block_properties(text: &str) -> Vec<(String, String)> {
+    let mut properties: Vec<(String, String)> = Vec::new();
+    for (key, value) in text.lines().filter_map(parse_property_line) {
+        match properties.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(entry) => entry.1 = value,
+            None => properties.push((key, value)),
+        }
+    }
+    properties
+}
+
+/// Returns block text without its property lines, for display.
+pub fn strip_block_properties(text: &str) -> String {
+    text.lines()
+        .filter(|line| parse_property_line(line).is_none())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sets `key:: value` in block text, replacing the first existing line for `key` or appending a
+/// new line.
+pub fn set_block_property_in_text(text: &str, key: &str, value: &str) -> String {
+    let key = key.trim().to_ascii_lowercase();
+    let line = format!("{key}:: {}", value.trim());
+    let mut replaced = false;
+    let mut lines: Vec<String> = Vec::new();
+    for existing in text.lines() {
+        if parse_property_line(existing).is_some_and(|(existing_key, _)| existing_key == key) {
+            if !replaced {
+                lines.push(line.clone());
+                replaced = true;
+            }
+            continue;
+        }
+        lines.push(existing.to_string());
+    }
+    if !replaced {
+        if lines.len() == 1 && lines[0].is_empty() {
+            lines.clear();
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Removes every `key:: value` line for `key` from block text.
+pub fn remove_block_property_in_text(text: &str, key: &str) -> String {
+    let key = key.trim().to_ascii_lowercase();
+    text.lines()
+        .filter(|line| parse_property_line(line).is_none_or(|(existing, _)| existing != key))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A property value parsed according to its `PropertyDefinition.value_type`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Text(String),
+    Number(f64),
+    Date(NaiveDate),
+    Checkbox(bool),
+    Select(String),
+    /// Comma-separated options.
+    MultiSelect(Vec<String>),
+    /// Page title, written as `[[Title]]` or bare.
+    PageRef(String),
+    Url(String),
+}
+
+impl PropertyValue {
+    /// Parses a raw stored value. Returns `None` when the value is empty or does not fit the
+    /// type; unknown types parse as text.
+    pub fn parse(value_type: &str, raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let value = match value_type {
+            "number" => Self::Number(raw.parse::<f64>().ok().filter(|n| n.is_finite())?),
+            "date" => Self::Date(NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok()?),
+            "checkbox" => match raw {
+                "true" => Self::Checkbox(true),
+                "false" => Self::Checkbox(false),
+                _ => return None,
+            },
+            "select" => Self::Select(raw.to_string()),
+            "multi_select" => Self::MultiSelect(split_multi_select(raw)),
+            "page" => {
+                let title = raw
+                    .strip_prefix("[[")
+                    .and_then(|rest| rest.strip_suffix("]]"))
+                    .unwrap_or(raw)
+                    .trim();
+                if title.is_empty() || title.contains("[[") || title.contains("]]") {
+                    return None;
+                }
+                Self::PageRef(title.to_string())
+            }
+            "url" if raw.starts_with("http://") || raw.starts_with("https://") => {
+                Self::Url(raw.to_string())
+            }
+            "url" => return None,
+            _ => Self::Text(raw.to_string()),
+        };
+        Some(value)
+    }
+
+    pub fn value_type(&self) -> &'static str {
+        match self {
+            Self::Text(_) => "text",
+            Self::Number(_) => "number",
+            Self::Date(_) => "date",
+            Self::Checkbox(_) => "checkbox",
+            Self::Select(_) => "select",
+            Self::MultiSelect(_) => "multi_select",
+            Self::PageRef(_) => "page",
+            Self::Url(_) => "url",
+        }
+    }
+
+    /// Canonical text form, as stored in `page_properties.value` and block text.
+    pub fn to_raw(&self) -> String {
+        match self {
+            Self::Text(value) | Self::Select(value) | Self::Url(value) => value.clone(),
+            Self::Number(value) => value.to_string(),
+            Self::Date(value) => value.format("%Y-%m-%d").to_string(),
+            Self::Checkbox(value) => value.to_string(),
+            Self::MultiSelect(values) => values.join(", "),
+            Self::PageRef(title) => format!("[[{title}]]"),
+        }
+    }
+}
+
+/// Splits a multi-select value on commas, dropping empty and repeated options.
+pub fn split_multi_select(raw: &str) -> Vec<String> {
+    let mut options: Vec<String> = Vec::new();
+    for option in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+    {
+        if !options
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(option))
+        {
+            options.push(option.to_string());
+        }
+    }
+    options
+}
+
+/// Checks a raw property value against a `PropertyDefinition.value_type`. Empty values are
+/// always valid and unknown types accept any value.
+pub fn is_valid_property_value(value_type: &str, value: &str) -> bool {
+    value.trim().is_empty() || PropertyValue::parse(value_type, value).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_valid_property_value, parse_block_properties, remove_block_property_in_text,
+        set_block_property_in_text, split_multi_select, strip_block_properties, PropertyValue,
+    };
+
+    #[test]
+    fn parse_block_properties_reads_key_value_lines() {
+        let text = "Ship the release\nstatus:: doing\nOwner:: [[Alex]]\nnot a::property line";
+        assert_eq!(
+            parse_block_properties(text),
+            vec![
+                ("status".to_string(), "doing".to_string()),
+                ("owner".to_string(), "[[Alex]]".to_string()),
+            ]
+        );
+        assert_eq!(
+            strip_block_properties(text),
+            "Ship the release\nnot a::property line"
+        );
+    }
+
+    #[test]
+    fn set_block_property_in_text_replaces_or_appends() {
+        let text = "Task\nstatus:: todo";
+        assert_eq!(
+            set_block_property_in_text(text, "status", "done"),
+            "Task\nstatus:: done"
+        );
+        assert_eq!(
+            set_block_property_in_text(text, "due", "2026-03-01"),
+            "Task\nstatus:: todo\ndue:: 2026-03-01"
+        );
+        assert_eq!(set_block_property_in_text("", "due", "x"), "due:: x");
+        assert_eq!(remove_block_property_in_text(text, "status"), "Task");
+    }
+
+    #[test]
+    fn is_valid_property_value_checks_types() {
+        assert!(is_valid_property_value("number", "3.5"));
+        assert!(!is_valid_property_value("number", "three"));
+        assert!(is_valid_property_value("date", "2026-02-28"));
+        assert!(!is_valid_property_value("date", "2026-02-30"));
+        assert!(is_valid_property_value("checkbox", "true"));
+        assert!(!is_valid_property_value("checkbox", "yes"));
+        assert!(is_valid_property_value("text", "anything"));
+        assert!(is_valid_property_value("number", ""));
+    }
+
+    #[test]
+    fn property_value_parses_and_formats_by_type() {
+        assert_eq!(
+            PropertyValue::parse("number", " 3.50 "),
+            Some(PropertyValue::Number(3.5))
+        );
+        assert_eq!(
+            PropertyValue::parse("page", "[[Project Alpha]]").map(|value| value.to_raw()),
+            Some("[[Project Alpha]]".to_string())
+        );
+        assert_eq!(
+            PropertyValue::parse("page", "Project Alpha"),
+            Some(PropertyValue::PageRef("Project Alpha".to_string()))
+        );
+        assert_eq!(
+            PropertyValue::parse("multi_select", "a, b,,A").map(|value| value.to_raw()),
+            Some("a, b".to_string())
+        );
+        assert_eq!(split_multi_select(" x ,y"), vec!["x", "y"]);
+        assert_eq!(PropertyValue::parse("url", "ftp://x"), None);
+        assert_eq!(PropertyValue::parse("number", ""), None);
+        assert_eq!(
+            PropertyValue::parse("custom", "raw").map(|value| value.value_type()),
+            Some("text")
+        );
+    }
+}
@@ -0,0 +1,572 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+/// Step size of an interval rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatKind {
+    Every {
+        count: u32,
+        unit: RepeatUnit,
+    },
+    /// Monday through Friday.
+    Weekday,
+    On(Weekday),
+}
+
+/// A task repeat rule read from a `repeat::` property.
+///
+/// `every 2 weeks`, `every weekday` and `every friday` roll forward from the task's due date,
+/// skipping occurrences that are already past when it is completed. `+1m` (or `+3d`, `+2w`,
+/// `+1y`) counts from the completion day instead.
+///
+/// Rules work on calendar dates in local wall-clock time, never on elapsed hours, so daylight
+/// saving changes cannot move an occurrence to a neighbouring day. Month and year steps that
+/// land past the end of a month clamp to its last day; the day they aim for is kept as
+/// `every month on day 31`, so a task due Jan 31 goes to Feb 28 and then back to Mar 31.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatRule {
+    pub kind: RepeatKind,
+    pub from_completion: bool,
+    /// Day of the month that month and year steps aim for, once it is past the 28th.
+    pub anchor_day: Option<u32>,
+}
+
+impl RepeatRule {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        if let Some(rest) = value.strip_prefix('+') {
+            let split = rest.find(|ch: char| !ch.is_ascii_digit())?;
+            let count = rest[..split]
+                .parse::<u32>()
+                .ok()
+                .filter(|count| *count > 0)?;
+            let unit = parse_unit(rest[split..].trim())?;
+            return Some(Self {
+                kind: RepeatKind::Every { count, unit },
+                from_completion: true,
+                anchor_day: None,
+            });
+        }
+        let rest = value.strip_prefix("every")?.trim();
+        let (rest, anchor_day) = match rest.split_once(" on day ") {
+            Some((rest, day)) => (
+                rest,
+                Some(
+                    day.trim()
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|day| (1..=31).contains(day))?,
+                ),
+            ),
+            None => (rest, None),
+        };
+        let mut words = rest.split_whitespace();
+        let first = words.next()?;
+        let kind = match (first.parse::<u32>(), words.next()) {
+            (Ok(count), Some(unit)) if count > 0 => RepeatKind::Every {
+                count,
+                unit: parse_unit(unit)?,
+            },
+            (Ok(_), _) => return None,
+            (Err(_), None) if first == "weekday" => RepeatKind::Weekday,
+            (Err(_), None) => match parse_unit(first) {
+                Some(unit) => RepeatKind::Every { count: 1, unit },
+                None => RepeatKind::On(first.parse::<Weekday>().ok()?),
+            },
+            (Err(_), Some(_)) => return None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+        if anchor_day.is_some() && !steps_by_month(kind) {
+            return None;
+        }
+        Some(Self {
+            kind,
+            from_completion: false,
+            anchor_day,
+        })
+    }
+
+    /// The canonical text written back to `repeat::`.
+    pub fn to_text(&self) -> String {
+        match (self.kind, self.from_completion) {
+            (RepeatKind::Every { count, unit }, true) => {
+                format!("+{count}{}", unit_suffix(unit))
+            }
+            (RepeatKind::Every { count, unit }, false) => {
+                let every = if count == 1 {
+                    format!("every {}", unit_name(unit))
+                } else {
+                    format!("every {count} {}s", unit_name(unit))
+                };
+                match self.anchor_day {
+                    Some(day) => format!("{every} on day {day}"),
+                    None => every,
+                }
+            }
+            (RepeatKind::Weekday, _) => "every weekday".to_string(),
+            (RepeatKind::On(day), _) => format!("every {}", weekday_name(day)),
+        }
+    }
+
+    /// The next due date once the task is completed on `completed`. `due` is the current due
+    /// date, or `None` for an undated task, which then counts from the completion day.
+    pub fn next_due(&self, due: Option<NaiveDate>, completed: NaiveDate) -> NaiveDate {
+        match due {
+            Some(due) if !self.from_completion => {
+                self.advance(due, self.steps_after(due, completed))
+            }
+            _ => match self.kind {
+                RepeatKind::Every { count, unit } => {
+                    add_steps(completed, count, unit, 1, completed.day())
+                }
+                _ => self.advance(completed, 1),
+            },
+        }
+    }
+
+    /// How many occurrences a task due on `due` moves forward when completed on `completed`:
+    /// at least one, and enough to pass the completion day.
+    pub fn steps_after(&self, due: NaiveDate, completed: NaiveDate) -> u32 {
+        if completed <= due {
+            return 1;
+        }
+        match self.kind {
+            RepeatKind::Every { .. } => (1..)
+                .find(|steps| self.advance(due, *steps) > completed)
+                .unwrap_or(1),
+            RepeatKind::Weekday | RepeatKind::On(_) => {
+                let passed = due
+                    .iter_days()
+                    .skip(1)
+                    .take_while(|date| *date <= completed)
+                    .filter(|date| self.matches(*date))
+                    .count();
+                u32::try_from(passed).unwrap_or(u32::MAX).saturating_add(1)
+            }
+        }
+    }
+
+    /// `date` moved forward by `steps` occurrences. Month and year steps aim for the anchor
+    /// day when `date` sits on it, clamped to the length of the month they land in.
+    pub fn advance(&self, date: NaiveDate, steps: u32) -> NaiveDate {
+        match self.kind {
+            RepeatKind::Every { count, unit } => {
+                add_steps(date, count, unit, steps, self.anchor_for(date))
+            }
+            RepeatKind::Weekday | RepeatKind::On(_) => date
+                .iter_days()
+                .skip(1)
+                .filter(|date| self.matches(*date))
+                .nth(steps.saturating_sub(1) as usize)
+                .unwrap_or(NaiveDate::MAX),
+        }
+    }
+
+    /// This rule with its anchor day taken from `dates`, the dates it now steps from. Only
+    /// days past the 28th need one; with several, the latest wins.
+    pub fn anchored_at(&self, dates: impl IntoIterator<Item = NaiveDate>) -> Self {
+        let anchor_day = dates
+            .into_iter()
+            .map(|date| self.anchor_for(date))
+            .max()
+            .filter(|day| steps_by_month(self.kind) && !self.from_completion && *day > 28);
+        Self {
+            anchor_day,
+            ..*self
+        }
+    }
+
+    /// The day `date` aims for: the rule's anchor when `date` is where it clamps to, otherwise
+    /// the date's own day.
+    fn anchor_for(&self, date: NaiveDate) -> u32 {
+        match self.anchor_day {
+            Some(anchor) if date.day() == anchor.min(days_in_month(date)) => anchor,
+            _ => date.day(),
+        }
+    }
+
+    fn matches(&self, date: NaiveDate) -> bool {
+        match self.kind {
+            RepeatKind::Every { .. } => true,
+            RepeatKind::Weekday => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            RepeatKind::On(day) => date.weekday() == day,
+        }
+    }
+}
+
+fn steps_by_month(kind: RepeatKind) -> bool {
+    matches!(
+        kind,
+        RepeatKind::Every {
+            unit: RepeatUnit::Month | RepeatUnit::Year,
+            ..
+        }
+    )
+}
+
+fn parse_unit(value: &str) -> Option<RepeatUnit> {
+    match value {
+        "d" | "day" | "days" => Some(RepeatUnit::Day),
+        "w" | "week" | "weeks" => Some(RepeatUnit::Week),
+        "m" | "month" | "months" => Some(RepeatUnit::Month),
+        "y" | "year" | "years" => Some(RepeatUnit::Year),
+        _ => None,
+    }
+}
+
+fn unit_suffix(unit: RepeatUnit) -> &'static str {
+    match unit {
+        RepeatUnit::Day => "d",
+        RepeatUnit::Week => "w",
+        RepeatUnit::Month => "m",
+        RepeatUnit::Year => "y",
+    }
+}
+
+fn unit_name(unit: RepeatUnit) -> &'static str {
+    match unit {
+        RepeatUnit::Day => "day",
+        RepeatUnit::Week => "week",
+        RepeatUnit::Month => "month",
+        RepeatUnit::Year => "year",
+    }
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
+
+fn add_steps(
+    base: NaiveDate,
+    count: u32,
+    unit: RepeatUnit,
+    steps: u32,
+    anchor_day: u32,
+) -> NaiveDate {
+    let amount = count.saturating_mul(steps);
+    let months = match unit {
+        RepeatUnit::Day => {
+            return base
+                .checked_add_days(Days::new(u64::from(amount)))
+                .unwrap_or(NaiveDate::MAX)
+        }
+        RepeatUnit::Week => {
+            return base
+                .checked_add_days(Days::new(u64::from(amount) * 7))
+                .unwrap_or(NaiveDate::MAX)
+        }
+        RepeatUnit::Month => amount,
+        RepeatUnit::Year => amount.saturating_mul(12),
+    };
+    let Some(month) = base
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(months)))
+    else {
+        return NaiveDate::MAX;
+    };
+    let day = anchor_day.min(days_in_month(month));
+    month.with_day(day).unwrap_or(month)
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    date.with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RepeatKind, RepeatRule, RepeatUnit};
+    use chrono::{Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").expect("date")
+    }
+
+    fn rule(value: &str) -> RepeatRule {
+        RepeatRule::parse(value).expect("rule")
+    }
+
+    #[test]
+    fn parse_reads_interval_weekday_and_completion_rules() {
+        assert_eq!(
+            rule("every 2 weeks").kind,
+            RepeatKind::Every {
+                count: 2,
+                unit: RepeatUnit::Week
+            }
+        );
+        assert_eq!(rule("Every month").to_text(), "every month");
+        assert_eq!(rule("every weekday").kind, RepeatKind::Weekday);
+        assert_eq!(rule("every friday").kind, RepeatKind::On(Weekday::Fri));
+        let completion = rule("+1m");
+        assert!(completion.from_completion);
+        assert_eq!(completion.to_text(), "+1m");
+        assert_eq!(rule("every 3 days").to_text(), "every 3 days");
+
+        for invalid in [
+            "",
+            "every",
+            "every 0 days",
+            "+0d",
+            "+2",
+            "every 2",
+            "daily",
+            "every 2 fortnights",
+        ] {
+            assert_eq!(RepeatRule::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn interval_rules_roll_from_the_due_date_past_the_completion() {
+        let biweekly = rule("every 2 weeks");
+        assert_eq!(
+            biweekly.next_due(Some(date("2026-03-02")), date("2026-03-02")),
+            date("2026-03-16")
+        );
+        // Finished early: the next occurrence still follows the due date.
+        assert_eq!(
+            biweekly.next_due(Some(date("2026-03-16")), date("2026-03-10")),
+            date("2026-03-30")
+        );
+        // Finished five weeks late: skip the occurrences already past.
+        assert_eq!(
+            biweekly.next_due(Some(date("2026-03-02")), date("2026-04-06")),
+            date("2026-04-13")
+        );
+        // Undated tasks count from the day they were done.
+        assert_eq!(
+            biweekly.next_due(None, date("2026-03-04")),
+            date("2026-03-18")
+        );
+    }
+
+    #[test]
+    fn month_steps_clamp_to_the_end_of_shorter_months() {
+        let monthly = rule("every month");
+        assert_eq!(
+            monthly.next_due(Some(date("2026-01-31")), date("2026-01-31")),
+            date("2026-02-28")
+        );
+        assert_eq!(
+            monthly.next_due(Some(date("2028-01-31")), date("2028-01-31")),
+            date("2028-02-29")
+        );
+        // Two steps from Jan 31 land on Mar 31, not on the clamped Feb 28 plus a month.
+        assert_eq!(
+            monthly.next_due(Some(date("2026-01-31")), date("2026-03-01")),
+            date("2026-03-31")
+        );
+        assert_eq!(
+            rule("+1m").next_due(Some(date("2026-01-15")), date("2026-08-31")),
+            date("2026-09-30")
+        );
+        assert_eq!(
+            rule("every year").next_due(Some(date("2028-02-29")), date("2028-02-29")),
+            date("2029-02-28")
+        );
+        assert_eq!(
+            rule("every month").next_due(Some(date("2026-12-31")), date("2026-12-31")),
+            date("2027-01-31")
+        );
+    }
+
+    #[test]
+    fn anchored_month_steps_return_to_the_anchor_day() {
+        let monthly = rule("every month");
+        let anchored = monthly.anchored_at([date("2026-01-31")]);
+        assert_eq!(anchored.anchor_day, Some(31));
+        assert_eq!(anchored.to_text(), "every month on day 31");
+        assert_eq!(RepeatRule::parse("every month on day 31"), Some(anchored));
+        assert_eq!(
+            anchored.next_due(Some(date("2026-01-31")), date("2026-01-31")),
+            date("2026-02-28")
+        );
+        // Clamped to Feb 28, the anchored rule goes back to the 31st; a plain one would not.
+        assert_eq!(
+            anchored.next_due(Some(date("2026-02-28")), date("2026-02-28")),
+            date("2026-03-31")
+        );
+        assert_eq!(
+            monthly.next_due(Some(date("2026-02-28")), date("2026-02-28")),
+            date("2026-03-28")
+        );
+        // The anchor survives a clamped date and is dropped once the task moves off it.
+        assert_eq!(anchored.anchored_at([date("2026-02-28")]), anchored);
+        assert_eq!(anchored.anchored_at([date("2026-02-15")]).anchor_day, None);
+        assert_eq!(monthly.anchored_at([date("2026-01-28")]).anchor_day, None);
+        assert_eq!(
+            rule("every 2 years on day 29").advance(date("2028-02-29"), 1),
+            date("2030-02-28")
+        );
+        // Other dates of the task step by the same rule on their own day.
+        assert_eq!(anchored.advance(date("2026-02-15"), 2), date("2026-04-15"));
+
+        for invalid in [
+            "every week on day 3",
+            "every friday on day 3",
+            "every month on day 32",
+            "every month on day",
+        ] {
+            assert_eq!(RepeatRule::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn weekday_rules_skip_weekends() {
+        let weekday = rule("every weekday");
+        assert_eq!(
+            weekday.next_due(Some(date("2026-03-06")), date("2026-03-06")),
+            date("2026-03-09")
+        );
+        assert_eq!(
+            weekday.next_due(Some(date("2026-03-03")), date("2026-03-03")),
+            date("2026-03-04")
+        );
+        // Done late on a Saturday for a Thursday task.
+        assert_eq!(
+            weekday.next_due(Some(date("2026-03-05")), date("2026-03-07")),
+            date("2026-03-09")
+        );
+        assert_eq!(
+            rule("every friday").next_due(Some(date("2026-03-06")), date("2026-03-06")),
+            date("2026-03-13")
+        );
+    }
+
+    /// US Eastern time as of 2026: clocks spring forward at 02:00 on Mar 8 and fall back at
+    /// 02:00 on Nov 1.
+    #[derive(Debug, Clone, Copy)]
+    struct Eastern;
+
+    impl Eastern {
+        fn offset_at(utc: &NaiveDateTime) -> FixedOffset {
+            let summer =
+                *utc >= datetime("2026-03-08 07:00") && *utc < datetime("2026-11-01 06:00");
+            FixedOffset::east_opt(if summer { -4 } else { -5 } * 3600).expect("offset")
+        }
+    }
+
+    impl TimeZone for Eastern {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Eastern
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).expect("midnight"))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let valid = [-4, -5]
+                .map(|hours| FixedOffset::east_opt(hours * 3600).expect("offset"))
+                .into_iter()
+                .filter(|offset| Self::offset_at(&(*local - *offset)) == *offset)
+                .collect::<Vec<_>>();
+            match valid[..] {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(offset),
+                [earlier, later, ..] => LocalResult::Ambiguous(earlier, later),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            Self::offset_at(&utc.and_hms_opt(0, 0, 0).expect("midnight"))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset_at(utc)
+        }
+    }
+
+    fn datetime(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").expect("datetime")
+    }
+
+    /// The local day a completion at `utc` happens on in the Eastern zone.
+    fn completed_on(utc: &str) -> NaiveDate {
+        Eastern.from_utc_datetime(&datetime(utc)).date_naive()
+    }
+
+    #[test]
+    fn occurrences_across_daylight_saving_changes_keep_their_local_date() {
+        // The zone really skips and repeats an hour.
+        assert_eq!(
+            Eastern.from_local_datetime(&datetime("2026-03-08 02:30")),
+            LocalResult::None
+        );
+        assert!(matches!(
+            Eastern.from_local_datetime(&datetime("2026-11-01 01:30")),
+            LocalResult::Ambiguous(..)
+        ));
+
+        // Done at 23:30 on the night clocks spring forward; the next day is still the 8th,
+        // even though 24 hours later it is already 00:30 on the 9th on the wall clock.
+        let completed = completed_on("2026-03-08 04:30");
+        assert_eq!(completed, date("2026-03-07"));
+        assert_eq!(
+            rule("every day").next_due(Some(date("2026-03-07")), completed),
+            date("2026-03-08")
+        );
+        assert_eq!(rule("+1d").next_due(None, completed), date("2026-03-08"));
+        assert_eq!(
+            (Eastern.from_utc_datetime(&datetime("2026-03-08 04:30")) + Duration::hours(24))
+                .date_naive(),
+            date("2026-03-09")
+        );
+
+        // A Sunday task done late Sunday evening, in winter and then in summer time.
+        let weekly = rule("every sunday");
+        let completed = completed_on("2026-03-02 03:00");
+        assert_eq!(completed, date("2026-03-01"));
+        assert_eq!(
+            weekly.next_due(Some(date("2026-03-01")), completed),
+            date("2026-03-08")
+        );
+        let completed = completed_on("2026-03-09 03:30");
+        assert_eq!(completed, date("2026-03-08"));
+        assert_eq!(
+            weekly.next_due(Some(date("2026-03-08")), completed),
+            date("2026-03-15")
+        );
+
+        // Both passes through the repeated 01:30 hour on fall-back day are Nov 1.
+        for utc in ["2026-11-01 05:30", "2026-11-01 06:30"] {
+            let completed = completed_on(utc);
+            assert_eq!(completed, date("2026-11-01"), "{utc}");
+            assert_eq!(
+                rule("every day").next_due(Some(date("2026-10-31")), completed),
+                date("2026-11-02")
+            );
+            assert_eq!(
+                rule("every weekday").next_due(Some(date("2026-10-30")), completed),
+                date("2026-11-02")
+            );
+            assert_eq!(
+                rule("every month").next_due(Some(date("2026-10-31")), completed),
+                date("2026-11-30")
+            );
+        }
+    }
+}
@@ -0,0 +1,257 @@
+const MINUTE_MS: i64 = 60 * 1000;
+pub const DAY_MS: i64 = 24 * 60 * MINUTE_MS;
+
+pub const DEFAULT_EASE: f64 = 2.5;
+pub const MIN_EASE: f64 = 1.3;
+const HARD_EASE_PENALTY: f64 = 0.15;
+const AGAIN_EASE_PENALTY: f64 = 0.2;
+const EASY_EASE_BONUS: f64 = 0.15;
+const HARD_INTERVAL_FACTOR: f64 = 1.2;
+const EASY_INTERVAL_FACTOR: f64 = 1.3;
+const MAX_INTERVAL_DAYS: i64 = 36_500;
+/// A failed item comes back within the same session.
+pub const RELEARN_DELAY_MS: i64 = 10 * MINUTE_MS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReviewGrade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl ReviewGrade {
+    pub const ALL: [ReviewGrade; 4] = [
+        ReviewGrade::Again,
+        ReviewGrade::Hard,
+        ReviewGrade::Good,
+        ReviewGrade::Easy,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReviewGrade::Again => "again",
+            ReviewGrade::Hard => "hard",
+            ReviewGrade::Good => "good",
+            ReviewGrade::Easy => "easy",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReviewGrade::Again => "Again",
+            ReviewGrade::Hard => "Hard",
+            ReviewGrade::Good => "Good",
+            ReviewGrade::Easy => "Easy",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "again" => Some(ReviewGrade::Again),
+            "hard" => Some(ReviewGrade::Hard),
+            "good" => Some(ReviewGrade::Good),
+            "easy" => Some(ReviewGrade::Easy),
+            _ => None,
+        }
+    }
+
+    /// Whether the item was recalled, for retention stats.
+    pub fn is_pass(self) -> bool {
+        self != ReviewGrade::Again
+    }
+}
+
+/// SM-2 scheduling state stored on each `review_queue` row.
+///
+/// `repetitions` counts successful reviews in a row and resets on `Again`, which also counts a
+/// lapse once the item has been learned. An `interval_days` of 0 means the item is (re)learning
+/// and is due again after [`RELEARN_DELAY_MS`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewCard {
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+    pub lapses: i64,
+}
+
+impl Default for ReviewCard {
+    fn default() -> Self {
+        Self {
+            ease: DEFAULT_EASE,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: 0,
+        }
+    }
+}
+
+impl ReviewCard {
+    /// The card's state after answering it with `grade`.
+    pub fn review(&self, grade: ReviewGrade) -> ReviewCard {
+        let previous = self.interval_days.max(1) as f64;
+        let good = match self.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (previous * self.ease).round() as i64,
+        }
+        .max(self.interval_days + 1);
+        let (ease, interval_days) = match grade {
+            ReviewGrade::Again => (self.ease - AGAIN_EASE_PENALTY, 0),
+            ReviewGrade::Hard => {
+                let hard = if self.repetitions == 0 {
+                    1
+                } else {
+                    ((previous * HARD_INTERVAL_FACTOR).round() as i64).max(self.interval_days + 1)
+                };
+                (self.ease - HARD_EASE_PENALTY, hard.min(good))
+            }
+            ReviewGrade::Good => (self.ease, good),
+            ReviewGrade::Easy => {
+                let easy = if self.repetitions == 0 {
+                    4
+                } else {
+                    ((good as f64 * EASY_INTERVAL_FACTOR).round() as i64).max(good + 1)
+                };
+                (self.ease + EASY_EASE_BONUS, easy)
+            }
+        };
+        let failed = grade == ReviewGrade::Again;
+        ReviewCard {
+            ease: ease.max(MIN_EASE),
+            interval_days: interval_days.min(MAX_INTERVAL_DAYS),
+            repetitions: if failed { 0 } else { self.repetitions + 1 },
+            lapses: self.lapses + i64::from(failed && self.repetitions > 0),
+        }
+    }
+
+    /// When the card is next due, given the time it was reviewed in Unix milliseconds.
+    pub fn due_at(&self, reviewed_at: i64) -> i64 {
+        if self.interval_days == 0 {
+            reviewed_at + RELEARN_DELAY_MS
+        } else {
+            reviewed_at + self.interval_days * DAY_MS
+        }
+    }
+}
+
+/// Short label for an interval, as shown on grade buttons: `10m`, `3d`, `2w`, `4mo`, `1.5y`.
+pub fn format_review_interval(interval_days: i64) -> String {
+    match interval_days {
+        days if days <= 0 => format!("{}m", RELEARN_DELAY_MS / MINUTE_MS),
+        days if days < 14 => format!("{days}d"),
+        days if days < 60 => format!("{}w", (days as f64 / 7.0).round() as i64),
+        days if days < 365 => format!("{}mo", (days as f64 / 30.0).round() as i64),
+        days => {
+            let years = days as f64 / 365.0;
+            if years < 10.0 {
+                format!("{:.1}y", years).replace(".0y", "y")
+            } else {
+                format!("{}y", years.round() as i64)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewStats {
+    /// Pending items due now.
+    pub due: i64,
+    pub pending: i64,
+    pub done: i64,
+    /// Answers logged since the stats window opened.
+    pub reviewed: i64,
+    /// `Again` answers in the window.
+    pub failed: i64,
+    /// Share of answers in the window that were not `Again`.
+    pub retention: Option<f64>,
+    pub average_ease: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_review_interval, ReviewCard, ReviewGrade, DAY_MS, MIN_EASE, RELEARN_DELAY_MS,
+    };
+
+    fn answer(card: ReviewCard, grades: &[ReviewGrade]) -> ReviewCard {
+        grades.iter().fold(card, |card, grade| card.review(*grade))
+    }
+
+    #[test]
+    fn good_answers_follow_the_sm2_ladder() {
+        use ReviewGrade::Good;
+        let card = ReviewCard::default();
+        let intervals = (0..4)
+            .scan(card, |card, _| {
+                *card = card.review(Good);
+                Some(card.interval_days)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(intervals, vec![1, 6, 15, 38]);
+        assert_eq!(answer(card, &[Good, Good]).repetitions, 2);
+    }
+
+    #[test]
+    fn grades_order_intervals_and_adjust_ease() {
+        let learned = answer(
+            ReviewCard::default(),
+            &[ReviewGrade::Good, ReviewGrade::Good],
+        );
+        let [again, hard, good, easy] = ReviewGrade::ALL.map(|grade| learned.review(grade));
+        assert_eq!(again.interval_days, 0);
+        assert!(hard.interval_days > learned.interval_days);
+        assert!(hard.interval_days < good.interval_days);
+        assert!(good.interval_days < easy.interval_days);
+        assert!(hard.ease < learned.ease && easy.ease > learned.ease);
+        assert_eq!(good.ease, learned.ease);
+
+        assert_eq!(
+            ReviewCard::default()
+                .review(ReviewGrade::Easy)
+                .interval_days,
+            4
+        );
+        assert_eq!(
+            ReviewCard::default()
+                .review(ReviewGrade::Hard)
+                .interval_days,
+            1
+        );
+    }
+
+    #[test]
+    fn again_resets_repetitions_and_counts_lapses_after_learning() {
+        let fresh = ReviewCard::default().review(ReviewGrade::Again);
+        assert_eq!(fresh.lapses, 0);
+        assert_eq!(fresh.due_at(1_000), 1_000 + RELEARN_DELAY_MS);
+
+        let learned = answer(ReviewCard::default(), &[ReviewGrade::Good; 3]);
+        let lapsed = learned.review(ReviewGrade::Again);
+        assert_eq!(lapsed.repetitions, 0);
+        assert_eq!(lapsed.lapses, 1);
+        assert_eq!(lapsed.review(ReviewGrade::Good).interval_days, 1);
+        assert_eq!(lapsed.review(ReviewGrade::Good).due_at(0), DAY_MS);
+    }
+
+    #[test]
+    fn ease_never_drops_below_the_floor() {
+        let card = answer(ReviewCard::default(), &[ReviewGrade::Again; 12]);
+        assert_eq!(card.ease, MIN_EASE);
+        assert!(card.review(ReviewGrade::Hard).interval_days >= 1);
+    }
+
+    #[test]
+    fn grades_parse_and_intervals_format() {
+        for grade in ReviewGrade::ALL {
+            assert_eq!(ReviewGrade::parse(grade.as_str()), Some(grade));
+        }
+        assert_eq!(ReviewGrade::parse("snooze"), None);
+        assert_eq!(format_review_interval(0), "10m");
+        assert_eq!(format_review_interval(6), "6d");
+        assert_eq!(format_review_interval(21), "3w");
+        assert_eq!(format_review_interval(120), "4mo");
+        assert_eq!(format_review_interval(365), "1y");
+        assert_eq!(format_review_interval(548), "1.5y");
+    }
+}
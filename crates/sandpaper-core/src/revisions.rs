@@ -0,0 +1,185 @@
+use crate::db::BlockSnapshot;
+use std::collections::{HashMap, HashSet};
+
+const MINUTE_MS: i64 = 60 * 1000;
+const HOUR_MS: i64 = 60 * MINUTE_MS;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+/// Saves closer together than this update the latest revision instead of adding one.
+pub const REVISION_INTERVAL_MS: i64 = 5 * MINUTE_MS;
+
+/// `(max age, bucket size)`: revisions younger than `max age` keep the newest one per bucket.
+/// A bucket size of 0 keeps every revision. Older revisions fall into weekly buckets.
+const COMPACTION_TIERS: &[(i64, i64)] =
+    &[(DAY_MS, 0), (7 * DAY_MS, HOUR_MS), (90 * DAY_MS, DAY_MS)];
+const FALLBACK_BUCKET_MS: i64 = 7 * DAY_MS;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRevisionRecord {
+    pub id: i64,
+    pub page_id: i64,
+    /// Unix milliseconds when the revision window opened.
+    pub created_at: i64,
+    pub block_count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRevision {
+    pub id: i64,
+    pub page_id: i64,
+    pub created_at: i64,
+    pub blocks: Vec<BlockSnapshot>,
+}
+
+/// Returns the ids of revisions to drop, given `(id, created_at)` pairs. Recent history is kept
+/// in full and thins out to hourly, daily and then weekly snapshots as it ages; the newest
+/// revision in each bucket survives.
+pub fn revisions_to_prune(revisions: &[(i64, i64)], now: i64) -> Vec<i64> {
+    let mut ordered = revisions.to_vec();
+    ordered.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+    let mut seen = HashSet::new();
+    let mut pruned = Vec::new();
+    for (id, created_at) in ordered {
+        let age = now.saturating_sub(created_at).max(0);
+        let (tier, bucket_size) = COMPACTION_TIERS
+            .iter()
+            .enumerate()
+            .find(|(_, (max_age, _))| age < *max_age)
+            .map(|(tier, (_, bucket_size))| (tier, *bucket_size))
+            .unwrap_or((COMPACTION_TIERS.len(), FALLBACK_BUCKET_MS));
+        if bucket_size == 0 {
+            continue;
+        }
+        if !seen.insert((tier, created_at.div_euclid(bucket_size))) {
+            pruned.push(id);
+        }
+    }
+    pruned
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockDiffKind {
+    Unchanged,
+    Added,
+    Removed,
+    /// Same block uid with different text, indent or type.
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockDiff {
+    pub uid: String,
+    pub kind: BlockDiffKind,
+    pub before: Option<BlockSnapshot>,
+    pub after: Option<BlockSnapshot>,
+}
+
+/// Block-level diff from `before` to `after`, matched by uid. Rows follow `after` order, with
+/// removed blocks placed after the block that preceded them in `before`.
+pub fn diff_blocks(before: &[BlockSnapshot], after: &[BlockSnapshot]) -> Vec<BlockDiff> {
+    let before_by_uid: HashMap<&str, &BlockSnapshot> = before
+        .iter()
+        .map(|block| (block.uid.as_str(), block))
+        .collect();
+    let after_uids: HashSet<&str> = after.iter().map(|block| block.uid.as_str()).collect();
+
+    // Removed blocks keyed by the uid of the nearest earlier block that still exists.
+    let mut removed_after: HashMap<Option<&str>, Vec<&BlockSnapshot>> = HashMap::new();
+    let mut anchor: Option<&str> = None;
+    for block in before {
+        if after_uids.contains(block.uid.as_str()) {
+            anchor = Some(block.uid.as_str());
+        } else {
+            removed_after.entry(anchor).or_default().push(block);
+        }
+    }
+    let removed = |block: &BlockSnapshot| BlockDiff {
+        uid: block.uid.clone(),
+        kind: BlockDiffKind::Removed,
+        before: Some(block.clone()),
+        after: None,
+    };
+
+    let mut diff: Vec<BlockDiff> = removed_after
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .map(removed)
+        .collect();
+    for block in after {
+        let previous = before_by_uid.get(block.uid.as_str()).copied();
+        let kind = match previous {
+            None => BlockDiffKind::Added,
+            Some(previous) if previous == block => BlockDiffKind::Unchanged,
+            Some(_) => BlockDiffKind::Changed,
+        };
+        diff.push(BlockDiff {
+            uid: block.uid.clone(),
+            kind,
+            before: previous.cloned(),
+            after: Some(block.clone()),
+        });
+        if let Some(blocks) = removed_after.remove(&Some(block.uid.as_str())) {
+            diff.extend(blocks.into_iter().map(removed));
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_blocks, revisions_to_prune, BlockDiffKind, DAY_MS, HOUR_MS, MINUTE_MS};
+    use crate::blocks::BlockType;
+    use crate::db::BlockSnapshot;
+
+    fn block(uid: &str, text: &str) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: uid.to_string(),
+            text: text.to_string(),
+            indent: 0,
+            block_type: BlockType::Text,
+        }
+    }
+
+    #[test]
+    fn diff_blocks_reports_changes_in_order() {
+        let before = vec![block("a", "one"), block("b", "two"), block("c", "three")];
+        let after = vec![block("a", "one"), block("c", "three!"), block("d", "four")];
+        let kinds: Vec<(String, BlockDiffKind)> = diff_blocks(&before, &after)
+            .into_iter()
+            .map(|row| (row.uid, row.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("a".to_string(), BlockDiffKind::Unchanged),
+                ("b".to_string(), BlockDiffKind::Removed),
+                ("c".to_string(), BlockDiffKind::Changed),
+                ("d".to_string(), BlockDiffKind::Added),
+            ]
+        );
+
+        let leading = diff_blocks(&before, &[block("c", "three")]);
+        assert_eq!(leading[0].uid, "a");
+        assert_eq!(leading[0].kind, BlockDiffKind::Removed);
+    }
+
+    #[test]
+    fn prune_thins_out_older_revisions() {
+        let now = 100 * DAY_MS + 12 * HOUR_MS + 30 * MINUTE_MS;
+        let revisions = vec![
+            (1, now - 10 * MINUTE_MS),
+            (2, now - 20 * MINUTE_MS),
+            // Same hour, two days ago: only the newer one survives.
+            (3, now - 2 * DAY_MS),
+            (4, now - 2 * DAY_MS - 5 * MINUTE_MS),
+            (5, now - 2 * DAY_MS - 3 * HOUR_MS),
+            // Same day, a month ago.
+            (6, now - 30 * DAY_MS),
+            (7, now - 30 * DAY_MS - HOUR_MS),
+        ];
+        let mut pruned = revisions_to_prune(&revisions, now);
+        pruned.sort();
+        assert_eq!(pruned, vec![4, 7]);
+    }
+}
@@ -0,0 +1,401 @@
+use crate::blocks::BlockType;
+use crate::links::extract_tags;
+use crate::properties::is_valid_property_value;
+use crate::views::{FilterOp, PropertyFilter};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Marks the start of a highlighted match in `SearchHit.snippet`.
+pub const HIGHLIGHT_START: char = '\u{2}';
+/// Marks the end of a highlighted match in `SearchHit.snippet`.
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOp {
+    Before,
+    OnOrBefore,
+    On,
+    OnOrAfter,
+    After,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateBound {
+    pub op: DateOp,
+    pub date: NaiveDate,
+}
+
+impl DateBound {
+    /// Half-open range of unix seconds `[start, end)` matching the bound; `None` is unbounded.
+    pub fn unix_range(&self) -> (Option<i64>, Option<i64>) {
+        let day_start = |date: NaiveDate| {
+            date.and_hms_opt(0, 0, 0)
+                .map(|dt| dt.and_utc().timestamp())
+                .unwrap_or_default()
+        };
+        let start = day_start(self.date);
+        let next = day_start(self.date.succ_opt().unwrap_or(self.date));
+        match self.op {
+            DateOp::Before => (None, Some(start)),
+            DateOp::OnOrBefore => (None, Some(next)),
+            DateOp::On => (Some(start), Some(next)),
+            DateOp::OnOrAfter => (Some(start), None),
+            DateOp::After => (Some(next), None),
+        }
+    }
+}
+
+/// A parsed search box query, e.g.
+/// `page:"Project X" type:todo tag:urgent updated:>2026-01-01 "exact phrase" -excluded`.
+///
+/// Bare words match as prefixes, quoted text as phrases, and `-word` / `-"phrase"` exclude
+/// blocks. Other `key:value` pairs filter on block properties the vault knows (`key:>3`,
+/// `key:<3` compare by the property type); see `Database::parse_search_query`. Anything else,
+/// like `http://x`, and filters with values that do not parse are searched as plain words.
+///
+/// `todo:open` and `todo:done` keep checkbox blocks that are unchecked or checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub phrases: Vec<String>,
+    pub excluded: Vec<String>,
+    pub pages: Vec<String>,
+    pub block_types: Vec<BlockType>,
+    pub tags: Vec<String>,
+    pub updated: Vec<DateBound>,
+    pub todo_done: Option<bool>,
+    pub properties: Vec<PropertyFilter>,
+}
+
+/// A block matched by `Database::search`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub block_id: i64,
+    pub block_uid: String,
+    pub page_uid: String,
+    pub page_title: String,
+    pub text: String,
+    /// Excerpt around the match with `HIGHLIGHT_START`/`HIGHLIGHT_END` around matched terms.
+    pub snippet: String,
+}
+
+fn is_filter_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_')
+}
+
+/// Splits the input into tokens, keeping quoted sections (and `key:"quoted value"`) together.
+/// Returns each token with whether its value was quoted.
+fn tokenize(input: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    for ch in input.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            ch if ch.is_whitespace() && !in_quotes => {
+                if !current.is_empty() || quoted {
+                    tokens.push((std::mem::take(&mut current), quoted));
+                }
+                quoted = false;
+            }
+            ch => current.push(ch),
+        }
+    }
+    if !current.is_empty() || quoted {
+        tokens.push((current, quoted));
+    }
+    tokens
+}
+
+fn parse_date_bound(value: &str) -> Option<DateBound> {
+    let (op, date) = if let Some(rest) = value.strip_prefix(">=") {
+        (DateOp::OnOrAfter, rest)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (DateOp::OnOrBefore, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (DateOp::After, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (DateOp::Before, rest)
+    } else {
+        (DateOp::On, value)
+    };
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?;
+    Some(DateBound { op, date })
+}
+
+fn parse_property_filter(key: &str, value: &str, value_type: &str) -> Option<PropertyFilter> {
+    let (op, value) = if let Some(rest) = value.strip_prefix('>') {
+        (FilterOp::GreaterThan, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (FilterOp::LessThan, rest)
+    } else {
+        (FilterOp::Equals, value)
+    };
+    if value.trim().is_empty() || !is_valid_property_value(value_type, value) {
+        return None;
+    }
+    Some(PropertyFilter {
+        key: key.to_ascii_lowercase(),
+        op,
+        value: value.trim().to_string(),
+        values: Vec::new(),
+    })
+}
+
+impl SearchQuery {
+    /// Parses `input` with no property filters.
+    pub fn parse(input: &str) -> Self {
+        Self::parse_with_properties(input, &HashMap::new())
+    }
+
+    /// Parses `input`, reading `key:value` as a property filter when `properties` (property
+    /// key to value type) has the key and the value parses as its type.
+    pub fn parse_with_properties(input: &str, properties: &HashMap<String, String>) -> Self {
+        let mut query = SearchQuery::default();
+        for (token, quoted) in tokenize(input) {
+            if let Some(excluded) = token.strip_prefix('-') {
+                if !excluded.is_empty() {
+                    query.excluded.push(excluded.to_string());
+                }
+                continue;
+            }
+            if token.starts_with('#') && !quoted {
+                let tags = extract_tags(&token);
+                if !tags.is_empty() {
+                    query.tags.extend(tags);
+                    continue;
+                }
+            }
+            if let Some((key, value)) = token.split_once(':') {
+                if !key.is_empty()
+                    && key.chars().all(is_filter_key_char)
+                    && query.apply_filter(key, value, properties)
+                {
+                    continue;
+                }
+            }
+            if token.is_empty() {
+                continue;
+            }
+            if quoted {
+                query.phrases.push(token);
+            } else {
+                query.terms.push(token);
+            }
+        }
+        query
+    }
+
+    /// Applies a `key:value` filter, returning false when the value does not parse.
+    fn apply_filter(
+        &mut self,
+        key: &str,
+        value: &str,
+        properties: &HashMap<String, String>,
+    ) -> bool {
+        let value = value.trim();
+        match key.to_ascii_lowercase().as_str() {
+            "page" if !value.is_empty() => self.pages.push(value.to_string()),
+            "type" => {
+                match serde_json::from_value::<BlockType>(serde_json::Value::String(
+                    value.to_ascii_lowercase(),
+                )) {
+                    Ok(block_type) => self.block_types.push(block_type),
+                    Err(_) => return false,
+                }
+            }
+            "tag" if !value.is_empty() => {
+                self.tags.push(value.trim_start_matches('#').to_lowercase())
+            }
+            "updated" => match parse_date_bound(value) {
+                Some(bound) => self.updated.push(bound),
+                None => return false,
+            },
+            "todo" => match value.to_ascii_lowercase().as_str() {
+                "open" => self.todo_done = Some(false),
+                "done" => self.todo_done = Some(true),
+                _ => return false,
+            },
+            key => match properties
+                .get(key)
+                .and_then(|value_type| parse_property_filter(key, value, value_type))
+            {
+                Some(filter) => self.properties.push(filter),
+                None => return false,
+            },
+        }
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == SearchQuery::default()
+    }
+
+    /// True when the query scopes blocks beyond matching their text.
+    pub fn has_filters(&self) -> bool {
+        !self.pages.is_empty()
+            || !self.block_types.is_empty()
+            || !self.tags.is_empty()
+            || !self.updated.is_empty()
+            || self.todo_done.is_some()
+            || !self.properties.is_empty()
+    }
+
+    /// FTS5 expression for the positive terms; `None` when there are none. Every term is
+    /// quoted, so user punctuation never reaches the FTS5 parser as syntax.
+    pub fn fts_match(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| format!("{}*", fts_quote(term)))
+            .chain(self.phrases.iter().map(|phrase| fts_quote(phrase)))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" AND "))
+    }
+
+    /// FTS5 expression matching any excluded term; `None` when nothing is excluded.
+    pub fn fts_excluded(&self) -> Option<String> {
+        let parts: Vec<String> = self.excluded.iter().map(|term| fts_quote(term)).collect();
+        (!parts.is_empty()).then(|| parts.join(" OR "))
+    }
+}
+
+/// Quotes text as an FTS5 string, doubling embedded quotes.
+pub fn fts_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Compiles free text into a safe FTS5 expression, or `None` when nothing is searchable.
+pub fn fts_match_expression(input: &str) -> Option<String> {
+    SearchQuery::parse(input).fts_match()
+}
+
+/// Splits a snippet into plain text and the byte ranges of its highlighted matches.
+pub fn snippet_highlights(snippet: &str) -> (String, Vec<std::ops::Range<usize>>) {
+    let mut text = String::with_capacity(snippet.len());
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for ch in snippet.chars() {
+        match ch {
+            HIGHLIGHT_START => start = Some(text.len()),
+            HIGHLIGHT_END => {
+                if let Some(start) = start.take() {
+                    if start < text.len() {
+                        ranges.push(start..text.len());
+                    }
+                }
+            }
+            ch => text.push(ch),
+        }
+    }
+    (text, ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fts_match_expression, fts_quote, snippet_highlights, DateOp, SearchQuery, HIGHLIGHT_END,
+        HIGHLIGHT_START,
+    };
+    use crate::blocks::BlockType;
+    use crate::views::FilterOp;
+    use std::collections::HashMap;
+
+    fn properties(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value_type)| (key.to_string(), value_type.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_splits_filters_terms_and_phrases() {
+        let query = SearchQuery::parse_with_properties(
+            r#"page:"Project X" type:todo tag:Urgent #later updated:>2026-01-01 "exact phrase" -excluded plan due:<2026-03-01"#,
+            &properties(&[("due", "date")]),
+        );
+        assert_eq!(query.pages, vec!["Project X"]);
+        assert_eq!(query.block_types, vec![BlockType::Todo]);
+        assert_eq!(query.tags, vec!["urgent", "later"]);
+        assert_eq!(query.updated[0].op, DateOp::After);
+        assert_eq!(query.phrases, vec!["exact phrase"]);
+        assert_eq!(query.excluded, vec!["excluded"]);
+        assert_eq!(query.terms, vec!["plan"]);
+        assert_eq!(query.properties[0].key, "due");
+        assert_eq!(query.properties[0].op, FilterOp::LessThan);
+        assert!(query.has_filters());
+
+        assert_eq!(SearchQuery::parse("todo:open").todo_done, Some(false));
+        assert_eq!(SearchQuery::parse("todo:Done").todo_done, Some(true));
+    }
+
+    #[test]
+    fn invalid_filters_fall_back_to_terms() {
+        let query = SearchQuery::parse("type:nonsense updated:soon todo:maybe");
+        assert_eq!(
+            query.terms,
+            vec!["type:nonsense", "updated:soon", "todo:maybe"]
+        );
+        assert!(!query.has_filters());
+    }
+
+    #[test]
+    fn only_known_properties_with_valid_values_filter() {
+        let input = "http://example.com note:foo due:tomorrow due:>2026-03-01";
+        let query = SearchQuery::parse(input);
+        assert_eq!(
+            query.terms,
+            vec![
+                "http://example.com",
+                "note:foo",
+                "due:tomorrow",
+                "due:>2026-03-01"
+            ]
+        );
+        assert!(!query.has_filters());
+
+        let query = SearchQuery::parse_with_properties(
+            input,
+            &properties(&[("note", "text"), ("due", "date")]),
+        );
+        assert_eq!(query.terms, vec!["http://example.com", "due:tomorrow"]);
+        let filters = query
+            .properties
+            .iter()
+            .map(|filter| (filter.key.as_str(), filter.op, filter.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            filters,
+            vec![
+                ("note", FilterOp::Equals, "foo"),
+                ("due", FilterOp::GreaterThan, "2026-03-01")
+            ]
+        );
+    }
+
+    #[test]
+    fn fts_expression_quotes_punctuation() {
+        assert_eq!(
+            fts_match_expression(r#"c++ (draft) "it's done""#).as_deref(),
+            Some(r#""c++"* AND "(draft)"* AND "it's done""#)
+        );
+        assert_eq!(fts_quote(r#"a"b"#), r#""a""b""#);
+        assert_eq!(fts_match_expression("  "), None);
+        assert_eq!(
+            SearchQuery::parse("-a -\"b c\"").fts_excluded().as_deref(),
+            Some(r#""a" OR "b c""#)
+        );
+    }
+
+    #[test]
+    fn snippet_highlights_strips_markers() {
+        let snippet = format!("the {HIGHLIGHT_START}plan{HIGHLIGHT_END} is set");
+        let (text, ranges) = snippet_highlights(&snippet);
+        assert_eq!(text, "the plan is set");
+        assert_eq!(ranges, vec![4..8]);
+    }
+}
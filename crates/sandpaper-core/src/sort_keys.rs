@@ -0,0 +1,144 @@
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+fn digit_index(byte: u8) -> Option<u8> {
+    DIGITS
+        .iter()
+        .position(|digit| *digit == byte)
+        .map(|ix| ix as u8)
+}
+
+fn to_digits(key: &str) -> Option<Vec<u8>> {
+    key.bytes().map(digit_index).collect()
+}
+
+fn from_digits(digits: &[u8]) -> String {
+    digits
+        .iter()
+        .map(|digit| DIGITS[*digit as usize] as char)
+        .collect()
+}
+
+fn midpoint(lo: &[u8], hi: Option<&[u8]>) -> Option<Vec<u8>> {
+    if let Some(hi) = hi {
+        let mut shared = 0;
+        while shared < hi.len() && lo.get(shared).copied().unwrap_or(0) == hi[shared] {
+            shared += 1;
+        }
+        if shared == hi.len() {
+            return None;
+        }
+        if shared > 0 {
+            let rest = midpoint(lo.get(shared..).unwrap_or(&[]), Some(&hi[shared..]))?;
+            let mut key = hi[..shared].to_vec();
+            key.extend(rest);
+            return Some(key);
+        }
+    }
+
+    let lo_digit = lo.first().copied().unwrap_or(0) as usize;
+    let hi_digit = hi.map(|hi| hi[0] as usize).unwrap_or(BASE);
+    if hi_digit <= lo_digit {
+        return None;
+    }
+    if hi_digit - lo_digit > 1 {
+        return Some(vec![((lo_digit + hi_digit).div_ceil(2)) as u8]);
+    }
+    if let Some(hi) = hi {
+        if hi.len() > 1 {
+            return Some(vec![hi[0]]);
+        }
+    }
+    let mut key = vec![lo_digit as u8];
+    key.extend(midpoint(lo.get(1..).unwrap_or(&[]), None)?);
+    Some(key)
+}
+
+/// Returns a key that sorts strictly between `lo` and `hi` (either bound may be open).
+///
+/// Keys use a base-62 alphabet in ASCII order, so they compare the same way in Rust and in
+/// SQLite's default `BINARY` collation. Returns `None` when no such key exists, e.g. when the
+/// bounds are out of order or contain characters outside the alphabet.
+pub fn key_between(lo: Option<&str>, hi: Option<&str>) -> Option<String> {
+    let lo_digits = match lo {
+        Some(lo) => to_digits(lo)?,
+        None => Vec::new(),
+    };
+    let hi_digits = match hi {
+        Some(hi) => Some(to_digits(hi)?),
+        None => None,
+    };
+    let key = from_digits(&midpoint(&lo_digits, hi_digits.as_deref())?);
+    let above_lo = lo.is_none_or(|lo| key.as_str() > lo);
+    let below_hi = hi.is_none_or(|hi| key.as_str() < hi);
+    (above_lo && below_hi).then_some(key)
+}
+
+/// Returns `count` ascending keys between `lo` and `hi`, bisecting so key length grows with
+/// `log(count)` rather than `count`.
+pub fn keys_between(lo: Option<&str>, hi: Option<&str>, count: usize) -> Option<Vec<String>> {
+    if count == 0 {
+        return Some(Vec::new());
+    }
+    let mid_ix = count / 2;
+    let mid = key_between(lo, hi)?;
+    let mut keys = keys_between(lo, Some(&mid), mid_ix)?;
+    let after = keys_between(Some(&mid), hi, count - mid_ix - 1)?;
+    keys.push(mid);
+    keys.extend(after);
+    Some(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_between, keys_between};
+
+    #[test]
+    fn key_between_open_bounds() {
+        let key = key_between(None, None).expect("key");
+        let before = key_between(None, Some(&key)).expect("before");
+        let after = key_between(Some(&key), None).expect("after");
+        assert!(before < key);
+        assert!(key < after);
+    }
+
+    #[test]
+    fn key_between_adjacent_keys_extends_length() {
+        let key = key_between(Some("a"), Some("b")).expect("key");
+        assert!(key.as_str() > "a" && key.as_str() < "b");
+        assert_eq!(key.len(), 2);
+    }
+
+    #[test]
+    fn key_between_handles_legacy_padded_keys() {
+        let key = key_between(Some("000009"), Some("000010")).expect("key");
+        assert!(key.as_str() > "000009" && key.as_str() < "000010");
+        let key = key_between(Some("000010"), Some("000011")).expect("key");
+        assert!(key.as_str() > "000010" && key.as_str() < "000011");
+    }
+
+    #[test]
+    fn key_between_rejects_impossible_ranges() {
+        assert_eq!(key_between(None, Some("000000")), None);
+        assert_eq!(key_between(Some("b"), Some("a")), None);
+        assert_eq!(key_between(Some("a"), Some("a")), None);
+        assert_eq!(key_between(Some("a-b"), None), None);
+    }
+
+    #[test]
+    fn keys_between_are_sorted_and_short() {
+        let keys = keys_between(None, None, 10_000).expect("keys");
+        assert_eq!(keys.len(), 10_000);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(keys.iter().all(|key| key.len() <= 4));
+    }
+
+    #[test]
+    fn keys_between_respects_bounds() {
+        let keys = keys_between(Some("a"), Some("b"), 5).expect("keys");
+        assert_eq!(keys.len(), 5);
+        assert!(keys.first().expect("first").as_str() > "a");
+        assert!(keys.last().expect("last").as_str() < "b");
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,640 @@
+use crate::properties::{
+    parse_block_properties, remove_block_property_in_text, set_block_property_in_text,
+    strip_block_properties,
+};
+use crate::recurrence::RepeatRule;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::cmp::Ordering;
+
+/// Block property holding a task's state when it is not plain TODO or DONE.
+pub const STATUS_KEY: &str = "status";
+pub const PRIORITY_KEY: &str = "priority";
+pub const SCHEDULED_KEY: &str = "scheduled";
+pub const DEADLINE_KEY: &str = "deadline";
+/// Block property stamped when a task moves to DONE.
+pub const COMPLETED_KEY: &str = "completed";
+/// Block property holding a `RepeatRule`.
+pub const REPEAT_KEY: &str = "repeat";
+/// Block property listing when a repeating task was last completed, newest last.
+pub const DONE_LOG_KEY: &str = "done-log";
+/// Completions kept in `done-log::`.
+pub const DONE_LOG_LIMIT: usize = 10;
+
+pub const TASK_DATE_FORMAT: &str = "%Y-%m-%d";
+pub const COMPLETED_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+const CHECKBOX_PREFIXES: &[(&str, bool)] = &[
+    ("- [x] ", true),
+    ("- [X] ", true),
+    ("- [ ] ", false),
+    ("[x] ", true),
+    ("[X] ", true),
+    ("[ ] ", false),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskState {
+    Todo,
+    Doing,
+    Waiting,
+    Done,
+    Canceled,
+}
+
+impl TaskState {
+    pub const ALL: [TaskState; 5] = [
+        TaskState::Todo,
+        TaskState::Doing,
+        TaskState::Waiting,
+        TaskState::Done,
+        TaskState::Canceled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskState::Todo => "todo",
+            TaskState::Doing => "doing",
+            TaskState::Waiting => "waiting",
+            TaskState::Done => "done",
+            TaskState::Canceled => "canceled",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskState::Todo => "TODO",
+            TaskState::Doing => "DOING",
+            TaskState::Waiting => "WAITING",
+            TaskState::Done => "DONE",
+            TaskState::Canceled => "CANCELED",
+        }
+    }
+
+    /// The lowercase `status::` values that read as this state.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            TaskState::Todo => &["todo"],
+            TaskState::Doing => &["doing", "now"],
+            TaskState::Waiting => &["waiting", "wait"],
+            TaskState::Done => &["done"],
+            TaskState::Canceled => &["canceled", "cancelled"],
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|state| state.aliases().contains(&value.as_str()))
+    }
+
+    pub fn is_open(&self) -> bool {
+        !matches!(self, TaskState::Done | TaskState::Canceled)
+    }
+
+    /// The state a click on the state badge moves to.
+    pub fn next(&self) -> Self {
+        match self {
+            TaskState::Todo => TaskState::Doing,
+            TaskState::Doing => TaskState::Waiting,
+            TaskState::Waiting => TaskState::Done,
+            TaskState::Done => TaskState::Canceled,
+            TaskState::Canceled => TaskState::Todo,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskPriority {
+    A,
+    B,
+    C,
+}
+
+impl TaskPriority {
+    pub const ALL: [TaskPriority; 3] = [TaskPriority::A, TaskPriority::B, TaskPriority::C];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::A => "A",
+            TaskPriority::B => "B",
+            TaskPriority::C => "C",
+        }
+    }
+
+    /// The lowercase `priority::` values, after any leading `#`, that read as this priority.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            TaskPriority::A => &["a", "high"],
+            TaskPriority::B => &["b", "medium"],
+            TaskPriority::C => &["c", "low"],
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().trim_start_matches('#').to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|priority| priority.aliases().contains(&value.as_str()))
+    }
+}
+
+/// A todo block read from its checkbox prefix and task properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    pub state: TaskState,
+    pub priority: Option<TaskPriority>,
+    pub scheduled: Option<NaiveDate>,
+    pub deadline: Option<NaiveDate>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub repeat: Option<RepeatRule>,
+    /// Past completions of a repeating task, oldest first.
+    pub done_log: Vec<NaiveDateTime>,
+    pub title: String,
+}
+
+impl Task {
+    /// The date the agenda files the task under: the earlier of its scheduled and deadline days.
+    pub fn agenda_date(&self) -> Option<NaiveDate> {
+        match (self.scheduled, self.deadline) {
+            (Some(scheduled), Some(deadline)) => Some(scheduled.min(deadline)),
+            (date, None) | (None, date) => date,
+        }
+    }
+}
+
+/// A task together with the block and page it lives on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskRecord {
+    pub block_uid: String,
+    pub page_uid: String,
+    pub page_title: String,
+    pub task: Task,
+}
+
+/// Narrows `Database::query_tasks`. Empty `states` matches every state; a date bound drops
+/// tasks without a scheduled or deadline date.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskFilter {
+    pub states: Vec<TaskState>,
+    pub page_uid: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub due_from: Option<NaiveDate>,
+    pub due_to: Option<NaiveDate>,
+}
+
+impl TaskFilter {
+    pub fn open() -> Self {
+        Self {
+            states: TaskState::ALL
+                .into_iter()
+                .filter(TaskState::is_open)
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        if !self.states.is_empty() && !self.states.contains(&task.state) {
+            return false;
+        }
+        if self.priority.is_some() && task.priority != self.priority {
+            return false;
+        }
+        if self.due_from.is_none() && self.due_to.is_none() {
+            return true;
+        }
+        let Some(date) = task.agenda_date() else {
+            return false;
+        };
+        self.due_from.is_none_or(|from| date >= from) && self.due_to.is_none_or(|to| date <= to)
+    }
+}
+
+/// Open dated tasks split around `today`, each group ordered by date, priority and title.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Agenda {
+    pub overdue: Vec<TaskRecord>,
+    pub today: Vec<TaskRecord>,
+    pub upcoming: Vec<TaskRecord>,
+}
+
+impl Agenda {
+    /// Groups open tasks; upcoming stops `upcoming_days` after today.
+    pub fn build(tasks: Vec<TaskRecord>, today: NaiveDate, upcoming_days: i64) -> Self {
+        let horizon = today + chrono::Duration::days(upcoming_days);
+        let mut agenda = Agenda::default();
+        for record in tasks {
+            if !record.task.state.is_open() {
+                continue;
+            }
+            let Some(date) = record.task.agenda_date() else {
+                continue;
+            };
+            match date.cmp(&today) {
+                Ordering::Less => agenda.overdue.push(record),
+                Ordering::Equal => agenda.today.push(record),
+                Ordering::Greater if date <= horizon => agenda.upcoming.push(record),
+                Ordering::Greater => {}
+            }
+        }
+        for group in [&mut agenda.overdue, &mut agenda.today, &mut agenda.upcoming] {
+            group.sort_by(|a, b| {
+                a.task
+                    .agenda_date()
+                    .cmp(&b.task.agenda_date())
+                    .then_with(|| priority_rank(&a.task).cmp(&priority_rank(&b.task)))
+                    .then_with(|| a.task.title.cmp(&b.task.title))
+            });
+        }
+        agenda
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overdue.is_empty() && self.today.is_empty() && self.upcoming.is_empty()
+    }
+}
+
+fn priority_rank(task: &Task) -> u8 {
+    match task.priority {
+        Some(TaskPriority::A) => 0,
+        Some(TaskPriority::B) => 1,
+        Some(TaskPriority::C) => 2,
+        None => 3,
+    }
+}
+
+/// Returns whether the checkbox is ticked and the prefix length, for text starting with
+/// `[ ] `, `[x] ` or their `- ` bulleted forms.
+pub fn task_checkbox(text: &str) -> Option<(bool, usize)> {
+    CHECKBOX_PREFIXES
+        .iter()
+        .find(|(prefix, _)| text.starts_with(prefix))
+        .map(|(prefix, checked)| (*checked, prefix.len()))
+}
+
+pub fn is_task_property(key: &str) -> bool {
+    [
+        STATUS_KEY,
+        PRIORITY_KEY,
+        SCHEDULED_KEY,
+        DEADLINE_KEY,
+        COMPLETED_KEY,
+        REPEAT_KEY,
+        DONE_LOG_KEY,
+    ]
+    .iter()
+    .any(|task_key| key.eq_ignore_ascii_case(task_key))
+}
+
+/// Drops the task property lines, which todo blocks show as chips rather than property rows.
+pub fn strip_task_properties(text: &str) -> String {
+    text.lines()
+        .filter(|line| {
+            !parse_block_properties(line)
+                .first()
+                .is_some_and(|(key, _)| is_task_property(key))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads a `YYYY-MM-DD` task date, also accepting the `[[YYYY-MM-DD]]` page link form.
+pub fn parse_task_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("[[")
+        .and_then(|inner| inner.strip_suffix("]]"))
+        .unwrap_or(value);
+    NaiveDate::parse_from_str(value.trim(), TASK_DATE_FORMAT).ok()
+}
+
+pub fn parse_task(text: &str) -> Option<Task> {
+    let (checked, prefix_len) = task_checkbox(text)?;
+    let mut task = Task {
+        state: if checked {
+            TaskState::Done
+        } else {
+            TaskState::Todo
+        },
+        priority: None,
+        scheduled: None,
+        deadline: None,
+        completed_at: None,
+        repeat: None,
+        done_log: Vec::new(),
+        title: strip_block_properties(&text[prefix_len..])
+            .trim()
+            .to_string(),
+    };
+    for (key, value) in parse_block_properties(text) {
+        match key.as_str() {
+            STATUS_KEY => {
+                // The checkbox decides open vs closed; the status picks the state within it.
+                if let Some(state) = TaskState::parse(&value) {
+                    if state.is_open() != checked {
+                        task.state = state;
+                    }
+                }
+            }
+            PRIORITY_KEY => task.priority = TaskPriority::parse(&value),
+            SCHEDULED_KEY => task.scheduled = parse_task_date(&value),
+            DEADLINE_KEY => task.deadline = parse_task_date(&value),
+            COMPLETED_KEY => {
+                task.completed_at =
+                    NaiveDateTime::parse_from_str(value.trim(), COMPLETED_FORMAT).ok()
+            }
+            REPEAT_KEY => task.repeat = RepeatRule::parse(&value),
+            DONE_LOG_KEY => {
+                task.done_log = value
+                    .split(',')
+                    .filter_map(|entry| {
+                        NaiveDateTime::parse_from_str(entry.trim(), COMPLETED_FORMAT).ok()
+                    })
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    Some(task)
+}
+
+/// Rewrites block text to `state`: ticks or clears the checkbox (adding one to plain text),
+/// keeps `status::` for the states a checkbox cannot express, and stamps `completed::` on the
+/// way into DONE. Completing an open repeating task instead logs the completion and reopens it
+/// with its dates rolled forward.
+pub fn set_task_state(text: &str, state: TaskState, now: NaiveDateTime) -> String {
+    let task = parse_task(text);
+    if state == TaskState::Done {
+        if let Some(task) = task.as_ref().filter(|task| task.state.is_open()) {
+            if let Some(rule) = task.repeat {
+                return complete_repeating_task(text, task, rule, now);
+            }
+        }
+    }
+    let was_done = task.is_some_and(|task| task.state == TaskState::Done);
+    let (bulleted, body) = match task_checkbox(text) {
+        Some((_, prefix_len)) => (text.starts_with("- "), &text[prefix_len..]),
+        None => (false, text),
+    };
+    let mark = if state.is_open() { "[ ]" } else { "[x]" };
+    let bullet = if bulleted { "- " } else { "" };
+    let mut next = format!("{bullet}{mark} {body}");
+    next = match state {
+        TaskState::Todo | TaskState::Done => remove_block_property_in_text(&next, STATUS_KEY),
+        _ => set_block_property_in_text(&next, STATUS_KEY, state.as_str()),
+    };
+    if state != TaskState::Done {
+        next = remove_block_property_in_text(&next, COMPLETED_KEY);
+    } else if !was_done {
+        next = set_block_property_in_text(
+            &next,
+            COMPLETED_KEY,
+            &now.format(COMPLETED_FORMAT).to_string(),
+        );
+    }
+    next
+}
+
+fn complete_repeating_task(
+    text: &str,
+    task: &Task,
+    rule: RepeatRule,
+    now: NaiveDateTime,
+) -> String {
+    let completed = now.date();
+    let due = task.agenda_date();
+    let anchored = rule.anchored_at(task.scheduled.into_iter().chain(task.deadline));
+    let next_due = anchored.next_due(due, completed);
+    let steps = due.map(|due| anchored.steps_after(due, completed));
+
+    let mut next = set_task_state(text, TaskState::Todo, now);
+    if anchored != rule {
+        next = set_block_property_in_text(&next, REPEAT_KEY, &anchored.to_text());
+    }
+    let mut rolled = false;
+    for key in [SCHEDULED_KEY, DEADLINE_KEY] {
+        let Some(raw) = parse_block_properties(&next)
+            .into_iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value)
+        else {
+            continue;
+        };
+        let Some(date) = parse_task_date(&raw) else {
+            continue;
+        };
+        let rolled_date = match steps {
+            Some(steps) if !anchored.from_completion => anchored.advance(date, steps),
+            // Rules counting from the completion keep the dates as far apart as they were.
+            _ => date + (next_due - due.unwrap_or(next_due)),
+        };
+        next = set_block_property_in_text(&next, key, &format_like(&raw, rolled_date));
+        rolled = true;
+    }
+    if !rolled {
+        next = set_block_property_in_text(
+            &next,
+            SCHEDULED_KEY,
+            &next_due.format(TASK_DATE_FORMAT).to_string(),
+        );
+    }
+
+    let mut log = task
+        .done_log
+        .iter()
+        .map(|entry| entry.format(COMPLETED_FORMAT).to_string())
+        .collect::<Vec<_>>();
+    log.push(now.format(COMPLETED_FORMAT).to_string());
+    let skip = log.len().saturating_sub(DONE_LOG_LIMIT);
+    set_block_property_in_text(&next, DONE_LOG_KEY, &log[skip..].join(", "))
+}
+
+/// Formats `date` the way `raw` was written, keeping the `[[...]]` page link form.
+fn format_like(raw: &str, date: NaiveDate) -> String {
+    let formatted = date.format(TASK_DATE_FORMAT).to_string();
+    if raw.trim().starts_with("[[") {
+        format!("[[{formatted}]]")
+    } else {
+        formatted
+    }
+}
+
+/// Checkbox click: closes an open task as DONE and reopens a closed one as TODO.
+pub fn toggle_task(text: &str, now: NaiveDateTime) -> Option<String> {
+    let task = parse_task(text)?;
+    let state = if task.state.is_open() {
+        TaskState::Done
+    } else {
+        TaskState::Todo
+    };
+    Some(set_task_state(text, state, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_task, parse_task_date, set_task_state, strip_task_properties, toggle_task, Agenda,
+        Task, TaskFilter, TaskPriority, TaskRecord, TaskState,
+    };
+    use chrono::NaiveDate;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").expect("date")
+    }
+
+    #[test]
+    fn parse_task_reads_state_priority_and_dates() {
+        let task = parse_task(
+            "- [ ] Draft the report\nstatus:: doing\npriority:: a\nscheduled:: 2026-03-01\ndeadline:: [[2026-03-05]]",
+        )
+        .expect("task");
+        assert_eq!(task.state, TaskState::Doing);
+        assert_eq!(task.priority, Some(TaskPriority::A));
+        assert_eq!(task.scheduled, Some(date("2026-03-01")));
+        assert_eq!(task.deadline, Some(date("2026-03-05")));
+        assert_eq!(task.title, "Draft the report");
+        assert_eq!(task.agenda_date(), Some(date("2026-03-01")));
+
+        assert_eq!(
+            strip_task_properties("- [ ] Draft\nowner:: sam\npriority:: a\ndeadline:: 2026-03-05"),
+            "- [ ] Draft\nowner:: sam"
+        );
+
+        let done = parse_task("[x] Filed\nstatus:: waiting").expect("task");
+        assert_eq!(done.state, TaskState::Done);
+        let canceled = parse_task("[x] Dropped\nstatus:: cancelled").expect("task");
+        assert_eq!(canceled.state, TaskState::Canceled);
+
+        assert!(parse_task("Plain block").is_none());
+        assert_eq!(parse_task_date("2026-13-01"), None);
+    }
+
+    #[test]
+    fn set_task_state_rewrites_checkbox_status_and_completion() {
+        let now = date("2026-03-02").and_hms_opt(9, 30, 0).expect("time");
+        let doing = set_task_state("- [ ] Ship\npriority:: b", TaskState::Doing, now);
+        assert_eq!(doing, "- [ ] Ship\npriority:: b\nstatus:: doing");
+
+        let done = set_task_state(&doing, TaskState::Done, now);
+        assert_eq!(
+            done,
+            "- [x] Ship\npriority:: b\ncompleted:: 2026-03-02 09:30"
+        );
+        let task = parse_task(&done).expect("task");
+        assert_eq!(task.state, TaskState::Done);
+        assert_eq!(task.completed_at, Some(now));
+
+        let later = date("2026-03-03").and_hms_opt(8, 0, 0).expect("time");
+        assert_eq!(set_task_state(&done, TaskState::Done, later), done);
+
+        let reopened = toggle_task(&done, later).expect("toggle");
+        assert_eq!(reopened, "- [ ] Ship\npriority:: b");
+
+        let canceled = set_task_state("Call the bank", TaskState::Canceled, now);
+        assert_eq!(canceled, "[x] Call the bank\nstatus:: canceled");
+        assert_eq!(
+            toggle_task(&canceled, now).expect("toggle"),
+            "[ ] Call the bank"
+        );
+    }
+
+    #[test]
+    fn completing_a_repeating_task_logs_it_and_rolls_the_dates() {
+        let now = date("2026-03-04").and_hms_opt(18, 5, 0).expect("time");
+        let text = "[ ] Pay rent\nrepeat:: every month\nscheduled:: 2026-02-28\ndeadline:: [[2026-03-03]]\nstatus:: doing";
+        let next = toggle_task(text, now).expect("toggle");
+        assert_eq!(
+            next,
+            "[ ] Pay rent\nrepeat:: every month\nscheduled:: 2026-03-28\ndeadline:: [[2026-04-03]]\ndone-log:: 2026-03-04 18:05"
+        );
+        let task = parse_task(&next).expect("task");
+        assert_eq!(task.state, TaskState::Todo);
+        assert_eq!(task.done_log, vec![now]);
+        assert_eq!(task.completed_at, None);
+
+        // Month-end tasks remember the day they aim for, and every date steps by the rule.
+        let month_end =
+            "[ ] Invoice\nrepeat:: every month\nscheduled:: 2026-01-31\ndeadline:: 2026-01-15";
+        let at = |day: &str| date(day).and_hms_opt(9, 0, 0).expect("time");
+        let february = toggle_task(month_end, at("2026-01-31")).expect("toggle");
+        let task = parse_task(&february).expect("task");
+        assert_eq!(task.scheduled, Some(date("2026-02-28")));
+        assert_eq!(task.deadline, Some(date("2026-02-15")));
+        assert!(february.contains("repeat:: every month on day 31"));
+        let march = toggle_task(&february, at("2026-02-28")).expect("toggle");
+        let task = parse_task(&march).expect("task");
+        assert_eq!(task.scheduled, Some(date("2026-03-31")));
+        assert_eq!(task.deadline, Some(date("2026-03-15")));
+
+        // Undated repeating tasks get a scheduled date counted from the completion.
+        let undated = set_task_state("- [ ] Water plants\nrepeat:: +3d", TaskState::Done, now);
+        assert_eq!(
+            undated,
+            "- [ ] Water plants\nrepeat:: +3d\nscheduled:: 2026-03-07\ndone-log:: 2026-03-04 18:05"
+        );
+
+        // The log keeps the most recent completions only.
+        let mut text = "[ ] Stretch\nrepeat:: every day\nscheduled:: 2026-01-01".to_string();
+        for day in 1..=12 {
+            let at = date("2026-01-01").and_hms_opt(7, 0, 0).expect("time")
+                + chrono::Duration::days(day - 1);
+            text = toggle_task(&text, at).expect("toggle");
+        }
+        let task = parse_task(&text).expect("task");
+        assert_eq!(task.scheduled, Some(date("2026-01-13")));
+        assert_eq!(task.done_log.len(), 10);
+        assert_eq!(
+            task.done_log.first().map(|at| at.date()),
+            Some(date("2026-01-03"))
+        );
+
+        // Canceling does not roll the task forward.
+        let canceled = set_task_state("[ ] Gym\nrepeat:: every weekday", TaskState::Canceled, now);
+        assert_eq!(
+            canceled,
+            "[x] Gym\nrepeat:: every weekday\nstatus:: canceled"
+        );
+    }
+
+    #[test]
+    fn agenda_groups_open_dated_tasks_around_today() {
+        let record = |uid: &str, text: &str| TaskRecord {
+            block_uid: uid.to_string(),
+            page_uid: "page".to_string(),
+            page_title: "Page".to_string(),
+            task: parse_task(text).expect("task"),
+        };
+        let tasks = vec![
+            record("late", "[ ] Late\ndeadline:: 2026-03-01"),
+            record("today-c", "[ ] Low\nscheduled:: 2026-03-02\npriority:: c"),
+            record("today-a", "[ ] High\nscheduled:: 2026-03-02\npriority:: a"),
+            record("soon", "[ ] Soon\nscheduled:: 2026-03-05"),
+            record("far", "[ ] Far\nscheduled:: 2026-04-30"),
+            record("done", "[x] Done\nscheduled:: 2026-03-01"),
+            record("undated", "[ ] Someday"),
+        ];
+        let agenda = Agenda::build(tasks.clone(), date("2026-03-02"), 7);
+        let uids = |group: &[TaskRecord]| {
+            group
+                .iter()
+                .map(|record| record.block_uid.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(uids(&agenda.overdue), vec!["late"]);
+        assert_eq!(uids(&agenda.today), vec!["today-a", "today-c"]);
+        assert_eq!(uids(&agenda.upcoming), vec!["soon"]);
+
+        let filter = TaskFilter {
+            due_to: Some(date("2026-03-02")),
+            ..TaskFilter::open()
+        };
+        let matched = tasks
+            .iter()
+            .map(|record| &record.task)
+            .filter(|task| filter.matches(task))
+            .map(|task: &Task| task.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(matched, vec!["Late", "Low", "High"]);
+    }
+}
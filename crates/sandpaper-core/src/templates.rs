@@ -0,0 +1,266 @@
+use crate::db::{BlockSnapshot, Database, PageRecord};
+use crate::links::{extract_tags, strip_tag};
+use crate::properties::strip_block_properties;
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Tag that marks a page as a template.
+pub const TEMPLATE_TAG: &str = "template";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+const PROMPT_PREFIX: &str = "prompt:";
+
+/// Values substituted into a template.
+///
+/// `{{date}}` and `{{time}}` come from `now` (`{{date:%b %d}}` takes a strftime format),
+/// `{{title}}` is the page the template lands on, and each `{{prompt:Name}}` reads `Name` from
+/// `fields`, asked for before expanding. `{{cursor}}` marks where editing continues. Anything
+/// else in braces, such as `{{cloze …}}` or `{{embed …}}`, is copied as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateVars {
+    pub now: NaiveDateTime,
+    pub title: String,
+    pub fields: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandedTemplate {
+    /// Blocks with fresh uids, indented from 0.
+    pub blocks: Vec<BlockSnapshot>,
+    /// Block index and byte offset of the first `{{cursor}}`.
+    pub cursor: Option<(usize, usize)>,
+}
+
+/// Pages tagged `#template`, by title.
+pub fn template_pages(db: &Database) -> rusqlite::Result<Vec<PageRecord>> {
+    let mut seen = HashSet::new();
+    let mut pages = Vec::new();
+    for record in db.list_blocks_for_tag(TEMPLATE_TAG)? {
+        if !seen.insert(record.page_uid.clone()) {
+            continue;
+        }
+        if let Some(page) = db.get_page_by_uid(&record.page_uid)? {
+            pages.push(page);
+        }
+    }
+    pages.sort_by_key(|page| page.title.to_lowercase());
+    Ok(pages)
+}
+
+/// Names of the `{{prompt:Name}}` fields in a template, in order of first use.
+pub fn template_fields(blocks: &[BlockSnapshot]) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for block in blocks {
+        for (_, inner) in variables(&block.text) {
+            if let Some(name) = inner.strip_prefix(PROMPT_PREFIX).map(str::trim) {
+                if !name.is_empty() && !fields.iter().any(|field| field == name) {
+                    fields.push(name.to_string());
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Copies a template's blocks with their types and nesting, filling in variables. Blocks that
+/// only carry the `#template` tag are left out and the tag is dropped from the rest.
+pub fn expand_template(blocks: &[BlockSnapshot], vars: &TemplateVars) -> ExpandedTemplate {
+    let kept = blocks
+        .iter()
+        .filter(|block| !is_template_marker(&block.text))
+        .collect::<Vec<_>>();
+    let base = kept.iter().map(|block| block.indent).min().unwrap_or(0);
+    let mut cursor = None;
+    let blocks = kept
+        .into_iter()
+        .enumerate()
+        .map(|(ix, block)| {
+            let (text, offset) = expand_text(&strip_template_tag(&block.text), vars);
+            if cursor.is_none() {
+                cursor = offset.map(|offset| (ix, offset));
+            }
+            BlockSnapshot {
+                uid: Uuid::new_v4().to_string(),
+                text,
+                indent: block.indent - base,
+                block_type: block.block_type,
+            }
+        })
+        .collect();
+    ExpandedTemplate { blocks, cursor }
+}
+
+/// Fills in the variables in one block's text. Returns the text and the offset of the first
+/// `{{cursor}}`, which is removed.
+fn expand_text(text: &str, vars: &TemplateVars) -> (String, Option<usize>) {
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = None;
+    let mut copied = 0;
+    for (range, inner) in variables(text) {
+        let value = match inner {
+            "cursor" => {
+                out.push_str(&text[copied..range.start]);
+                cursor.get_or_insert(out.len());
+                copied = range.end;
+                continue;
+            }
+            "date" => Some(vars.now.format(DATE_FORMAT).to_string()),
+            "time" => Some(vars.now.format(TIME_FORMAT).to_string()),
+            "title" => Some(vars.title.clone()),
+            _ => {
+                if let Some(format) = inner.strip_prefix("date:") {
+                    is_valid_format(format).then(|| vars.now.format(format).to_string())
+                } else {
+                    inner
+                        .strip_prefix(PROMPT_PREFIX)
+                        .map(|name| vars.fields.get(name.trim()).cloned().unwrap_or_default())
+                }
+            }
+        };
+        if let Some(value) = value {
+            out.push_str(&text[copied..range.start]);
+            out.push_str(&value);
+            copied = range.end;
+        }
+    }
+    out.push_str(&text[copied..]);
+    (out, cursor)
+}
+
+/// `{{…}}` spans in `text` with their trimmed contents.
+fn variables(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut cursor = 0;
+    while let Some(start) = text[cursor..].find("{{").map(|offset| cursor + offset) {
+        let Some(end) = text[start + 2..]
+            .find("}}")
+            .map(|offset| start + 2 + offset)
+        else {
+            break;
+        };
+        found.push((start..end + 2, text[start + 2..end].trim()));
+        cursor = end + 2;
+    }
+    found
+}
+
+fn is_valid_format(format: &str) -> bool {
+    !format.is_empty() && !StrftimeItems::new(format).any(|item| item == Item::Error)
+}
+
+fn has_template_tag(text: &str) -> bool {
+    extract_tags(text).iter().any(|tag| tag == TEMPLATE_TAG)
+}
+
+fn is_template_marker(text: &str) -> bool {
+    has_template_tag(text) && strip_template_tag(&strip_block_properties(text)).is_empty()
+}
+
+fn strip_template_tag(text: &str) -> String {
+    if !has_template_tag(text) {
+        return text.to_string();
+    }
+    strip_tag(text, TEMPLATE_TAG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_template, template_fields, template_pages, TemplateVars};
+    use crate::blocks::BlockType;
+    use crate::db::{BlockSnapshot, Database};
+    use chrono::NaiveDateTime;
+    use std::collections::HashMap;
+
+    fn block(text: &str, indent: i64, block_type: BlockType) -> BlockSnapshot {
+        BlockSnapshot {
+            uid: format!("t-{indent}-{text}"),
+            text: text.to_string(),
+            indent,
+            block_type,
+        }
+    }
+
+    fn vars(fields: &[(&str, &str)]) -> TemplateVars {
+        TemplateVars {
+            now: NaiveDateTime::parse_from_str("2026-03-04 09:30", "%Y-%m-%d %H:%M")
+                .expect("datetime"),
+            title: "Weekly sync".to_string(),
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn variables_fill_in_and_unknown_braces_stay() {
+        let blocks = [
+            block("#template", 0, BlockType::Text),
+            block("{{title}} · {{date}}", 1, BlockType::Heading2),
+            block("Started {{time}} on {{date:%A}}", 2, BlockType::Text),
+            block("Host: {{prompt:Host}} {{ cursor }}", 2, BlockType::Todo),
+            block("The {{cloze answer}} and {{date:%Q}}", 1, BlockType::Quote),
+        ];
+        assert_eq!(template_fields(&blocks), vec!["Host"]);
+
+        let expanded = expand_template(&blocks, &vars(&[("Host", "Ada")]));
+        let texts = expanded
+            .blocks
+            .iter()
+            .map(|block| (block.text.as_str(), block.indent, block.block_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                ("Weekly sync · 2026-03-04", 0, BlockType::Heading2),
+                ("Started 09:30 on Wednesday", 1, BlockType::Text),
+                ("Host: Ada ", 1, BlockType::Todo),
+                ("The {{cloze answer}} and {{date:%Q}}", 0, BlockType::Quote),
+            ]
+        );
+        assert_eq!(expanded.cursor, Some((2, "Host: Ada ".len())));
+        assert!(expanded
+            .blocks
+            .iter()
+            .all(|block| !block.uid.starts_with("t-")));
+    }
+
+    #[test]
+    fn template_tag_is_dropped_and_missing_fields_are_blank() {
+        let blocks = [
+            block("Agenda #template", 0, BlockType::Text),
+            block("Owner: {{prompt:Owner}}", 1, BlockType::Text),
+        ];
+        let expanded = expand_template(&blocks, &vars(&[]));
+        assert_eq!(expanded.blocks[0].text, "Agenda");
+        assert_eq!(expanded.blocks[1].text, "Owner: ");
+        assert_eq!(expanded.cursor, None);
+    }
+
+    #[test]
+    fn template_pages_lists_pages_tagged_template() {
+        let mut db = Database::new_in_memory().expect("db init");
+        db.run_migrations().expect("migrations");
+        let meeting = db.insert_page("meeting", "Meeting").expect("insert");
+        let notes = db.insert_page("notes", "Notes").expect("insert");
+        db.replace_blocks_for_page(
+            meeting,
+            &[
+                block("#template", 0, BlockType::Text),
+                block("Attendees #template", 0, BlockType::Text),
+            ],
+        )
+        .expect("meeting blocks");
+        db.replace_blocks_for_page(notes, &[block("#templates", 0, BlockType::Text)])
+            .expect("notes blocks");
+
+        let titles = template_pages(&db)
+            .expect("templates")
+            .into_iter()
+            .map(|page| page.title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Meeting"]);
+    }
+}
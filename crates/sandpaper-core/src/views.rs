@@ -0,0 +1,244 @@
+use crate::db::{PagePropertyRecord, PageRecord};
+use serde::{Deserialize, Serialize};
+
+/// Pseudo property key that addresses the page title in filters, sorts and columns.
+pub const TITLE_KEY: &str = "title";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "value")]
+pub enum PageSource {
+    #[default]
+    All,
+    /// Pages with at least one block carrying the tag.
+    Tag(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Equals,
+    NotEquals,
+    Contains,
+    IsEmpty,
+    IsNotEmpty,
+    /// Typed comparisons: numbers numerically, dates and text by their text form.
+    LessThan,
+    GreaterThan,
+    /// Inclusive range between `value` and `values[0]`.
+    Between,
+    /// Matches any of `values`; multi-select properties match when any option is listed.
+    In,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyFilter {
+    pub key: String,
+    pub op: FilterOp,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertySort {
+    pub key: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageQuery {
+    #[serde(default)]
+    pub source: PageSource,
+    #[serde(default)]
+    pub filters: Vec<PropertyFilter>,
+    #[serde(default)]
+    pub sorts: Vec<PropertySort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewLayout {
+    #[default]
+    Table,
+    Board,
+    List,
+}
+
+/// Per-block configuration of a database view, stored as JSON in the block text.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseViewConfig {
+    #[serde(default)]
+    pub layout: ViewLayout,
+    #[serde(default)]
+    pub query: PageQuery,
+    /// Property keys shown as columns; empty shows every property definition.
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>,
+}
+
+impl DatabaseViewConfig {
+    /// Reads a config from block text. Text that is not a config (e.g. blocks created before
+    /// views were configurable) yields the default view over all pages.
+    pub fn from_block_text(text: &str) -> Self {
+        serde_json::from_str(text.trim()).unwrap_or_default()
+    }
+
+    pub fn to_block_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Toggles sorting on `key`: ascending, then descending, then unsorted.
+    pub fn toggle_sort(&mut self, key: &str) {
+        match self.query.sorts.iter().position(|sort| sort.key == key) {
+            Some(ix) if !self.query.sorts[ix].descending => self.query.sorts[ix].descending = true,
+            Some(ix) => {
+                self.query.sorts.remove(ix);
+            }
+            None => self.query.sorts.insert(
+                0,
+                PropertySort {
+                    key: key.to_string(),
+                    descending: false,
+                },
+            ),
+        }
+    }
+}
+
+pub type PageQueryRow = (PageRecord, Vec<PagePropertyRecord>);
+
+/// Groups query rows by the value of `key`. Groups follow `options` order first, then first
+/// appearance; rows without a value land in a trailing group with an empty name.
+pub fn group_rows<'a>(
+    rows: &'a [PageQueryRow],
+    key: &str,
+    options: &[String],
+) -> Vec<(String, Vec<&'a PageQueryRow>)> {
+    let mut groups: Vec<(String, Vec<&PageQueryRow>)> = options
+        .iter()
+        .map(|option| (option.clone(), Vec::new()))
+        .collect();
+    let mut ungrouped = Vec::new();
+    for row in rows {
+        let value = row
+            .1
+            .iter()
+            .find(|prop| prop.key == key)
+            .map(|prop| prop.value.trim())
+            .unwrap_or("");
+        if value.is_empty() {
+            ungrouped.push(row);
+            continue;
+        }
+        match groups.iter_mut().find(|(name, _)| name == value) {
+            Some((_, members)) => members.push(row),
+            None => groups.push((value.to_string(), vec![row])),
+        }
+    }
+    if !ungrouped.is_empty() {
+        groups.push((String::new(), ungrouped));
+    }
+    groups
+}
+
+/// Parses `PropertyDefinition.options`, a JSON array of option names.
+pub fn parse_select_options(options: Option<&str>) -> Vec<String> {
+    options
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(raw).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        group_rows, DatabaseViewConfig, FilterOp, PageQuery, PageSource, PropertyFilter, ViewLayout,
+    };
+    use crate::db::{PagePropertyRecord, PageRecord};
+
+    fn row(id: i64, status: Option<&str>) -> (PageRecord, Vec<PagePropertyRecord>) {
+        let page = PageRecord {
+            id,
+            uid: format!("page-{id}"),
+            title: format!("Page {id}"),
+        };
+        let props = status
+            .map(|value| PagePropertyRecord {
+                id,
+                page_id: id,
+                key: "status".to_string(),
+                value: value.to_string(),
+                value_type: "select".to_string(),
+                sort_order: 0,
+            })
+            .into_iter()
+            .collect();
+        (page, props)
+    }
+
+    #[test]
+    fn config_round_trips_and_defaults_legacy_text() {
+        let config = DatabaseViewConfig {
+            layout: ViewLayout::Board,
+            query: PageQuery {
+                source: PageSource::Tag("project".to_string()),
+                filters: vec![PropertyFilter {
+                    key: "status".to_string(),
+                    op: FilterOp::NotEquals,
+                    value: "done".to_string(),
+                    values: Vec::new(),
+                }],
+                ..PageQuery::default()
+            },
+            columns: vec!["status".to_string()],
+            group_by: Some("status".to_string()),
+        };
+        let text = config.to_block_text();
+        assert_eq!(DatabaseViewConfig::from_block_text(&text), config);
+        assert_eq!(
+            DatabaseViewConfig::from_block_text("Database view block"),
+            DatabaseViewConfig::default()
+        );
+    }
+
+    #[test]
+    fn toggle_sort_cycles_direction() {
+        let mut config = DatabaseViewConfig::default();
+        config.toggle_sort("due");
+        assert!(!config.query.sorts[0].descending);
+        config.toggle_sort("due");
+        assert!(config.query.sorts[0].descending);
+        config.toggle_sort("due");
+        assert!(config.query.sorts.is_empty());
+    }
+
+    #[test]
+    fn group_rows_orders_by_options_then_appearance() {
+        let rows = vec![
+            row(1, Some("doing")),
+            row(2, None),
+            row(3, Some("blocked")),
+            row(4, Some("todo")),
+        ];
+        let options = vec!["todo".to_string(), "doing".to_string()];
+        let groups = group_rows(&rows, "status", &options);
+        let summary: Vec<(String, usize)> = groups
+            .iter()
+            .map(|(name, members)| (name.clone(), members.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("todo".to_string(), 1),
+                ("doing".to_string(), 1),
+                ("blocked".to_string(), 1),
+                (String::new(), 1),
+            ]
+        );
+    }
+}
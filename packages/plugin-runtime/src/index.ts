@@ -5,8 +5,24 @@ export type {
   PluginBlockContext,
   PluginBlockControl,
   PluginBlockView,
+  PluginCommandBlock,
+  PluginCommandContext,
   PluginCommandDefinition,
+  PluginCommandEdit,
+  PluginCommandResult,
+  PluginDataApi,
+  PluginDataBlock,
+  PluginDataPage,
+  PluginDataSearchHit,
+  PluginEventMap,
+  PluginEventName,
+  PluginNewBlock,
+  PluginPanelContext,
   PluginPanelDefinition,
+  PluginPanelHandlers,
+  PluginPanelNode,
+  PluginPanelRender,
+  PluginPanelView,
   PluginRegister,
   PluginRendererDefinition,
   PluginRendererHandlers
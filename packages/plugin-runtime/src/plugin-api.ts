@@ -17,6 +17,34 @@ export type PluginPanelDefinition = {
   location?: string | null;
 };
 
+export type PluginPanelNode =
+  | { type: "text"; text: string; style?: "heading" | "muted" }
+  | { type: "list"; items: PluginPanelNode[] }
+  | { type: "button"; id: string; label: string; value?: unknown }
+  | { type: "input"; id: string; placeholder?: string; value?: string }
+  | { type: "link"; label: string; page?: string; block?: string };
+
+export type PluginPanelView =
+  | PluginPanelNode[]
+  | {
+      title?: string;
+      children: PluginPanelNode[];
+    };
+
+export type PluginPanelContext = PluginCommandContext & {
+  panel: { id: string };
+  action?: { id: string; value?: unknown };
+};
+
+export type PluginPanelRender = (
+  ctx: PluginPanelContext
+) => PluginPanelView | void | Promise<PluginPanelView | void>;
+
+export type PluginPanelHandlers = {
+  render: PluginPanelRender;
+  onAction?: PluginPanelRender;
+};
+
 export type PluginBlockControl =
   | {
       id: string;
@@ -88,18 +116,113 @@ export type PluginRendererHandlers = {
   onAction?: (ctx: PluginBlockContext) => PluginBlockView | Promise<PluginBlockView>;
 };
 
+export type PluginCommandBlock = {
+  uid: string;
+  text: string;
+  cursor?: number;
+};
+
+export type PluginCommandContext = {
+  page: { uid: string; title: string } | null;
+  block: PluginCommandBlock | null;
+  selection: PluginCommandBlock[];
+  settings: Record<string, unknown>;
+};
+
+export type PluginCommandEdit =
+  | { type: "setText"; uid: string; text: string }
+  | { type: "insertBlocks"; blocks: PluginNewBlock[]; after?: string | null }
+  | { type: "deleteBlock"; uid: string };
+
+export type PluginCommandResult = {
+  edits?: PluginCommandEdit[];
+  openPanel?: string;
+  notification?: {
+    message: string;
+    kind?: "info" | "success" | "warning" | "error";
+  };
+};
+
+export type PluginDataBlock = {
+  uid: string;
+  text: string;
+  indent: number;
+  type: string;
+  properties: Record<string, string>;
+};
+
+export type PluginDataPage = {
+  uid: string;
+  title: string;
+};
+
+export type PluginDataSearchHit = {
+  uid: string;
+  text: string;
+  pageUid: string;
+  pageTitle: string;
+};
+
+export type PluginNewBlock =
+  | string
+  | {
+      text: string;
+      indent?: number;
+      type?: string;
+    };
+
+export type PluginDataApi = {
+  getPage: (uidOrTitle: string) => (PluginDataPage & { blocks: PluginDataBlock[] }) | null;
+  listPages: () => PluginDataPage[];
+  searchBlocks: (query: string, limit?: number) => PluginDataSearchHit[];
+  getBlock: (uid: string) => (PluginDataBlock & { pageUid: string }) | null;
+  setBlockText: (uid: string, text: string) => void;
+  setBlockProperty: (uid: string, key: string, value?: string | null) => void;
+  createPage: (title: string, blocks?: PluginNewBlock[]) => PluginDataPage;
+  insertBlocks: (page: string, blocks: PluginNewBlock[], afterUid?: string | null) => string[];
+};
+
+export type PluginEventMap = {
+  "page.opened": { page: PluginDataPage };
+  "page.saved": { page: PluginDataPage; blocks: PluginDataBlock[] };
+  "block.created": { page: PluginDataPage; block: PluginDataBlock };
+  "block.changed": { page: PluginDataPage; block: PluginDataBlock };
+  "block.deleted": { page: PluginDataPage; block: PluginDataBlock };
+  "vault.changed": { vault: { id: string | null; path: string } };
+  "review.completed": {
+    itemId: number;
+    pageUid: string;
+    blockUid: string;
+    grade: "again" | "hard" | "good" | "easy" | null;
+    dueAt: number;
+  };
+};
+
+export type PluginEventName = keyof PluginEventMap;
+
 export type PluginApi = {
+  data: PluginDataApi;
+
+  on: <E extends PluginEventName>(
+    event: E,
+    handler: (
+      payload: PluginEventMap[E]
+    ) => PluginCommandResult | void | Promise<PluginCommandResult | void>
+  ) => void;
+
   registerRenderer: (
     def: PluginRendererDefinition,
     handlers: PluginRendererHandlers
   ) => void;
   registerCommand: (
     def: PluginCommandDefinition,
-    handler?: () => void | Promise<void>
+    handler?: (
+      ctx: PluginCommandContext
+    ) => PluginCommandResult | void | Promise<PluginCommandResult | void>
   ) => void;
   registerPanel: (
     def: PluginPanelDefinition,
-    handler?: () => void | Promise<void>
+    handlers?: PluginPanelRender | PluginPanelHandlers
   ) => void;
 };
 